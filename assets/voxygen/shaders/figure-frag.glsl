@@ -4,32 +4,19 @@
 
 in vec3 f_pos;
 in vec3 f_col;
+in float f_glow;
 flat in vec3 f_norm;
 
-layout (std140)
-uniform u_locals {
-	mat4 model_mat;
-	vec4 model_col;
-};
-
-struct BoneData {
-	mat4 bone_mat;
-};
-
-layout (std140)
-uniform u_bones {
-	BoneData bones[16];
-};
-
 #include <sky.glsl>
 #include <light.glsl>
+#include <shadow.glsl>
 #include <srgb.glsl>
 
 out vec4 tgt_color;
 
 void main() {
-	vec3 light = get_sun_diffuse(f_norm, time_of_day.x) + light_at(f_pos, f_norm);
-	vec3 surf_color = srgb_to_linear(model_col.rgb * f_col) * 4.0 * light;
+	vec3 light = get_sun_diffuse(f_norm, time_of_day.x, shadow_at(f_pos, f_norm)) + light_at(f_pos, f_norm);
+	vec3 surf_color = srgb_to_linear(f_col) * (4.0 * light + f_glow);
 
 	float fog_level = fog(f_pos.xyz, focus_pos.xyz, medium.x);
 	vec3 fog_color = get_sky_color(normalize(f_pos - cam_pos.xyz), time_of_day.x, true);
@@ -0,0 +1,52 @@
+#version 330 core
+
+#include <globals.glsl>
+
+in vec3 v_pos;
+in vec3 v_norm;
+in vec3 v_col;
+in float v_glow;
+in uint v_bone_idx;
+in vec4 inst_mat0;
+in vec4 inst_mat1;
+in vec4 inst_mat2;
+in vec4 inst_mat3;
+in vec4 inst_col;
+in uint inst_bone_base;
+
+struct BoneData {
+	mat4 bone_mat;
+};
+
+layout (std140)
+uniform u_bones {
+	// Each instance has its own run of bones, starting at `inst_bone_base`. Must match
+	// `MAX_BATCH_BONES` in `render::pipelines::figure`
+	BoneData bones[256];
+};
+
+out vec3 f_pos;
+out vec3 f_col;
+out float f_glow;
+flat out vec3 f_norm;
+
+void main() {
+	mat4 model_mat;
+	model_mat[0] = inst_mat0;
+	model_mat[1] = inst_mat1;
+	model_mat[2] = inst_mat2;
+	model_mat[3] = inst_mat3;
+
+	// Pre-calculate bone matrix
+	mat4 combined_mat = model_mat * bones[inst_bone_base + v_bone_idx].bone_mat;
+
+	f_pos = (combined_mat * vec4(v_pos, 1)).xyz;
+
+	f_col = inst_col.rgb * v_col;
+	f_glow = v_glow;
+
+	// Calculate normal here rather than for each pixel in the fragment shader
+	f_norm = (combined_mat * vec4(v_norm, 0.0)).xyz;
+
+	gl_Position = proj_mat * view_mat * vec4(f_pos, 1);
+}
@@ -5,6 +5,7 @@
 in vec3 v_pos;
 in vec3 v_norm;
 in vec3 v_col;
+in float v_glow;
 in uint v_bone_idx;
 
 layout (std140)
@@ -19,11 +20,13 @@ struct BoneData {
 
 layout (std140)
 uniform u_bones {
-	BoneData bones[16];
+	// Must match `MAX_BONES` in `render::pipelines::figure`
+	BoneData bones[32];
 };
 
 out vec3 f_pos;
 out vec3 f_col;
+out float f_glow;
 flat out vec3 f_norm;
 
 void main() {
@@ -34,7 +37,8 @@ void main() {
 		combined_mat * 
 		vec4(v_pos, 1)).xyz;
 
-	f_col = v_col;
+	f_col = model_col.rgb * v_col;
+	f_glow = v_glow;
 
 	// Calculate normal here rather than for each pixel in the fragment shader
 	f_norm = (
@@ -13,6 +13,8 @@ uniform u_locals {
     vec3 model_offs;
 };
 
+uniform sampler2D t_refraction;
+
 out vec4 tgt_color;
 
 #include <sky.glsl>
@@ -49,10 +51,19 @@ void main() {
 	reflect_ray_dir.z = max(reflect_ray_dir.z, 0.05);
 
 	vec3 reflect_color = get_sky_color(reflect_ray_dir, time_of_day.x, false) * f_light;
-	// 0 = 100% reflection, 1 = translucent water
-	float passthrough = pow(dot(faceforward(norm, norm, cam_to_frag), -cam_to_frag), 1.0);
 
-	vec4 color = mix(vec4(reflect_color, 1.0), vec4(surf_color, 0.5 / (1.0 + light * 2.0)), passthrough);
+	// Whatever's behind the surface, bent by the waves
+	const float REFRACTION_STRENGTH = 0.3;
+	vec2 screen_uv = gl_FragCoord.xy / screen_res.xy;
+	vec2 refract_uv = clamp(screen_uv + (norm.xy - f_norm.xy) * REFRACTION_STRENGTH, 0.0, 1.0);
+	vec3 refract_color = mix(texture(t_refraction, refract_uv).rgb, surf_color, 0.25);
+
+	// Schlick's approximation of the Fresnel effect: water reflects more the flatter it's seen from
+	const float WATER_REFLECTANCE = 0.02;
+	float cos_view = max(dot(faceforward(norm, norm, cam_to_frag), -cam_to_frag), 0.0);
+	float fresnel = WATER_REFLECTANCE + (1.0 - WATER_REFLECTANCE) * pow(1.0 - cos_view, 5.0);
+
+	vec4 color = vec4(mix(refract_color, reflect_color, fresnel), 1.0);
 
-    tgt_color = mix(color, vec4(fog_color, 0.0), fog_level);
+    tgt_color = mix(color, vec4(fog_color, 1.0), fog_level);
 }
@@ -10,4 +10,8 @@ uniform u_globals {
 	vec4 screen_res;
 	uvec4 light_count;
 	uvec4 medium;
+	// x = density, y = height falloff, z = base height
+	vec4 fog_params;
+	// xy = horizontal wind velocity, in blocks per second
+	vec4 wind;
 };
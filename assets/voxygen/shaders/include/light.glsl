@@ -1,6 +1,8 @@
 struct Light {
 	vec4 light_pos;
 	vec4 light_col;
+	vec4 light_reach_pos;
+	vec4 light_reach_neg;
 };
 
 layout (std140)
@@ -14,6 +16,20 @@ float attenuation_strength(vec3 rpos) {
 	return 1.0 / (rpos.x * rpos.x + rpos.y * rpos.y + rpos.z * rpos.z);
 }
 
+// How much of a light reaches a point `dist` away from it in the direction `dir`, fading out just
+// beyond where terrain stops the light along the nearest axes
+float light_reach(Light L, vec3 dir, float dist) {
+	const float REACH_FALLOFF = 1.5;
+
+	// A negative reach means nothing stops the light along that axis
+	vec3 reach = mix(L.light_reach_neg.xyz, L.light_reach_pos.xyz, step(0.0, dir));
+	vec3 unblocked = 1.0 - step(0.0, reach);
+	vec3 axis_reach = max(unblocked, clamp((reach + REACH_FALLOFF - dist) / REACH_FALLOFF, 0.0, 1.0));
+
+	// The squared components of a normalized direction sum to 1, so weight each axis by them
+	return dot(dir * dir, axis_reach);
+}
+
 vec3 light_at(vec3 wpos, vec3 wnorm) {
 	const float LIGHT_AMBIENCE = 0.025;
 
@@ -29,7 +45,8 @@ vec3 light_at(vec3 wpos, vec3 wnorm) {
 		// Pre-calculate difference between light and fragment
 		vec3 difference = light_pos - wpos;
 
-		float strength = attenuation_strength(difference);
+		// Lights don't shine through the terrain around them
+		float strength = attenuation_strength(difference) * light_reach(L, -normalize(difference), length(difference));
 
 		// Multiply the vec3 only once
 		vec3 color = srgb_to_linear(L.light_col.rgb) * (strength * L.light_col.a);
@@ -0,0 +1,43 @@
+struct Shadow {
+	mat4 shadow_mat;
+	vec4 shadow_split;
+};
+
+layout (std140)
+uniform u_shadows {
+	// Must match `MAX_SHADOW_CASCADES` in `render::pipelines`
+	Shadow shadows[3];
+};
+
+uniform sampler2DArray t_shadow_maps;
+
+// How much of the sun's light reaches the given point, from 0 (fully shadowed) to 1 (fully lit)
+float shadow_at(vec3 wpos, vec3 wnorm) {
+	const float SHADOW_BIAS = 0.002;
+
+	float dist = distance(wpos, focus_pos.xyz);
+
+	for (int i = 0; i < 3; i ++) {
+		// Cascades are ordered nearest first, so the first one that reaches this far is the sharpest
+		if (dist < shadows[i].shadow_split.x) {
+			// Nudge the point along its normal to avoid surfaces shadowing themselves
+			vec4 shadow_pos = shadows[i].shadow_mat * vec4(wpos + normalize(wnorm) * 0.1, 1);
+			vec3 coord = shadow_pos.xyz / shadow_pos.w * 0.5 + 0.5;
+
+			// Average neighbouring texels to soften the edges of shadows
+			vec2 texel = 1.0 / vec2(textureSize(t_shadow_maps, 0).xy);
+			float lit = 0.0;
+			for (int x = -1; x <= 1; x ++) {
+				for (int y = -1; y <= 1; y ++) {
+					float depth = texture(t_shadow_maps, vec3(coord.xy + vec2(x, y) * texel, float(i))).r;
+					lit += coord.z - SHADOW_BIAS > depth ? 0.0 : 1.0;
+				}
+			}
+
+			return lit / 9.0;
+		}
+	}
+
+	// Beyond the furthest cascade, or with shadows turned off
+	return 1.0;
+}
@@ -16,6 +16,7 @@ const vec3 SKY_NIGHT_TOP = vec3(0.001, 0.001, 0.0025);
 const vec3 SKY_NIGHT_MID = vec3(0.001, 0.005, 0.02);
 const vec3 SKY_NIGHT_BOT = vec3(0.002, 0.002, 0.005);
 const vec3 NIGHT_LIGHT   = vec3(0.002, 0.01, 0.03);
+const vec3 MOON_LIGHT    = vec3(0.04, 0.06, 0.12);
 
 vec3 get_sun_dir(float time_of_day) {
 	const float TIME_FACTOR = (PI * 2.0) / (3600.0 * 24.0);
@@ -30,9 +31,21 @@ float get_sun_brightness(vec3 sun_dir) {
 	return max(-sun_dir.z + 0.6, 0.0);
 }
 
+// The moon rises as the sun sets, on a path tilted a little away from the sun's
+vec3 get_moon_dir(float time_of_day) {
+	vec3 sun_dir = get_sun_dir(time_of_day);
+
+	return normalize(vec3(-sun_dir.x, 0.3, -sun_dir.z));
+}
+
+float get_moon_brightness(vec3 moon_dir) {
+	return max(-moon_dir.z, 0.0);
+}
+
 const float PERSISTENT_AMBIANCE = 0.008;
 
-vec3 get_sun_diffuse(vec3 norm, float time_of_day) {
+// `shadow` scales the direct sunlight, leaving the ambient light untouched
+vec3 get_sun_diffuse(vec3 norm, float time_of_day, float shadow) {
 	const float SUN_AMBIANCE = 0.15;
 
 	vec3 sun_dir = get_sun_dir(time_of_day);
@@ -51,11 +64,19 @@ vec3 get_sun_diffuse(vec3 norm, float time_of_day) {
 		max(-sun_dir.z, 0)
 	);
 
-	vec3 diffuse_light = (SUN_AMBIANCE + max(dot(-norm, sun_dir), 0.0) * sun_color) * sun_light + PERSISTENT_AMBIANCE;
+	// The shadow map is cast from the sun, so moonlight is never shadowed
+	vec3 moon_dir = get_moon_dir(time_of_day);
+	vec3 moon_light = max(dot(-norm, moon_dir), 0.0) * MOON_LIGHT * get_moon_brightness(moon_dir);
+
+	vec3 diffuse_light = (SUN_AMBIANCE + max(dot(-norm, sun_dir), 0.0) * sun_color * shadow) * sun_light + moon_light + PERSISTENT_AMBIANCE;
 
 	return diffuse_light;
 }
 
+vec3 get_sun_diffuse(vec3 norm, float time_of_day) {
+	return get_sun_diffuse(norm, time_of_day, 1.0);
+}
+
 // This has been extracted into a function to allow quick exit when detecting a star.
 float is_star_at(vec3 dir) {
 	float star_scale = 30.0;
@@ -144,7 +165,35 @@ vec3 get_sky_color(vec3 dir, float time_of_day, bool with_stars) {
 	vec3 sun_surf = pow(max(dot(dir, -sun_dir) - 0.0045, 0.0), 1000.0) * SUN_SURF_COLOR;
 	vec3 sun_light = (sun_halo + sun_surf) * clamp(dir.z * 10.0, 0, 1);
 
-	return sky_color + sun_light;
+	// Moon
+
+	const vec3 MOON_HALO_COLOR = vec3(0.3, 0.4, 0.6) * 0.05;
+	const vec3 MOON_SURF_COLOR = vec3(0.8, 0.85, 1.0) * 2.0;
+
+	vec3 moon_dir = get_moon_dir(time_of_day);
+	float moon_dot = dot(dir, -moon_dir);
+	vec3 moon_halo = pow(max(moon_dot, 0.0), 64.0) * MOON_HALO_COLOR;
+	vec3 moon_surf = smoothstep(0.9990, 0.9993, moon_dot) * MOON_SURF_COLOR;
+	// Outshone by the sky during the day
+	vec3 moon_light = (moon_halo + moon_surf) * clamp(dir.z * 10.0, 0, 1) * max(sun_dir.z, 0.1);
+
+	return sky_color + sun_light + moon_light;
+}
+
+// How much of the view between `cam_pos` and `f_pos` is lost to haze that thickens close to the
+// ground, integrated along the line of sight
+float height_fog(vec3 f_pos, vec3 cam_pos) {
+	float density = fog_params.x;
+	float falloff = fog_params.y;
+	float base_height = fog_params.z;
+
+	float dist = distance(f_pos, cam_pos);
+	float rise = (f_pos.z - cam_pos.z) * falloff;
+	// The average density along the ray, avoiding dividing by zero for level rays
+	float spread = abs(rise) > 0.0001 ? (1.0 - exp(-rise)) / rise : 1.0;
+	float amount = density * exp(-(cam_pos.z - base_height) * falloff) * spread * dist;
+
+	return 1.0 - exp(-amount);
 }
 
 float fog(vec3 f_pos, vec3 focus_pos, uint medium) {
@@ -162,5 +211,8 @@ float fog(vec3 f_pos, vec3 focus_pos, uint medium) {
 	float fog = distance(f_pos.xy, focus_pos.xy) / fog_radius;
 	float mist = distance(f_pos, focus_pos) / mist_radius;
 
-	return pow(clamp((max(fog, mist) - min_fog) / (max_fog - min_fog), 0.0, 1.0), 1.7);
+	// Fade out towards the edge of the view distance, so that missing chunks aren't noticed
+	float edge_fog = pow(clamp((max(fog, mist) - min_fog) / (max_fog - min_fog), 0.0, 1.0), 1.7);
+
+	return max(edge_fog, height_fog(f_pos, cam_pos.xyz));
 }
@@ -0,0 +1,9 @@
+// How far foliage with the given sway (from 0 to 1) at `pos` is bent by the wind, fluttering as
+// gusts pass over it. The shadow pass bends foliage the same way, so that shadows follow it.
+vec3 foliage_sway(vec3 pos, float sway) {
+	float flutter = sin(tick.x * 2.1 + pos.x * 0.3 + pos.y * 0.2) * sin(tick.x * 0.7 + pos.y * 0.1);
+	return sway * vec3(
+		wind.xy * (0.06 + 0.04 * flutter),
+		0.05 * flutter * length(wind.xy)
+	);
+}
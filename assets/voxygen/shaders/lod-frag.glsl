@@ -0,0 +1,32 @@
+#version 330 core
+
+#include <globals.glsl>
+#include <srgb.glsl>
+
+in vec3 f_pos;
+in vec3 f_norm;
+in vec3 f_col;
+
+out vec4 tgt_color;
+
+#include <sky.glsl>
+
+// How far, in blocks, the distant landscape fades into the sky over
+const float LOD_FOG_DIST = 8000.0;
+
+void main() {
+	// The loaded chunks are drawn here instead
+	if (distance(f_pos.xy, focus_pos.xy) < view_distance.x) {
+		discard;
+	}
+
+	vec3 norm = normalize(f_norm);
+	vec3 surf_color = srgb_to_linear(f_col) * get_sun_diffuse(norm, time_of_day.x);
+
+	float far_fog = pow(clamp(distance(f_pos, cam_pos.xyz) / LOD_FOG_DIST, 0.0, 1.0), 1.5);
+	float fog_level = max(far_fog, height_fog(f_pos, cam_pos.xyz));
+	vec3 fog_color = get_sky_color(normalize(f_pos - cam_pos.xyz), time_of_day.x, true);
+	vec3 color = mix(surf_color, fog_color, fog_level);
+
+	tgt_color = vec4(color, 1.0);
+}
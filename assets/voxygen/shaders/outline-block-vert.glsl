@@ -0,0 +1,20 @@
+#version 330 core
+
+#include <globals.glsl>
+
+in vec3 v_pos;
+
+layout (std140)
+uniform u_outline {
+	mat4 outline_mat;
+	vec4 outline_col;
+	vec4 outline_params;
+};
+
+void main() {
+	gl_Position =
+		proj_mat *
+		view_mat *
+		outline_mat *
+		vec4(v_pos, 1);
+}
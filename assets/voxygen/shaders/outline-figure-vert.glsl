@@ -0,0 +1,42 @@
+#version 330 core
+
+#include <globals.glsl>
+
+in vec3 v_pos;
+in vec3 v_norm;
+in vec3 v_col;
+in float v_glow;
+in uint v_bone_idx;
+
+layout (std140)
+uniform u_locals {
+	mat4 model_mat;
+	vec4 model_col;
+};
+
+struct BoneData {
+	mat4 bone_mat;
+};
+
+layout (std140)
+uniform u_bones {
+	// Must match `MAX_BONES` in `render::pipelines::figure`
+	BoneData bones[32];
+};
+
+layout (std140)
+uniform u_outline {
+	mat4 outline_mat;
+	vec4 outline_col;
+	vec4 outline_params;
+};
+
+void main() {
+	mat4 combined_mat = model_mat * bones[v_bone_idx].bone_mat;
+
+	vec3 f_pos = (combined_mat * vec4(v_pos, 1)).xyz;
+	vec3 f_norm = normalize((combined_mat * vec4(v_norm, 0.0)).xyz);
+
+	// Push the surface outwards in world space, so the outline is as thick whatever the figure's scale
+	gl_Position = proj_mat * view_mat * vec4(f_pos + f_norm * outline_params.x, 1);
+}
@@ -0,0 +1,14 @@
+#version 330 core
+
+layout (std140)
+uniform u_outline {
+	mat4 outline_mat;
+	vec4 outline_col;
+	vec4 outline_params;
+};
+
+out vec4 tgt_color;
+
+void main() {
+	tgt_color = outline_col;
+}
@@ -0,0 +1,24 @@
+#version 330 core
+
+#include <globals.glsl>
+
+in vec3 f_pos;
+flat in vec3 f_norm;
+in vec4 f_col;
+
+out vec4 tgt_color;
+
+#include <sky.glsl>
+#include <light.glsl>
+#include <srgb.glsl>
+
+void main() {
+	vec3 light = get_sun_diffuse(f_norm, time_of_day.x) + light_at(f_pos, f_norm);
+	vec3 surf_color = srgb_to_linear(f_col.rgb) * light;
+
+	float fog_level = fog(f_pos.xyz, focus_pos.xyz, medium.x);
+	vec3 fog_color = get_sky_color(normalize(f_pos - cam_pos.xyz), time_of_day.x, true);
+	vec3 color = mix(surf_color, fog_color, fog_level);
+
+	tgt_color = vec4(color, f_col.a);
+}
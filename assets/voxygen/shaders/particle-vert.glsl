@@ -0,0 +1,35 @@
+#version 330 core
+
+#include <globals.glsl>
+
+in vec3 v_pos;
+in vec3 v_norm;
+in vec3 inst_pos;
+in vec3 inst_vel;
+in vec4 inst_col;
+in float inst_time;
+in float inst_lifespan;
+in float inst_size;
+in float inst_gravity;
+
+out vec3 f_pos;
+flat out vec3 f_norm;
+out vec4 f_col;
+
+void main() {
+	// Must match `Instance::pos_at` in `render::pipelines::particle`
+	float age = tick.x - inst_time;
+	vec3 center = inst_pos + inst_vel * age - vec3(0, 0, 0.5 * inst_gravity * age * age);
+
+	// Particles shrink away as they reach the end of their life
+	float life = clamp(1.0 - age / inst_lifespan, 0.0, 1.0);
+
+	f_pos = center + v_pos * inst_size * sqrt(life);
+	f_norm = v_norm;
+	f_col = vec4(inst_col.rgb, inst_col.a * life);
+
+	gl_Position =
+		proj_mat *
+		view_mat *
+		vec4(f_pos, 1);
+}
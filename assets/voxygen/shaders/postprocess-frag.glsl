@@ -1,6 +1,7 @@
 #version 330 core
 
 #include <globals.glsl>
+#include <anti-aliasing.glsl>
 
 uniform sampler2D src_color;
 
@@ -8,7 +9,8 @@ in vec2 f_pos;
 
 layout (std140)
 uniform u_locals {
-	vec4 nul;
+	// x = gamma, y = bloom (0 or 1)
+	vec4 post_params;
 };
 
 out vec4 tgt_color;
@@ -160,15 +162,66 @@ vec3 hsv2rgb(vec3 c) {
     return c.z * mix(K.xxx, clamp(p - K.xxx, 0.0, 1.0), c.y);
 }
 
+// How much brighter than white something has to be before it blooms, and how strongly it does
+const float BLOOM_THRESHOLD = 1.0;
+const float BLOOM_STRENGTH = 0.6;
+
+// Light spilling out of anything brighter than the threshold, gathered from successively blurrier
+// mipmaps of the scene so that it spreads out widely at little cost.
+vec3 bloom(sampler2D tex, vec2 uv) {
+	vec3 glow = vec3(0.0);
+	for (int level = 2; level < 7; level++) {
+		glow += max(textureLod(tex, uv, float(level)).rgb - BLOOM_THRESHOLD, 0.0);
+	}
+	return glow * BLOOM_STRENGTH / 5.0;
+}
+
+// Filmic tonemapping (Narkowicz's fit of the ACES curve), mapping HDR colors into [0, 1]
+vec3 tonemap(vec3 color) {
+	const float a = 2.51;
+	const float b = 0.03;
+	const float c = 2.43;
+	const float d = 0.59;
+	const float e = 0.14;
+	return clamp((color * (a * color + b)) / (color * (c * color + d) + e), 0.0, 1.0);
+}
+
+// The color that everything fades towards when looking through water
+const vec3 UNDERWATER_COLOR = vec3(0.02, 0.12, 0.22);
+
+// Rippling distortion of the view from underwater, with two sets of waves drifting across each
+// other so that the pattern never visibly repeats.
+vec2 underwater_distort(vec2 uv) {
+	vec2 waves = vec2(
+		sin(uv.y * 16.0 + tick.x * 1.3) + sin(uv.y * 37.0 - tick.x * 0.7) * 0.4,
+		sin(uv.x * 24.0 + tick.x) + sin(uv.x * 29.0 + tick.x * 1.7) * 0.4
+	);
+	return clamp(uv + waves * 0.004, 0.0, 1.0);
+}
+
+// Grade the view from underwater: colors wash out and turn towards the color of the water, and
+// the edges of the view darken, so that little can be made out beyond what's nearby.
+vec3 underwater_grade(vec3 color, vec2 uv) {
+	float luma = dot(color, vec3(0.299, 0.587, 0.114));
+	vec3 washed = mix(color, vec3(luma), 0.6);
+	vec3 tinted = mix(washed * vec3(0.4, 0.7, 1.0), UNDERWATER_COLOR, 0.35);
+
+	float vignette = 1.0 - pow(length(uv - 0.5) * 1.3, 2.0);
+	return tinted * clamp(vignette, 0.2, 1.0);
+}
+
 void main() {
 	vec2 uv = (f_pos + 1.0) * 0.5;
 
 	if (medium.x == 1u) {
-		uv = clamp(uv + vec2(sin(uv.y * 16.0 + tick.x), sin(uv.x * 24.0 + tick.x)) * 0.005, 0, 1);
+		uv = underwater_distort(uv);
 	}
 
+#if FXAA
 	vec4 fxaa_color = fxaa_apply(src_color, uv * screen_res.xy, screen_res.xy);
-	//vec4 fxaa_color = texture(src_color, uv);
+#else
+	vec4 fxaa_color = texture(src_color, uv);
+#endif
 
 	vec4 hsva_color = vec4(rgb2hsv(fxaa_color.rgb), fxaa_color.a);
 	hsva_color.y *= 1.45;
@@ -178,8 +231,14 @@ void main() {
     //vec4 final_color = vec4(hsv2rgb(hsva_color.rgb), hsva_color.a);
 
 	if (medium.x == 1u) {
-		final_color *= vec4(0.2, 0.2, 0.8, 1.0);
+		final_color.rgb = underwater_grade(final_color.rgb, uv);
 	}
 
+	if (post_params.y > 0.5) {
+		final_color.rgb += bloom(src_color, uv);
+	}
+
+	final_color.rgb = pow(tonemap(final_color.rgb), vec3(1.0 / post_params.x));
+
 	tgt_color = vec4(final_color.rgb, 1);
 }
@@ -0,0 +1,20 @@
+#version 330 core
+
+#include <anti-aliasing.glsl>
+
+uniform sampler2DMS src_color;
+
+out vec4 tgt_color;
+
+// Average the samples of each pixel of the multisampled scene, so that it can be sampled like any
+// other texture from then on.
+void main() {
+	ivec2 texel = ivec2(gl_FragCoord.xy);
+
+	vec4 color = vec4(0.0);
+	for (int i = 0; i < MSAA_SAMPLES; i++) {
+		color += texelFetch(src_color, texel, i);
+	}
+
+	tgt_color = color / float(MSAA_SAMPLES);
+}
@@ -0,0 +1,34 @@
+#version 330 core
+
+in vec3 v_pos;
+in vec3 v_norm;
+in vec3 v_col;
+in float v_glow;
+in uint v_bone_idx;
+
+layout (std140)
+uniform u_locals {
+	mat4 model_mat;
+	vec4 model_col;
+};
+
+struct BoneData {
+	mat4 bone_mat;
+};
+
+layout (std140)
+uniform u_bones {
+	// Must match `MAX_BONES` in `render::pipelines::figure`
+	BoneData bones[32];
+};
+
+layout (std140)
+uniform u_shadow_locals {
+	mat4 shadow_mat;
+};
+
+void main() {
+	vec3 f_pos = (model_mat * bones[v_bone_idx].bone_mat * vec4(v_pos, 1)).xyz;
+
+	gl_Position = shadow_mat * vec4(f_pos, 1);
+}
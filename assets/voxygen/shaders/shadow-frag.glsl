@@ -0,0 +1,4 @@
+#version 330 core
+
+// Only depth is written to the shadow map, so there's nothing to do here
+void main() {}
@@ -0,0 +1,29 @@
+#version 330 core
+
+#include <globals.glsl>
+#include <sway.glsl>
+
+in uint v_pos_norm;
+in uint v_col_light;
+in uint v_sway;
+
+layout (std140)
+uniform u_locals {
+	vec3 model_offs;
+};
+
+layout (std140)
+uniform u_shadow_locals {
+	mat4 shadow_mat;
+};
+
+void main() {
+	vec3 f_pos = vec3(
+		float((v_pos_norm >>  0) & 0x00FFu),
+		float((v_pos_norm >>  8) & 0x00FFu),
+		float((v_pos_norm >> 16) & 0x1FFFu)
+	) + model_offs;
+	f_pos += foliage_sway(f_pos, float(v_sway) / 255.0);
+
+	gl_Position = shadow_mat * vec4(f_pos, 1);
+}
@@ -6,17 +6,23 @@
 in vec3 v_pos;
 in vec3 v_norm;
 in vec3 v_col;
+in vec2 v_uv;
 in vec4 inst_mat0;
 in vec4 inst_mat1;
 in vec4 inst_mat2;
 in vec4 inst_mat3;
 in vec3 inst_col;
 in float inst_wind_sway;
+in float inst_wind_phase;
+in float inst_wind_twist;
+in float inst_fade;
 
 out vec3 f_pos;
 flat out vec3 f_norm;
 out vec3 f_col;
+out vec2 f_uv;
 out float f_light;
+out float f_fade;
 
 const float SCALE = 1.0 / 11.0;
 
@@ -27,20 +33,30 @@ void main() {
 	inst_mat[2] = inst_mat2;
 	inst_mat[3] = inst_mat3;
 
-	f_pos = (inst_mat * vec4(v_pos * SCALE, 1)).xyz;
+	// Wind twisting about the sprite's up axis
+	float twist = inst_wind_twist * sin(tick.x * 2.3 + inst_mat3.x * 0.7 + inst_mat3.y * 0.3) * sin(tick.x * 0.45);
+	mat3 twist_mat = mat3(
+		cos(twist), sin(twist), 0,
+		-sin(twist), cos(twist), 0,
+		0, 0, 1
+	);
+
+	f_pos = (inst_mat * vec4(twist_mat * v_pos * SCALE, 1)).xyz;
 
 	// Wind waving
 	f_pos += inst_wind_sway * vec3(
-		sin(tick.x * 1.5 + f_pos.y * 0.1) * sin(tick.x * 0.35),
-		sin(tick.x * 1.5 + f_pos.x * 0.1) * sin(tick.x * 0.25),
+		sin(tick.x * 1.5 + f_pos.y * 0.1 + inst_wind_phase) * sin(tick.x * 0.35),
+		sin(tick.x * 1.5 + f_pos.x * 0.1 + inst_wind_phase) * sin(tick.x * 0.25),
 		0.0
 	) * pow(abs(v_pos.z) * SCALE, 1.3) * 0.2;
 
-	f_norm = (inst_mat * vec4(v_norm, 0)).xyz;
+	f_norm = (inst_mat * vec4(twist_mat * v_norm, 0)).xyz;
 
 	f_col = srgb_to_linear(v_col) * srgb_to_linear(inst_col);
+	f_uv = v_uv;
 
 	f_light = 1.0;
+	f_fade = inst_fade;
 
 	gl_Position =
 		proj_mat *
@@ -16,9 +16,10 @@ out vec4 tgt_color;
 
 #include <sky.glsl>
 #include <light.glsl>
+#include <shadow.glsl>
 
 void main() {
-	vec3 light = get_sun_diffuse(f_norm, time_of_day.x) * f_light + light_at(f_pos, f_norm);
+	vec3 light = get_sun_diffuse(f_norm, time_of_day.x, shadow_at(f_pos, f_norm)) * f_light + light_at(f_pos, f_norm);
 	vec3 surf_color = f_col * light;
 
 	float fog_level = fog(f_pos.xyz, focus_pos.xyz, medium.x);
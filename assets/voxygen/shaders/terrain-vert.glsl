@@ -2,9 +2,11 @@
 
 #include <globals.glsl>
 #include <srgb.glsl>
+#include <sway.glsl>
 
 in uint v_pos_norm;
 in uint v_col_light;
+in uint v_sway;
 
 layout (std140)
 uniform u_locals {
@@ -26,6 +28,9 @@ void main() {
 		float((v_pos_norm >> 16) & 0x1FFFu)
 	) + model_offs;
 
+	// Foliage bends with the wind
+	f_pos += foliage_sway(f_pos, float(v_sway) / 255.0);
+
 	// TODO: last 3 bits in v_pos_norm should be a number between 0 and 5, rather than 0-2 and a direction.
 	uint norm_axis = (v_pos_norm >> 30) & 0x3u;
 
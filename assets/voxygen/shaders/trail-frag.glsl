@@ -0,0 +1,18 @@
+#version 330 core
+
+#include <globals.glsl>
+
+in vec3 f_pos;
+in vec4 f_col;
+
+out vec4 tgt_color;
+
+#include <sky.glsl>
+
+void main() {
+	float fog_level = fog(f_pos.xyz, focus_pos.xyz, medium.x);
+	vec3 fog_color = get_sky_color(normalize(f_pos - cam_pos.xyz), time_of_day.x, true);
+
+	// Trails fade into the fog, rather than taking on its colour
+	tgt_color = vec4(mix(f_col.rgb, fog_color, fog_level), f_col.a * (1.0 - fog_level));
+}
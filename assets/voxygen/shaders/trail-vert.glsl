@@ -0,0 +1,19 @@
+#version 330 core
+
+#include <globals.glsl>
+
+in vec3 v_pos;
+in vec4 v_col;
+
+out vec3 f_pos;
+out vec4 f_col;
+
+void main() {
+	f_pos = v_pos;
+	f_col = v_col;
+
+	gl_Position =
+		proj_mat *
+		view_mat *
+		vec4(f_pos, 1);
+}
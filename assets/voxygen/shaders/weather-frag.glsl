@@ -0,0 +1,28 @@
+#version 330 core
+
+#include <globals.glsl>
+
+in vec3 f_pos;
+in float f_edge;
+
+layout (std140)
+uniform u_locals {
+	vec4 weather_col;
+	vec4 weather_box;
+	vec4 weather_drops;
+};
+
+out vec4 tgt_color;
+
+#include <sky.glsl>
+#include <srgb.glsl>
+
+void main() {
+	// Drops are lit by the sky they fall through
+	vec3 light = get_sun_diffuse(vec3(0, 0, 1), time_of_day.x);
+	vec3 color = srgb_to_linear(weather_col.rgb) * light;
+
+	float alpha = weather_col.a * (1.0 - smoothstep(0.6, 1.0, f_edge));
+
+	tgt_color = vec4(color, alpha);
+}
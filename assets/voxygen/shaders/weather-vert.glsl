@@ -0,0 +1,44 @@
+#version 330 core
+
+#include <globals.glsl>
+
+in vec2 v_pos;
+in vec3 inst_pos;
+
+layout (std140)
+uniform u_locals {
+	vec4 weather_col;
+	vec4 weather_box;
+	vec4 weather_drops;
+};
+
+out vec3 f_pos;
+out float f_edge;
+
+void main() {
+	vec3 box_size = weather_box.xyz;
+
+	// Drops are carried along by the wind as they fall
+	vec3 vel = vec3(wind.xy, -weather_drops.x);
+	vec3 start = inst_pos * box_size;
+	vec3 drop_pos = start + vel * tick.x;
+	drop_pos.xy += sin(tick.x + inst_pos.yx * 40.0) * weather_drops.w;
+
+	// Wrap the drop around into the box, so that it's always falling somewhere near the camera
+	vec3 box_min = cam_pos.xyz - box_size * 0.5;
+	drop_pos = box_min + mod(drop_pos - box_min, box_size);
+
+	// Stretch the drop out along its path, and turn it to face the camera
+	vec3 along = normalize(vel);
+	vec3 across = normalize(cross(along, drop_pos - cam_pos.xyz));
+	f_pos = drop_pos + across * v_pos.x * weather_drops.y - along * v_pos.y * weather_drops.z;
+
+	// How close the drop is to the sides of the box, where it should fade out rather than pop
+	vec2 from_centre = abs(drop_pos.xy - cam_pos.xy) / box_size.xy;
+	f_edge = max(from_centre.x, from_centre.y) * 2.0;
+
+	gl_Position =
+		proj_mat *
+		view_mat *
+		vec4(f_pos, 1);
+}
@@ -12,7 +12,7 @@ use common::{
     msg::{ClientMsg, ClientState, RequestStateError, ServerError, ServerInfo, ServerMsg},
     net::PostBox,
     state::{State, Uid},
-    terrain::{block::Block, TerrainChunk, TerrainChunkSize},
+    terrain::{block::Block, LodMap, TerrainChunk, TerrainChunkSize},
     vol::RectVolSize,
     ChatType,
 };
@@ -54,6 +54,7 @@ pub struct Client {
     loaded_distance: Option<u32>,
 
     pending_chunks: HashMap<Vec2<i32>, Instant>,
+    lod: LodMap,
 }
 
 impl Client {
@@ -64,11 +65,12 @@ impl Client {
         let mut postbox = PostBox::to(addr)?;
 
         // Wait for initial sync
-        let (state, entity, server_info) = match postbox.next_message() {
+        let (state, entity, server_info, lod) = match postbox.next_message() {
             Some(ServerMsg::InitialSync {
                 ecs_state,
                 entity_uid,
                 server_info,
+                lod,
             }) => {
                 // TODO: Voxygen should display this.
                 if server_info.git_hash != common::util::GIT_HASH.to_string() {
@@ -84,7 +86,7 @@ impl Client {
                     .ecs()
                     .entity_from_uid(entity_uid)
                     .ok_or(Error::ServerWentMad)?;
-                (state, entity, server_info)
+                (state, entity, server_info, lod)
             }
             Some(ServerMsg::Error(ServerError::TooManyPlayers)) => {
                 return Err(Error::TooManyPlayers)
@@ -117,6 +119,7 @@ impl Client {
             loaded_distance: None,
 
             pending_chunks: HashMap::new(),
+            lod,
         })
     }
 
@@ -210,6 +213,11 @@ impl Client {
         self.loaded_distance
     }
 
+    /// The coarse heightmap of the whole world the server sent on connecting.
+    pub fn lod(&self) -> &LodMap {
+        &self.lod
+    }
+
     pub fn current_chunk(&self) -> Option<Arc<TerrainChunk>> {
         let chunk_pos = Vec2::from(
             self.state
@@ -461,6 +469,9 @@ impl Client {
                     ServerMsg::EcsSync(sync_package) => {
                         self.state.ecs_mut().sync_with_package(sync_package)
                     }
+                    ServerMsg::Weather(weather) => {
+                        self.state.set_weather(weather);
+                    }
                     ServerMsg::EntityPos { entity, pos } => {
                         if let Some(entity) = self.state.ecs().entity_from_uid(entity) {
                             self.state.write_component(entity, pos);
@@ -0,0 +1,40 @@
+#[macro_use]
+extern crate criterion;
+
+use criterion::black_box;
+use criterion::Criterion;
+
+use vek::*;
+use veloren_common::{
+    figure::{cell::Cell, Segment},
+    vol::*,
+};
+
+const SIZE: u32 = 128;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    // Setup: Create a large segment filled with a gradient of colors.
+    let mut segment = Segment::filled(Vec3::broadcast(SIZE), Cell::empty(), ());
+    for pos in segment.full_pos_iter() {
+        let col = pos.map(|e| (e * 2) as u8);
+        segment
+            .set(pos, Cell::new(Rgb::new(col.x, col.y, col.z)))
+            .unwrap();
+    }
+
+    let recolor = |rgb: Rgb<u8>| {
+        let lum = (rgb.r as f32 * 0.3 + rgb.g as f32 * 0.6 + rgb.b as f32 * 0.1) / 255.0;
+        Rgb::new(255, 128, 64).map(|e| (e as f32 * lum.powf(1.0 / 2.2)) as u8)
+    };
+
+    c.bench_function("segment: serial recolor", |b| {
+        b.iter(|| black_box(segment.clone().map_rgb(recolor)))
+    });
+
+    c.bench_function("segment: parallel recolor", |b| {
+        b.iter(|| black_box(segment.par_map_rgb(recolor)))
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
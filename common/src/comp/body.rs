@@ -1,3 +1,5 @@
+pub mod bird_medium;
+pub mod fish_small;
 pub mod humanoid;
 pub mod object;
 pub mod quadruped;
@@ -11,6 +13,8 @@ pub enum Body {
     Humanoid(humanoid::Body),
     Quadruped(quadruped::Body),
     QuadrupedMedium(quadruped_medium::Body),
+    BirdMedium(bird_medium::Body),
+    FishSmall(fish_small::Body),
     Object(object::Body),
 }
 
@@ -21,6 +25,23 @@ impl Body {
             _ => false,
         }
     }
+
+    /// Whether the body has wings to fly with.
+    pub fn can_fly(&self) -> bool {
+        match self {
+            Body::BirdMedium(_) => true,
+            _ => false,
+        }
+    }
+
+    /// The name of the bone of the body's skeleton that riders sit on, if it can be ridden.
+    pub fn mount_bone(&self) -> Option<&'static str> {
+        match self {
+            Body::Quadruped(_) => Some("pig_chest"),
+            Body::QuadrupedMedium(_) => Some("wolf_torso_mid"),
+            _ => None,
+        }
+    }
 }
 
 impl Component for Body {
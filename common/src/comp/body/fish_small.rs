@@ -0,0 +1,24 @@
+use rand::{seq::SliceRandom, thread_rng};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Body {
+    pub species: Species,
+}
+
+impl Body {
+    pub fn random() -> Self {
+        let mut rng = thread_rng();
+        Self {
+            species: *(&ALL_SPECIES).choose(&mut rng).unwrap(),
+        }
+    }
+}
+
+/// Each species has its own head, body and tail fin models, which share a skeleton.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Species {
+    Carp,
+    Trout,
+    Eel,
+}
+const ALL_SPECIES: [Species; 3] = [Species::Carp, Species::Trout, Species::Eel];
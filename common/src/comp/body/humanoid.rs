@@ -199,6 +199,14 @@ impl Race {
     pub fn num_eye_colors(self) -> usize {
         self.eye_colors().len()
     }
+    /// How brightly the eyes of this race glow in the dark.
+    pub fn eye_glow(self) -> f32 {
+        match self {
+            Race::Undead => 0.6,
+            Race::Danari => 0.25,
+            _ => 0.0,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
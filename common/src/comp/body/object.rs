@@ -1,4 +1,4 @@
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{seq::IteratorRandom, thread_rng};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Body {
@@ -54,11 +54,23 @@ pub enum Body {
 impl Body {
     pub fn random() -> Self {
         let mut rng = thread_rng();
-        *(&ALL_OBJECTS).choose(&mut rng).unwrap()
+        *ALL_OBJECTS
+            .iter()
+            .filter(|body| body.spawns_randomly())
+            .choose(&mut rng)
+            .unwrap()
+    }
+
+    /// Whether `random` may pick this object. Pouches only exist to hold dropped items.
+    pub fn spawns_randomly(self) -> bool {
+        match self {
+            Body::Pouch => false,
+            _ => true,
+        }
     }
 }
 
-const ALL_OBJECTS: [Body; 46] = [
+pub const ALL_OBJECTS: [Body; 47] = [
     Body::Bomb,
     Body::Scarecrow,
     Body::Cauldron,
@@ -105,4 +117,25 @@ const ALL_OBJECTS: [Body; 46] = [
     Body::CarpetHumanSquare,
     Body::CarpetHumanSquare2,
     Body::CarpetHumanSquircle,
+    Body::Pouch,
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pouches_do_not_spawn_randomly() {
+        assert!(!Body::Pouch.spawns_randomly());
+        assert_eq!(
+            ALL_OBJECTS
+                .iter()
+                .filter(|body| body.spawns_randomly())
+                .count(),
+            ALL_OBJECTS.len() - 1
+        );
+        for _ in 0..1000 {
+            assert_ne!(Body::random(), Body::Pouch);
+        }
+    }
+}
@@ -54,6 +54,8 @@ impl Component for MountState {
     type Storage = FlaggedStorage<Self, IDVStorage<Self>>;
 }
 
+/// Riding another entity, whose uid is given. Riders are carried along on their mount's
+/// `Body::mount_bone`.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Mounting(pub Uid);
 
@@ -0,0 +1,45 @@
+use specs::{Component, FlaggedStorage};
+use specs_idvs::IDVStorage;
+
+/// Something a character does to express themselves, until they next move or act.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Emote {
+    Wave,
+    Dance,
+}
+
+pub const ALL_EMOTES: [Emote; 2] = [Emote::Wave, Emote::Dance];
+
+impl Emote {
+    /// The name used to perform the emote from chat.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Emote::Wave => "wave",
+            Emote::Dance => "dance",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ALL_EMOTES
+            .iter()
+            .find(|emote| emote.name() == name)
+            .copied()
+    }
+}
+
+impl Component for Emote {
+    type Storage = FlaggedStorage<Self, IDVStorage<Self>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emote_names() {
+        for emote in ALL_EMOTES.iter() {
+            assert_eq!(Emote::from_name(emote.name()), Some(*emote));
+        }
+        assert_eq!(Emote::from_name("stop"), None);
+    }
+}
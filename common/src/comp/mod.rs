@@ -3,6 +3,7 @@ mod agent;
 mod body;
 mod character_state;
 mod controller;
+mod emote;
 mod inputs;
 mod inventory;
 mod last;
@@ -14,9 +15,10 @@ mod visual;
 // Reexports
 pub use admin::Admin;
 pub use agent::Agent;
-pub use body::{humanoid, object, quadruped, quadruped_medium, Body};
+pub use body::{bird_medium, fish_small, humanoid, object, quadruped, quadruped_medium, Body};
 pub use character_state::{ActionState, CharacterState, MovementState};
 pub use controller::{ControlEvent, Controller, MountState, Mounting};
+pub use emote::{Emote, ALL_EMOTES};
 pub use inputs::CanBuild;
 pub use inventory::{item, Inventory, InventoryUpdate, Item};
 pub use last::Last;
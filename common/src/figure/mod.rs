@@ -5,6 +5,7 @@ pub use mat_cell::Material;
 use self::cell::Cell;
 use self::mat_cell::MatCell;
 use crate::{
+    util::{linear_to_srgb, rgb_to_hsv, srgb_to_linear},
     vol::{IntoFullPosIterator, IntoFullVolIterator, ReadVol, SizedVol, Vox, WriteVol},
     volumes::dyna::Dyna,
 };
@@ -64,6 +65,194 @@ impl Segment {
     pub fn map_rgb(self, transform: impl Fn(Rgb<u8>) -> Rgb<u8>) -> Self {
         self.map(|cell| cell.get_color().map(|rgb| Cell::new(transform(rgb))))
     }
+
+    /// Transform cell colors in parallel, which is considerably faster for large segments
+    pub fn par_map_rgb(&self, transform: impl Fn(Rgb<u8>) -> Rgb<u8> + Sync) -> Self {
+        let mut segment = self.clone();
+        segment.par_map_vox(|cell| match cell.get_color() {
+            Some(rgb) => Cell::new(transform(rgb)),
+            None => *cell,
+        });
+        segment
+    }
+
+    /// Recolor the region of same-colored voxels connected (by faces) to the `seed` voxel,
+    /// leaving the rest of the segment untouched
+    pub fn flood_recolor(&self, seed: Vec3<i32>, new_color: Rgb<u8>) -> Self {
+        let mut segment = self.clone();
+        let target = match self.get(seed).ok().and_then(|cell| cell.get_color()) {
+            Some(rgb) => rgb,
+            None => return segment,
+        };
+        if target == new_color {
+            return segment;
+        }
+
+        let mut stack = vec![seed];
+        while let Some(pos) = stack.pop() {
+            match segment.get(pos).ok().and_then(|cell| cell.get_color()) {
+                Some(rgb) if rgb == target => {}
+                _ => continue,
+            }
+            segment.set(pos, Cell::new(new_color)).unwrap();
+
+            for dir in [
+                Vec3::unit_x(),
+                -Vec3::unit_x(),
+                Vec3::unit_y(),
+                -Vec3::unit_y(),
+                Vec3::unit_z(),
+                -Vec3::unit_z(),
+            ]
+            .iter()
+            {
+                stack.push(pos + *dir);
+            }
+        }
+
+        segment
+    }
+
+    /// The most common hue among the voxels of the segment, in degrees. Each voxel's hue is
+    /// weighted by its saturation so that greys and near-greys don't skew the result.
+    pub fn dominant_hue(&self) -> f32 {
+        const BINS: usize = 36;
+        const BIN_SIZE: f32 = 360.0 / BINS as f32;
+
+        // Total weight and weighted hue sum for each bin
+        let mut bins = [(0.0f32, 0.0f32); BINS];
+        for (_, cell) in self.full_vol_iter() {
+            if let Some(rgb) = cell.get_color() {
+                let hsv = rgb_to_hsv(rgb.map(|e| e as f32 / 255.0));
+                let bin = ((hsv.x / BIN_SIZE) as usize).min(BINS - 1);
+                bins[bin].0 += hsv.y;
+                bins[bin].1 += hsv.x * hsv.y;
+            }
+        }
+
+        bins.iter()
+            .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            .filter(|(weight, _)| *weight > 0.0)
+            .map(|(weight, hue_sum)| hue_sum / weight)
+            .unwrap_or(0.0)
+    }
+
+    /// Darken voxels that sit on an edge or corner of the figure (i.e: those that have more than
+    /// one face exposed to empty space), baking a cheap outline into the colors
+    pub fn edge_darken(&self, amount: f32) -> Self {
+        let mut segment = self.clone();
+        let factor = 1.0 - amount.min(1.0).max(0.0);
+        for (pos, cell) in self.full_vol_iter() {
+            let rgb = match cell.get_color() {
+                Some(rgb) => rgb,
+                None => continue,
+            };
+            let exposed_faces = [
+                Vec3::unit_x(),
+                -Vec3::unit_x(),
+                Vec3::unit_y(),
+                -Vec3::unit_y(),
+                Vec3::unit_z(),
+                -Vec3::unit_z(),
+            ]
+            .iter()
+            .filter(|dir| self.get(pos + **dir).map(|c| c.is_empty()).unwrap_or(true))
+            .count();
+
+            if exposed_faces > 1 {
+                let darkened =
+                    linear_to_srgb(srgb_to_linear(rgb.map(|e| e as f32 / 255.0)) * factor);
+                segment
+                    .set(pos, Cell::new(darkened.map(|e| (e * 255.0).round() as u8)))
+                    .unwrap();
+            }
+        }
+
+        segment
+    }
+
+    /// Shrink the segment by `factor` along each axis for use as a lower level of detail, where
+    /// each cell of the result covers a `factor`^3 block of the original. A block is filled if any
+    /// of its cells are, with their colors averaged in linear space so that distant figures don't
+    /// darken as they would with naive sRGB averaging.
+    pub fn downsample_linear(&self, factor: usize) -> Self {
+        let factor = factor.max(1) as u32;
+        let size = self.size().map(|e| (e + factor - 1) / factor);
+
+        // Summed linear color and filled cell count for each block
+        let mut blocks = vec![(Rgb::<f32>::zero(), 0u32); size.product() as usize];
+        for (pos, cell) in self.full_vol_iter() {
+            if let Some(rgb) = cell.get_color() {
+                let block = pos.map(|e| e as u32 / factor);
+                let idx = (block.x + (block.y + block.z * size.y) * size.x) as usize;
+                blocks[idx].0 += srgb_to_linear(rgb.map(|e| e as f32 / 255.0));
+                blocks[idx].1 += 1;
+            }
+        }
+
+        let mut segment = Segment::filled(size, Cell::empty(), ());
+        for pos in segment.full_pos_iter() {
+            let idx = (pos.x + (pos.y + pos.z * size.y as i32) * size.x as i32) as usize;
+            let (sum, count) = blocks[idx];
+            if count > 0 {
+                let rgb = linear_to_srgb(sum / count as f32)
+                    .map(|e| (e.min(1.0).max(0.0) * 255.0).round() as u8);
+                segment.set(pos, Cell::new(rgb)).unwrap();
+            }
+        }
+
+        segment
+    }
+
+    /// Compress the segment into runs of identical cells
+    pub fn to_rle(&self) -> SegmentRle {
+        let mut runs: Vec<(u32, Cell)> = Vec::new();
+        for (_, cell) in self.full_vol_iter() {
+            if let Some((len, last)) = runs.last_mut() {
+                if *last == *cell {
+                    *len += 1;
+                    continue;
+                }
+            }
+            runs.push((1, *cell));
+        }
+
+        SegmentRle {
+            size: self.size(),
+            runs,
+        }
+    }
+
+    /// Expand a run-length-encoded segment back into a full segment
+    pub fn from_rle(rle: &SegmentRle) -> Self {
+        let mut segment = Segment::filled(rle.size, Cell::empty(), ());
+        let cells = rle
+            .runs
+            .iter()
+            .flat_map(|(len, cell)| std::iter::repeat(*cell).take(*len as usize));
+        for (pos, cell) in segment.full_pos_iter().zip(cells) {
+            segment.set(pos, cell).unwrap();
+        }
+
+        segment
+    }
+}
+
+/// A compact, run-length-encoded form of a `Segment`.
+///
+/// Runs follow the iteration order of `full_vol_iter`, so most of the empty space around a figure
+/// collapses into a handful of runs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SegmentRle {
+    size: Vec3<u32>,
+    runs: Vec<(u32, Cell)>,
+}
+
+impl SegmentRle {
+    /// Number of runs in the encoding
+    pub fn run_count(&self) -> usize {
+        self.runs.len()
+    }
 }
 
 // TODO: move
@@ -178,3 +367,145 @@ impl From<&DotVoxData> for MatSegment {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_segment() -> Segment {
+        let mut segment = Segment::filled(Vec3::new(8, 8, 8), Cell::empty(), ());
+        segment
+            .set(Vec3::new(1, 2, 3), Cell::new(Rgb::new(255, 0, 0)))
+            .unwrap();
+        segment
+            .set(Vec3::new(2, 2, 3), Cell::new(Rgb::new(255, 0, 0)))
+            .unwrap();
+        segment
+            .set(Vec3::new(5, 6, 7), Cell::new(Rgb::new(0, 0, 255)))
+            .unwrap();
+        segment
+    }
+
+    #[test]
+    fn rle_round_trip() {
+        let segment = test_segment();
+        let decoded = Segment::from_rle(&segment.to_rle());
+
+        assert_eq!(decoded.size(), segment.size());
+        for (pos, cell) in segment.full_vol_iter() {
+            assert_eq!(decoded.get(pos).unwrap(), cell);
+        }
+    }
+
+    #[test]
+    fn edge_darken() {
+        let color = Rgb::new(200, 100, 50);
+        let segment = Segment::filled(Vec3::new(3, 3, 3), Cell::new(color), ()).edge_darken(0.5);
+
+        assert_eq!(
+            segment.get(Vec3::new(1, 1, 1)).unwrap().get_color(),
+            Some(color)
+        );
+        let corner = segment
+            .get(Vec3::new(0, 0, 0))
+            .unwrap()
+            .get_color()
+            .unwrap();
+        assert!(corner.r < color.r && corner.g < color.g && corner.b < color.b);
+    }
+
+    #[test]
+    fn rle_is_compact() {
+        let segment = test_segment();
+        assert!(segment.to_rle().run_count() < segment.size().product() as usize / 10);
+    }
+
+    #[test]
+    fn par_map_rgb_matches_serial() {
+        let segment = test_segment();
+        let transform = |rgb: Rgb<u8>| Rgb::new(rgb.g, rgb.b / 2, rgb.r);
+
+        let parallel = segment.par_map_rgb(transform);
+        let serial = segment.map_rgb(transform);
+        for (pos, cell) in serial.full_vol_iter() {
+            assert_eq!(parallel.get(pos).unwrap(), cell);
+        }
+    }
+
+    #[test]
+    fn dominant_hue() {
+        // Mostly orange, with a little blue and some grey
+        let mut segment = Segment::filled(Vec3::new(4, 4, 4), Cell::new(Rgb::new(255, 128, 0)), ());
+        for x in 0..4 {
+            segment
+                .set(Vec3::new(x, 0, 0), Cell::new(Rgb::new(0, 0, 255)))
+                .unwrap();
+            segment
+                .set(Vec3::new(x, 1, 0), Cell::new(Rgb::new(90, 90, 90)))
+                .unwrap();
+        }
+
+        let orange_hue = rgb_to_hsv(Rgb::new(1.0, 128.0 / 255.0, 0.0)).x;
+        assert!((segment.dominant_hue() - orange_hue).abs() < 1.0);
+    }
+
+    #[test]
+    fn flood_recolor() {
+        let red = Rgb::new(255, 0, 0);
+        let green = Rgb::new(0, 255, 0);
+        let segment = test_segment().flood_recolor(Vec3::new(1, 2, 3), green);
+
+        // The connected red voxels are recolored...
+        assert_eq!(
+            segment.get(Vec3::new(1, 2, 3)).unwrap().get_color(),
+            Some(green)
+        );
+        assert_eq!(
+            segment.get(Vec3::new(2, 2, 3)).unwrap().get_color(),
+            Some(green)
+        );
+        // ...but separate regions are left alone
+        assert_eq!(
+            segment.get(Vec3::new(5, 6, 7)).unwrap().get_color(),
+            Some(Rgb::new(0, 0, 255))
+        );
+
+        let mut two_regions = test_segment();
+        two_regions.set(Vec3::new(6, 6, 7), Cell::new(red)).unwrap();
+        let segment = two_regions.flood_recolor(Vec3::new(6, 6, 7), green);
+        assert_eq!(
+            segment.get(Vec3::new(6, 6, 7)).unwrap().get_color(),
+            Some(green)
+        );
+        assert_eq!(
+            segment.get(Vec3::new(1, 2, 3)).unwrap().get_color(),
+            Some(red)
+        );
+    }
+
+    #[test]
+    fn downsample_linear() {
+        let color = Rgb::new(200, 100, 50);
+        let segment = Segment::filled(Vec3::new(4, 4, 5), Cell::new(color), ());
+        let lod = segment.downsample_linear(2);
+
+        // Uniform colors come through unchanged, and partial blocks are kept
+        assert_eq!(lod.size(), Vec3::new(2, 2, 3));
+        for (_, cell) in lod.full_vol_iter() {
+            assert_eq!(cell.get_color(), Some(color));
+        }
+
+        // Averaging black and white in linear space gives a lighter grey than sRGB averaging
+        let mut checker = Segment::filled(Vec3::new(2, 1, 1), Cell::new(Rgb::zero()), ());
+        checker
+            .set(Vec3::new(1, 0, 0), Cell::new(Rgb::broadcast(255)))
+            .unwrap();
+        let grey = checker
+            .downsample_linear(2)
+            .get(Vec3::zero())
+            .unwrap()
+            .get_color()
+            .unwrap();
+        assert!(grey.r > 128 && grey.r == grey.g && grey.g == grey.b);
+    }
+}
@@ -9,6 +9,7 @@ sphynx::sum_type! {
     pub enum EcsResPacket {
         Time(state::Time),
         TimeOfDay(state::TimeOfDay),
+        Weather(state::Weather),
     }
 }
 impl sphynx::ResPacket for EcsResPacket {}
@@ -29,6 +30,7 @@ sphynx::sum_type! {
         Scale(comp::Scale),
         MountState(comp::MountState),
         Mounting(comp::Mounting),
+        Emote(comp::Emote),
     }
 }
 // Automatically derive From<T> for EcsCompPhantom
@@ -48,6 +50,7 @@ sphynx::sum_type! {
         Scale(PhantomData<comp::Scale>),
         MountState(PhantomData<comp::MountState>),
         Mounting(PhantomData<comp::Mounting>),
+        Emote(PhantomData<comp::Emote>),
     }
 }
 impl sphynx::CompPacket for EcsCompPacket {
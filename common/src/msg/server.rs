@@ -1,7 +1,7 @@
 use super::{ClientState, EcsCompPacket, EcsResPacket};
 use crate::{
-    comp,
-    terrain::{Block, TerrainChunk},
+    comp, state,
+    terrain::{Block, LodMap, TerrainChunk},
     ChatType,
 };
 use hashbrown::HashMap;
@@ -28,6 +28,7 @@ pub enum ServerMsg {
         ecs_state: sphynx::StatePackage<EcsCompPacket, EcsResPacket>,
         entity_uid: u64,
         server_info: ServerInfo,
+        lod: LodMap,
     },
     StateAnswer(Result<ClientState, (RequestStateError, ClientState)>),
     ForceState(ClientState),
@@ -39,6 +40,7 @@ pub enum ServerMsg {
     },
     SetPlayerEntity(u64),
     EcsSync(sphynx::SyncPackage<EcsCompPacket, EcsResPacket>),
+    Weather(state::Weather),
     EntityPos {
         entity: u64,
         pos: comp::Pos,
@@ -10,6 +10,8 @@ pub enum NpcKind {
     Humanoid,
     Wolf,
     Pig,
+    Bird,
+    Fish,
 }
 
 impl NpcKind {
@@ -18,6 +20,8 @@ impl NpcKind {
             NpcKind::Humanoid => "humanoid",
             NpcKind::Wolf => "wolf",
             NpcKind::Pig => "pig",
+            NpcKind::Bird => "bird",
+            NpcKind::Fish => "fish",
         }
     }
 }
@@ -30,6 +34,8 @@ impl FromStr for NpcKind {
             "humanoid" => Ok(NpcKind::Humanoid),
             "wolf" => Ok(NpcKind::Wolf),
             "pig" => Ok(NpcKind::Pig),
+            "bird" => Ok(NpcKind::Bird),
+            "fish" => Ok(NpcKind::Fish),
             _ => Err(()),
         }
     }
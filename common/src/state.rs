@@ -6,7 +6,7 @@ use crate::{
     event::{EventBus, LocalEvent, ServerEvent},
     msg::{EcsCompPacket, EcsResPacket},
     sys,
-    terrain::{Block, TerrainChunk, TerrainGrid},
+    terrain::{Block, TerrainChunk, TerrainChunkMeta, TerrainGrid},
     vol::WriteVol,
 };
 use hashbrown::{HashMap, HashSet};
@@ -30,6 +30,50 @@ const DAY_CYCLE_FACTOR: f64 = 24.0 * 2.0;
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TimeOfDay(pub f64);
 
+/// Below this temperature (see `TerrainChunkMeta::temp`), it snows rather than rains.
+const FREEZING_TEMP: f32 = -0.4;
+
+/// What falls from the sky in bad weather.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Precipitation {
+    Rain,
+    Snow,
+}
+
+/// How far the precipitation has to drift from what clients were last sent before they're sent
+/// it again.
+const WEATHER_SYNC_THRESHOLD: f32 = 0.05;
+
+/// A resource that stores the world's weather. The server changes it over time, and clients are
+/// kept in sync with it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Weather {
+    /// How heavily it's raining or snowing, from 0.0 (clear skies) to 1.0 (a downpour).
+    pub precipitation: f32,
+}
+
+impl Weather {
+    /// What's falling on a chunk, and how heavily, depending on its biome and how cold it is.
+    pub fn precipitation_in(&self, meta: &TerrainChunkMeta) -> Option<(Precipitation, f32)> {
+        let intensity = (self.precipitation * meta.biome().precipitation()).min(1.0);
+        if intensity <= 0.0 {
+            None
+        } else if meta.temp() < FREEZING_TEMP {
+            Some((Precipitation::Snow, intensity))
+        } else {
+            Some((Precipitation::Rain, intensity))
+        }
+    }
+
+    /// Whether clients that were last sent the `synced` weather need to be sent this weather.
+    /// Small changes are held back so that the weather isn't sent every tick while it turns, but
+    /// rain starting or stopping always is.
+    pub fn needs_sync(&self, synced: &Weather) -> bool {
+        (self.precipitation - synced.precipitation).abs() >= WEATHER_SYNC_THRESHOLD
+            || (self.precipitation > 0.0) != (synced.precipitation > 0.0)
+    }
+}
+
 /// A resource that stores the tick (i.e: physics) time.
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Time(pub f64);
@@ -122,6 +166,7 @@ impl State {
         ecs.register_synced::<comp::Scale>();
         ecs.register_synced::<comp::Mounting>();
         ecs.register_synced::<comp::MountState>();
+        ecs.register_synced::<comp::Emote>();
 
         // Register components send from clients -> server
         ecs.register::<comp::Controller>();
@@ -149,6 +194,7 @@ impl State {
 
         // Register synced resources used by the ECS.
         ecs.insert_synced(TimeOfDay(0.0));
+        ecs.insert_synced(Weather::default());
 
         // Register unsynced resources used by the ECS.
         ecs.add_resource(Time(0.0));
@@ -212,6 +258,16 @@ impl State {
         self.ecs.read_resource::<TimeOfDay>().0
     }
 
+    /// Get the world's current weather.
+    pub fn get_weather(&self) -> Weather {
+        *self.ecs.read_resource::<Weather>()
+    }
+
+    /// Change the world's weather.
+    pub fn set_weather(&mut self, weather: Weather) {
+        *self.ecs.write_resource::<Weather>() = weather;
+    }
+
     /// Get the current in-game time.
     ///
     /// Note that this does not correspond to the time of day.
@@ -434,3 +490,46 @@ impl State {
         self.ecs.write_resource::<TerrainChanges>().clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::BiomeKind;
+
+    #[test]
+    fn precipitation_depends_on_chunk() {
+        let weather = Weather { precipitation: 0.5 };
+
+        let grassland = TerrainChunkMeta::new(None, BiomeKind::Grassland, 0.2);
+        assert_eq!(
+            weather.precipitation_in(&grassland),
+            Some((Precipitation::Rain, 0.5))
+        );
+
+        let snowlands = TerrainChunkMeta::new(None, BiomeKind::Snowlands, -0.8);
+        assert_eq!(
+            weather.precipitation_in(&snowlands),
+            Some((Precipitation::Snow, 0.5))
+        );
+
+        assert_eq!(Weather::default().precipitation_in(&grassland), None);
+    }
+
+    #[test]
+    fn weather_sync_threshold() {
+        let weather = |precipitation| Weather { precipitation };
+        let synced = weather(0.5);
+
+        // Turning a little at a time isn't worth telling clients about...
+        assert!(!weather(0.51).needs_sync(&synced));
+        assert!(!synced.needs_sync(&synced));
+        // ...until it adds up
+        assert!(weather(0.56).needs_sync(&synced));
+        assert!(weather(0.44).needs_sync(&synced));
+
+        // Rain stopping or starting is always sent, however light it was
+        let drizzle = weather(0.01);
+        assert!(weather(0.0).needs_sync(&drizzle));
+        assert!(drizzle.needs_sync(&weather(0.0)));
+    }
+}
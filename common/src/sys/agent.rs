@@ -1,16 +1,23 @@
-use crate::comp::{
-    Agent, CharacterState, Controller, MountState, MovementState::Glide, Pos, Stats,
+use crate::{
+    comp::{Agent, Body, CharacterState, Controller, MountState, MovementState::Glide, Pos, Stats},
+    terrain::TerrainGrid,
+    vol::ReadVol,
 };
 use rand::{seq::SliceRandom, thread_rng};
-use specs::{Entities, Join, ReadStorage, System, WriteStorage};
+use specs::{Entities, Join, ReadExpect, ReadStorage, System, WriteStorage};
 use vek::*;
 
+/// How high above the ground flying creatures like to stay when they aren't chasing anything.
+const FLY_HEIGHT: f32 = 12.0;
+
 /// This system will allow NPCs to modify their controller
 pub struct Sys;
 impl<'a> System<'a> for Sys {
     type SystemData = (
         Entities<'a>,
+        ReadExpect<'a, TerrainGrid>,
         ReadStorage<'a, Pos>,
+        ReadStorage<'a, Body>,
         ReadStorage<'a, Stats>,
         ReadStorage<'a, CharacterState>,
         WriteStorage<'a, Agent>,
@@ -20,7 +27,17 @@ impl<'a> System<'a> for Sys {
 
     fn run(
         &mut self,
-        (entities, positions, stats, character_states, mut agents, mut controllers, mount_states): Self::SystemData,
+        (
+            entities,
+            terrain,
+            positions,
+            bodies,
+            stats,
+            character_states,
+            mut agents,
+            mut controllers,
+            mount_states,
+        ): Self::SystemData,
     ) {
         for (entity, pos, agent, controller, mount_state) in (
             &entities,
@@ -159,6 +176,31 @@ impl<'a> System<'a> for Sys {
                     }
                 }
             }
+
+            // Creatures with wings take off and flap to stay level with whatever they're
+            // following, or a little above the ground otherwise
+            if bodies.get(entity).map_or(false, |body| body.can_fly()) {
+                let target_height = match &*agent {
+                    Agent::Pet { target, .. }
+                    | Agent::Enemy {
+                        target: Some(target),
+                        ..
+                    } => positions.get(*target).map(|tgt_pos| tgt_pos.0.z),
+                    _ => None,
+                };
+                controller.glide = true;
+                controller.jump = match target_height {
+                    Some(target_height) => pos.0.z < target_height + 1.0,
+                    None => match terrain
+                        .ray(pos.0, pos.0 - Vec3::unit_z() * FLY_HEIGHT)
+                        .ignore_error()
+                        .cast()
+                    {
+                        (_, Ok(Some(_))) => true,
+                        _ => false,
+                    },
+                };
+            }
         }
     }
 }
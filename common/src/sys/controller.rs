@@ -4,7 +4,7 @@ use super::{
 };
 use crate::{
     comp::{
-        item, ActionState::*, Body, CharacterState, ControlEvent, Controller, Item,
+        item, ActionState::*, Body, CharacterState, ControlEvent, Controller, Emote, Item,
         MovementState::*, PhysicsState, Stats, Vel,
     },
     event::{EventBus, LocalEvent, ServerEvent},
@@ -31,6 +31,7 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Vel>,
         ReadStorage<'a, PhysicsState>,
         WriteStorage<'a, CharacterState>,
+        WriteStorage<'a, Emote>,
     );
 
     fn run(
@@ -46,6 +47,7 @@ impl<'a> System<'a> for Sys {
             velocities,
             physics_states,
             mut character_states,
+            mut emotes,
         ): Self::SystemData,
     ) {
         let mut server_emitter = server_bus.emitter();
@@ -83,6 +85,17 @@ impl<'a> System<'a> for Sys {
                 character.movement = Stand;
             }
 
+            // Moving or acting stops emotes
+            if controller.move_dir.magnitude_squared() > 0.0
+                || controller.primary
+                || controller.secondary
+                || controller.jump
+                || controller.roll
+                || controller.glide
+            {
+                emotes.remove(entity);
+            }
+
             // Look
             controller.look_dir = controller
                 .look_dir
@@ -95,7 +108,7 @@ impl<'a> System<'a> for Sys {
                 && !physics.on_ground
                 && (character.action == Idle || character.action.is_wield())
                 && character.movement == Jump
-                && body.is_humanoid()
+                && (body.is_humanoid() || body.can_fly())
             {
                 character.movement = Glide;
             } else if !controller.glide && character.movement == Glide {
@@ -1,8 +1,8 @@
 use super::phys::GRAVITY;
 use crate::{
     comp::{
-        ActionState::*, CharacterState, Controller, Mounting, MovementState::*, Ori, PhysicsState,
-        Pos, Stats, Vel,
+        ActionState::*, Body, CharacterState, Controller, Mounting, MovementState::*, Ori,
+        PhysicsState, Pos, Stats, Vel,
     },
     state::DeltaTime,
     terrain::TerrainGrid,
@@ -28,6 +28,11 @@ const BLOCK_SPEED: f32 = 75.0;
 // Gravity is 9.81 * 4, so this makes gravity equal to .15
 const GLIDE_ANTIGRAV: f32 = GRAVITY * 0.96;
 const CLIMB_SPEED: f32 = 5.0;
+// Flapping only just overcomes gravity, so flying creatures climb slowly
+const FLAP_ACCEL: f32 = GRAVITY * 1.3;
+const FLY_CLIMB_SPEED: f32 = 8.0;
+// How directly (as the cosine of the angle) a character must move into a wall to climb it
+const CLIMB_PRESS_THRESHOLD: f32 = 0.7;
 
 pub const MOVEMENT_THRESHOLD_VEL: f32 = 3.0;
 
@@ -39,6 +44,7 @@ impl<'a> System<'a> for Sys {
         ReadExpect<'a, TerrainGrid>,
         Read<'a, DeltaTime>,
         ReadStorage<'a, Stats>,
+        ReadStorage<'a, Body>,
         ReadStorage<'a, Controller>,
         ReadStorage<'a, PhysicsState>,
         WriteStorage<'a, CharacterState>,
@@ -55,6 +61,7 @@ impl<'a> System<'a> for Sys {
             _terrain,
             dt,
             stats,
+            bodies,
             controllers,
             physics_states,
             mut character_states,
@@ -68,6 +75,7 @@ impl<'a> System<'a> for Sys {
         for (
             _entity,
             stats,
+            body,
             controller,
             physics,
             mut character,
@@ -78,6 +86,7 @@ impl<'a> System<'a> for Sys {
         ) in (
             &entities,
             &stats,
+            bodies.maybe(),
             &controllers,
             &physics_states,
             &mut character_states,
@@ -181,6 +190,15 @@ impl<'a> System<'a> for Sys {
                         .max(0.2);
             }
 
+            // Flap, for creatures with wings, to stay aloft or climb
+            if character.movement == Glide
+                && controller.jump
+                && body.map_or(false, |body| body.can_fly())
+                && vel.0.z < FLY_CLIMB_SPEED
+            {
+                vel.0.z += dt.0 * FLAP_ACCEL;
+            }
+
             // Roll
             if let Roll { time_left } = &mut character.movement {
                 character.action = Idle;
@@ -193,14 +211,29 @@ impl<'a> System<'a> for Sys {
                 }
             }
 
-            // Climb
+            // Climb, either when asked to or when moving into a wall, whether from the ground, in
+            // the air or while already climbing (but not when gliding or swimming into one)
+            let pressing_into_wall = physics
+                .on_wall
+                .map(Vec2::<f32>::from)
+                .filter(|wall_dir| {
+                    !physics.in_fluid
+                        && character.movement != Glide
+                        && wall_dir.magnitude_squared() > 0.001
+                })
+                .map(|wall_dir| {
+                    wall_dir.normalized().dot(controller.move_dir) > CLIMB_PRESS_THRESHOLD
+                })
+                .unwrap_or(false);
+            let climb_up = controller.climb || pressing_into_wall;
+            let mut climbing = false;
             if let (true, Some(_wall_dir)) = (
-                (controller.climb | controller.climb_down) && vel.0.z <= CLIMB_SPEED,
+                (climb_up | controller.climb_down) && vel.0.z <= CLIMB_SPEED,
                 physics.on_wall,
             ) {
                 if controller.climb_down && !controller.climb {
                     vel.0 -= dt.0 * vel.0.map(|e| e.abs().powf(1.5) * e.signum() * 6.0);
-                } else if controller.climb && !controller.climb_down {
+                } else if climb_up && !controller.climb_down {
                     vel.0.z = (vel.0.z + dt.0 * GRAVITY * 1.25).min(CLIMB_SPEED);
                 } else {
                     vel.0.z = vel.0.z + dt.0 * GRAVITY * 1.5;
@@ -213,13 +246,15 @@ impl<'a> System<'a> for Sys {
 
                 character.movement = Climb;
                 character.action = Idle;
+                climbing = true;
             } else if let Climb = character.movement {
                 character.movement = Jump;
             }
 
+            // Climbing from the ground carries on up the wall rather than stopping
             if physics.on_ground
                 && (character.movement == Jump
-                    || character.movement == Climb
+                    || (character.movement == Climb && !climbing)
                     || character.movement == Glide
                     || character.movement == Swim)
             {
@@ -1,6 +1,6 @@
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BiomeKind {
     Void,
     Grassland,
@@ -11,3 +11,33 @@ pub enum BiomeKind {
     Swamp,
     Forest,
 }
+
+impl BiomeKind {
+    /// How exposed the region is to wind, where 1.0 is open ground.
+    pub fn wind_strength(&self) -> f32 {
+        match self {
+            BiomeKind::Void => 0.0,
+            BiomeKind::Grassland => 1.0,
+            BiomeKind::Ocean => 1.2,
+            BiomeKind::Mountain => 1.4,
+            BiomeKind::Snowlands => 1.1,
+            BiomeKind::Desert => 0.9,
+            BiomeKind::Swamp => 0.6,
+            BiomeKind::Forest => 0.5,
+        }
+    }
+
+    /// How much of the world's rain or snow falls on the region, where 1.0 is the usual amount.
+    pub fn precipitation(&self) -> f32 {
+        match self {
+            BiomeKind::Void => 0.0,
+            BiomeKind::Grassland => 1.0,
+            BiomeKind::Ocean => 1.0,
+            BiomeKind::Mountain => 1.2,
+            BiomeKind::Snowlands => 1.0,
+            BiomeKind::Desert => 0.1,
+            BiomeKind::Swamp => 1.4,
+            BiomeKind::Forest => 1.2,
+        }
+    }
+}
@@ -29,6 +29,8 @@ pub enum BlockKind {
     Apple,
     Mushroom,
     Liana,
+    /// The leaves of trees and other plants, which bend with the wind.
+    Leaves,
 }
 
 impl BlockKind {
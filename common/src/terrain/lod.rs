@@ -0,0 +1,82 @@
+use serde_derive::{Deserialize, Serialize};
+use vek::*;
+
+/// The height and color of the ground at a point of a `LodMap`.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LodSample {
+    pub alt: u16,
+    pub col: Rgb<u8>,
+}
+
+/// A coarse heightmap of the whole world, sampled on a regular grid. Clients use it to draw
+/// terrain far beyond the chunks they have loaded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LodMap {
+    size: Vec2<u32>,
+    spacing: u32,
+    samples: Vec<LodSample>,
+}
+
+impl LodMap {
+    /// Build a map with `size` samples along each axis, `spacing` blocks apart, from the sample
+    /// at each grid position.
+    pub fn generate(
+        size: Vec2<u32>,
+        spacing: u32,
+        mut sample: impl FnMut(Vec2<u32>) -> LodSample,
+    ) -> Self {
+        let samples = (0..size.y)
+            .map(|y| (0..size.x).map(move |x| Vec2::new(x, y)))
+            .flatten()
+            .map(|pos| sample(pos))
+            .collect();
+
+        Self {
+            size,
+            spacing,
+            samples,
+        }
+    }
+
+    /// The number of samples along each axis.
+    pub fn size(&self) -> Vec2<u32> {
+        self.size
+    }
+
+    /// How far apart, in blocks, neighbouring samples are.
+    pub fn spacing(&self) -> u32 {
+        self.spacing
+    }
+
+    pub fn get(&self, pos: Vec2<u32>) -> Option<&LodSample> {
+        if pos.x < self.size.x && pos.y < self.size.y {
+            self.samples.get((pos.y * self.size.x + pos.x) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// The position, in world space, of the sample at the given grid position.
+    pub fn wpos(&self, pos: Vec2<u32>) -> Vec2<i32> {
+        pos.map(|e| (e * self.spacing) as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lod_map_lookup() {
+        let map = LodMap::generate(Vec2::new(4, 3), 64, |pos| LodSample {
+            alt: (pos.x * 10 + pos.y) as u16,
+            col: Rgb::zero(),
+        });
+
+        assert_eq!(map.get(Vec2::new(2, 1)).map(|s| s.alt), Some(21));
+        assert_eq!(map.get(Vec2::new(3, 2)).map(|s| s.alt), Some(32));
+        assert_eq!(map.get(Vec2::new(4, 0)), None);
+        assert_eq!(map.get(Vec2::new(0, 3)), None);
+        assert_eq!(map.wpos(Vec2::new(2, 1)), Vec2::new(128, 64));
+    }
+}
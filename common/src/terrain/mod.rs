@@ -1,12 +1,14 @@
 pub mod biome;
 pub mod block;
 pub mod chonk;
+pub mod lod;
 pub mod structure;
 
 // Reexports
 pub use self::{
     biome::BiomeKind,
     block::{Block, BlockKind},
+    lod::{LodMap, LodSample},
     structure::Structure,
 };
 
@@ -29,17 +31,19 @@ impl RectVolSize for TerrainChunkSize {
 pub struct TerrainChunkMeta {
     name: Option<String>,
     biome: BiomeKind,
+    temp: f32,
 }
 
 impl TerrainChunkMeta {
-    pub fn new(name: Option<String>, biome: BiomeKind) -> Self {
-        Self { name, biome }
+    pub fn new(name: Option<String>, biome: BiomeKind, temp: f32) -> Self {
+        Self { name, biome, temp }
     }
 
     pub fn void() -> Self {
         Self {
             name: None,
             biome: BiomeKind::Void,
+            temp: 0.0,
         }
     }
 
@@ -53,6 +57,11 @@ impl TerrainChunkMeta {
     pub fn biome(&self) -> BiomeKind {
         self.biome
     }
+
+    /// How warm the chunk is, from -1.0 (the coldest in the world) to 1.0 (the hottest).
+    pub fn temp(&self) -> f32 {
+        self.temp
+    }
 }
 
 // Terrain type aliases
@@ -1,6 +1,6 @@
 pub const GIT_HASH: &str = include_str!(concat!(env!("OUT_DIR"), "/githash"));
 
-use vek::{Mat3, Rgb, Rgba, Vec3};
+use vek::{Lerp, Mat3, Rgb, Rgba, Vec3};
 
 #[inline(always)]
 pub fn srgb_to_linear(col: Rgb<f32>) -> Rgb<f32> {
@@ -35,6 +35,46 @@ pub fn linear_to_srgba(col: Rgba<f32>) -> Rgba<f32> {
     Rgba::from_translucent(linear_to_srgb(Rgb::from(col)), col.a)
 }
 
+/// Adds two colors in linear space, saturating at white
+#[inline(always)]
+pub fn add_srgb(a: Rgb<f32>, b: Rgb<f32>) -> Rgb<f32> {
+    linear_to_srgb((srgb_to_linear(a) + srgb_to_linear(b)).map(|e| e.min(1.0).max(0.0)))
+}
+
+/// Subtracts one color from another in linear space, saturating at black
+#[inline(always)]
+pub fn sub_srgb(a: Rgb<f32>, b: Rgb<f32>) -> Rgb<f32> {
+    linear_to_srgb((srgb_to_linear(a) - srgb_to_linear(b)).map(|e| e.min(1.0).max(0.0)))
+}
+
+/// Interpolates between two colors in linear space, which avoids the dark midpoints produced by
+/// interpolating sRGB values directly
+#[inline(always)]
+pub fn lerp_srgb(a: Rgb<f32>, b: Rgb<f32>, t: f32) -> Rgb<f32> {
+    linear_to_srgb(Lerp::lerp(
+        srgb_to_linear(a),
+        srgb_to_linear(b),
+        t.min(1.0).max(0.0),
+    ))
+}
+
+/// Like `lerp_srgb`, but alpha (which is already linear) is interpolated directly
+#[inline(always)]
+pub fn lerp_srgba(a: Rgba<f32>, b: Rgba<f32>, t: f32) -> Rgba<f32> {
+    let t = t.min(1.0).max(0.0);
+    Rgba::from_translucent(
+        lerp_srgb(Rgb::from(a), Rgb::from(b), t),
+        a.a + (b.a - a.a) * t,
+    )
+}
+
+/// Fades a color towards the fog color in linear space. A factor of 0 leaves the color untouched
+/// while a factor of 1 returns the fog color.
+#[inline(always)]
+pub fn apply_fog(col: Rgb<f32>, fog_col: Rgb<f32>, factor: f32) -> Rgb<f32> {
+    lerp_srgb(col, fog_col, factor)
+}
+
 /// Convert rgb to hsv. Expects rgb to be [0, 1].
 #[inline(always)]
 pub fn rgb_to_hsv(rgb: Rgb<f32>) -> Vec3<f32> {
@@ -66,12 +106,14 @@ pub fn rgb_to_hsv(rgb: Rgb<f32>) -> Vec3<f32> {
 
     Vec3::new(h, s, v)
 }
-/// Convert hsv to rgb. Expects h [0, 360], s [0, 1], v [0, 1]
+/// Convert hsv to rgb. Expects s [0, 1], v [0, 1]. Hues outside of [0, 360) wrap around.
 #[inline(always)]
 pub fn hsv_to_rgb(hsv: Vec3<f32>) -> Rgb<f32> {
     let (h, s, v) = hsv.into_tuple();
     let c = s * v;
-    let h = h / 60.0;
+    // rem_euclid can round up to exactly 360 for tiny negative hues
+    let h = h.rem_euclid(360.0);
+    let h = if h >= 360.0 { 0.0 } else { h / 60.0 };
     let x = c * (1.0 - (h % 2.0 - 1.0).abs());
     let m = v - c;
 
@@ -91,6 +133,23 @@ pub fn hsv_to_rgb(hsv: Vec3<f32>) -> Rgb<f32> {
 
     Rgb::new(r + m, g + m, b + m)
 }
+/// Interpolate between two rgb colors in hsv, taking the shorter way around the hue wheel.
+/// Expects rgb to be [0, 1].
+#[inline(always)]
+pub fn lerp_hsv(a: Rgb<f32>, b: Rgb<f32>, t: f32) -> Rgb<f32> {
+    let (a, b) = (rgb_to_hsv(a), rgb_to_hsv(b));
+    let t = t.min(1.0).max(0.0);
+
+    let mut hue_diff = b.x - a.x;
+    if hue_diff > 180.0 {
+        hue_diff -= 360.0;
+    } else if hue_diff < -180.0 {
+        hue_diff += 360.0;
+    }
+    let h = (a.x + hue_diff * t + 360.0) % 360.0;
+
+    hsv_to_rgb(Vec3::new(h, a.y + (b.y - a.y) * t, a.z + (b.z - a.z) * t))
+}
 /// Convert linear rgb to CIExyY
 #[inline(always)]
 pub fn rgb_to_xyy(rgb: Rgb<f32>) -> Vec3<f32> {
@@ -118,6 +177,175 @@ pub fn xyy_to_rgb(xyy: Vec3<f32>) -> Rgb<f32> {
     )
 }
 
+/// Convert linear rgb to OKLab, a perceptually uniform space where equal steps look like equal
+/// changes in color
+#[inline(always)]
+#[allow(clippy::excessive_precision)]
+pub fn linear_to_oklab(col: Rgb<f32>) -> Vec3<f32> {
+    let lms = Mat3::new(
+        0.4122214708,
+        0.5363325363,
+        0.0514459929,
+        0.2119034982,
+        0.6806995451,
+        0.1073969566,
+        0.0883024619,
+        0.2817188376,
+        0.6299787005,
+    ) * Vec3::from(col);
+
+    Mat3::new(
+        0.2104542553,
+        0.7936177850,
+        -0.0040720468,
+        1.9779984951,
+        -2.4285922050,
+        0.4505937099,
+        0.0259040371,
+        0.7827717662,
+        -0.8086757660,
+    ) * lms.map(f32::cbrt)
+}
+/// Convert OKLab to linear rgb
+#[inline(always)]
+#[allow(clippy::excessive_precision)]
+pub fn oklab_to_linear(lab: Vec3<f32>) -> Rgb<f32> {
+    let lms = Mat3::new(
+        1.0,
+        0.3963377774,
+        0.2158037573,
+        1.0,
+        -0.1055613458,
+        -0.0638541728,
+        1.0,
+        -0.0894841775,
+        -1.2914855480,
+    ) * lab;
+
+    Rgb::from(
+        Mat3::new(
+            4.0767416621,
+            -3.3077115913,
+            0.2309699292,
+            -1.2684380046,
+            2.6097574011,
+            -0.3413193965,
+            -0.0041960863,
+            -0.7034186147,
+            1.7076147010,
+        ) * lms.map(|e| e * e * e),
+    )
+}
+/// Convert OKLab to its polar form, OKLCh (lightness, chroma, hue in degrees [0, 360))
+#[inline(always)]
+pub fn oklab_to_oklch(lab: Vec3<f32>) -> Vec3<f32> {
+    let h = lab.z.atan2(lab.y).to_degrees();
+    Vec3::new(
+        lab.x,
+        (lab.y * lab.y + lab.z * lab.z).sqrt(),
+        if h < 0.0 { h + 360.0 } else { h },
+    )
+}
+/// Convert OKLCh back to OKLab
+#[inline(always)]
+pub fn oklch_to_oklab(lch: Vec3<f32>) -> Vec3<f32> {
+    let h = lch.z.to_radians();
+    Vec3::new(lch.x, lch.y * h.cos(), lch.y * h.sin())
+}
+/// Convert linear rgb to CIELAB (L* from 0 to 100) under the D65 white point
+#[inline(always)]
+#[allow(clippy::excessive_precision)]
+pub fn linear_to_cielab(col: Rgb<f32>) -> Vec3<f32> {
+    let xyz = Mat3::new(
+        0.4124564, 0.3575761, 0.1804375, 0.2126729, 0.7151522, 0.0721750, 0.0193339, 0.1191920,
+        0.9503041,
+    ) * Vec3::from(col);
+    let white = Vec3::new(0.95047, 1.0, 1.08883);
+
+    let f = |t: f32| {
+        const DELTA: f32 = 6.0 / 29.0;
+        if t > DELTA * DELTA * DELTA {
+            t.cbrt()
+        } else {
+            t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+        }
+    };
+    let f = (xyz / white).map(f);
+
+    Vec3::new(116.0 * f.y - 16.0, 500.0 * (f.x - f.y), 200.0 * (f.y - f.z))
+}
+/// The CIEDE2000 color difference between two CIELAB colors. A difference of about 1 is just
+/// noticeable, while differences of more than about 10 make the colors look distinct.
+#[allow(clippy::many_single_char_names)]
+pub fn delta_e_2000(lab1: Vec3<f32>, lab2: Vec3<f32>) -> f32 {
+    let pow7 = |x: f32| x.powi(7);
+    // Hue angle in degrees, from 0 to 360
+    let hue = |b: f32, a: f32| {
+        if a == 0.0 && b == 0.0 {
+            0.0
+        } else {
+            let h = b.atan2(a).to_degrees();
+            if h < 0.0 {
+                h + 360.0
+            } else {
+                h
+            }
+        }
+    };
+
+    let chroma = |lab: Vec3<f32>| (lab.y * lab.y + lab.z * lab.z).sqrt();
+    let c_mean = (chroma(lab1) + chroma(lab2)) / 2.0;
+    let g = 0.5 * (1.0 - (pow7(c_mean) / (pow7(c_mean) + pow7(25.0))).sqrt());
+    let (a1, a2) = (lab1.y * (1.0 + g), lab2.y * (1.0 + g));
+    let (c1, c2) = (
+        (a1 * a1 + lab1.z * lab1.z).sqrt(),
+        (a2 * a2 + lab2.z * lab2.z).sqrt(),
+    );
+    let (h1, h2) = (hue(lab1.z, a1), hue(lab2.z, a2));
+
+    let dl = lab2.x - lab1.x;
+    let dc = c2 - c1;
+    let dh = if c1 * c2 == 0.0 {
+        0.0
+    } else if h2 - h1 > 180.0 {
+        h2 - h1 - 360.0
+    } else if h2 - h1 < -180.0 {
+        h2 - h1 + 360.0
+    } else {
+        h2 - h1
+    };
+    let dh = 2.0 * (c1 * c2).sqrt() * (dh / 2.0).to_radians().sin();
+
+    let l_mean = (lab1.x + lab2.x) / 2.0;
+    let c_mean = (c1 + c2) / 2.0;
+    let h_mean = if c1 * c2 == 0.0 {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180.0 {
+        (h1 + h2) / 2.0
+    } else if h1 + h2 < 360.0 {
+        (h1 + h2 + 360.0) / 2.0
+    } else {
+        (h1 + h2 - 360.0) / 2.0
+    };
+
+    let cos_deg = |x: f32| x.to_radians().cos();
+    let t = 1.0 - 0.17 * cos_deg(h_mean - 30.0)
+        + 0.24 * cos_deg(2.0 * h_mean)
+        + 0.32 * cos_deg(3.0 * h_mean + 6.0)
+        - 0.20 * cos_deg(4.0 * h_mean - 63.0);
+    let d_theta = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+    let r_c = 2.0 * (pow7(c_mean) / (pow7(c_mean) + pow7(25.0))).sqrt();
+    let s_l = 1.0 + 0.015 * (l_mean - 50.0).powi(2) / (20.0 + (l_mean - 50.0).powi(2)).sqrt();
+    let s_c = 1.0 + 0.045 * c_mean;
+    let s_h = 1.0 + 0.015 * c_mean * t;
+    let r_t = -(2.0 * d_theta).to_radians().sin() * r_c;
+
+    let (dl, dc, dh) = (dl / s_l, dc / s_c, dh / s_h);
+    (dl * dl + dc * dc + dh * dh + r_t * dc * dh)
+        .max(0.0)
+        .sqrt()
+}
+
 #[inline(always)]
 pub fn saturate_srgb(col: Rgb<f32>, value: f32) -> Rgb<f32> {
     let mut hsv = rgb_to_hsv(srgb_to_linear(col));
@@ -134,3 +362,258 @@ pub fn chromify_srgb(luma: Rgb<f32>, chroma: Rgb<f32>) -> Rgb<f32> {
 
     linear_to_srgb(xyy_to_rgb(xyy).map(|e| e.min(1.0).max(0.0)))
 }
+
+/// Tints a color by the approximate color of a blackbody at the given temperature (in kelvin),
+/// for white-balance style warm/cool shifts. 6500K leaves colors nearly unchanged, lower
+/// temperatures warm them and higher temperatures cool them. Temperatures are clamped to
+/// [1000, 12000].
+#[allow(clippy::excessive_precision)]
+pub fn shift_temperature_srgb(col: Rgb<f32>, kelvin: f32) -> Rgb<f32> {
+    // Approximation of the Planckian locus in sRGB, fitted to blackbody data
+    let temp = kelvin.min(12000.0).max(1000.0) / 100.0;
+    let r = if temp <= 66.0 {
+        255.0
+    } else {
+        329.698727446 * (temp - 60.0).powf(-0.1332047592)
+    };
+    let g = if temp <= 66.0 {
+        99.4708025861 * temp.ln() - 161.1195681661
+    } else {
+        288.1221695283 * (temp - 60.0).powf(-0.0755148492)
+    };
+    let b = if temp >= 66.0 {
+        255.0
+    } else if temp <= 19.0 {
+        0.0
+    } else {
+        138.5177312231 * (temp - 10.0).ln() - 305.0447927307
+    };
+
+    let tint = Rgb::new(r, g, b).map(|e| e.min(255.0).max(0.0));
+    let tint = tint / tint.reduce_partial_max();
+
+    linear_to_srgb((srgb_to_linear(col) * srgb_to_linear(tint)).map(|e| e.min(1.0).max(0.0)))
+}
+
+/// Scores how harmonious a palette is, from 0 (clashing) to 1 (harmonious), based on how evenly
+/// its hues are spaced around the color wheel (as in complementary, triadic, etc. palettes) and
+/// how consistent the brightness of its colors is.
+pub fn palette_harmony_score(colors: &[Rgb<u8>]) -> f32 {
+    if colors.len() < 2 {
+        return 1.0;
+    }
+
+    let hsvs = colors
+        .iter()
+        .map(|col| rgb_to_hsv(col.map(|e| e as f32 / 255.0)))
+        .collect::<Vec<_>>();
+
+    // Even hue spacing, ignoring greys (which have no meaningful hue)
+    let mut hues = hsvs
+        .iter()
+        .filter(|hsv| hsv.y > 0.1)
+        .map(|hsv| hsv.x)
+        .collect::<Vec<_>>();
+    hues.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let hue_score = if hues.len() < 2 {
+        1.0
+    } else {
+        let ideal_gap = 360.0 / hues.len() as f32;
+        let gap_error = hues
+            .iter()
+            .zip(hues.iter().cycle().skip(1))
+            .map(|(a, b)| ((b - a + 360.0) % 360.0 - ideal_gap).abs())
+            .sum::<f32>()
+            / hues.len() as f32;
+        1.0 - (gap_error / ideal_gap).min(1.0)
+    };
+
+    // Consistent value
+    let mean_value = hsvs.iter().map(|hsv| hsv.z).sum::<f32>() / hsvs.len() as f32;
+    let value_spread = (hsvs
+        .iter()
+        .map(|hsv| (hsv.z - mean_value).powi(2))
+        .sum::<f32>()
+        / hsvs.len() as f32)
+        .sqrt();
+    let value_score = 1.0 - (value_spread * 2.0).min(1.0);
+
+    (hue_score + value_score) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rgb_close(a: Rgb<f32>, b: Rgb<f32>) {
+        assert!(
+            (a - b).map(|e| e.abs()).reduce_partial_max() < 1e-4,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn add_sub_srgb() {
+        let col = Rgb::new(0.2, 0.5, 0.8);
+        assert_rgb_close(add_srgb(col, Rgb::zero()), col);
+        assert_rgb_close(add_srgb(col, Rgb::one()), Rgb::one());
+        assert_rgb_close(sub_srgb(col, col), Rgb::zero());
+    }
+
+    #[test]
+    fn fog() {
+        let col = Rgb::new(0.2, 0.5, 0.8);
+        let fog_col = Rgb::new(0.7, 0.7, 0.75);
+        assert_rgb_close(apply_fog(col, fog_col, 0.0), col);
+        assert_rgb_close(apply_fog(col, fog_col, 1.0), fog_col);
+    }
+
+    #[test]
+    fn hsv_interpolation() {
+        let red = Rgb::new(1.0, 0.0, 0.0);
+        let green = Rgb::new(0.0, 1.0, 0.0);
+        let blue = Rgb::new(0.0, 0.0, 1.0);
+        assert_rgb_close(lerp_hsv(red, green, 0.5), Rgb::new(1.0, 1.0, 0.0));
+        // Red to blue should pass through magenta rather than green
+        assert_rgb_close(lerp_hsv(red, blue, 0.5), Rgb::new(1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn oklab_round_trip() {
+        for col in [
+            Rgb::one(),
+            Rgb::zero(),
+            Rgb::new(1.0, 0.0, 0.0),
+            Rgb::new(0.0, 1.0, 0.0),
+            Rgb::new(0.0, 0.0, 1.0),
+        ]
+        .iter()
+        {
+            assert_rgb_close(oklab_to_linear(linear_to_oklab(*col)), *col);
+            assert_rgb_close(
+                oklab_to_linear(oklch_to_oklab(oklab_to_oklch(linear_to_oklab(*col)))),
+                *col,
+            );
+        }
+
+        // White has full lightness and no chroma
+        let white = linear_to_oklab(Rgb::one());
+        assert!((white.x - 1.0).abs() < 1e-3 && white.y.abs() < 1e-3 && white.z.abs() < 1e-3);
+    }
+
+    #[test]
+    fn ciede2000() {
+        // Reference pairs from Sharma, Wu and Dalal's CIEDE2000 test data
+        for &(lab1, lab2, expected) in [
+            (
+                Vec3::new(50.0, 2.6772, -79.7751),
+                Vec3::new(50.0, 0.0, -82.7485),
+                2.0425,
+            ),
+            (
+                Vec3::new(50.0, 0.0, 0.0),
+                Vec3::new(50.0, -1.0, 2.0),
+                2.3669,
+            ),
+            (
+                Vec3::new(50.0, 2.5, 0.0),
+                Vec3::new(73.0, 25.0, -18.0),
+                27.1492,
+            ),
+            (
+                Vec3::new(22.7233, 20.0904, -46.694),
+                Vec3::new(23.0331, 14.973, -42.5619),
+                2.0373,
+            ),
+        ]
+        .iter()
+        {
+            let diff = delta_e_2000(lab1, lab2);
+            assert!((diff - expected).abs() < 1e-3, "{} != {}", diff, expected);
+            assert!((delta_e_2000(lab2, lab1) - expected).abs() < 1e-3);
+        }
+
+        let white = linear_to_cielab(Rgb::one());
+        assert!((white.x - 100.0).abs() < 1e-2 && white.y.abs() < 1e-2 && white.z.abs() < 1e-2);
+        assert_eq!(delta_e_2000(white, white), 0.0);
+    }
+
+    #[test]
+    fn srgb_interpolation() {
+        let mid = lerp_srgb(Rgb::zero(), Rgb::one(), 0.5).map(|e| e * 255.0);
+        assert!(mid.map(|e| (e - 188.0).abs() < 1.0).reduce_and());
+
+        let col = Rgb::new(0.2, 0.5, 0.8);
+        assert_rgb_close(lerp_srgb(col, Rgb::one(), -1.0), col);
+        assert_rgb_close(lerp_srgb(col, Rgb::one(), 2.0), Rgb::one());
+
+        let mid = lerp_srgba(Rgba::new(0.0, 0.0, 0.0, 0.0), Rgba::one(), 0.5);
+        assert!((mid.a - 0.5).abs() < 1e-6);
+        assert!(mid.r > 0.7);
+    }
+
+    #[test]
+    fn hsv_hue_wrapping() {
+        let red = Rgb::new(1.0, 0.0, 0.0);
+        let yellow = Rgb::new(1.0, 1.0, 0.0);
+        let green = Rgb::new(0.0, 1.0, 0.0);
+        let magenta = Rgb::new(1.0, 0.0, 1.0);
+        for &(h, col) in [
+            (0.0, red),
+            (60.0, yellow),
+            (120.0, green),
+            (360.0, red),
+            (720.0, red),
+            (-60.0, magenta),
+        ]
+        .iter()
+        {
+            let rgb = hsv_to_rgb(Vec3::new(h, 1.0, 1.0));
+            assert_rgb_close(rgb, col);
+            assert_rgb_close(hsv_to_rgb(rgb_to_hsv(rgb)), col);
+        }
+
+        // Hues just either side of the wrap-around point stay close to red
+        assert_rgb_close(hsv_to_rgb(Vec3::new(359.9999, 1.0, 1.0)), red);
+        assert_rgb_close(hsv_to_rgb(Vec3::new(-0.00001, 1.0, 1.0)), red);
+    }
+
+    #[test]
+    fn palette_harmony() {
+        let triadic = [
+            Rgb::new(200, 40, 40),
+            Rgb::new(40, 200, 40),
+            Rgb::new(40, 40, 200),
+        ];
+        let clash = [
+            Rgb::new(255, 0, 0),
+            Rgb::new(120, 50, 0),
+            Rgb::new(255, 0, 60),
+        ];
+
+        let triadic_score = palette_harmony_score(&triadic);
+        let clash_score = palette_harmony_score(&clash);
+        assert!(triadic_score > 0.9);
+        assert!(triadic_score > clash_score);
+        assert!(clash_score >= 0.0 && clash_score <= 1.0);
+    }
+
+    #[test]
+    fn temperature_shift() {
+        let col = Rgb::new(0.6, 0.6, 0.6);
+        let neutral = shift_temperature_srgb(col, 6500.0);
+        assert!((neutral - col).map(|e| e.abs()).reduce_partial_max() < 0.02);
+
+        let warm = shift_temperature_srgb(col, 3000.0);
+        assert!(warm.r > warm.b);
+        let cool = shift_temperature_srgb(col, 10000.0);
+        assert!(cool.b > cool.r);
+
+        for &kelvin in [-100.0, 0.0, 500.0, 50000.0].iter() {
+            let shifted = shift_temperature_srgb(col, kelvin);
+            assert!(shifted.map(|e| e.is_finite()).reduce_and());
+        }
+    }
+}
@@ -2,6 +2,7 @@ use crate::vol::{
     BaseVol, DefaultPosIterator, DefaultVolIterator, IntoPosIterator, IntoVolIterator, ReadVol,
     SizedVol, Vox, WriteVol,
 };
+use rayon::prelude::*;
 use serde_derive::{Deserialize, Serialize};
 use vek::*;
 
@@ -113,4 +114,14 @@ impl<V: Vox + Clone, M> Dyna<V, M> {
     pub fn metadata_mut(&mut self) -> &mut M {
         &mut self.meta
     }
+
+    /// Transform every voxel in the volume, splitting the work across threads.
+    pub fn par_map_vox(&mut self, transform: impl Fn(&V) -> V + Sync)
+    where
+        V: Send + Sync,
+    {
+        self.vox
+            .par_iter_mut()
+            .for_each(|vox| *vox = transform(vox));
+    }
 }
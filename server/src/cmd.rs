@@ -9,7 +9,7 @@ use common::{
     event::{EventBus, ServerEvent},
     msg::ServerMsg,
     npc::{get_npc_name, NpcKind},
-    state::TimeOfDay,
+    state::{TimeOfDay, Weather},
 };
 use rand::Rng;
 use specs::{Builder, Entity as EcsEntity, Join};
@@ -120,6 +120,13 @@ lazy_static! {
             true,
             handle_time,
         ),
+        ChatCommand::new(
+            "weather",
+            "{}",
+            "/weather <clear|rain|storm> or [Precipitation] : Set the weather",
+            true,
+            handle_weather,
+        ),
         ChatCommand::new(
             "spawn",
             "{} {} {d}",
@@ -185,6 +192,13 @@ lazy_static! {
             false,
             handle_lantern,
         ),
+        ChatCommand::new(
+            "emote",
+            "{}",
+            "/emote <sit|wave|dance|stop> : Express yourself until you next move",
+            false,
+            handle_emote,
+        ),
         ChatCommand::new(
             "explosion",
             "{}",
@@ -311,6 +325,46 @@ fn handle_time(server: &mut Server, entity: EcsEntity, args: String, action: &Ch
     );
 }
 
+fn handle_weather(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    let weather = scan_fmt_some!(&args, action.arg_fmt, String);
+    let precipitation = match weather.as_ref().map(|s| s.as_str()) {
+        Some("clear") => 0.0,
+        Some("rain") => 0.5,
+        Some("storm") => 1.0,
+        Some(n) => match n.parse::<f32>() {
+            Ok(n) if n >= 0.0 && n <= 1.0 => n,
+            _ => {
+                server.clients.notify(
+                    entity,
+                    ServerMsg::private(format!("'{}' is not a valid weather.", n)),
+                );
+                return;
+            }
+        },
+        None => {
+            let precipitation = server.state.ecs().read_resource::<Weather>().precipitation;
+            server.clients.notify(
+                entity,
+                ServerMsg::private(format!("The precipitation is {:.2}", precipitation)),
+            );
+            return;
+        }
+    };
+
+    // Change the weather straight away, and keep it that way for a while
+    server
+        .state
+        .ecs_mut()
+        .write_resource::<Weather>()
+        .precipitation = precipitation;
+    server.weather_forecast = precipitation;
+
+    server.clients.notify(
+        entity,
+        ServerMsg::private(format!("Precipitation changed to: {:.2}", precipitation)),
+    );
+}
+
 fn handle_health(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
     if let Ok(hp) = scan_fmt!(&args, action.arg_fmt, u32) {
         if let Some(stats) = server
@@ -517,6 +571,8 @@ fn kind_to_body(kind: NpcKind) -> comp::Body {
         NpcKind::Humanoid => comp::Body::Humanoid(comp::humanoid::Body::random()),
         NpcKind::Pig => comp::Body::Quadruped(comp::quadruped::Body::random()),
         NpcKind::Wolf => comp::Body::QuadrupedMedium(comp::quadruped_medium::Body::random()),
+        NpcKind::Bird => comp::Body::BirdMedium(comp::bird_medium::Body::random()),
+        NpcKind::Fish => comp::Body::FishSmall(comp::fish_small::Body::random()),
     }
 }
 
@@ -741,6 +797,57 @@ fn handle_lantern(server: &mut Server, entity: EcsEntity, args: String, action:
     }
 }
 
+fn handle_emote(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    let name = scan_fmt_some!(&args, action.arg_fmt, String);
+    match name.as_ref().map(|s| s.as_str()) {
+        None | Some("stop") => {
+            server
+                .state
+                .ecs()
+                .write_storage::<comp::Emote>()
+                .remove(entity);
+        }
+        // Sitting is a movement rather than an emote, so that it ends the same way as sitting
+        // down with the sit key does
+        Some("sit") => {
+            server
+                .state
+                .ecs()
+                .write_storage::<comp::Emote>()
+                .remove(entity);
+            if let Some(character) = server
+                .state
+                .ecs()
+                .write_storage::<comp::CharacterState>()
+                .get_mut(entity)
+            {
+                character.movement = comp::MovementState::Sit;
+            }
+            server.state.write_component(entity, comp::ForceUpdate);
+        }
+        Some(name) => match comp::Emote::from_name(name) {
+            Some(emote) => {
+                let _ = server
+                    .state
+                    .ecs()
+                    .write_storage::<comp::Emote>()
+                    .insert(entity, emote);
+            }
+            None => server.clients.notify(
+                entity,
+                ServerMsg::private(format!(
+                    "Unknown emote '{}', try one of: {}",
+                    name,
+                    std::iter::once("sit")
+                        .chain(comp::ALL_EMOTES.iter().map(|emote| emote.name()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            ),
+        },
+    }
+}
+
 fn handle_explosion(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
     let radius = scan_fmt!(&args, action.arg_fmt, f32).unwrap_or(8.0);
 
@@ -22,8 +22,8 @@ use common::{
     event::{EventBus, ServerEvent},
     msg::{ClientMsg, ClientState, RequestStateError, ServerError, ServerInfo, ServerMsg},
     net::PostOffice,
-    state::{BlockChange, State, TimeOfDay, Uid},
-    terrain::{block::Block, TerrainChunk, TerrainChunkSize, TerrainGrid},
+    state::{BlockChange, State, TimeOfDay, Uid, Weather},
+    terrain::{block::Block, LodMap, TerrainChunk, TerrainChunkSize, TerrainGrid},
     vol::{ReadVol, RectVolSize, Vox},
 };
 use crossbeam::channel;
@@ -43,6 +43,13 @@ use vek::*;
 use world::{ChunkSupplement, World};
 
 const CLIENT_TIMEOUT: f64 = 20.0; // Seconds
+const CORPSE_DURATION: f64 = 10.0; // Seconds
+/// How many chunks apart the samples of the LOD heightmap sent to clients are.
+const LOD_CHUNKS_PER_SAMPLE: u32 = 4;
+/// How long, on average, the weather stays the same before it starts to turn.
+const WEATHER_CHANGE_TIME: f32 = 600.0; // Seconds
+/// How long the weather takes to go from clear skies to a downpour, or back.
+const WEATHER_TURN_TIME: f32 = 60.0; // Seconds
 
 pub enum Event {
     ClientConnected {
@@ -63,6 +70,12 @@ struct SpawnPoint(Vec3<f32>);
 pub struct Server {
     state: State,
     world: Arc<World>,
+    /// A coarse heightmap of the world, sent to clients so they can draw the distant landscape.
+    lod: LodMap,
+    /// The precipitation that the weather is slowly turning towards.
+    weather_forecast: f32,
+    /// The weather that clients were last sent, so that they're only sent it again once it changes.
+    synced_weather: Weather,
 
     postoffice: PostOffice<ServerMsg, ClientMsg>,
     clients: Clients,
@@ -101,9 +114,15 @@ impl Server {
         // Set starting time for the server.
         state.ecs_mut().write_resource::<TimeOfDay>().0 = settings.start_time;
 
+        let world = World::generate(settings.world_seed);
+        let lod = world.sim().lod_map(LOD_CHUNKS_PER_SAMPLE);
+
         let this = Self {
             state,
-            world: Arc::new(World::generate(settings.world_seed)),
+            world: Arc::new(world),
+            lod,
+            weather_forecast: 0.0,
+            synced_weather: Weather::default(),
 
             postoffice: PostOffice::bind(addrs.into())?,
             clients: Clients::empty(),
@@ -251,8 +270,6 @@ impl Server {
             let state = &mut self.state;
             let clients = &mut self.clients;
 
-            let mut todo_remove = None;
-
             match event {
                 ServerEvent::Explosion { pos, radius } => {
                     const RAYS: usize = 500;
@@ -338,9 +355,8 @@ impl Server {
                         let _ = ecs.write_storage().insert(entity, comp::Vel(Vec3::zero()));
                         let _ = ecs.write_storage().insert(entity, comp::ForceUpdate);
                         client.force_state(ClientState::Dead);
-                    } else {
-                        todo_remove = Some(entity.clone());
                     }
+                    // Other corpses are left to lie for a while, and removed in `tick`
                 }
 
                 ServerEvent::Respawn(entity) => {
@@ -370,6 +386,11 @@ impl Server {
                         .get(mounter)
                         .is_none()
                     {
+                        let rideable = state
+                            .ecs()
+                            .read_storage::<comp::Body>()
+                            .get(mountee)
+                            .map_or(false, |body| body.mount_bone().is_some());
                         let not_mounting_yet = if let Some(comp::MountState::Unmounted) = state
                             .ecs()
                             .write_storage::<comp::MountState>()
@@ -381,7 +402,7 @@ impl Server {
                             false
                         };
 
-                        if not_mounting_yet {
+                        if rideable && not_mounting_yet {
                             if let (Some(mounter_uid), Some(mountee_uid)) = (
                                 state.ecs().uid_from_entity(mounter),
                                 state.ecs().uid_from_entity(mountee),
@@ -411,11 +432,27 @@ impl Server {
                     state.delete_component::<comp::Mounting>(mounter);
                 }
             }
+        }
+    }
 
-            if let Some(entity) = todo_remove {
-                let _ = state.ecs_mut().delete_entity_synced(entity);
-            }
+    /// Every so often pick a new forecast, and turn the weather towards it.
+    fn tick_weather(&mut self) {
+        let dt = self.state.get_delta_time();
+        let mut rng = rand::thread_rng();
+        if rng.gen::<f32>() < dt / WEATHER_CHANGE_TIME {
+            // Clear skies are the most common
+            self.weather_forecast = if rng.gen::<f32>() < 0.6 {
+                0.0
+            } else {
+                rng.gen_range(0.2, 1.0)
+            };
         }
+
+        let mut weather = self.state.ecs().write_resource::<Weather>();
+        let max_change = dt / WEATHER_TURN_TIME;
+        weather.precipitation += (self.weather_forecast - weather.precipitation)
+            .max(-max_change)
+            .min(max_change);
     }
 
     /// Execute a single server tick, handle input and update the game state by the given duration.
@@ -460,6 +497,9 @@ impl Server {
         // Tick the world
         self.world.tick(dt);
 
+        // Change the weather
+        self.tick_weather();
+
         let before_tick_5 = Instant::now();
         // 5) Fetch any generated `TerrainChunk`s and insert them into the terrain.
         // Also, send the chunk data to anybody that is close by.
@@ -506,10 +546,14 @@ impl Server {
                     );
                     let body = comp::Body::Humanoid(comp::humanoid::Body::random());
                     (stats, body)
-                } else {
+                } else if rand::random::<f32>() < 0.8 {
                     let stats = comp::Stats::new("Wolf".to_string(), None);
                     let body = comp::Body::QuadrupedMedium(comp::quadruped_medium::Body::random());
                     (stats, body)
+                } else {
+                    let stats = comp::Stats::new("Bird".to_string(), None);
+                    let body = comp::Body::BirdMedium(comp::bird_medium::Body::random());
+                    (stats, body)
                 };
                 let mut scale = 1.0;
 
@@ -645,6 +689,28 @@ impl Server {
             let _ = self.state.ecs_mut().delete_entity(entity);
         }
 
+        // Remove the corpses of everything but clients (who respawn instead) once they've lain
+        // long enough to be seen
+        let clients = &self.clients;
+        let corpses = (
+            &self.state.ecs().entities(),
+            &self.state.ecs().read_storage::<comp::Stats>(),
+        )
+            .join()
+            .filter(|(entity, stats)| {
+                stats.is_dead
+                    && clients.get(entity).is_none()
+                    && stats
+                        .health
+                        .last_change
+                        .map_or(true, |(_, time, _)| time > CORPSE_DURATION)
+            })
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in corpses {
+            let _ = self.state.ecs_mut().delete_entity_synced(entity);
+        }
+
         let before_tick_7 = Instant::now();
         // 7) Update Metrics
         self.metrics
@@ -713,6 +779,7 @@ impl Server {
                     ecs_state: self.state.ecs().gen_state_package(),
                     entity_uid: self.state.ecs().uid_from_entity(entity).unwrap().into(), // Can't fail.
                     server_info: self.server_info.clone(),
+                    lod: self.lod.clone(),
                 });
 
                 frontend_events.push(Event::ClientConnected { entity });
@@ -1159,6 +1226,14 @@ impl Server {
         self.clients
             .notify_registered(ServerMsg::EcsSync(self.state.ecs_mut().next_sync_package()));
 
+        // Clients joining are sent the weather along with the rest of the world, so only the
+        // clients already here need to hear about it changing
+        let weather = self.state.get_weather();
+        if weather.needs_sync(&self.synced_weather) {
+            self.clients.notify_registered(ServerMsg::Weather(weather));
+            self.synced_weather = weather;
+        }
+
         let ecs = self.state.ecs_mut();
 
         // Sync physics
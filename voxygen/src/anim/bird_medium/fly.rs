@@ -0,0 +1,56 @@
+use super::{
+    super::{Animation, SkeletonAttr},
+    set_attachments, wing_ori, BirdMediumSkeleton,
+};
+use vek::*;
+
+/// How quickly the wings beat, in radians per second.
+const FLAP_RATE: f32 = 14.0;
+/// The horizontal speed at which a bird can glide rather than flap to stay aloft.
+const GLIDE_SPEED: f32 = 12.0;
+
+/// Flying: beating the wings hard when climbing or flying slowly, and gliding with only the odd
+/// beat when flying fast or descending. The legs tuck up and the body levels out with speed.
+pub struct FlyAnimation;
+
+impl Animation for FlyAnimation {
+    type Skeleton = BirdMediumSkeleton;
+    type Dependency = Vec3<f32>;
+
+    fn update_skeleton(
+        skeleton: &Self::Skeleton,
+        velocity: Self::Dependency,
+        anim_time: f64,
+        _rate: &mut f32,
+        _skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        let mut next = (*skeleton).clone();
+        set_attachments(&mut next);
+
+        let speed = (Vec2::<f32>::from(velocity).magnitude() / GLIDE_SPEED).min(1.0);
+        // How hard the bird has to work to stay up, from gliding (0) to beating its wings (1)
+        let effort = (1.0 - speed * 0.8 + velocity.z * 0.15).min(1.0).max(0.1);
+        let phase = anim_time as f32 * FLAP_RATE;
+        let flap = phase.sin() * 0.9 * effort;
+        // Nose down to pick up speed, and up to climb
+        let pitch = -speed * 0.3 + (velocity.z * 0.05).min(0.3).max(-0.3);
+
+        // Each downstroke lifts the body a little
+        next.torso.offset = Vec3::new(0.0, 0.0, 7.0 - phase.cos() * 0.4 * effort) / 11.0;
+        next.torso.ori = Quaternion::rotation_x(pitch);
+        next.torso.scale = Vec3::one() / 11.0;
+
+        // Keep the head level, whatever the body is doing
+        next.head.ori = Quaternion::rotation_x(-pitch * 0.8 + flap * 0.05);
+
+        next.tail.ori = Quaternion::rotation_x(-pitch * 0.5 + phase.cos() * 0.1 * effort);
+
+        next.wing_l.ori = wing_ori(-1.0, 1.0, flap + 0.1);
+        next.wing_r.ori = wing_ori(1.0, 1.0, flap + 0.1);
+
+        next.leg_l.ori = Quaternion::rotation_x(-1.2);
+        next.leg_r.ori = Quaternion::rotation_x(-1.2);
+
+        next
+    }
+}
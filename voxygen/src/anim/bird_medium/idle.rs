@@ -0,0 +1,62 @@
+use super::{
+    super::{Animation, SkeletonAttr},
+    set_attachments, wing_ori, BirdMediumSkeleton,
+};
+use std::{f32::consts::PI, ops::Mul};
+use vek::*;
+
+/// How often, in seconds, a resting bird pecks at the ground.
+const PECK_INTERVAL: f32 = 3.0;
+const PECK_DURATION: f32 = 0.4;
+
+/// Standing about with wings folded, glancing around and now and then pecking at the ground.
+pub struct IdleAnimation;
+
+impl Animation for IdleAnimation {
+    type Skeleton = BirdMediumSkeleton;
+    type Dependency = f64;
+
+    fn update_skeleton(
+        skeleton: &Self::Skeleton,
+        global_time: Self::Dependency,
+        anim_time: f64,
+        _rate: &mut f32,
+        _skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        let mut next = (*skeleton).clone();
+        set_attachments(&mut next);
+
+        let breathe = (anim_time as f32 * 3.0).sin();
+        let time = (global_time + anim_time) as f32;
+
+        // Birds turn their heads in quick jerks rather than smoothly
+        let head_look = Vec2::new(
+            (time / 1.5).floor().mul(7331.0).sin() * 0.6,
+            (time / 1.5).floor().mul(1337.0).sin() * 0.2,
+        );
+        let peck_time = time % PECK_INTERVAL;
+        let peck = if peck_time < PECK_DURATION {
+            (peck_time / PECK_DURATION * PI).sin()
+        } else {
+            0.0
+        };
+
+        next.torso.offset = Vec3::new(0.0, 0.0, 7.0 + breathe * 0.1 - peck * 0.5) / 11.0;
+        next.torso.ori = Quaternion::rotation_x(-peck * 0.4);
+        next.torso.scale = Vec3::one() / 11.0;
+
+        next.head.ori = Quaternion::rotation_z(head_look.x * (1.0 - peck))
+            * Quaternion::rotation_x(head_look.y - peck * 0.9);
+
+        next.tail.ori = Quaternion::rotation_x(-0.2 + breathe * 0.03 + peck * 0.3);
+
+        next.wing_l.ori = wing_ori(-1.0, 0.0, 0.0);
+        next.wing_r.ori = wing_ori(1.0, 0.0, 0.0);
+
+        // Lean forward over the legs when pecking
+        next.leg_l.ori = Quaternion::rotation_x(peck * 0.4);
+        next.leg_r.ori = Quaternion::rotation_x(peck * 0.4);
+
+        next
+    }
+}
@@ -0,0 +1,61 @@
+use super::{
+    super::{squash_stretch, Animation, SkeletonAttr},
+    set_attachments, wing_ori, BirdMediumSkeleton,
+};
+use std::f32::consts::PI;
+use vek::*;
+
+/// How long, in seconds, a bird takes to settle after touching down.
+pub const LAND_DURATION: f32 = 0.6;
+
+fn ease(t: f32) -> f32 {
+    let t = t.min(1.0).max(0.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Touching down: the wings flare up and forward to brake and the legs reach for the ground,
+/// before the wings fold away and the body settles.
+pub struct LandAnimation;
+
+impl Animation for LandAnimation {
+    type Skeleton = BirdMediumSkeleton;
+    type Dependency = ();
+
+    fn update_skeleton(
+        skeleton: &Self::Skeleton,
+        _: Self::Dependency,
+        anim_time: f64,
+        _rate: &mut f32,
+        _skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        let mut next = (*skeleton).clone();
+        set_attachments(&mut next);
+
+        let t = anim_time as f32 / LAND_DURATION;
+        let flare = 1.0 - ease(t / 0.6);
+        let fold = ease((t - 0.3) / 0.7);
+        // A couple of quick beats to brake
+        let beat = (t * 12.0).sin() * 0.3 * flare;
+
+        // The body rears up, then dips as the legs take its weight
+        let dip = (t * PI).sin() * (1.0 - flare);
+        next.torso.offset = Vec3::new(0.0, 0.0, 7.0 - dip * 0.8) / 11.0;
+        next.torso.ori = Quaternion::rotation_x(flare * 0.5);
+        // Squashing down a little as it takes the weight
+        next.torso.scale = squash_stretch(1.0 - dip * 0.15) / 11.0;
+
+        next.head.ori = Quaternion::rotation_x(-flare * 0.4);
+
+        next.tail.ori = Quaternion::rotation_x(-0.2 - flare * 0.4);
+
+        next.wing_l.ori =
+            wing_ori(-1.0, 1.0 - fold, 0.8 + beat) * Quaternion::rotation_z(-flare * 0.4);
+        next.wing_r.ori =
+            wing_ori(1.0, 1.0 - fold, 0.8 + beat) * Quaternion::rotation_z(flare * 0.4);
+
+        next.leg_l.ori = Quaternion::rotation_x(flare * 0.6);
+        next.leg_r.ori = Quaternion::rotation_x(flare * 0.6);
+
+        next
+    }
+}
@@ -0,0 +1,160 @@
+pub mod fly;
+pub mod idle;
+pub mod land;
+
+// Reexports
+pub use self::fly::FlyAnimation;
+pub use self::idle::IdleAnimation;
+pub use self::land::{LandAnimation, LAND_DURATION};
+
+use super::{keyframe::KeyframeSkeleton, Bone, Skeleton};
+use crate::render::FigureBoneData;
+use vek::*;
+
+/// A bird, with wings that fold against its body. Everything hangs off the torso, so that
+/// pitching the body in flight carries the head, wings, tail and legs with it.
+#[derive(Clone)]
+pub struct BirdMediumSkeleton {
+    torso: Bone,
+    head: Bone,
+    tail: Bone,
+    wing_l: Bone,
+    wing_r: Bone,
+    leg_l: Bone,
+    leg_r: Bone,
+}
+
+impl BirdMediumSkeleton {
+    pub fn new() -> Self {
+        Self {
+            torso: Bone::default(),
+            head: Bone::default(),
+            tail: Bone::default(),
+            wing_l: Bone::default(),
+            wing_r: Bone::default(),
+            leg_l: Bone::default(),
+            leg_r: Bone::default(),
+        }
+    }
+}
+
+impl Skeleton for BirdMediumSkeleton {
+    const BONE_COUNT: usize = 7;
+
+    fn fill_matrices(&self, bones: &mut [FigureBoneData]) {
+        let torso_mat = self.torso.compute_base_matrix();
+
+        bones.copy_from_slice(&[
+            FigureBoneData::new(torso_mat),
+            FigureBoneData::new(torso_mat * self.head.compute_base_matrix()),
+            FigureBoneData::new(torso_mat * self.tail.compute_base_matrix()),
+            FigureBoneData::new(torso_mat * self.wing_l.compute_base_matrix()),
+            FigureBoneData::new(torso_mat * self.wing_r.compute_base_matrix()),
+            FigureBoneData::new(torso_mat * self.leg_l.compute_base_matrix()),
+            FigureBoneData::new(torso_mat * self.leg_r.compute_base_matrix()),
+        ]);
+    }
+
+    fn interpolate(&mut self, target: &Self, dt: f32) {
+        self.torso.interpolate(&target.torso, dt);
+        self.head.interpolate(&target.head, dt);
+        self.tail.interpolate(&target.tail, dt);
+        self.wing_l.interpolate(&target.wing_l, dt);
+        self.wing_r.interpolate(&target.wing_r, dt);
+        self.leg_l.interpolate(&target.leg_l, dt);
+        self.leg_r.interpolate(&target.leg_r, dt);
+    }
+
+    fn blend(&mut self, from: &Self, to: &Self, factor: f32) {
+        self.torso.blend(&from.torso, &to.torso, factor);
+        self.head.blend(&from.head, &to.head, factor);
+        self.tail.blend(&from.tail, &to.tail, factor);
+        self.wing_l.blend(&from.wing_l, &to.wing_l, factor);
+        self.wing_r.blend(&from.wing_r, &to.wing_r, factor);
+        self.leg_l.blend(&from.leg_l, &to.leg_l, factor);
+        self.leg_r.blend(&from.leg_r, &to.leg_r, factor);
+    }
+}
+
+impl KeyframeSkeleton for BirdMediumSkeleton {
+    fn bone_mut(&mut self, name: &str) -> Option<&mut Bone> {
+        match name {
+            "torso" => Some(&mut self.torso),
+            "head" => Some(&mut self.head),
+            "tail" => Some(&mut self.tail),
+            "wing_l" => Some(&mut self.wing_l),
+            "wing_r" => Some(&mut self.wing_r),
+            "leg_l" => Some(&mut self.leg_l),
+            "leg_r" => Some(&mut self.leg_r),
+            _ => None,
+        }
+    }
+}
+
+/// The orientation of the wing on the given `side` (-1 for left, 1 for right), from folded back
+/// against the body at a `spread` of 0 to held straight out and raised by `lift` radians at a
+/// `spread` of 1.
+pub fn wing_ori(side: f32, spread: f32, lift: f32) -> Quaternion<f32> {
+    let folded = Quaternion::rotation_z(-side * 1.4) * Quaternion::rotation_y(side * 0.2);
+    let out = Quaternion::rotation_y(-side * lift);
+    vek::ops::Slerp::slerp(folded, out, spread.min(1.0).max(0.0))
+}
+
+/// Pose the bones that every bird animation places the same way, relative to the torso.
+fn set_attachments(next: &mut BirdMediumSkeleton) {
+    next.head.offset = Vec3::new(0.0, 3.5, 2.5);
+    next.head.scale = Vec3::one();
+    next.tail.offset = Vec3::new(0.0, -3.5, 1.0);
+    next.tail.scale = Vec3::one();
+    next.wing_l.offset = Vec3::new(-3.0, 0.5, 1.5);
+    next.wing_l.scale = Vec3::one();
+    next.wing_r.offset = Vec3::new(3.0, 0.5, 1.5);
+    next.wing_r.scale = Vec3::one();
+    next.leg_l.offset = Vec3::new(-1.5, 0.0, -3.0);
+    next.leg_l.scale = Vec3::one();
+    next.leg_r.offset = Vec3::new(1.5, 0.0, -3.0);
+    next.leg_r.scale = Vec3::one();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::Animation, super::SkeletonAttr, *};
+
+    /// Where the tip of the wing with the given bone index is, in figure space.
+    fn wing_tip(skeleton: &BirdMediumSkeleton, bone: usize, side: f32) -> Vec3<f32> {
+        let mat = Mat4::from_col_arrays(skeleton.compute_matrices()[bone].bone_mat);
+        Vec3::from(mat * Vec4::new(side * 8.0, 0.0, 0.0, 1.0))
+    }
+
+    #[test]
+    fn wings_fold_and_flap() {
+        let attr = SkeletonAttr::default();
+        let skeleton = BirdMediumSkeleton::new();
+        let shoulder = 3.0 / 11.0;
+
+        // At rest, the wings are folded back along the body
+        let idle = IdleAnimation::update_skeleton(&skeleton, 0.0, 0.5, &mut 1.0, &attr);
+        let tip = wing_tip(&idle, 3, -1.0);
+        assert!(tip.x.abs() < 0.5 && tip.y < -0.5);
+
+        // In flight, they reach out to the sides and beat up and down
+        let tips = (0..20)
+            .map(|i| {
+                let fly = FlyAnimation::update_skeleton(
+                    &skeleton,
+                    Vec3::zero(),
+                    i as f64 * 0.05,
+                    &mut 1.0,
+                    &attr,
+                );
+                wing_tip(&fly, 4, 1.0)
+            })
+            .collect::<Vec<_>>();
+        assert!(tips.iter().all(|tip| tip.x > shoulder + 0.3));
+        let heights = tips.iter().map(|tip| tip.z);
+        let (low, high) = heights.fold((std::f32::MAX, std::f32::MIN), |(lo, hi), z| {
+            (lo.min(z), hi.max(z))
+        });
+        assert!(high - low > 0.5);
+    }
+}
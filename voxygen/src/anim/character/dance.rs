@@ -0,0 +1,100 @@
+use super::{
+    super::{Animation, SkeletonAttr},
+    CharacterSkeleton,
+};
+use std::f32::consts::PI;
+use vek::*;
+
+/// Beats per second of the dance.
+const TEMPO: f32 = 2.0;
+
+/// Dancing on the spot: bouncing to the beat, swaying the hips, stepping side to side and pumping
+/// the arms in turn.
+pub struct DanceAnimation;
+
+impl Animation for DanceAnimation {
+    type Skeleton = CharacterSkeleton;
+    type Dependency = ();
+
+    fn update_skeleton(
+        skeleton: &Self::Skeleton,
+        _: Self::Dependency,
+        anim_time: f64,
+        _rate: &mut f32,
+        skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        let mut next = (*skeleton).clone();
+
+        // One step to each side takes two beats
+        let step = (anim_time as f32 * TEMPO * PI).sin();
+        let bounce = (anim_time as f32 * TEMPO * PI * 2.0).sin().abs();
+        let pump = (anim_time as f32 * TEMPO * PI * 2.0).sin();
+
+        next.head.offset = Vec3::new(
+            0.0 + skeleton_attr.neck_right,
+            0.0 + skeleton_attr.neck_forward,
+            skeleton_attr.neck_height + 15.0 + bounce * 0.5,
+        );
+        next.head.ori =
+            Quaternion::rotation_x(bounce * 0.2 - 0.1) * Quaternion::rotation_y(step * 0.15);
+        next.head.scale = Vec3::one() * skeleton_attr.head_scale;
+
+        next.chest.offset = Vec3::new(0.0, 0.0, 7.0 + bounce * 0.5);
+        next.chest.ori = Quaternion::rotation_z(step * 0.3);
+        next.chest.scale = Vec3::one();
+
+        next.belt.offset = Vec3::new(step * 0.5, 0.0, 5.0 + bounce * 0.3);
+        next.belt.ori = Quaternion::rotation_z(-step * 0.2);
+        next.belt.scale = Vec3::one();
+
+        next.shorts.offset = Vec3::new(step * 1.0, 0.0, 2.0);
+        next.shorts.ori = Quaternion::rotation_z(-step * 0.4);
+        next.shorts.scale = Vec3::one();
+
+        // Each arm punches up on alternate beats
+        let l_pump = pump.max(0.0);
+        let r_pump = (-pump).max(0.0);
+        next.l_hand.offset = Vec3::new(-7.0 - l_pump * 1.0, 2.0, 3.0 + l_pump * 9.0);
+        next.l_hand.ori = Quaternion::rotation_y(-l_pump * 2.8) * Quaternion::rotation_x(0.3);
+        next.l_hand.scale = Vec3::one();
+
+        next.r_hand.offset = Vec3::new(7.0 + r_pump * 1.0, 2.0, 3.0 + r_pump * 9.0);
+        next.r_hand.ori = Quaternion::rotation_y(r_pump * 2.8) * Quaternion::rotation_x(0.3);
+        next.r_hand.scale = Vec3::one();
+
+        // Stepping out to the side and back
+        next.l_foot.offset = Vec3::new(-3.4 + step.min(0.0) * 2.0, 0.0, 8.0 + (-step).max(0.0));
+        next.l_foot.ori = Quaternion::rotation_y(step.min(0.0) * 0.3);
+        next.l_foot.scale = Vec3::one();
+
+        next.r_foot.offset = Vec3::new(3.4 + step.max(0.0) * 2.0, 0.0, 8.0 + step.max(0.0));
+        next.r_foot.ori = Quaternion::rotation_y(step.max(0.0) * 0.3);
+        next.r_foot.scale = Vec3::one();
+
+        next.weapon.offset = Vec3::new(
+            -7.0 + skeleton_attr.weapon_x,
+            -5.0 + skeleton_attr.weapon_y,
+            15.0,
+        );
+        next.weapon.ori = Quaternion::rotation_y(2.5) * Quaternion::rotation_z(1.57);
+        next.weapon.scale = Vec3::one();
+
+        next.l_shoulder.offset = Vec3::new(-5.0, 0.0, 4.7);
+        next.l_shoulder.ori = Quaternion::rotation_y(-l_pump * 0.3);
+        next.l_shoulder.scale = Vec3::one() * 1.1;
+
+        next.r_shoulder.offset = Vec3::new(5.0, 0.0, 4.7);
+        next.r_shoulder.ori = Quaternion::rotation_y(r_pump * 0.3);
+        next.r_shoulder.scale = Vec3::one() * 1.1;
+
+        next.draw.offset = Vec3::new(0.0, 5.0, 0.0);
+        next.draw.ori = Quaternion::rotation_y(0.0);
+        next.draw.scale = Vec3::one() * 0.0;
+
+        next.torso.offset = Vec3::new(0.0, -0.2, 0.1 + bounce * 0.1) * skeleton_attr.scaler;
+        next.torso.ori = Quaternion::rotation_y(step * 0.1);
+        next.torso.scale = Vec3::one() / 11.0 * skeleton_attr.scaler;
+
+        next
+    }
+}
@@ -0,0 +1,119 @@
+use super::{
+    super::{Animation, SkeletonAttr},
+    CharacterSkeleton,
+};
+use vek::*;
+
+/// How long, in seconds, a character takes to collapse to the ground after dying.
+pub const DEATH_DURATION: f32 = 1.0;
+
+fn ease(t: f32) -> f32 {
+    let t = t.min(1.0).max(0.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Collapsing to the ground after dying: the knees give way, then the body topples backwards and
+/// lies still, limbs splayed, for as long as the corpse remains.
+pub struct DyingAnimation;
+
+impl Animation for DyingAnimation {
+    type Skeleton = CharacterSkeleton;
+    type Dependency = ();
+
+    fn update_skeleton(
+        skeleton: &Self::Skeleton,
+        _: Self::Dependency,
+        anim_time: f64,
+        _rate: &mut f32,
+        skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        let mut next = (*skeleton).clone();
+
+        let t = anim_time as f32 / DEATH_DURATION;
+        // The knees give way first, then the body topples over
+        let buckle = ease(t / 0.4);
+        let fall = ease((t - 0.2) / 0.8);
+        let splay = ease((t - 0.5) / 0.5);
+
+        next.head.offset = Vec3::new(
+            0.0 + skeleton_attr.neck_right,
+            0.0 + skeleton_attr.neck_forward,
+            skeleton_attr.neck_height + 15.0,
+        );
+        next.head.ori = Quaternion::rotation_z(splay * 0.5)
+            * Quaternion::rotation_x(buckle * -0.4 * (1.0 - fall) + fall * 0.2);
+        next.head.scale = Vec3::one() * skeleton_attr.head_scale;
+
+        next.chest.offset = Vec3::new(0.0, 0.0, 7.0);
+        next.chest.ori = Quaternion::rotation_x(buckle * -0.3 * (1.0 - fall));
+        next.chest.scale = Vec3::one();
+
+        next.belt.offset = Vec3::new(0.0, 0.0, 5.0);
+        next.belt.ori = Quaternion::rotation_x(buckle * -0.2 * (1.0 - fall));
+        next.belt.scale = Vec3::one();
+
+        next.shorts.offset = Vec3::new(0.0, 0.0, 2.0);
+        next.shorts.ori = Quaternion::rotation_x(0.0);
+        next.shorts.scale = Vec3::one();
+
+        // The arms go limp, then fall out to the sides
+        next.l_hand.offset = Vec3::new(-7.5 - splay * 3.0, buckle * 2.0, splay * 4.0);
+        next.l_hand.ori =
+            Quaternion::rotation_y(-splay * 1.2) * Quaternion::rotation_x(buckle * 0.3);
+        next.l_hand.scale = Vec3::one();
+
+        next.r_hand.offset = Vec3::new(7.5 + splay * 3.0, buckle * 2.0, splay * 3.0);
+        next.r_hand.ori =
+            Quaternion::rotation_y(splay * 0.9) * Quaternion::rotation_x(buckle * 0.3);
+        next.r_hand.scale = Vec3::one();
+
+        // The knees bend forward as they buckle, then the legs straighten out along the ground
+        next.l_foot.offset = Vec3::new(
+            -3.4 - splay * 1.0,
+            buckle * 3.0 * (1.0 - fall),
+            8.0 - buckle * 2.0 * (1.0 - fall),
+        );
+        next.l_foot.ori = Quaternion::rotation_x(buckle * 0.6 * (1.0 - fall));
+        next.l_foot.scale = Vec3::one();
+
+        next.r_foot.offset = Vec3::new(
+            3.4 + splay * 1.5,
+            buckle * 3.0 * (1.0 - fall),
+            8.0 - buckle * 2.0 * (1.0 - fall),
+        );
+        next.r_foot.ori = Quaternion::rotation_x(buckle * 0.6 * (1.0 - fall))
+            * Quaternion::rotation_y(splay * 0.3);
+        next.r_foot.scale = Vec3::one();
+
+        next.weapon.offset = Vec3::new(
+            -7.0 + skeleton_attr.weapon_x,
+            -5.0 + skeleton_attr.weapon_y,
+            15.0,
+        );
+        next.weapon.ori = Quaternion::rotation_y(2.5) * Quaternion::rotation_z(1.57);
+        next.weapon.scale = Vec3::one();
+
+        next.l_shoulder.offset = Vec3::new(-5.0, 0.0, 4.7);
+        next.l_shoulder.ori = Quaternion::rotation_y(-splay * 0.4);
+        next.l_shoulder.scale = Vec3::one() * 1.1;
+
+        next.r_shoulder.offset = Vec3::new(5.0, 0.0, 4.7);
+        next.r_shoulder.ori = Quaternion::rotation_y(splay * 0.3);
+        next.r_shoulder.scale = Vec3::one() * 1.1;
+
+        next.draw.offset = Vec3::new(0.0, 5.0, 0.0);
+        next.draw.ori = Quaternion::rotation_y(0.0);
+        next.draw.scale = Vec3::one() * 0.0;
+
+        // Sinking as the knees give way, then lying on the back, resting on the ground
+        next.torso.offset = Vec3::new(
+            0.0,
+            -0.2 - fall * 0.3,
+            0.1 - buckle * 0.3 * (1.0 - fall) + fall * 0.3,
+        ) * skeleton_attr.scaler;
+        next.torso.ori = Quaternion::rotation_x(fall * 1.5);
+        next.torso.scale = Vec3::one() / 11.0 * skeleton_attr.scaler;
+
+        next
+    }
+}
@@ -0,0 +1,101 @@
+use super::{
+    super::{Animation, SkeletonAttr},
+    CharacterSkeleton,
+};
+use vek::*;
+
+pub struct FallAnimation;
+
+impl Animation for FallAnimation {
+    type Skeleton = CharacterSkeleton;
+    type Dependency = (Vec3<f32>, f64);
+
+    fn update_skeleton(
+        skeleton: &Self::Skeleton,
+        (velocity, global_time): Self::Dependency,
+        anim_time: f64,
+        _rate: &mut f32,
+        skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        let mut next = (*skeleton).clone();
+        // How far the limbs spread, growing as the fall speeds up
+        let spread = (-velocity.z / 30.0).min(1.0).max(0.0);
+        let flail = (global_time as f32 * 16.0).sin() * spread;
+        let flail_alt = (global_time as f32 * 16.0 + 1.2).sin() * spread;
+        let settle = (anim_time as f32 * 3.0).min(1.0);
+
+        next.head.offset = Vec3::new(
+            0.0 + skeleton_attr.neck_right,
+            0.0 + skeleton_attr.neck_forward,
+            skeleton_attr.neck_height + 15.0,
+        );
+        next.head.ori = Quaternion::rotation_x(-0.2 - spread * 0.25);
+        next.head.scale = Vec3::one() * skeleton_attr.head_scale;
+
+        next.chest.offset = Vec3::new(0.0, 0.0, 8.0);
+        next.chest.ori = Quaternion::rotation_z(0.0);
+        next.chest.scale = Vec3::one();
+
+        next.belt.offset = Vec3::new(0.0, 0.0, 6.0);
+        next.belt.ori = Quaternion::rotation_z(0.0);
+        next.belt.scale = Vec3::one();
+
+        next.shorts.offset = Vec3::new(0.0, 0.0, 3.0);
+        next.shorts.ori = Quaternion::rotation_z(0.0);
+        next.shorts.scale = Vec3::one();
+
+        next.l_hand.offset = Vec3::new(
+            -8.0 - spread * 3.0,
+            0.0,
+            0.0 + settle * 4.0 + spread * 3.0 + flail * 0.8,
+        );
+        next.l_hand.ori =
+            Quaternion::rotation_y(-0.4 - spread * 0.8) * Quaternion::rotation_x(flail * 0.3);
+        next.l_hand.scale = Vec3::one();
+
+        next.r_hand.offset = Vec3::new(
+            8.0 + spread * 3.0,
+            0.0,
+            0.0 + settle * 4.0 + spread * 3.0 + flail_alt * 0.8,
+        );
+        next.r_hand.ori =
+            Quaternion::rotation_y(0.4 + spread * 0.8) * Quaternion::rotation_x(flail_alt * 0.3);
+        next.r_hand.scale = Vec3::one();
+
+        next.l_foot.offset = Vec3::new(-3.4 - spread * 1.5, 0.0, 7.0);
+        next.l_foot.ori =
+            Quaternion::rotation_y(-spread * 0.3) * Quaternion::rotation_x(flail_alt * 0.4);
+        next.l_foot.scale = Vec3::one();
+
+        next.r_foot.offset = Vec3::new(3.4 + spread * 1.5, 0.0, 7.0);
+        next.r_foot.ori =
+            Quaternion::rotation_y(spread * 0.3) * Quaternion::rotation_x(flail * 0.4);
+        next.r_foot.scale = Vec3::one();
+
+        next.weapon.offset = Vec3::new(
+            -7.0 + skeleton_attr.weapon_x,
+            -5.0 + skeleton_attr.weapon_y,
+            15.0,
+        );
+        next.weapon.ori = Quaternion::rotation_y(2.5) * Quaternion::rotation_z(1.57);
+        next.weapon.scale = Vec3::one();
+
+        next.l_shoulder.offset = Vec3::new(-5.0, 0.0, 4.7);
+        next.l_shoulder.ori = Quaternion::rotation_y(-spread * 0.3);
+        next.l_shoulder.scale = Vec3::one() * 1.1;
+
+        next.r_shoulder.offset = Vec3::new(5.0, 0.0, 4.7);
+        next.r_shoulder.ori = Quaternion::rotation_y(spread * 0.3);
+        next.r_shoulder.scale = Vec3::one() * 1.1;
+
+        next.draw.offset = Vec3::new(0.0, 5.0, 0.0);
+        next.draw.ori = Quaternion::rotation_y(0.0);
+        next.draw.scale = Vec3::one() * 0.0;
+
+        next.torso.offset = Vec3::new(0.0, -0.2, 0.0) * skeleton_attr.scaler;
+        next.torso.ori = Quaternion::rotation_x(0.1 * spread);
+        next.torso.scale = Vec3::one() / 11.0 * skeleton_attr.scaler;
+
+        next
+    }
+}
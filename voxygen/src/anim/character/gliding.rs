@@ -5,6 +5,15 @@ use super::{
 use std::{f32::consts::PI, ops::Mul};
 use vek::*;
 
+/// How long, in seconds, the glider takes to unfold after jumping into a glide.
+const DEPLOY_DURATION: f32 = 0.4;
+/// The horizontal speed at which the body is stretched out flat beneath the glider.
+const FULL_PITCH_SPEED: f32 = 24.0;
+/// The furthest the body rolls into a turn, in radians.
+const MAX_BANK: f32 = 0.6;
+
+/// Hanging beneath the glider, unfolding it first. The body pitches forward with speed and banks
+/// into turns.
 pub struct GlidingAnimation;
 
 impl Animation for GlidingAnimation {
@@ -21,6 +30,8 @@ impl Animation for GlidingAnimation {
         let mut next = (*skeleton).clone();
 
         let speed = Vec2::<f32>::from(velocity).magnitude();
+        let deploy = (anim_time as f32 / DEPLOY_DURATION).min(1.0);
+        let deploy = deploy * deploy * (3.0 - 2.0 * deploy);
 
         let wave_slow = (anim_time as f32 * 7.0).sin();
         let wave_slow_cos = (anim_time as f32 * 7.0).cos();
@@ -56,13 +67,20 @@ impl Animation for GlidingAnimation {
         } else {
             0.0
         } * 0.8;
+        let bank = (tilt * 5.0).min(MAX_BANK).max(-MAX_BANK);
+        // Level out with speed, and tip the nose down in a dive
+        let pitch = -1.2 * (speed / FULL_PITCH_SPEED).min(1.0)
+            + (velocity.z * 0.02).min(0.0).max(-0.3)
+            + wave_very_slow * 0.10;
 
         next.head.offset = Vec3::new(
             0.0 + skeleton_attr.neck_right,
             0.0 + skeleton_attr.neck_forward,
             skeleton_attr.neck_height + 6.0,
         );
-        next.head.ori = Quaternion::rotation_x(0.35 - wave_very_slow * 0.10 + head_look.y)
+        // The head stays level with the horizon as the body pitches and banks
+        next.head.ori = Quaternion::rotation_y(-bank * 0.5)
+            * Quaternion::rotation_x(-pitch * 0.6 - wave_very_slow * 0.10 + head_look.y)
             * Quaternion::rotation_z(head_look.x + wave_very_slow_cos * 0.15);
         next.head.scale = Vec3::one() * skeleton_attr.head_scale;
 
@@ -78,20 +96,29 @@ impl Animation for GlidingAnimation {
         next.shorts.ori = Quaternion::rotation_z(wave_very_slow_cos * 0.25);
         next.shorts.scale = Vec3::one();
 
-        next.l_hand.offset = Vec3::new(
-            -9.5 + wave_very_slow_cos * -1.5,
-            -7.0 + wave_very_slow_cos * 1.5,
-            9.0,
+        // The arms reach up to the glider as it unfolds, pulling down on the inside of turns
+        next.l_hand.offset = Lerp::lerp(
+            Vec3::new(-7.5, 0.0, 0.0),
+            Vec3::new(
+                -9.5 + wave_very_slow_cos * -1.5,
+                -7.0 + wave_very_slow_cos * 1.5,
+                9.0 - bank.max(0.0) * 3.0,
+            ),
+            deploy,
         );
-        next.l_hand.ori = Quaternion::rotation_x(1.0 + wave_very_slow_cos * -0.1);
+        next.l_hand.ori = Quaternion::rotation_x((1.0 + wave_very_slow_cos * -0.1) * deploy);
         next.l_hand.scale = Vec3::one();
 
-        next.r_hand.offset = Vec3::new(
-            9.5 + wave_very_slow_cos * -1.5,
-            -7.0 + wave_very_slow_cos * -1.5,
-            9.0,
+        next.r_hand.offset = Lerp::lerp(
+            Vec3::new(7.5, 0.0, 0.0),
+            Vec3::new(
+                9.5 + wave_very_slow_cos * -1.5,
+                -7.0 + wave_very_slow_cos * -1.5,
+                9.0 + bank.min(0.0) * 3.0,
+            ),
+            deploy,
         );
-        next.r_hand.ori = Quaternion::rotation_x(1.0 + wave_very_slow_cos * -0.10);
+        next.r_hand.ori = Quaternion::rotation_x((1.0 + wave_very_slow_cos * -0.10) * deploy);
         next.r_hand.scale = Vec3::one();
 
         next.l_foot.offset = Vec3::new(-3.4, 1.0, -2.0);
@@ -123,14 +150,18 @@ impl Animation for GlidingAnimation {
         next.r_shoulder.ori = Quaternion::rotation_x(0.0);
         next.r_shoulder.scale = Vec3::one() * 1.1;
 
-        next.draw.offset = Vec3::new(0.0, -13.0 + wave_very_slow * 0.10, 6.0);
-        next.draw.ori =
-            Quaternion::rotation_x(1.0) * Quaternion::rotation_y(wave_very_slow_cos * 0.04);
-        next.draw.scale = Vec3::one();
+        // The glider swings up from the back and unfolds
+        next.draw.offset = Lerp::lerp(
+            Vec3::new(0.0, 5.0, 0.0),
+            Vec3::new(0.0, -13.0 + wave_very_slow * 0.10, 6.0),
+            deploy,
+        );
+        next.draw.ori = Quaternion::rotation_x(deploy)
+            * Quaternion::rotation_y(wave_very_slow_cos * 0.04 * deploy);
+        next.draw.scale = Vec3::new(deploy, 0.3 + deploy * 0.7, 0.3 + deploy * 0.7);
 
         next.torso.offset = Vec3::new(0.0, 6.0, 15.0) / 11.0 * skeleton_attr.scaler;
-        next.torso.ori = Quaternion::rotation_x(-0.05 * speed.max(12.0) + wave_very_slow * 0.10)
-            * Quaternion::rotation_y(tilt * 16.0);
+        next.torso.ori = Quaternion::rotation_x(pitch) * Quaternion::rotation_y(bank);
         next.torso.scale = Vec3::one() / 11.0 * skeleton_attr.scaler;
 
         next
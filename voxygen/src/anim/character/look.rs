@@ -0,0 +1,117 @@
+use std::f32::consts::PI;
+use vek::*;
+
+/// The furthest the head turns to either side, in radians.
+pub const MAX_YAW: f32 = 1.1;
+/// The furthest the head tilts up and down, in radians.
+pub const MAX_PITCH_UP: f32 = 0.5;
+pub const MAX_PITCH_DOWN: f32 = 0.6;
+/// Targets further behind than this (in radians either side) are ignored rather than craning
+/// the neck towards them.
+pub const MAX_TARGET_YAW: f32 = 2.0;
+/// How much of the turn of the head the chest follows.
+pub const CHEST_TWIST: f32 = 0.25;
+/// The height of the eyes above a character's position, in blocks.
+pub const EYE_HEIGHT: f32 = 1.6;
+/// How quickly the head turns towards a new target, per second.
+const TURN_RATE: f32 = 6.0;
+
+/// An additive turn of the head (and, slightly, the chest) towards a point of interest, applied
+/// on top of whatever the base animation does with those bones.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct HeadLook {
+    /// Turn to the left (positive) or right (negative) relative to the body, in radians.
+    pub yaw: f32,
+    /// Tilt up (positive) or down (negative), in radians.
+    pub pitch: f32,
+}
+
+impl HeadLook {
+    /// The look of a character at `pos` facing `ori` towards `target`, clamped to how far a head
+    /// can turn.
+    pub fn towards(pos: Vec3<f32>, ori: Vec3<f32>, target: Vec3<f32>) -> Self {
+        let eyes = pos + Vec3::unit_z() * EYE_HEIGHT;
+        let dir = target - eyes;
+        let flat_dist = Vec2::<f32>::from(dir).magnitude();
+        if flat_dist < 0.001 || Vec2::<f32>::from(ori).magnitude_squared() < 0.001 {
+            return Self::default();
+        }
+
+        // Angles are measured from the y axis towards the x axis, like the orientation of figures
+        let mut yaw = ori.x.atan2(ori.y) - dir.x.atan2(dir.y);
+        if yaw > PI {
+            yaw -= 2.0 * PI;
+        } else if yaw < -PI {
+            yaw += 2.0 * PI;
+        }
+        if yaw.abs() > MAX_TARGET_YAW {
+            return Self::default();
+        }
+
+        Self {
+            yaw: yaw.min(MAX_YAW).max(-MAX_YAW),
+            pitch: dir
+                .z
+                .atan2(flat_dist)
+                .min(MAX_PITCH_UP)
+                .max(-MAX_PITCH_DOWN),
+        }
+    }
+
+    /// Turn towards `target` over time, so that the head doesn't snap between targets.
+    pub fn approach(&mut self, target: Self, dt: f32) {
+        let factor = 1.0 - (-dt * TURN_RATE).exp();
+        *self = self.lerp(target, factor);
+    }
+
+    pub fn lerp(self, other: Self, factor: f32) -> Self {
+        Self {
+            yaw: self.yaw + (other.yaw - self.yaw) * factor,
+            pitch: self.pitch + (other.pitch - self.pitch) * factor,
+        }
+    }
+
+    /// The rotation of the head, applied before the head's own orientation. The head isn't
+    /// attached to the chest, so it makes the whole turn itself.
+    pub fn head_rotation(&self) -> Quaternion<f32> {
+        Quaternion::rotation_z(self.yaw) * Quaternion::rotation_x(self.pitch)
+    }
+
+    /// The rotation of the chest, applied before the chest's own orientation.
+    pub fn chest_rotation(&self) -> Quaternion<f32> {
+        Quaternion::rotation_z(self.yaw * CHEST_TWIST)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn look_towards() {
+        let pos = Vec3::zero();
+        let eyes = Vec3::unit_z() * EYE_HEIGHT;
+        let forward = Vec3::unit_y();
+
+        assert_eq!(
+            HeadLook::towards(pos, forward, eyes + Vec3::unit_y()),
+            HeadLook::default()
+        );
+
+        // Turning to the left, and up
+        let look = HeadLook::towards(pos, forward, eyes + Vec3::new(-1.0, 1.0, 0.5));
+        assert!(look.yaw > 0.0 && look.pitch > 0.0);
+        assert!((look.yaw - PI / 4.0).abs() < 1e-4);
+
+        // Clamped to how far the head can turn
+        let look = HeadLook::towards(pos, forward, eyes + Vec3::new(1.0, 0.2, -5.0));
+        assert_eq!(look.yaw, -MAX_YAW);
+        assert_eq!(look.pitch, -MAX_PITCH_DOWN);
+
+        // Ignores targets behind the character
+        assert_eq!(
+            HeadLook::towards(pos, forward, eyes - Vec3::unit_y()),
+            HeadLook::default()
+        );
+    }
+}
@@ -3,14 +3,19 @@ pub mod block;
 pub mod blockidle;
 pub mod cidle;
 pub mod climb;
+pub mod dance;
+pub mod dying;
+pub mod fall;
 pub mod gliding;
 pub mod idle;
 pub mod jump;
+pub mod look;
 pub mod roll;
 pub mod run;
 pub mod sit;
 pub mod stand;
 pub mod swim;
+pub mod swing;
 pub mod wield;
 
 // Reexports
@@ -19,18 +24,57 @@ pub use self::block::BlockAnimation;
 pub use self::blockidle::BlockIdleAnimation;
 pub use self::cidle::CidleAnimation;
 pub use self::climb::ClimbAnimation;
+pub use self::dance::DanceAnimation;
+pub use self::dying::{DyingAnimation, DEATH_DURATION};
+pub use self::fall::FallAnimation;
 pub use self::gliding::GlidingAnimation;
 pub use self::idle::IdleAnimation;
 pub use self::jump::JumpAnimation;
+pub use self::look::HeadLook;
 pub use self::roll::RollAnimation;
 pub use self::run::RunAnimation;
 pub use self::sit::SitAnimation;
 pub use self::stand::StandAnimation;
 pub use self::swim::SwimAnimation;
+pub use self::swing::{AttackSequence, SwingAnimation, SwingPhase};
 pub use self::wield::WieldAnimation;
 
-use super::{Bone, Skeleton};
+use super::{keyframe::KeyframeSkeleton, Animation, Bone, Skeleton, SkeletonAttr};
 use crate::render::FigureBoneData;
+use common::comp::item::Tool;
+use vek::*;
+
+/// The bone indices of the feet.
+pub const L_FOOT_BONE: usize = 6;
+pub const R_FOOT_BONE: usize = 7;
+/// The bone index of an item held in the right hand, which follows the hand as it's animated.
+pub const R_HAND_WEAPON_BONE: usize = 13;
+/// How far, in blocks, characters travel over a full run cycle of a step with each foot.
+pub const STRIDE_LENGTH: f32 = 2.5;
+
+/// Where the joints that limbs turn about are in their models. The neck is at the bottom of the
+/// head, the wrist at the top of the hand, and the shoulder under the middle of its pad.
+pub const NECK_PIVOT: Vec3<f32> = Vec3 {
+    x: 0.0,
+    y: 0.0,
+    z: -2.0,
+};
+pub const WRIST_PIVOT: Vec3<f32> = Vec3 {
+    x: 0.0,
+    y: 0.0,
+    z: 2.0,
+};
+pub const SHOULDER_PIVOT: Vec3<f32> = Vec3 {
+    x: 0.0,
+    y: 0.0,
+    z: -1.5,
+};
+/// Characters have no legs, so their feet swing from the hip, nine voxels above the sole.
+pub const HIP_PIVOT: Vec3<f32> = Vec3 {
+    x: 0.0,
+    y: 0.0,
+    z: 0.0,
+};
 
 #[derive(Clone)]
 pub struct CharacterSkeleton {
@@ -47,6 +91,8 @@ pub struct CharacterSkeleton {
     r_shoulder: Bone,
     draw: Bone,
     torso: Bone,
+    r_hand_weapon: Bone,
+    look: HeadLook,
 }
 
 impl CharacterSkeleton {
@@ -65,24 +111,206 @@ impl CharacterSkeleton {
             r_shoulder: Bone::default(),
             draw: Bone::default(),
             torso: Bone::default(),
+            r_hand_weapon: Bone::default(),
+            look: HeadLook::default(),
+        }
+        .with_joint_pivots()
+    }
+
+    /// Have the head, hands, shoulders and feet turn about their joints.
+    fn with_joint_pivots(mut self) -> Self {
+        self.head.pivot = NECK_PIVOT;
+        self.l_hand.pivot = WRIST_PIVOT;
+        self.r_hand.pivot = WRIST_PIVOT;
+        self.l_shoulder.pivot = SHOULDER_PIVOT;
+        self.r_shoulder.pivot = SHOULDER_PIVOT;
+        self.l_foot.pivot = HIP_PIVOT;
+        self.r_foot.pivot = HIP_PIVOT;
+        self
+    }
+
+    /// The authored rest pose: standing upright, arms at the sides and feet planted.
+    ///
+    /// Unlike `new()`, which leaves every bone at its default, this is the reference that
+    /// additive animations should compute their deltas against.
+    pub fn neutral() -> Self {
+        fn bone(offset: Vec3<f32>, ori: Quaternion<f32>, scale: Vec3<f32>) -> Bone {
+            Bone {
+                offset,
+                ori,
+                scale,
+                pivot: Vec3::zero(),
+            }
+        }
+
+        Self {
+            head: bone(
+                Vec3::new(0.0, 0.0, 15.0),
+                Quaternion::identity(),
+                Vec3::one(),
+            ),
+            chest: bone(
+                Vec3::new(0.0, 0.0, 7.0),
+                Quaternion::identity(),
+                Vec3::one(),
+            ),
+            belt: bone(
+                Vec3::new(0.0, 0.0, 5.0),
+                Quaternion::identity(),
+                Vec3::one(),
+            ),
+            shorts: bone(
+                Vec3::new(0.0, 0.0, 2.0),
+                Quaternion::identity(),
+                Vec3::one(),
+            ),
+            l_hand: bone(
+                Vec3::new(-7.5, 0.0, 0.0),
+                Quaternion::identity(),
+                Vec3::one(),
+            ),
+            r_hand: bone(
+                Vec3::new(7.5, 0.0, 0.0),
+                Quaternion::identity(),
+                Vec3::one(),
+            ),
+            l_foot: bone(
+                Vec3::new(-3.4, -0.1, 8.0),
+                Quaternion::rotation_x(0.05),
+                Vec3::one(),
+            ),
+            r_foot: bone(
+                Vec3::new(3.4, -0.1, 8.0),
+                Quaternion::rotation_x(0.05),
+                Vec3::one(),
+            ),
+            weapon: bone(
+                Vec3::new(-7.0, -5.0, 15.0),
+                Quaternion::rotation_y(2.5) * Quaternion::rotation_z(1.57),
+                Vec3::one(),
+            ),
+            l_shoulder: bone(
+                Vec3::new(-5.0, 0.0, 4.7),
+                Quaternion::identity(),
+                Vec3::one() * 1.1,
+            ),
+            r_shoulder: bone(
+                Vec3::new(5.0, 0.0, 4.7),
+                Quaternion::identity(),
+                Vec3::one() * 1.1,
+            ),
+            draw: bone(
+                Vec3::new(0.0, 5.0, 0.0),
+                Quaternion::identity(),
+                Vec3::zero(),
+            ),
+            torso: bone(
+                Vec3::new(0.0, -0.2, 0.1),
+                Quaternion::identity(),
+                Vec3::one() / 11.0,
+            ),
+            r_hand_weapon: bone(Vec3::zero(), Quaternion::identity(), Vec3::one()),
+            look: HeadLook::default(),
+        }
+        .with_joint_pivots()
+    }
+
+    /// Grip `tool` in the right hand the way it's held when wielded, so that while it's drawn it
+    /// follows the hand wherever the animations move it.
+    pub fn hold_weapon(&mut self, tool: Option<Tool>, skeleton_attr: &SkeletonAttr) {
+        let wield = WieldAnimation::update_skeleton(
+            &Self::neutral(),
+            (tool, 0.0, 0.0),
+            0.0,
+            &mut 1.0,
+            skeleton_attr,
+        );
+        self.r_hand_weapon = grip(&wield.r_hand, &wield.weapon);
+    }
+
+    /// Turn the head and chest towards a point of interest, on top of the pose given by the
+    /// animation.
+    pub fn set_look(&mut self, look: HeadLook) {
+        self.look = look;
+    }
+
+    /// Estimates how fast each bone moved since `prev`, in figure space and in the order used by
+    /// `compute_matrices`. Useful for driving trails and motion blur from the right hand or weapon.
+    pub fn bone_velocities(
+        &self,
+        prev: &Self,
+        dt: f32,
+    ) -> [Vec3<f32>; <CharacterSkeleton as Skeleton>::BONE_COUNT] {
+        let mut velocities = [Vec3::zero(); <CharacterSkeleton as Skeleton>::BONE_COUNT];
+        if dt <= 0.0 {
+            return velocities;
         }
+
+        let translation = |bone: &FigureBoneData| {
+            let col = bone.bone_mat[3];
+            Vec3::new(col[0], col[1], col[2])
+        };
+        let current = self.compute_matrices();
+        let prev = prev.compute_matrices();
+        for (i, velocity) in velocities.iter_mut().enumerate() {
+            *velocity = (translation(&current[i]) - translation(&prev[i])) / dt;
+        }
+
+        velocities
+    }
+
+    /// Flatten the bone matrices into a contiguous buffer for upload, with each matrix stored in
+    /// column-major order as the shader expects.
+    pub fn to_bone_buffer(&self) -> [f32; 16 * 16] {
+        let mut buffer = [0.0; 16 * 16];
+        for (bone, data) in self.compute_matrices().iter().enumerate() {
+            for (col, column) in data.bone_mat.iter().enumerate() {
+                let start = bone * 16 + col * 4;
+                buffer[start..start + 4].copy_from_slice(column);
+            }
+        }
+        buffer
+    }
+}
+
+/// The bone that places `held` where it is when its parent is `hand` rather than the chest. Only
+/// uniform scales and an unpivoted `held` are supported.
+fn grip(hand: &Bone, held: &Bone) -> Bone {
+    let inv_ori = hand.ori.conjugate();
+    Bone {
+        offset: hand.pivot + inv_ori * ((held.offset - hand.offset) / hand.scale - hand.pivot),
+        ori: inv_ori * held.ori,
+        scale: held.scale / hand.scale,
+        pivot: Vec3::zero(),
     }
 }
 
 impl Skeleton for CharacterSkeleton {
-    fn compute_matrices(&self) -> [FigureBoneData; 16] {
-        let chest_mat = self.chest.compute_base_matrix();
+    const BONE_COUNT: usize = 14;
+
+    fn fill_matrices(&self, bones: &mut [FigureBoneData]) {
+        // The look is layered on top of the animated pose of the head and chest
+        let chest_mat = Bone {
+            ori: self.look.chest_rotation() * self.chest.ori,
+            ..self.chest
+        }
+        .compute_base_matrix();
         let torso_mat = self.torso.compute_base_matrix();
         let l_hand_mat = self.l_hand.compute_base_matrix();
+        let r_hand_mat = self.r_hand.compute_base_matrix();
         let weapon_mat = self.weapon.compute_base_matrix();
-        let head_mat = self.head.compute_base_matrix();
-        [
+        let head_mat = Bone {
+            ori: self.look.head_rotation() * self.head.ori,
+            ..self.head
+        }
+        .compute_base_matrix();
+        bones.copy_from_slice(&[
             FigureBoneData::new(torso_mat * head_mat),
             FigureBoneData::new(torso_mat * chest_mat),
             FigureBoneData::new(torso_mat * self.belt.compute_base_matrix()),
             FigureBoneData::new(torso_mat * self.shorts.compute_base_matrix()),
             FigureBoneData::new(torso_mat * chest_mat * l_hand_mat),
-            FigureBoneData::new(torso_mat * chest_mat * self.r_hand.compute_base_matrix()),
+            FigureBoneData::new(torso_mat * chest_mat * r_hand_mat),
             FigureBoneData::new(torso_mat * self.l_foot.compute_base_matrix()),
             FigureBoneData::new(torso_mat * self.r_foot.compute_base_matrix()),
             FigureBoneData::new(torso_mat * chest_mat * weapon_mat),
@@ -90,10 +318,10 @@ impl Skeleton for CharacterSkeleton {
             FigureBoneData::new(torso_mat * chest_mat * self.r_shoulder.compute_base_matrix()),
             FigureBoneData::new(torso_mat * self.draw.compute_base_matrix()),
             FigureBoneData::new(torso_mat),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-        ]
+            FigureBoneData::new(
+                torso_mat * chest_mat * r_hand_mat * self.r_hand_weapon.compute_base_matrix(),
+            ),
+        ]);
     }
 
     fn interpolate(&mut self, target: &Self, dt: f32) {
@@ -110,5 +338,301 @@ impl Skeleton for CharacterSkeleton {
         self.r_shoulder.interpolate(&target.r_shoulder, dt);
         self.draw.interpolate(&target.draw, dt);
         self.torso.interpolate(&target.torso, dt);
+        self.r_hand_weapon.interpolate(&target.r_hand_weapon, dt);
+        self.look.approach(target.look, dt);
+    }
+
+    fn blend(&mut self, from: &Self, to: &Self, factor: f32) {
+        self.head.blend(&from.head, &to.head, factor);
+        self.chest.blend(&from.chest, &to.chest, factor);
+        self.belt.blend(&from.belt, &to.belt, factor);
+        self.shorts.blend(&from.shorts, &to.shorts, factor);
+        self.l_hand.blend(&from.l_hand, &to.l_hand, factor);
+        self.r_hand.blend(&from.r_hand, &to.r_hand, factor);
+        self.l_foot.blend(&from.l_foot, &to.l_foot, factor);
+        self.r_foot.blend(&from.r_foot, &to.r_foot, factor);
+        self.weapon.blend(&from.weapon, &to.weapon, factor);
+        self.l_shoulder
+            .blend(&from.l_shoulder, &to.l_shoulder, factor);
+        self.r_shoulder
+            .blend(&from.r_shoulder, &to.r_shoulder, factor);
+        self.draw.blend(&from.draw, &to.draw, factor);
+        self.torso.blend(&from.torso, &to.torso, factor);
+        self.r_hand_weapon
+            .blend(&from.r_hand_weapon, &to.r_hand_weapon, factor);
+        self.look = from.look.lerp(to.look, factor.min(1.0).max(0.0));
+    }
+}
+
+impl KeyframeSkeleton for CharacterSkeleton {
+    fn bone_mut(&mut self, name: &str) -> Option<&mut Bone> {
+        match name {
+            "head" => Some(&mut self.head),
+            "chest" => Some(&mut self.chest),
+            "belt" => Some(&mut self.belt),
+            "shorts" => Some(&mut self.shorts),
+            "l_hand" => Some(&mut self.l_hand),
+            "r_hand" => Some(&mut self.r_hand),
+            "l_foot" => Some(&mut self.l_foot),
+            "r_foot" => Some(&mut self.r_foot),
+            "weapon" => Some(&mut self.weapon),
+            "l_shoulder" => Some(&mut self.l_shoulder),
+            "r_shoulder" => Some(&mut self.r_shoulder),
+            "draw" => Some(&mut self.draw),
+            "torso" => Some(&mut self.torso),
+            "r_hand_weapon" => Some(&mut self.r_hand_weapon),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neutral_pose() {
+        let neutral = CharacterSkeleton::neutral().compute_matrices();
+        assert_ne!(neutral, CharacterSkeleton::new().compute_matrices());
+        assert_eq!(neutral, CharacterSkeleton::neutral().compute_matrices());
+    }
+
+    #[test]
+    fn limbs_turn_about_their_joints() {
+        let joint = |skeleton: &CharacterSkeleton, bone: usize, pivot: Vec3<f32>| {
+            let mat = Mat4::from_col_arrays(skeleton.compute_matrices()[bone].bone_mat);
+            Vec3::from(mat * Vec4::from_point(pivot))
+        };
+        let neutral = CharacterSkeleton::neutral();
+        let mut raised = neutral.clone();
+        raised.l_hand.ori = Quaternion::rotation_x(1.2);
+        raised.head.ori = Quaternion::rotation_z(0.8);
+        raised.l_shoulder.ori = Quaternion::rotation_y(0.5);
+
+        // The hand swings about the wrist, the head about the neck and so on
+        for (bone, pivot) in &[(4, WRIST_PIVOT), (0, NECK_PIVOT), (9, SHOULDER_PIVOT)] {
+            let moved = joint(&raised, *bone, *pivot) - joint(&neutral, *bone, *pivot);
+            assert!(moved.magnitude() < 1e-4);
+        }
+        // Anywhere else on the hand does move
+        assert!(
+            (joint(&raised, 4, Vec3::zero()) - joint(&neutral, 4, Vec3::zero())).magnitude() > 0.01
+        );
+    }
+
+    #[test]
+    fn bone_velocities() {
+        let prev = CharacterSkeleton::neutral();
+        for velocity in prev.bone_velocities(&prev, 0.5).iter() {
+            assert_eq!(*velocity, Vec3::zero());
+        }
+
+        let mut moved = prev.clone();
+        moved.torso.offset += Vec3::new(1.0, 0.0, 0.0);
+        let velocities = moved.bone_velocities(&prev, 0.5);
+        assert_eq!(velocities.len(), CharacterSkeleton::BONE_COUNT);
+        for velocity in velocities.iter() {
+            assert!(velocity.distance(Vec3::new(2.0, 0.0, 0.0)) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn look_layer() {
+        let neutral = CharacterSkeleton::neutral();
+        let mut looking = neutral.clone();
+        looking.set_look(HeadLook {
+            yaw: 0.5,
+            pitch: 0.2,
+        });
+
+        let (before, after) = (neutral.compute_matrices(), looking.compute_matrices());
+        // The head and chest turn, the legs stay put
+        assert_ne!(before[0], after[0]);
+        assert_ne!(before[1], after[1]);
+        assert_eq!(before[6], after[6]);
+        assert_eq!(before[7], after[7]);
+    }
+
+    #[test]
+    fn bone_buffer_layout() {
+        let skeleton = CharacterSkeleton::neutral();
+        let buffer = skeleton.to_bone_buffer();
+
+        for (bone, data) in skeleton.compute_matrices().iter().enumerate() {
+            let mut cols = [0.0; 16];
+            cols.copy_from_slice(&buffer[bone * 16..(bone + 1) * 16]);
+            let mat = Mat4::from_col_array(cols);
+            assert_eq!(FigureBoneData::new(mat), *data);
+        }
+    }
+
+    #[test]
+    fn fall_spreads_limbs() {
+        use super::super::{Animation, SkeletonAttr};
+
+        let pose = |fall_speed: f32| {
+            FallAnimation::update_skeleton(
+                &CharacterSkeleton::new(),
+                (Vec3::new(0.0, 0.0, -fall_speed), 0.0),
+                1.0,
+                &mut 1.0,
+                &SkeletonAttr::default(),
+            )
+        };
+        let (slow, fast) = (pose(2.0), pose(40.0));
+
+        assert!(
+            fast.r_hand.offset.x - fast.l_hand.offset.x
+                > slow.r_hand.offset.x - slow.l_hand.offset.x
+        );
+        assert!(
+            fast.r_foot.offset.x - fast.l_foot.offset.x
+                > slow.r_foot.offset.x - slow.l_foot.offset.x
+        );
+    }
+
+    #[test]
+    fn swimming_strokes() {
+        use super::super::{Animation, SkeletonAttr};
+
+        let pose = |speed: f32, time: f64| {
+            SwimAnimation::update_skeleton(
+                &CharacterSkeleton::new(),
+                Vec3::new(0.0, speed, 0.0),
+                time,
+                &mut 1.0,
+                &SkeletonAttr::default(),
+            )
+        };
+
+        // Swimmers lie flatter the faster they go
+        let up = |skeleton: &CharacterSkeleton| (skeleton.torso.ori * Vec3::unit_z()).z;
+        assert!(up(&pose(10.0, 0.0)) < up(&pose(0.0, 0.0)));
+
+        // The arms take turns to stroke
+        let (start, later) = (pose(10.0, 0.0), pose(10.0, 0.25));
+        assert!(start.l_hand.offset.distance(later.l_hand.offset) > 1.0);
+        assert!(start.l_hand.offset.z != start.r_hand.offset.z);
+    }
+
+    #[test]
+    fn glider_unfolds() {
+        use super::super::{Animation, SkeletonAttr};
+
+        let pose = |velocity: Vec3<f32>, last_ori: Vec3<f32>, time: f64| {
+            GlidingAnimation::update_skeleton(
+                &CharacterSkeleton::new(),
+                (velocity, Vec3::unit_y(), last_ori, 0.0),
+                time,
+                &mut 1.0,
+                &SkeletonAttr::default(),
+            )
+        };
+        let forward = Vec3::unit_y();
+
+        // The glider starts packed away and unfolds
+        let (start, open) = (
+            pose(Vec3::zero(), forward, 0.0),
+            pose(Vec3::zero(), forward, 1.0),
+        );
+        assert!(start.draw.scale.x < open.draw.scale.x);
+        assert!(start.l_hand.offset.z < open.l_hand.offset.z);
+
+        // Faster gliding pitches the body further forward
+        let up = |skeleton: &CharacterSkeleton| (skeleton.torso.ori * Vec3::unit_z()).z;
+        assert!(up(&pose(forward * 20.0, forward, 1.0)) < up(&pose(forward * 2.0, forward, 1.0)));
+
+        // Sharp turns bank the body without rolling it over
+        let turning = pose(forward * 20.0, Vec3::unit_x(), 1.0);
+        assert!(up(&turning) > 0.0);
+    }
+
+    #[test]
+    fn corpses_lie_still() {
+        use super::super::{Animation, SkeletonAttr};
+
+        let pose = |time: f64| {
+            DyingAnimation::update_skeleton(
+                &CharacterSkeleton::new(),
+                (),
+                time,
+                &mut 1.0,
+                &SkeletonAttr::default(),
+            )
+        };
+
+        // Upright at the moment of death, lying down once collapsed
+        let up = |skeleton: &CharacterSkeleton| (skeleton.torso.ori * Vec3::unit_z()).z;
+        assert!(up(&pose(0.0)) > 0.99);
+        assert!(up(&pose(DEATH_DURATION as f64)) < 0.1);
+        assert_eq!(
+            pose(DEATH_DURATION as f64).compute_matrices(),
+            pose(DEATH_DURATION as f64 * 10.0).compute_matrices()
+        );
+    }
+
+    #[test]
+    fn blend_endpoints() {
+        let from = CharacterSkeleton::neutral();
+        let mut to = from.clone();
+        to.torso.offset += Vec3::new(0.3, -0.7, 1.1);
+        to.torso.ori = Quaternion::rotation_z(0.4);
+        to.chest.ori = Quaternion::rotation_x(-0.9);
+        to.r_hand.scale *= 1.3;
+
+        let mut blended = CharacterSkeleton::new();
+        blended.blend(&from, &to, 0.0);
+        assert_eq!(blended.compute_matrices(), from.compute_matrices());
+        blended.blend(&from, &to, 1.0);
+        assert_eq!(blended.compute_matrices(), to.compute_matrices());
+        // Factors are clamped
+        blended.blend(&from, &to, 2.0);
+        assert_eq!(blended.compute_matrices(), to.compute_matrices());
+
+        blended.blend(&from, &to, 0.5);
+        assert_ne!(blended.compute_matrices(), from.compute_matrices());
+        assert_ne!(blended.compute_matrices(), to.compute_matrices());
+    }
+
+    #[test]
+    fn weapon_follows_hand() {
+        let translation = |bone: &FigureBoneData| {
+            Vec3::new(
+                bone.bone_mat[3][0],
+                bone.bone_mat[3][1],
+                bone.bone_mat[3][2],
+            )
+        };
+
+        let rest = CharacterSkeleton::neutral();
+        let mut raised = rest.clone();
+        raised.r_hand.offset += Vec3::new(0.0, 2.0, 4.0);
+        let (rest, raised) = (rest.compute_matrices(), raised.compute_matrices());
+
+        let hand_moved = translation(&raised[5]) - translation(&rest[5]);
+        let weapon_moved =
+            translation(&raised[R_HAND_WEAPON_BONE]) - translation(&rest[R_HAND_WEAPON_BONE]);
+        assert!(hand_moved.magnitude() > 0.0);
+        assert!(hand_moved.distance(weapon_moved) < 1e-5);
+    }
+
+    #[test]
+    fn weapon_held_as_wielded() {
+        let skeleton_attr = SkeletonAttr::default();
+        let mut wielded = WieldAnimation::update_skeleton(
+            &CharacterSkeleton::neutral(),
+            (Some(Tool::Sword), 0.0, 0.0),
+            0.0,
+            &mut 1.0,
+            &skeleton_attr,
+        );
+        wielded.hold_weapon(Some(Tool::Sword), &skeleton_attr);
+
+        // Held in the hand, the weapon ends up where the wield pose puts it
+        let matrices = wielded.compute_matrices();
+        let (held, placed) = (matrices[R_HAND_WEAPON_BONE].bone_mat, matrices[8].bone_mat);
+        for (held, placed) in held.iter().flatten().zip(placed.iter().flatten()) {
+            assert!((held - placed).abs() < 1e-4, "{:?} != {:?}", held, placed);
+        }
     }
 }
@@ -1,11 +1,14 @@
 use super::{
-    super::{Animation, SkeletonAttr},
-    CharacterSkeleton,
+    super::{stride_rate, stride_reach, Animation, SkeletonAttr},
+    CharacterSkeleton, STRIDE_LENGTH,
 };
 use std::f32::consts::PI;
 use std::ops::Mul;
 use vek::*;
 
+/// How quickly, in radians per unit of animation time, the feet step.
+const STEP_SPEED: f32 = 1.5;
+
 pub struct RunAnimation;
 
 impl Animation for RunAnimation {
@@ -22,7 +25,10 @@ impl Animation for RunAnimation {
         let mut next = (*skeleton).clone();
 
         let speed = Vec2::<f32>::from(velocity).magnitude();
-        *rate = speed;
+        // Take a step with each foot every stride, so that planted feet don't slide
+        *rate = stride_rate(speed, STRIDE_LENGTH, STEP_SPEED);
+        let reach = stride_reach(STRIDE_LENGTH, skeleton_attr.scaler / 11.0);
+        let step = anim_time as f32 * STEP_SPEED;
 
         let constant = 1.0;
         let wave = (((5.0)
@@ -105,11 +111,13 @@ impl Animation for RunAnimation {
         next.r_hand.ori = Quaternion::rotation_x(wave_cos * -0.8);
         next.r_hand.scale = Vec3::one();
 
-        next.l_foot.offset = Vec3::new(-3.4, 0.0 + wave_cos * 1.0, 6.0 - wave_cos_dub * 0.7);
+        // Feet are lifted as they swing forward, and planted as they swing back
+        next.l_foot.offset = Vec3::new(-3.4, step.sin() * reach, 6.0 + step.cos().max(0.0) * 1.5);
         next.l_foot.ori = Quaternion::rotation_x(-0.0 - wave_cos * 1.2);
         next.l_foot.scale = Vec3::one();
 
-        next.r_foot.offset = Vec3::new(3.4, 0.0 - wave_cos * 1.0, 6.0 - wave_cos_dub * 0.7);
+        next.r_foot.offset =
+            Vec3::new(3.4, -step.sin() * reach, 6.0 + (-step.cos()).max(0.0) * 1.5);
         next.r_foot.ori = Quaternion::rotation_x(-0.0 + wave_cos * 1.2);
         next.r_foot.scale = Vec3::one();
 
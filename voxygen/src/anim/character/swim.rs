@@ -3,99 +3,100 @@ use super::{
     CharacterSkeleton,
 };
 use std::f32::consts::PI;
-use std::ops::Mul;
 use vek::*;
 
+/// The horizontal speed at which a swimmer is stretched out in a full front crawl, rather than
+/// treading water upright.
+const STROKE_SPEED: f32 = 5.0;
+
+/// Swimming, blending from treading water upright when still to a front crawl with arm strokes
+/// and a flutter kick when moving.
 pub struct SwimAnimation;
 
 impl Animation for SwimAnimation {
     type Skeleton = CharacterSkeleton;
-    type Dependency = (f32, f32, f64);
+    type Dependency = Vec3<f32>;
 
     fn update_skeleton(
         skeleton: &Self::Skeleton,
-        (velocity, orientation, global_time): Self::Dependency,
+        velocity: Self::Dependency,
         anim_time: f64,
         _rate: &mut f32,
         skeleton_attr: &SkeletonAttr,
     ) -> Self::Skeleton {
         let mut next = (*skeleton).clone();
 
-        let wave = (anim_time as f32 * velocity * 1.2).sin();
-        let wave_cos = (anim_time as f32 * velocity * 1.2).cos();
-
-        let wave_diff = (anim_time as f32 * velocity * 0.6).sin();
-        let wave_cos_dub = (anim_time as f32 * velocity * 2.4).cos();
-        let wave_stop = (anim_time as f32 * 2.6).min(PI / 2.0).sin();
-        let head_look = Vec2::new(
-            ((global_time + anim_time) as f32 / 2.0)
-                .floor()
-                .mul(7331.0)
-                .sin()
-                * 0.2,
-            ((global_time + anim_time) as f32 / 2.0)
-                .floor()
-                .mul(1337.0)
-                .sin()
-                * 0.1,
-        );
-
-        let vel = Vec2::from(velocity);
-        let ori = (Vec2::from(orientation)).normalized();
-
-        let _tilt = if Vec2::new(ori, vel)
-            .map(|v| Vec2::<f32>::from(v).magnitude_squared())
-            .reduce_partial_min()
-            > 0.001
-        {
-            vel.normalized().dot(ori.normalized()).min(1.0).acos()
-        } else {
-            0.0
-        };
+        // How far from treading water (0) to a full stroke (1) the swimmer is
+        let stroke = (Vec2::<f32>::from(velocity).magnitude() / STROKE_SPEED).min(1.0);
+        let phase = anim_time as f32 * (3.0 + stroke * 3.0);
+        let kick = (phase * 2.0).sin();
+        // Leaning into the stroke, and pitching up or down when rising or sinking
+        let pitch = -stroke * 1.3 + (velocity.z * 0.05).min(0.4).max(-0.4);
 
         next.head.offset = Vec3::new(
-            0.0,
+            0.0 + skeleton_attr.neck_right,
             -1.0 + skeleton_attr.neck_forward,
-            skeleton_attr.neck_height + 15.0 + wave_cos * 1.3,
+            skeleton_attr.neck_height + 15.0,
         );
-        next.head.ori = Quaternion::rotation_z(head_look.x + wave * 0.1)
-            * Quaternion::rotation_x(head_look.y + 0.35);
+        // Keep the face forward, turning to the side to breathe in time with the stroke
+        next.head.ori = Quaternion::rotation_z(phase.sin() * 0.3 * stroke)
+            * Quaternion::rotation_x(-pitch * 0.7);
         next.head.scale = Vec3::one() * skeleton_attr.head_scale;
 
-        next.chest.offset = Vec3::new(0.0, 0.0, 7.0 + wave_cos * 1.1);
-        next.chest.ori = Quaternion::rotation_z(wave * 0.2);
+        next.chest.offset = Vec3::new(0.0, 0.0, 7.0);
+        next.chest.ori = Quaternion::rotation_y(phase.sin() * 0.25 * stroke);
         next.chest.scale = Vec3::one();
 
-        next.belt.offset = Vec3::new(0.0, 0.0, 5.0 + wave_cos * 1.1);
-        next.belt.ori = Quaternion::rotation_z(wave * 0.35);
+        next.belt.offset = Vec3::new(0.0, 0.0, 5.0);
+        next.belt.ori = Quaternion::rotation_y(phase.sin() * 0.15 * stroke);
         next.belt.scale = Vec3::one();
 
-        next.shorts.offset = Vec3::new(0.0, 0.0, 2.0 + wave_cos * 1.1);
-        next.shorts.ori = Quaternion::rotation_z(wave * 0.6);
+        next.shorts.offset = Vec3::new(0.0, 0.0, 2.0);
+        next.shorts.ori = Quaternion::rotation_y(phase.sin() * 0.1 * stroke);
         next.shorts.scale = Vec3::one();
 
-        next.l_hand.offset = Vec3::new(
-            -7.5 + wave_cos_dub * 1.0,
-            2.0 + wave_cos * 5.0,
-            0.0 - wave * 1.5,
-        );
-        next.l_hand.ori = Quaternion::rotation_x(wave_cos * 0.8);
+        // Each arm circles the shoulder: overhead, pulling through beneath the body, then back
+        // over to the front. When treading water, the hands scull back and forth at the sides.
+        let arm = |side: f32, angle: f32| {
+            let crawl = Vec3::new(side * 7.5, angle.sin() * 5.0, 5.0 + angle.cos() * 5.0);
+            let scull = Vec3::new(
+                side * (8.0 + phase.sin() * 1.5),
+                2.0 + phase.cos() * 2.0,
+                3.0,
+            );
+            let ori = vek::ops::Slerp::slerp(
+                Quaternion::rotation_y(side * 0.4) * Quaternion::rotation_z(phase.sin() * 0.5),
+                Quaternion::rotation_x(PI - angle),
+                stroke,
+            );
+            (Lerp::lerp(scull, crawl, stroke), ori)
+        };
+        let (l_hand, l_hand_ori) = arm(-1.0, phase);
+        let (r_hand, r_hand_ori) = arm(1.0, phase + PI);
+
+        next.l_hand.offset = l_hand;
+        next.l_hand.ori = l_hand_ori;
         next.l_hand.scale = Vec3::one();
 
-        next.r_hand.offset = Vec3::new(
-            7.5 - wave_cos_dub * 1.0,
-            2.0 - wave_cos * 5.0,
-            0.0 + wave * 1.5,
-        );
-        next.r_hand.ori = Quaternion::rotation_x(wave_cos * -0.8);
+        next.r_hand.offset = r_hand;
+        next.r_hand.ori = r_hand_ori;
         next.r_hand.scale = Vec3::one();
 
-        next.l_foot.offset = Vec3::new(-3.4, 0.0 + wave_cos * 1.0, 6.0 - wave_cos_dub * 0.7);
-        next.l_foot.ori = Quaternion::rotation_x(-0.0 - wave_cos * 1.5);
+        // A flutter kick when swimming, or slower circling kicks to stay afloat
+        next.l_foot.offset = Vec3::new(
+            -3.4 - phase.cos() * (1.0 - stroke),
+            kick * stroke + phase.sin() * (1.0 - stroke),
+            6.0,
+        );
+        next.l_foot.ori = Quaternion::rotation_x(kick * 0.6 * stroke - 0.2);
         next.l_foot.scale = Vec3::one();
 
-        next.r_foot.offset = Vec3::new(3.4, 0.0 - wave_cos * 1.0, 6.0 - wave_cos_dub * 0.7);
-        next.r_foot.ori = Quaternion::rotation_x(-0.0 + wave_cos * 1.5);
+        next.r_foot.offset = Vec3::new(
+            3.4 + phase.cos() * (1.0 - stroke),
+            -kick * stroke - phase.sin() * (1.0 - stroke),
+            6.0,
+        );
+        next.r_foot.ori = Quaternion::rotation_x(-kick * 0.6 * stroke - 0.2);
         next.r_foot.scale = Vec3::one();
 
         next.weapon.offset = Vec3::new(
@@ -103,26 +104,25 @@ impl Animation for SwimAnimation {
             -5.0 + skeleton_attr.weapon_y,
             15.0,
         );
-        next.weapon.ori =
-            Quaternion::rotation_y(2.5) * Quaternion::rotation_z(1.57 + wave_cos * 0.25);
+        next.weapon.ori = Quaternion::rotation_y(2.5) * Quaternion::rotation_z(1.57);
         next.weapon.scale = Vec3::one();
 
         next.l_shoulder.offset = Vec3::new(-5.0, 0.0, 4.7);
-        next.l_shoulder.ori = Quaternion::rotation_x(wave_cos * 0.15);
+        next.l_shoulder.ori = Quaternion::rotation_x(phase.cos() * 0.3 * stroke);
         next.l_shoulder.scale = Vec3::one() * 1.1;
 
         next.r_shoulder.offset = Vec3::new(5.0, 0.0, 4.7);
-        next.r_shoulder.ori = Quaternion::rotation_x(wave * 0.15);
+        next.r_shoulder.ori = Quaternion::rotation_x(-phase.cos() * 0.3 * stroke);
         next.r_shoulder.scale = Vec3::one() * 1.1;
 
         next.draw.offset = Vec3::new(0.0, 5.0, 0.0);
         next.draw.ori = Quaternion::rotation_y(0.0);
         next.draw.scale = Vec3::one() * 0.0;
 
-        next.torso.offset = Vec3::new(0.0, -0.2 + wave * -0.08, 0.4) * skeleton_attr.scaler;
-        next.torso.ori =
-            Quaternion::rotation_x(wave_stop * velocity * -0.06 + wave_diff * velocity * -0.005)
-                * Quaternion::rotation_y(0.0);
+        // Bob gently at the surface
+        next.torso.offset =
+            Vec3::new(0.0, -0.2, 0.4 + (phase * 0.5).sin() * 0.1) * skeleton_attr.scaler;
+        next.torso.ori = Quaternion::rotation_x(pitch);
         next.torso.scale = Vec3::one() / 11.0 * skeleton_attr.scaler;
 
         next
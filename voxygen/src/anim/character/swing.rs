@@ -0,0 +1,310 @@
+use super::{
+    super::{Animation, SkeletonAttr},
+    CharacterSkeleton,
+};
+use common::comp::item::Tool;
+use vek::*;
+
+/// The stages of a melee attack.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SwingPhase {
+    /// Drawing the tool back before the strike.
+    Windup,
+    /// The strike itself.
+    Swing,
+    /// Returning to a ready stance after the strike.
+    Recovery,
+}
+
+/// How long, in seconds, each phase of an attack with a tool lasts.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SwingTiming {
+    pub windup: f32,
+    pub swing: f32,
+    pub recovery: f32,
+}
+
+impl SwingTiming {
+    pub fn for_tool(tool: Tool) -> Self {
+        // Heavier tools take longer to wind up, but all attacks fit within the attack duration
+        let (windup, swing, recovery) = match tool {
+            Tool::Daggers => (0.05, 0.1, 0.15),
+            Tool::Sword | Tool::SwordShield => (0.1, 0.15, 0.2),
+            Tool::Axe => (0.15, 0.15, 0.2),
+            Tool::Hammer => (0.2, 0.1, 0.2),
+            Tool::Bow => (0.25, 0.05, 0.2),
+            Tool::Staff => (0.15, 0.1, 0.25),
+        };
+        Self {
+            windup,
+            swing,
+            recovery,
+        }
+    }
+
+    pub fn total(&self) -> f32 {
+        self.windup + self.swing + self.recovery
+    }
+}
+
+/// Sequences the phases of a character's attack: windup, then swing, then recovery.
+#[derive(Clone, Debug, Default)]
+pub struct AttackSequence {
+    timing: Option<SwingTiming>,
+    time: f32,
+}
+
+impl AttackSequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new attack with `tool`, from the beginning of its windup.
+    pub fn start(&mut self, tool: Tool) {
+        self.timing = Some(SwingTiming::for_tool(tool));
+        self.time = 0.0;
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
+    }
+
+    /// The current phase of the attack and how far through it (from 0 to 1) the attack is, or
+    /// `None` if no attack has been started. Finished attacks stay at the end of their recovery.
+    pub fn phase(&self) -> Option<(SwingPhase, f32)> {
+        let timing = self.timing?;
+        let swing_start = timing.windup;
+        let recovery_start = timing.windup + timing.swing;
+
+        Some(if self.time < swing_start {
+            (SwingPhase::Windup, self.time / timing.windup)
+        } else if self.time < recovery_start {
+            (SwingPhase::Swing, (self.time - swing_start) / timing.swing)
+        } else {
+            (
+                SwingPhase::Recovery,
+                ((self.time - recovery_start) / timing.recovery).min(1.0),
+            )
+        })
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.timing
+            .map(|timing| self.time >= timing.total())
+            .unwrap_or(true)
+    }
+}
+
+/// A key pose of the hands and tool during an attack.
+#[derive(Copy, Clone)]
+struct SwingPose {
+    l_hand: Vec3<f32>,
+    r_hand: Vec3<f32>,
+    ori: Quaternion<f32>,
+    // Rotation of the torso about the vertical axis
+    twist: f32,
+}
+
+impl SwingPose {
+    fn new(l_hand: Vec3<f32>, r_hand: Vec3<f32>, ori: Quaternion<f32>, twist: f32) -> Self {
+        Self {
+            l_hand,
+            r_hand,
+            ori,
+            twist,
+        }
+    }
+
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            l_hand: Lerp::lerp(self.l_hand, other.l_hand, t),
+            r_hand: Lerp::lerp(self.r_hand, other.r_hand, t),
+            ori: vek::ops::Slerp::slerp(self.ori, other.ori, t),
+            twist: self.twist + (other.twist - self.twist) * t,
+        }
+    }
+}
+
+/// The ready, drawn back and struck poses of an attack with `tool`.
+fn key_poses(tool: Tool) -> (SwingPose, SwingPose, SwingPose) {
+    let ready = SwingPose::new(
+        Vec3::new(-6.0, 3.5, 0.0),
+        Vec3::new(-6.0, 3.0, -2.0),
+        Quaternion::rotation_x(-0.3),
+        0.0,
+    );
+
+    match tool {
+        // A horizontal slash
+        Tool::Sword | Tool::SwordShield => (
+            ready,
+            SwingPose::new(
+                Vec3::new(2.0, 2.0, 3.0),
+                Vec3::new(2.0, 1.5, 1.0),
+                Quaternion::rotation_z(1.2) * Quaternion::rotation_x(-0.3),
+                0.4,
+            ),
+            SwingPose::new(
+                Vec3::new(-10.0, 8.0, 0.0),
+                Vec3::new(-10.0, 7.5, -2.0),
+                Quaternion::rotation_z(-1.2) * Quaternion::rotation_x(-0.6),
+                -0.5,
+            ),
+        ),
+        // A diagonal chop
+        Tool::Axe => (
+            ready,
+            SwingPose::new(
+                Vec3::new(3.0, 2.0, 6.0),
+                Vec3::new(3.0, 1.5, 4.0),
+                Quaternion::rotation_y(0.8) * Quaternion::rotation_z(1.0),
+                0.3,
+            ),
+            SwingPose::new(
+                Vec3::new(-8.0, 8.0, -2.0),
+                Vec3::new(-8.0, 7.5, -4.0),
+                Quaternion::rotation_y(-0.6) * Quaternion::rotation_z(-1.0),
+                -0.4,
+            ),
+        ),
+        // An overhead slam
+        Tool::Hammer => (
+            SwingPose::new(
+                Vec3::new(-7.0, 8.25, 3.0),
+                Vec3::new(7.0, 7.0, -1.5),
+                Quaternion::rotation_x(-0.3) * Quaternion::rotation_y(-1.2),
+                0.0,
+            ),
+            SwingPose::new(
+                Vec3::new(-3.0, 0.0, 10.0),
+                Vec3::new(3.0, 0.0, 8.0),
+                Quaternion::rotation_x(1.8),
+                0.0,
+            ),
+            SwingPose::new(
+                Vec3::new(-3.0, 9.0, -3.0),
+                Vec3::new(3.0, 9.0, -5.0),
+                Quaternion::rotation_x(-1.2),
+                0.0,
+            ),
+        ),
+        // Drawing the string back, then loosing the arrow
+        Tool::Bow => (
+            ready,
+            SwingPose::new(
+                Vec3::new(-2.0, 8.0, 2.0),
+                Vec3::new(2.0, -1.0, 2.0),
+                Quaternion::rotation_z(1.57),
+                0.6,
+            ),
+            SwingPose::new(
+                Vec3::new(-2.0, 8.0, 2.0),
+                Vec3::new(3.0, -3.0, 3.0),
+                Quaternion::rotation_z(1.57),
+                0.6,
+            ),
+        ),
+        // A forward thrust
+        Tool::Staff | Tool::Daggers => (
+            ready,
+            SwingPose::new(
+                Vec3::new(-4.0, 0.0, 2.0),
+                Vec3::new(-4.0, -0.5, 0.0),
+                Quaternion::rotation_x(0.3),
+                0.2,
+            ),
+            SwingPose::new(
+                Vec3::new(-4.0, 12.0, 2.0),
+                Vec3::new(-4.0, 11.5, 0.0),
+                Quaternion::rotation_x(-0.6),
+                -0.2,
+            ),
+        ),
+    }
+}
+
+/// A melee attack with a tool, posed according to the phase of the attack and how far through
+/// that phase it is (see `AttackSequence`).
+pub struct SwingAnimation;
+
+impl Animation for SwingAnimation {
+    type Skeleton = CharacterSkeleton;
+    type Dependency = (Tool, SwingPhase, f32);
+
+    fn update_skeleton(
+        skeleton: &Self::Skeleton,
+        (tool, phase, progress): Self::Dependency,
+        _anim_time: f64,
+        _rate: &mut f32,
+        skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        let mut next = (*skeleton).clone();
+
+        let t = progress.min(1.0).max(0.0);
+        let (ready, drawn, struck) = key_poses(tool);
+        let pose = match phase {
+            SwingPhase::Windup => ready.lerp(drawn, t * t * (3.0 - 2.0 * t)),
+            // Strikes are fast at first, then slow down as they follow through
+            SwingPhase::Swing => drawn.lerp(struck, 1.0 - (1.0 - t) * (1.0 - t)),
+            SwingPhase::Recovery => struck.lerp(ready, t * t * (3.0 - 2.0 * t)),
+        };
+
+        next.l_hand.offset = pose.l_hand;
+        next.l_hand.ori = pose.ori;
+        next.l_hand.scale = Vec3::one() * 1.01;
+
+        next.r_hand.offset = pose.r_hand;
+        next.r_hand.ori = pose.ori;
+        next.r_hand.scale = Vec3::one() * 1.01;
+
+        next.weapon.offset =
+            pose.r_hand + Vec3::new(skeleton_attr.weapon_x, 1.0 + skeleton_attr.weapon_y, 2.0);
+        next.weapon.ori = pose.ori;
+        next.weapon.scale = Vec3::one();
+
+        next.torso.ori = skeleton.torso.ori * Quaternion::rotation_z(pose.twist);
+
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attack_phases() {
+        let mut attack = AttackSequence::new();
+        assert_eq!(attack.phase(), None);
+        assert!(attack.is_finished());
+
+        attack.start(Tool::Hammer);
+        let mut phases = vec![];
+        while !attack.is_finished() {
+            let (phase, progress) = attack.phase().unwrap();
+            assert!(progress >= 0.0 && progress <= 1.0);
+            if phases.last() != Some(&phase) {
+                phases.push(phase);
+            }
+            attack.update(0.01);
+        }
+        assert_eq!(
+            phases,
+            vec![SwingPhase::Windup, SwingPhase::Swing, SwingPhase::Recovery]
+        );
+
+        // Finished attacks hold the end of their recovery
+        attack.update(1.0);
+        assert_eq!(attack.phase(), Some((SwingPhase::Recovery, 1.0)));
+    }
+
+    #[test]
+    fn attacks_fit_attack_duration() {
+        use common::{comp::item::ALL_TOOLS, sys::combat::ATTACK_DURATION};
+
+        let attack_duration = ATTACK_DURATION.as_millis() as f32 / 1000.0;
+        for tool in ALL_TOOLS.iter() {
+            assert!(SwingTiming::for_tool(*tool).total() <= attack_duration + 1e-4);
+        }
+    }
+}
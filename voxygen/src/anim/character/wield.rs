@@ -9,11 +9,11 @@ pub struct WieldAnimation;
 
 impl Animation for WieldAnimation {
     type Skeleton = CharacterSkeleton;
-    type Dependency = (f32, f64);
+    type Dependency = (Option<Tool>, f32, f64);
 
     fn update_skeleton(
         skeleton: &Self::Skeleton,
-        (_velocity, _global_time): Self::Dependency,
+        (tool, _velocity, _global_time): Self::Dependency,
         anim_time: f64,
         _rate: &mut f32,
         skeleton_attr: &SkeletonAttr,
@@ -22,8 +22,8 @@ impl Animation for WieldAnimation {
 
         let wave = (anim_time as f32 * 12.0).sin();
 
-        match Tool::Hammer {
-            //TODO: Inventory
+        // Without a tool, wield as if holding a hammer
+        match tool.unwrap_or(Tool::Hammer) {
             Tool::Sword => {
                 next.l_hand.offset = Vec3::new(-6.0, 3.75, 0.25);
                 next.l_hand.ori = Quaternion::rotation_x(-0.3);
@@ -0,0 +1,60 @@
+use super::{
+    super::{Animation, SkeletonAttr},
+    FirstPersonSkeleton,
+};
+use common::sys::combat::ATTACK_DURATION;
+use std::f32::consts::PI;
+use vek::*;
+
+/// The fraction of an attack spent drawing the weapon back before swinging it.
+const WIND_UP: f32 = 0.3;
+
+/// A swing across the view: the weapon is drawn back over the right shoulder, then brought down
+/// and across to the left, with the left hand pulled in to make room for it.
+pub struct AttackAnimation;
+
+impl Animation for AttackAnimation {
+    type Skeleton = FirstPersonSkeleton;
+    type Dependency = ();
+
+    fn update_skeleton(
+        skeleton: &Self::Skeleton,
+        _: Self::Dependency,
+        anim_time: f64,
+        _rate: &mut f32,
+        _skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        let mut next = (*skeleton).clone();
+
+        let progress = (anim_time as f32 / (ATTACK_DURATION.as_millis() as f32 / 1000.0)).min(1.0);
+        // Ease into the wind up, then snap through the swing and slow to a stop
+        let (draw, swing) = if progress < WIND_UP {
+            ((progress / WIND_UP * PI / 2.0).sin(), 0.0)
+        } else {
+            (
+                1.0,
+                ((progress - WIND_UP) / (1.0 - WIND_UP) * PI / 2.0).sin(),
+            )
+        };
+
+        next.l_hand.offset = Vec3::new(-6.0 + draw * 1.5, 6.0, -7.0 - draw * 1.0);
+        next.l_hand.ori = Quaternion::rotation_x(0.3) * Quaternion::rotation_y(-0.2);
+        next.l_hand.scale = Vec3::one();
+
+        next.r_hand.offset = Vec3::new(
+            6.0 + draw * 1.0 - swing * 10.0,
+            7.0 + draw * -2.0 + swing * 5.0,
+            -6.0 + draw * 6.0 - swing * 8.0,
+        );
+        next.r_hand.ori = Quaternion::rotation_x(0.3 + draw * 0.6 - swing * 1.4)
+            * Quaternion::rotation_y(0.2 + swing * 0.8);
+        next.r_hand.scale = Vec3::one();
+
+        next.weapon.offset = next.r_hand.offset + Vec3::new(0.0, 0.5, 1.0);
+        next.weapon.ori = Quaternion::rotation_x(-0.3 + draw * 0.9 - swing * 2.2)
+            * Quaternion::rotation_y(0.2 + swing * 1.2);
+        next.weapon.scale = Vec3::one();
+
+        next
+    }
+}
@@ -0,0 +1,50 @@
+use super::{
+    super::{Animation, SkeletonAttr},
+    FirstPersonSkeleton,
+};
+use std::f32::consts::PI;
+use vek::*;
+
+/// How long, in seconds, it takes to raise the guard.
+const RAISE_TIME: f32 = 0.15;
+
+/// Guarding: the weapon is raised and held across the view with both hands, trembling slightly
+/// under the strain.
+pub struct BlockAnimation;
+
+impl Animation for BlockAnimation {
+    type Skeleton = FirstPersonSkeleton;
+    type Dependency = ();
+
+    fn update_skeleton(
+        skeleton: &Self::Skeleton,
+        _: Self::Dependency,
+        anim_time: f64,
+        _rate: &mut f32,
+        _skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        let mut next = (*skeleton).clone();
+
+        let raise = (anim_time as f32 / RAISE_TIME * PI / 2.0)
+            .min(PI / 2.0)
+            .sin();
+        let tremble = (anim_time as f32 * 20.0).sin() * 0.1;
+
+        next.l_hand.offset = Vec3::new(-6.0 + raise * 3.0, 7.0 + raise, -6.0 + raise * 4.0);
+        next.l_hand.ori = Quaternion::rotation_x(0.3) * Quaternion::rotation_y(-0.2 - raise * 1.2);
+        next.l_hand.scale = Vec3::one();
+
+        next.r_hand.offset =
+            Vec3::new(6.0 - raise * 1.0, 7.0 + raise, -6.0 + raise * 3.0 + tremble);
+        next.r_hand.ori = Quaternion::rotation_x(0.3) * Quaternion::rotation_y(0.2 + raise * 1.2);
+        next.r_hand.scale = Vec3::one();
+
+        // Turned on its side to guard across the face
+        next.weapon.offset = next.r_hand.offset + Vec3::new(0.0, 0.5, 1.0);
+        next.weapon.ori =
+            Quaternion::rotation_x(-0.3) * Quaternion::rotation_y(0.2 - raise * (PI / 2.0 + 0.2));
+        next.weapon.scale = Vec3::one();
+
+        next
+    }
+}
@@ -0,0 +1,55 @@
+use super::{
+    super::{Animation, SkeletonAttr},
+    FirstPersonSkeleton,
+};
+use vek::*;
+
+/// The speed, in blocks per second, at which the hands bob the most.
+const MAX_BOB_SPEED: f32 = 8.0;
+
+/// Hands held low at the edges of the view, breathing gently and bobbing with each step when
+/// walking. The weapon, if drawn, is held upright in the right hand.
+pub struct IdleAnimation;
+
+impl Animation for IdleAnimation {
+    type Skeleton = FirstPersonSkeleton;
+    type Dependency = f32;
+
+    fn update_skeleton(
+        skeleton: &Self::Skeleton,
+        speed: Self::Dependency,
+        anim_time: f64,
+        _rate: &mut f32,
+        _skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        let mut next = (*skeleton).clone();
+
+        let breathe = (anim_time as f32 * 1.5).sin();
+        let walk = (speed / MAX_BOB_SPEED).min(1.0);
+        // The hands dip once per step, and sway from side to side once per stride
+        let step = (anim_time as f32 * 10.0).sin();
+        let sway = (anim_time as f32 * 5.0).sin();
+
+        next.l_hand.offset = Vec3::new(
+            -6.0 + sway * walk * 0.5,
+            7.0,
+            -6.0 + breathe * 0.2 - step.abs() * walk * 0.6,
+        );
+        next.l_hand.ori = Quaternion::rotation_x(0.3) * Quaternion::rotation_y(-0.2);
+        next.l_hand.scale = Vec3::one();
+
+        next.r_hand.offset = Vec3::new(
+            6.0 + sway * walk * 0.5,
+            7.0,
+            -6.0 + breathe * 0.2 - step.abs() * walk * 0.6,
+        );
+        next.r_hand.ori = Quaternion::rotation_x(0.3) * Quaternion::rotation_y(0.2);
+        next.r_hand.scale = Vec3::one();
+
+        next.weapon.offset = next.r_hand.offset + Vec3::new(0.0, 0.5, 1.0);
+        next.weapon.ori = Quaternion::rotation_x(-0.3) * Quaternion::rotation_y(0.2);
+        next.weapon.scale = Vec3::one();
+
+        next
+    }
+}
@@ -0,0 +1,109 @@
+pub mod attack;
+pub mod block;
+pub mod idle;
+
+// Reexports
+pub use self::attack::AttackAnimation;
+pub use self::block::BlockAnimation;
+pub use self::idle::IdleAnimation;
+
+use super::{character::WRIST_PIVOT, keyframe::KeyframeSkeleton, Bone, Skeleton};
+use crate::render::FigureBoneData;
+use vek::*;
+
+/// The player's own hands and weapon, as seen in first person. Rather than hanging off a body,
+/// everything is posed relative to the eyes, which tilt up and down with the camera so that the
+/// hands stay in view wherever the player looks.
+#[derive(Clone)]
+pub struct FirstPersonSkeleton {
+    l_hand: Bone,
+    r_hand: Bone,
+    weapon: Bone,
+    /// How far the camera is looking down, in radians.
+    pitch: f32,
+}
+
+impl FirstPersonSkeleton {
+    pub fn new() -> Self {
+        Self {
+            l_hand: Bone {
+                pivot: WRIST_PIVOT,
+                ..Bone::default()
+            },
+            r_hand: Bone {
+                pivot: WRIST_PIVOT,
+                ..Bone::default()
+            },
+            weapon: Bone::default(),
+            pitch: 0.0,
+        }
+    }
+
+    /// Tilt the view, and the hands with it, to follow the camera's pitch.
+    pub fn set_pitch(&mut self, pitch: f32) {
+        self.pitch = pitch;
+    }
+}
+
+impl Skeleton for FirstPersonSkeleton {
+    const BONE_COUNT: usize = 3;
+
+    fn fill_matrices(&self, bones: &mut [FigureBoneData]) {
+        let view_mat = Mat4::rotation_x(-self.pitch) * Mat4::scaling_3d(Vec3::from(1.0 / 11.0));
+
+        bones.copy_from_slice(&[
+            FigureBoneData::new(view_mat * self.l_hand.compute_base_matrix()),
+            FigureBoneData::new(view_mat * self.r_hand.compute_base_matrix()),
+            FigureBoneData::new(view_mat * self.weapon.compute_base_matrix()),
+        ]);
+    }
+
+    fn interpolate(&mut self, target: &Self, dt: f32) {
+        self.l_hand.interpolate(&target.l_hand, dt);
+        self.r_hand.interpolate(&target.r_hand, dt);
+        self.weapon.interpolate(&target.weapon, dt);
+    }
+
+    fn blend(&mut self, from: &Self, to: &Self, factor: f32) {
+        self.l_hand.blend(&from.l_hand, &to.l_hand, factor);
+        self.r_hand.blend(&from.r_hand, &to.r_hand, factor);
+        self.weapon.blend(&from.weapon, &to.weapon, factor);
+    }
+}
+
+impl KeyframeSkeleton for FirstPersonSkeleton {
+    fn bone_mut(&mut self, name: &str) -> Option<&mut Bone> {
+        match name {
+            "l_hand" => Some(&mut self.l_hand),
+            "r_hand" => Some(&mut self.r_hand),
+            "weapon" => Some(&mut self.weapon),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anim::{Animation, SkeletonAttr};
+
+    #[test]
+    fn hands_follow_pitch() {
+        let pose = IdleAnimation::update_skeleton(
+            &FirstPersonSkeleton::new(),
+            0.0,
+            0.0,
+            &mut 1.0,
+            &SkeletonAttr::default(),
+        );
+        let height = |pitch: f32| {
+            let mut skeleton = pose.clone();
+            skeleton.set_pitch(pitch);
+            skeleton.compute_matrices()[1].bone_mat[3][2]
+        };
+
+        // Looking down brings the hands down with the view, and looking up raises them
+        assert!(height(0.5) < height(0.0));
+        assert!(height(-0.5) > height(0.0));
+    }
+}
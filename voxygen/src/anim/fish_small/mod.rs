@@ -0,0 +1,72 @@
+pub mod swim;
+
+// Reexports
+pub use self::swim::SwimAnimation;
+
+use super::{keyframe::KeyframeSkeleton, Bone, Skeleton};
+use crate::render::FigureBoneData;
+
+/// A fish or serpent, whose body is a chain of segments from the head back to the tail fin. Each
+/// segment is posed relative to the one in front of it, so that bending one carries the rest of
+/// the body behind it.
+#[derive(Clone)]
+pub struct FishSmallSkeleton {
+    head: Bone,
+    body_front: Bone,
+    body_back: Bone,
+    tail: Bone,
+}
+
+impl FishSmallSkeleton {
+    pub fn new() -> Self {
+        Self {
+            head: Bone::default(),
+            body_front: Bone::default(),
+            body_back: Bone::default(),
+            tail: Bone::default(),
+        }
+    }
+}
+
+impl Skeleton for FishSmallSkeleton {
+    const BONE_COUNT: usize = 4;
+
+    fn fill_matrices(&self, bones: &mut [FigureBoneData]) {
+        let body_front_mat = self.body_front.compute_base_matrix();
+        let body_back_mat = body_front_mat * self.body_back.compute_base_matrix();
+
+        bones.copy_from_slice(&[
+            FigureBoneData::new(body_front_mat * self.head.compute_base_matrix()),
+            FigureBoneData::new(body_front_mat),
+            FigureBoneData::new(body_back_mat),
+            FigureBoneData::new(body_back_mat * self.tail.compute_base_matrix()),
+        ]);
+    }
+
+    fn interpolate(&mut self, target: &Self, dt: f32) {
+        self.head.interpolate(&target.head, dt);
+        self.body_front.interpolate(&target.body_front, dt);
+        self.body_back.interpolate(&target.body_back, dt);
+        self.tail.interpolate(&target.tail, dt);
+    }
+
+    fn blend(&mut self, from: &Self, to: &Self, factor: f32) {
+        self.head.blend(&from.head, &to.head, factor);
+        self.body_front
+            .blend(&from.body_front, &to.body_front, factor);
+        self.body_back.blend(&from.body_back, &to.body_back, factor);
+        self.tail.blend(&from.tail, &to.tail, factor);
+    }
+}
+
+impl KeyframeSkeleton for FishSmallSkeleton {
+    fn bone_mut(&mut self, name: &str) -> Option<&mut Bone> {
+        match name {
+            "head" => Some(&mut self.head),
+            "body_front" => Some(&mut self.body_front),
+            "body_back" => Some(&mut self.body_back),
+            "tail" => Some(&mut self.tail),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use super::{
+    super::{Animation, SkeletonAttr},
+    FishSmallSkeleton,
+};
+use vek::*;
+
+/// How many radians of the wave each segment lags behind the one in front of it.
+const SEGMENT_LAG: f32 = 1.1;
+/// How far each segment, from the head back to the tail fin, swings to the side, in radians.
+/// The wave grows as it travels down the body, so the head stays fairly steady.
+const AMPLITUDES: [f32; 4] = [0.1, 0.15, 0.35, 0.55];
+/// The speed at which fish beat their tails hardest.
+const MAX_SWIM_SPEED: f32 = 8.0;
+
+/// The sideways swing of each segment, from the head back to the tail fin, `phase` radians into
+/// the wave, at a `strength` between 0 (drifting) and 1 (swimming flat out).
+pub fn spine_wave(phase: f32, strength: f32) -> [f32; 4] {
+    let mut swings = [0.0; 4];
+    for (i, (swing, amplitude)) in swings.iter_mut().zip(AMPLITUDES.iter()).enumerate() {
+        *swing = (phase - i as f32 * SEGMENT_LAG).sin() * amplitude * (0.3 + strength * 0.7);
+    }
+    swings
+}
+
+/// Swimming by sending a wave down the spine from head to tail, which beats faster and wider the
+/// faster the fish swims. Drifting fish still gently sway to hold their place.
+pub struct SwimAnimation;
+
+impl Animation for SwimAnimation {
+    type Skeleton = FishSmallSkeleton;
+    type Dependency = Vec3<f32>;
+
+    fn update_skeleton(
+        skeleton: &Self::Skeleton,
+        velocity: Self::Dependency,
+        anim_time: f64,
+        rate: &mut f32,
+        _skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        let mut next = (*skeleton).clone();
+
+        let strength = (velocity.magnitude() / MAX_SWIM_SPEED).min(1.0);
+        // Beat faster when swimming faster, without the wave jumping when the speed changes
+        *rate = 1.0 + strength * 2.0;
+        let phase = anim_time as f32 * 6.0;
+        let [head, body_front, body_back, tail] = spine_wave(phase, strength);
+        // Nose up or down when rising or diving
+        let pitch = (velocity.z / MAX_SWIM_SPEED).min(0.6).max(-0.6);
+
+        // The wave travels back along the chain, so each segment only turns by how much more it
+        // swings than the one in front of it
+        next.body_front.offset = Vec3::new(0.0, 0.0, 3.0) / 11.0;
+        next.body_front.ori = Quaternion::rotation_z(body_front) * Quaternion::rotation_x(pitch);
+        next.body_front.scale = Vec3::one() / 11.0;
+
+        next.head.offset = Vec3::new(0.0, 3.0, 0.0);
+        next.head.ori = Quaternion::rotation_z(head - body_front);
+        next.head.scale = Vec3::one();
+
+        next.body_back.offset = Vec3::new(0.0, -3.0, 0.0);
+        next.body_back.ori = Quaternion::rotation_z(body_back - body_front);
+        next.body_back.scale = Vec3::one();
+
+        next.tail.offset = Vec3::new(0.0, -6.0, 0.0);
+        next.tail.ori = Quaternion::rotation_z(tail - body_back);
+        next.tail.scale = Vec3::one();
+
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wave_travels_to_tail() {
+        // Each segment reaches the peak of the wave after the one in front of it
+        let peak_phase = |segment: usize| {
+            (0..628)
+                .map(|i| i as f32 * 0.01)
+                .max_by(|a, b| {
+                    spine_wave(*a, 1.0)[segment]
+                        .partial_cmp(&spine_wave(*b, 1.0)[segment])
+                        .unwrap()
+                })
+                .unwrap()
+        };
+        let (head, tail) = (peak_phase(0), peak_phase(3));
+        let lag = (tail - head + 6.28) % 6.28;
+        assert!((lag - 3.0 * SEGMENT_LAG).abs() < 0.05);
+
+        // The tail swings widest, more so when swimming hard
+        let widest = |strength: f32| {
+            (0..628)
+                .map(|i| spine_wave(i as f32 * 0.01, strength)[3].abs())
+                .fold(0.0f32, f32::max)
+        };
+        assert!(widest(1.0) > widest(0.0));
+        assert!(widest(1.0) > AMPLITUDES[0] * 2.0);
+    }
+}
@@ -11,26 +11,13 @@ impl FixtureSkeleton {
 }
 
 impl Skeleton for FixtureSkeleton {
-    fn compute_matrices(&self) -> [FigureBoneData; 16] {
-        [
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-            FigureBoneData::new(vek::Mat4::identity()),
-        ]
+    const BONE_COUNT: usize = 1;
+
+    fn fill_matrices(&self, bones: &mut [FigureBoneData]) {
+        bones.copy_from_slice(&[FigureBoneData::new(vek::Mat4::identity())]);
     }
 
     fn interpolate(&mut self, _target: &Self, _dt: f32) {}
+
+    fn blend(&mut self, _from: &Self, _to: &Self, _factor: f32) {}
 }
@@ -0,0 +1,268 @@
+use super::{Animation, Bone, Skeleton, SkeletonAttr};
+use common::assets::{self, watch::ReloadIndicator, Asset};
+use hashbrown::HashMap;
+use serde_derive::Deserialize;
+use std::{fs::File, io::BufReader, marker::PhantomData, sync::Arc};
+use vek::*;
+
+/// How a value moves from one keyframe to the next.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Hold the value of the keyframe until the next one.
+    Step,
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::Linear
+    }
+}
+
+impl Easing {
+    /// Map the linear progress `t` (from 0 to 1) between two keyframes to eased progress.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.min(1.0).max(0.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+            Easing::Step => 0.0,
+        }
+    }
+}
+
+/// The pose of a bone at a point in time. Parts of the pose that aren't given are left as they are
+/// in the pose that the animation is applied to, or interpolated between the keyframes that do
+/// give them.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Keyframe {
+    /// Seconds since the start of the animation.
+    pub time: f32,
+    #[serde(default)]
+    pub offset: Option<[f32; 3]>,
+    /// Rotations about the x, y and z axes in radians, applied in that order.
+    #[serde(default)]
+    pub ori: Option<[f32; 3]>,
+    #[serde(default)]
+    pub scale: Option<[f32; 3]>,
+    /// The point the bone rotates about, in the space of its model.
+    #[serde(default)]
+    pub pivot: Option<[f32; 3]>,
+    /// How to move from this keyframe to the next one.
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+/// An animation made of keyframes for each bone, loaded from an asset so that it can be changed
+/// without recompiling.
+#[derive(Clone, Debug, Deserialize)]
+pub struct KeyframeAnimationData {
+    /// The length of the animation in seconds.
+    pub duration: f32,
+    /// Whether the animation starts over once it ends, rather than holding its last pose.
+    #[serde(default)]
+    pub looping: bool,
+    /// The keyframes of each animated bone, keyed by bone name.
+    pub bones: HashMap<String, Vec<Keyframe>>,
+}
+
+impl Asset for KeyframeAnimationData {
+    const ENDINGS: &'static [&'static str] = &["ron"];
+    fn parse(buf_reader: BufReader<File>) -> Result<Self, assets::Error> {
+        let mut data: Self =
+            ron::de::from_reader(buf_reader).expect("Error parsing keyframe animation");
+        // Sampling expects keyframes in order
+        for keyframes in data.bones.values_mut() {
+            keyframes.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+        }
+        Ok(data)
+    }
+}
+
+impl KeyframeAnimationData {
+    /// Load an animation, reloading it whenever the asset changes.
+    pub fn load_watched(
+        specifier: &str,
+        indicator: &mut ReloadIndicator,
+    ) -> Result<Arc<Self>, assets::Error> {
+        assets::load_watched::<Self>(specifier, indicator)
+    }
+
+    /// Apply the animation at `time` seconds to the bones of `skeleton`. Bones that the skeleton
+    /// doesn't have are ignored.
+    pub fn apply<S: KeyframeSkeleton>(&self, skeleton: &mut S, time: f32) {
+        let time = if self.looping && self.duration > 0.0 {
+            time % self.duration
+        } else {
+            time.min(self.duration)
+        };
+
+        for (name, keyframes) in self.bones.iter() {
+            if let Some(bone) = skeleton.bone_mut(name) {
+                if let Some(offset) =
+                    sample(keyframes, time, |k| k.offset.map(Vec3::from), Lerp::lerp)
+                {
+                    bone.offset = offset;
+                }
+                if let Some(ori) = sample(
+                    keyframes,
+                    time,
+                    |k| k.ori.map(euler_to_quaternion),
+                    vek::ops::Slerp::slerp,
+                ) {
+                    bone.ori = ori;
+                }
+                if let Some(scale) =
+                    sample(keyframes, time, |k| k.scale.map(Vec3::from), Lerp::lerp)
+                {
+                    bone.scale = scale;
+                }
+                if let Some(pivot) =
+                    sample(keyframes, time, |k| k.pivot.map(Vec3::from), Lerp::lerp)
+                {
+                    bone.pivot = pivot;
+                }
+            }
+        }
+    }
+}
+
+fn euler_to_quaternion([x, y, z]: [f32; 3]) -> Quaternion<f32> {
+    Quaternion::rotation_x(x) * Quaternion::rotation_y(y) * Quaternion::rotation_z(z)
+}
+
+/// Sample one part of a bone's pose at `time`, from the keyframes that give that part.
+fn sample<T: Copy>(
+    keyframes: &[Keyframe],
+    time: f32,
+    channel: impl Fn(&Keyframe) -> Option<T>,
+    mix: impl Fn(T, T, f32) -> T,
+) -> Option<T> {
+    let mut keys = keyframes
+        .iter()
+        .filter_map(|key| channel(key).map(|value| (key, value)));
+    let mut prev = keys.next()?;
+    if time <= prev.0.time {
+        return Some(prev.1);
+    }
+    for next in keys {
+        if time < next.0.time {
+            let t = (time - prev.0.time) / (next.0.time - prev.0.time);
+            return Some(mix(prev.1, next.1, prev.0.easing.apply(t)));
+        }
+        prev = next;
+    }
+    Some(prev.1)
+}
+
+/// A skeleton whose bones can be looked up by name, so that keyframe animations can be applied
+/// to it.
+pub trait KeyframeSkeleton: Skeleton {
+    fn bone_mut(&mut self, name: &str) -> Option<&mut Bone>;
+}
+
+/// Applies a `KeyframeAnimationData` asset to a skeleton, on top of the pose of the skeleton it's
+/// given.
+pub struct KeyframeAnimation<S>(PhantomData<S>);
+
+impl<S: KeyframeSkeleton + Clone> Animation for KeyframeAnimation<S> {
+    type Skeleton = S;
+    type Dependency = Arc<KeyframeAnimationData>;
+
+    fn update_skeleton(
+        skeleton: &Self::Skeleton,
+        data: Self::Dependency,
+        anim_time: f64,
+        _rate: &mut f32,
+        _skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        let mut next = (*skeleton).clone();
+        data.apply(&mut next, anim_time as f32);
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anim::character::CharacterSkeleton;
+
+    #[test]
+    fn keyframe_sampling() {
+        let data: KeyframeAnimationData = ron::de::from_str(
+            "(
+                duration: 2.0,
+                looping: true,
+                bones: {
+                    \"head\": [
+                        (time: 0.0, offset: Some((0.0, 0.0, 0.0))),
+                        (time: 1.0, offset: Some((10.0, 0.0, 0.0)), easing: Step),
+                        (time: 2.0, offset: Some((0.0, 0.0, 0.0))),
+                    ],
+                    \"tail\": [(time: 0.0, offset: Some((1.0, 1.0, 1.0)))],
+                },
+            )",
+        )
+        .unwrap();
+        let keys = &data.bones["head"];
+        let offset_at = |time| {
+            sample(keys, time, |k| k.offset.map(Vec3::from), Lerp::lerp)
+                .unwrap()
+                .x
+        };
+
+        assert_eq!(offset_at(0.0), 0.0);
+        assert_eq!(offset_at(0.5), 5.0);
+        // Stepped keyframes hold their value
+        assert_eq!(offset_at(1.5), 10.0);
+        assert_eq!(offset_at(2.5), 0.0);
+        // Parts of the pose without keyframes are left alone
+        assert!(sample(keys, 0.5, |k| k.scale.map(Vec3::from), Lerp::lerp).is_none());
+
+        // Unknown bones (e.g: `tail` on a humanoid) are ignored, and looping wraps around
+        let mut skeleton = CharacterSkeleton::new();
+        data.apply(&mut skeleton, 2.5);
+        assert_eq!(skeleton.bone_mut("head").unwrap().offset.x, 5.0);
+    }
+
+    #[test]
+    fn example_animation_loads() {
+        let data = assets::load::<KeyframeAnimationData>("voxygen.anim.character.wave").unwrap();
+        let skeleton = CharacterSkeleton::new();
+        let start = KeyframeAnimation::<CharacterSkeleton>::update_skeleton(
+            &skeleton,
+            data.clone(),
+            0.0,
+            &mut 1.0,
+            &SkeletonAttr::default(),
+        );
+        let looped = KeyframeAnimation::<CharacterSkeleton>::update_skeleton(
+            &skeleton,
+            data.clone(),
+            data.duration as f64,
+            &mut 1.0,
+            &SkeletonAttr::default(),
+        );
+        assert_eq!(start.compute_matrices(), looped.compute_matrices());
+    }
+
+    #[test]
+    fn easing_endpoints() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ]
+        .iter()
+        {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+        }
+    }
+}
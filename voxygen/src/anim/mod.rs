@@ -1,11 +1,16 @@
+pub mod bird_medium;
 pub mod character;
+pub mod first_person;
+pub mod fish_small;
 pub mod fixture;
+pub mod keyframe;
 pub mod object;
 pub mod quadruped;
 pub mod quadrupedmedium;
 
 use crate::render::FigureBoneData;
 use common::comp::{self, item::Tool};
+use std::f32::consts::PI;
 use vek::*;
 
 #[derive(Copy, Clone)]
@@ -13,6 +18,9 @@ pub struct Bone {
     pub offset: Vec3<f32>,
     pub ori: Quaternion<f32>,
     pub scale: Vec3<f32>,
+    /// The point, in the space of the bone's model, that the bone rotates about. This lets limbs
+    /// turn about their joints rather than wherever their model's origin happens to be.
+    pub pivot: Vec3<f32>,
 }
 
 impl Bone {
@@ -21,30 +29,256 @@ impl Bone {
             offset: Vec3::zero(),
             ori: Quaternion::identity(),
             scale: Vec3::broadcast(1.0 / 11.0),
+            pivot: Vec3::zero(),
         }
     }
 
     pub fn compute_base_matrix(&self) -> Mat4<f32> {
         Mat4::<f32>::translation_3d(self.offset)
             * Mat4::scaling_3d(self.scale)
+            * Mat4::<f32>::translation_3d(self.pivot)
             * Mat4::from(self.ori)
+            * Mat4::<f32>::translation_3d(-self.pivot)
     }
 
     /// Change the current bone to be more like `target`.
     fn interpolate(&mut self, target: &Bone, dt: f32) {
-        // TODO: Make configurable.
-        let factor = (15.0 * dt).min(1.0);
+        let factor = interpolation_factor(dt);
         self.offset += (target.offset - self.offset) * factor;
         self.ori = vek::ops::Slerp::slerp(self.ori, target.ori, factor);
         self.scale += (target.scale - self.scale) * factor;
+        self.pivot += (target.pivot - self.pivot) * factor;
     }
+
+    /// Set the bone to a mix of `from` and `to`, where a `factor` of 0 gives `from` and 1 gives
+    /// `to`.
+    fn blend(&mut self, from: &Bone, to: &Bone, factor: f32) {
+        let factor = factor.min(1.0).max(0.0);
+        // Hit the endpoints exactly, which lerping and slerping don't guarantee
+        if factor == 0.0 {
+            *self = *from;
+        } else if factor == 1.0 {
+            *self = *to;
+        } else {
+            self.offset = from.offset * (1.0 - factor) + to.offset * factor;
+            self.ori = vek::ops::Slerp::slerp(from.ori, to.ori, factor);
+            self.scale = from.scale * (1.0 - factor) + to.scale * factor;
+            self.pivot = from.pivot * (1.0 - factor) + to.pivot * factor;
+        }
+    }
+}
+
+/// How quickly bones approach their target pose, per second. The remaining distance to the target
+/// shrinks by a factor of `exp(-INTERPOLATION_RATE * dt)` each frame, independent of frame rate.
+/// This rate moves bones a quarter of the way to their target each frame at 60 FPS.
+// TODO: Make configurable.
+#[allow(clippy::excessive_precision)]
+const INTERPOLATION_RATE: f32 = 17.260_924;
+
+/// The fraction of the way to its target that a bone should move over a frame of length `dt`.
+fn interpolation_factor(dt: f32) -> f32 {
+    1.0 - (-dt * INTERPOLATION_RATE).exp()
+}
+
+/// A bone that follows its animated target through a spring-damper rather than directly, giving
+/// secondary motion (cloth, ears, tails, etc.) that lags behind and settles after movement.
+#[derive(Copy, Clone)]
+pub struct SpringBone {
+    pub bone: Bone,
+    velocity: Vec3<f32>,
+    stiffness: f32,
+    damping: f32,
+}
+
+impl SpringBone {
+    /// Critical damping is `2.0 * stiffness.sqrt()`; less than this will cause the bone to
+    /// overshoot and oscillate before settling.
+    pub fn new(bone: Bone, stiffness: f32, damping: f32) -> Self {
+        Self {
+            bone,
+            velocity: Vec3::zero(),
+            stiffness,
+            damping,
+        }
+    }
+
+    /// Pull the bone toward `target`.
+    pub fn update(&mut self, target: &Bone, dt: f32) {
+        let accel =
+            (target.offset - self.bone.offset) * self.stiffness - self.velocity * self.damping;
+        self.velocity += accel * dt;
+        self.bone.offset += self.velocity * dt;
+
+        let factor = (self.stiffness.sqrt() * dt).min(1.0);
+        self.bone.ori = vek::ops::Slerp::slerp(self.bone.ori, target.ori, factor);
+        self.bone.scale += (target.scale - self.bone.scale) * factor;
+        self.bone.pivot = target.pivot;
+    }
+}
+
+/// A scale that stretches a bone along its z axis by `stretch` and squashes it across the other
+/// two to keep its volume, for squash-and-stretch (e.g: landing from a jump).
+pub fn squash_stretch(stretch: f32) -> Vec3<f32> {
+    let across = 1.0 / stretch.max(0.01).sqrt();
+    Vec3::new(across, across, stretch)
+}
+
+/// The size of figures, in blocks per unit of figure space, before they are scaled.
+pub const FIGURE_SCALE: f32 = 0.8;
+
+/// The rate to play a cycle that repeats every `2π / cycle_speed` of animation time at, for it to
+/// repeat once per stride of `stride_length` blocks when moving at `speed` blocks per second.
+pub fn stride_rate(speed: f32, stride_length: f32, cycle_speed: f32) -> f32 {
+    speed / stride_length * 2.0 * PI / cycle_speed
+}
+
+/// How far, in units of a model scaled by `model_scale`, feet should swing to either side in a
+/// stride of `stride_length` blocks to keep pace with the ground beneath them.
+///
+/// A foot swinging back and forth like a sine wave moves fastest in the middle of its swing, when
+/// it's planted, and matches the speed of the ground then if it reaches `stride_length / 2π`.
+pub fn stride_reach(stride_length: f32, model_scale: f32) -> f32 {
+    stride_length / (2.0 * PI) / (FIGURE_SCALE * model_scale)
+}
+
+/// Speeds between which four legged creatures switch from trotting to galloping.
+const TROT_SPEED: f32 = 4.0;
+const GALLOP_SPEED: f32 = 10.0;
+
+/// How far between a trot (0) and a full gallop (1) a four legged creature moving at `speed` is.
+pub fn gallop_factor(speed: f32) -> f32 {
+    ((speed - TROT_SPEED) / (GALLOP_SPEED - TROT_SPEED))
+        .min(1.0)
+        .max(0.0)
+}
+
+/// The phase of each leg's stride (front left, front right, back left, back right) as a fraction
+/// of the stride cycle. Diagonal pairs of legs move together in a trot, while a gallop staggers
+/// all four.
+pub fn leg_phases(speed: f32) -> [f32; 4] {
+    const TROT: [f32; 4] = [0.0, 0.5, 0.5, 0.0];
+    const GALLOP: [f32; 4] = [0.0, 0.1, 0.6, 0.5];
+
+    let t = gallop_factor(speed);
+    let t = t * t * (3.0 - 2.0 * t);
+    let mut phases = TROT;
+    for (phase, gallop) in phases.iter_mut().zip(GALLOP.iter()) {
+        *phase += (gallop - *phase) * t;
+    }
+    phases
 }
 
 pub trait Skeleton: Send + Sync + 'static {
-    fn compute_matrices(&self) -> [FigureBoneData; 16];
+    /// The number of bones in the skeleton, which can't exceed `MAX_FIGURE_BONES`.
+    const BONE_COUNT: usize;
+
+    /// Write the matrices of the skeleton's `BONE_COUNT` bones into `bones`, which must hold exactly
+    /// that many, in the order of the bone indices used by its meshes. Reusing `bones` from frame
+    /// to frame saves allocating for every figure.
+    fn fill_matrices(&self, bones: &mut [FigureBoneData]);
+
+    /// Like `fill_matrices`, but into a newly allocated `Vec`.
+    fn compute_matrices(&self) -> Vec<FigureBoneData> {
+        let mut bones = vec![FigureBoneData::default(); Self::BONE_COUNT];
+        self.fill_matrices(&mut bones);
+        bones
+    }
 
     /// Change the current skeleton to be more like `target`.
     fn interpolate(&mut self, target: &Self, dt: f32);
+
+    /// Set the skeleton to a crossfade between the poses `from` and `to`, where a `factor` of 0
+    /// gives `from` and 1 gives `to`.
+    fn blend(&mut self, from: &Self, to: &Self, factor: f32);
+
+    /// The index, in the order of `compute_matrices`, of the bone with the given name, so that
+    /// other figures can be attached to it.
+    fn bone_index(_name: &str) -> Option<usize>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+/// Crossfades a skeleton between animation states, so that switching from one animation to
+/// another (e.g: idle to run) doesn't make the pose pop.
+///
+/// When a fade is started, the last pose produced by the blender is held and faded out over the
+/// fade duration in favour of the poses of the new animation.
+#[derive(Clone)]
+pub struct AnimationBlender<S> {
+    from: Option<S>,
+    last: Option<S>,
+    fade_time: f32,
+    fade_duration: f32,
+}
+
+impl<S: Skeleton + Clone> AnimationBlender<S> {
+    pub fn new() -> Self {
+        Self {
+            from: None,
+            last: None,
+            fade_time: 0.0,
+            fade_duration: 0.0,
+        }
+    }
+
+    /// Start fading from the current pose to the poses given to `update` over `duration`
+    /// seconds. Starting a fade part way through another fades from the current mix of the two.
+    pub fn start_fade(&mut self, duration: f32) {
+        self.from = self.last.clone();
+        self.fade_time = 0.0;
+        self.fade_duration = duration;
+    }
+
+    /// The weight of the new animation, eased from 0 at the start of a fade to 1 at its end.
+    pub fn weight(&self) -> f32 {
+        if self.from.is_none() || self.fade_duration <= 0.0 {
+            return 1.0;
+        }
+        let t = (self.fade_time / self.fade_duration).min(1.0).max(0.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    /// Advance the fade by `dt` seconds, returning the pose to display given the `target` pose
+    /// of the current animation.
+    pub fn update(&mut self, target: S, dt: f32) -> S {
+        self.fade_time += dt;
+        if self.fade_time >= self.fade_duration {
+            self.from = None;
+        }
+
+        let pose = match &self.from {
+            Some(from) => {
+                let mut pose = target.clone();
+                pose.blend(from, &target, self.weight());
+                pose
+            }
+            None => target,
+        };
+        self.last = Some(pose.clone());
+        pose
+    }
+
+    /// Mix any number of poses according to their weights, which needn't sum to 1. Returns
+    /// `None` if no pose has a positive weight.
+    pub fn mix(poses: &[(&S, f32)]) -> Option<S> {
+        let mut mixed: Option<S> = None;
+        let mut total_weight = 0.0;
+        for (pose, weight) in poses.iter().filter(|(_, weight)| *weight > 0.0) {
+            total_weight += weight;
+            mixed = Some(match mixed {
+                Some(acc) => {
+                    let mut next = acc.clone();
+                    next.blend(&acc, pose, weight / total_weight);
+                    next
+                }
+                None => (*pose).clone(),
+            });
+        }
+        mixed
+    }
 }
 
 pub struct SkeletonAttr {
@@ -169,6 +403,14 @@ impl<'a> From<&'a comp::humanoid::Body> for SkeletonAttr {
     }
 }
 
+/// The phase (between 0 and 1) of the current beat at `global_time` for music at the given
+/// tempo. Animations that use this instead of their own `anim_time` stay in sync with the beat,
+/// and with each other.
+pub fn sync_to_beat(global_time: f64, bpm: f32) -> f32 {
+    let beats = global_time * bpm as f64 / 60.0;
+    (beats - beats.floor()) as f32
+}
+
 pub trait Animation {
     type Skeleton;
     type Dependency;
@@ -182,3 +424,238 @@ pub trait Animation {
         skeleton_attr: &SkeletonAttr,
     ) -> Self::Skeleton;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gaits() {
+        // Diagonal pairs of legs move together in a trot
+        let [lf, rf, lb, rb] = leg_phases(0.0);
+        assert_eq!(lf, rb);
+        assert_eq!(rf, lb);
+        assert!(lf != rf);
+
+        // All legs are staggered in a gallop
+        let phases = leg_phases(100.0);
+        for (i, a) in phases.iter().enumerate() {
+            for b in phases[i + 1..].iter() {
+                assert!((a - b).abs() > 0.05);
+            }
+        }
+    }
+
+    fn step_response(stiffness: f32, damping: f32) -> (f32, f32) {
+        let mut spring = SpringBone::new(Bone::default(), stiffness, damping);
+        let mut target = Bone::default();
+        target.offset = Vec3::unit_x();
+
+        let mut peak = 0.0f32;
+        for _ in 0..600 {
+            spring.update(&target, 1.0 / 60.0);
+            peak = peak.max(spring.bone.offset.x);
+        }
+        (peak, spring.bone.offset.x)
+    }
+
+    #[test]
+    fn spring_bone_damping() {
+        // Critically damped springs settle without overshooting
+        let (peak, end) = step_response(100.0, 20.0);
+        assert!(peak <= 1.0 + 1e-3);
+        assert!((end - 1.0).abs() < 1e-3);
+
+        // Under-damped springs overshoot
+        let (peak, _) = step_response(100.0, 2.0);
+        assert!(peak > 1.1);
+    }
+
+    #[test]
+    fn bone_pivot() {
+        let mut bone = Bone::default();
+        bone.scale = Vec3::one();
+        bone.offset = Vec3::new(1.0, 2.0, 3.0);
+        bone.pivot = Vec3::new(0.0, 0.0, 4.0);
+        bone.ori = Quaternion::rotation_x(1.0);
+
+        // The pivot stays put however the bone is turned
+        let mat = bone.compute_base_matrix();
+        let pivot = mat * Vec4::new(0.0, 0.0, 4.0, 1.0);
+        assert!((Vec3::from(pivot) - Vec3::new(1.0, 2.0, 7.0)).magnitude() < 1e-4);
+        // Without one, the bone turns about its origin as before
+        bone.pivot = Vec3::zero();
+        let origin = bone.compute_base_matrix() * Vec4::new(0.0, 0.0, 0.0, 1.0);
+        assert!((Vec3::from(origin) - bone.offset).magnitude() < 1e-4);
+
+        let squashed = squash_stretch(0.25);
+        assert!((squashed.product() - 1.0).abs() < 1e-4);
+        assert_eq!(squashed.z, 0.25);
+    }
+
+    #[derive(Clone)]
+    struct TestSkeleton(Bone);
+
+    impl Skeleton for TestSkeleton {
+        const BONE_COUNT: usize = 1;
+
+        fn fill_matrices(&self, bones: &mut [FigureBoneData]) {
+            bones.copy_from_slice(&[FigureBoneData::new(self.0.compute_base_matrix())]);
+        }
+
+        fn interpolate(&mut self, target: &Self, dt: f32) {
+            self.0.interpolate(&target.0, dt);
+        }
+
+        fn blend(&mut self, from: &Self, to: &Self, factor: f32) {
+            self.0.blend(&from.0, &to.0, factor);
+        }
+    }
+
+    fn pose_at(x: f32) -> TestSkeleton {
+        let mut bone = Bone::default();
+        bone.offset = Vec3::new(x, 0.0, 0.0);
+        TestSkeleton(bone)
+    }
+
+    #[test]
+    fn bone_counts() {
+        use crate::render::MAX_FIGURE_BONES;
+
+        fn check<S: Skeleton>(skeleton: S) {
+            assert_eq!(skeleton.compute_matrices().len(), S::BONE_COUNT);
+            assert!(S::BONE_COUNT <= MAX_FIGURE_BONES);
+        }
+        check(character::CharacterSkeleton::new());
+        check(quadruped::QuadrupedSkeleton::new());
+        check(quadrupedmedium::QuadrupedMediumSkeleton::new());
+        check(bird_medium::BirdMediumSkeleton::new());
+        check(fish_small::FishSmallSkeleton::new());
+        check(first_person::FirstPersonSkeleton::new());
+        check(object::ObjectSkeleton::new());
+        check(fixture::FixtureSkeleton::new());
+    }
+
+    #[test]
+    fn mount_bones() {
+        use common::comp::{quadruped, quadruped_medium};
+
+        // Riders can be attached to every bone that bodies are ridden by
+        let pig = comp::Body::Quadruped(quadruped::Body::random());
+        let wolf = comp::Body::QuadrupedMedium(quadruped_medium::Body::random());
+        assert!(pig
+            .mount_bone()
+            .and_then(quadruped::QuadrupedSkeleton::bone_index)
+            .is_some());
+        assert!(wolf
+            .mount_bone()
+            .and_then(quadrupedmedium::QuadrupedMediumSkeleton::bone_index)
+            .is_some());
+    }
+
+    #[test]
+    fn bones_found_by_name_match_their_index() {
+        use keyframe::KeyframeSkeleton;
+
+        fn check<S: KeyframeSkeleton>(mut skeleton: S, name: &str) {
+            skeleton.bone_mut(name).unwrap().offset = Vec3::new(1.0, 2.0, 3.0);
+            let index = S::bone_index(name).unwrap();
+            let mat = Mat4::from_col_arrays(skeleton.compute_matrices()[index].bone_mat);
+            assert_eq!(Vec3::from(mat.cols.w), Vec3::new(1.0, 2.0, 3.0));
+        }
+        check(quadruped::QuadrupedSkeleton::new(), "pig_leg_rb");
+        check(
+            quadrupedmedium::QuadrupedMediumSkeleton::new(),
+            "wolf_torso_mid",
+        );
+    }
+
+    #[test]
+    fn blender_crossfade() {
+        let mut blender = AnimationBlender::new();
+        // Without a fade, the target is used as is
+        assert_eq!(blender.update(pose_at(0.0), 0.1).0.offset.x, 0.0);
+
+        blender.start_fade(1.0);
+        let x = blender.update(pose_at(10.0), 0.5).0.offset.x;
+        assert!((x - 5.0).abs() < 1e-4);
+
+        // The fade is eased rather than linear
+        let x = blender.update(pose_at(10.0), 0.25).0.offset.x;
+        assert!(x > 5.0 && x < 10.0);
+        assert_eq!(blender.update(pose_at(10.0), 0.25).0.offset.x, 10.0);
+        assert_eq!(blender.weight(), 1.0);
+
+        // A new fade starts from the current pose
+        blender.start_fade(1.0);
+        let mid = blender.update(pose_at(-10.0), 0.0).0.offset.x;
+        assert_eq!(mid, 10.0);
+    }
+
+    #[test]
+    fn blender_mix() {
+        let (a, b, c) = (pose_at(0.0), pose_at(6.0), pose_at(12.0));
+        let mixed = AnimationBlender::mix(&[(&a, 1.0), (&b, 1.0), (&c, 1.0)]).unwrap();
+        assert!((mixed.0.offset.x - 6.0).abs() < 1e-4);
+
+        let mixed = AnimationBlender::mix(&[(&a, 0.0), (&c, 2.0)]).unwrap();
+        assert_eq!(mixed.0.offset.x, 12.0);
+        assert!(AnimationBlender::<TestSkeleton>::mix(&[(&a, 0.0)]).is_none());
+    }
+
+    #[test]
+    fn beat_phase() {
+        // At 120 BPM, a beat lasts half a second
+        for &t in [0.1, 3.3, 100.45].iter() {
+            assert!((sync_to_beat(t, 120.0) - sync_to_beat(t + 0.5, 120.0)).abs() < 1e-4);
+        }
+        assert!((sync_to_beat(10.25, 120.0) - 0.5).abs() < 1e-4);
+
+        // The phase wraps around smoothly at the start of each beat
+        let phase_dist = |a: f32, b: f32| (a - b).abs().min(1.0 - (a - b).abs());
+        for i in 0..1000 {
+            let t = i as f64 * 0.01;
+            assert!(phase_dist(sync_to_beat(t, 120.0), sync_to_beat(t + 0.001, 120.0)) < 0.01);
+        }
+    }
+
+    #[test]
+    fn stride_matches_ground() {
+        let (speed, stride_length, cycle_speed, model_scale) = (7.0, 2.5, 1.5, 1.0 / 11.0);
+        let rate = stride_rate(speed, stride_length, cycle_speed);
+        let reach = stride_reach(stride_length, model_scale);
+        let foot =
+            |anim_time: f32| (anim_time * cycle_speed).sin() * reach * model_scale * FIGURE_SCALE;
+
+        // Over a second, planted feet move back exactly as fast as the ground passes beneath them
+        let dt = 0.001;
+        let mut anim_time = 0.0;
+        let mut fastest = 0.0f32;
+        for _ in 0..1000 {
+            let next_time = anim_time + dt * rate;
+            fastest = fastest.max((foot(next_time) - foot(anim_time)).abs() / dt);
+            anim_time = next_time;
+        }
+        assert!((fastest - speed).abs() < 0.01);
+        // ...and complete a cycle for every stride
+        assert!((anim_time * cycle_speed / (2.0 * PI) - speed / stride_length).abs() < 1e-3);
+    }
+
+    #[test]
+    fn interpolation_is_framerate_independent() {
+        assert!((interpolation_factor(1.0 / 60.0) - 0.25).abs() < 1e-4);
+
+        let mut target = Bone::default();
+        target.offset = Vec3::new(10.0, 0.0, 0.0);
+        let after = |fps: u32| {
+            let mut bone = Bone::default();
+            for _ in 0..fps / 2 {
+                bone.interpolate(&target, 1.0 / fps as f32);
+            }
+            bone.offset.x
+        };
+
+        // Half a second of animation ends up at the same place regardless of frame rate
+        assert!((after(30) - after(144)).abs() < 1e-3);
+    }
+}
@@ -14,26 +14,15 @@ impl ObjectSkeleton {
 const SCALE: f32 = 1.0 / 11.0;
 
 impl Skeleton for ObjectSkeleton {
-    fn compute_matrices(&self) -> [FigureBoneData; 16] {
-        [
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-            FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(SCALE))),
-        ]
+    const BONE_COUNT: usize = 1;
+
+    fn fill_matrices(&self, bones: &mut [FigureBoneData]) {
+        bones.copy_from_slice(&[FigureBoneData::new(Mat4::scaling_3d(Vec3::broadcast(
+            SCALE,
+        )))]);
     }
 
     fn interpolate(&mut self, _target: &Self, _dt: f32) {}
+
+    fn blend(&mut self, _from: &Self, _to: &Self, _factor: f32) {}
 }
@@ -7,9 +7,12 @@ pub use self::idle::IdleAnimation;
 pub use self::jump::JumpAnimation;
 pub use self::run::RunAnimation;
 
-use super::{Bone, Skeleton};
+use super::{keyframe::KeyframeSkeleton, Bone, Skeleton};
 use crate::render::FigureBoneData;
 
+/// How far, in blocks, pigs travel over a full run cycle of their legs.
+pub const STRIDE_LENGTH: f32 = 1.2;
+
 #[derive(Clone)]
 pub struct QuadrupedSkeleton {
     pig_head: Bone,
@@ -20,6 +23,16 @@ pub struct QuadrupedSkeleton {
     pig_leg_rb: Bone,
 }
 
+/// The names of the skeleton's bones, in the order of `compute_matrices`.
+const BONE_NAMES: [&str; 6] = [
+    "pig_head",
+    "pig_chest",
+    "pig_leg_lf",
+    "pig_leg_rf",
+    "pig_leg_lb",
+    "pig_leg_rb",
+];
+
 impl QuadrupedSkeleton {
     pub fn new() -> Self {
         Self {
@@ -31,28 +44,33 @@ impl QuadrupedSkeleton {
             pig_leg_rb: Bone::default(),
         }
     }
+
+    /// The bone at `index` in the order of `BONE_NAMES`.
+    fn bone_at_mut(&mut self, index: usize) -> Option<&mut Bone> {
+        match index {
+            0 => Some(&mut self.pig_head),
+            1 => Some(&mut self.pig_chest),
+            2 => Some(&mut self.pig_leg_lf),
+            3 => Some(&mut self.pig_leg_rf),
+            4 => Some(&mut self.pig_leg_lb),
+            5 => Some(&mut self.pig_leg_rb),
+            _ => None,
+        }
+    }
 }
 
 impl Skeleton for QuadrupedSkeleton {
-    fn compute_matrices(&self) -> [FigureBoneData; 16] {
-        [
+    const BONE_COUNT: usize = 6;
+
+    fn fill_matrices(&self, bones: &mut [FigureBoneData]) {
+        bones.copy_from_slice(&[
             FigureBoneData::new(self.pig_head.compute_base_matrix()),
             FigureBoneData::new(self.pig_chest.compute_base_matrix()),
             FigureBoneData::new(self.pig_leg_lf.compute_base_matrix()),
             FigureBoneData::new(self.pig_leg_rf.compute_base_matrix()),
             FigureBoneData::new(self.pig_leg_lb.compute_base_matrix()),
             FigureBoneData::new(self.pig_leg_rb.compute_base_matrix()),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-        ]
+        ]);
     }
 
     fn interpolate(&mut self, target: &Self, dt: f32) {
@@ -63,4 +81,28 @@ impl Skeleton for QuadrupedSkeleton {
         self.pig_leg_lb.interpolate(&target.pig_leg_lb, dt);
         self.pig_leg_rb.interpolate(&target.pig_leg_rb, dt);
     }
+
+    fn blend(&mut self, from: &Self, to: &Self, factor: f32) {
+        self.pig_head.blend(&from.pig_head, &to.pig_head, factor);
+        self.pig_chest.blend(&from.pig_chest, &to.pig_chest, factor);
+        self.pig_leg_lf
+            .blend(&from.pig_leg_lf, &to.pig_leg_lf, factor);
+        self.pig_leg_rf
+            .blend(&from.pig_leg_rf, &to.pig_leg_rf, factor);
+        self.pig_leg_lb
+            .blend(&from.pig_leg_lb, &to.pig_leg_lb, factor);
+        self.pig_leg_rb
+            .blend(&from.pig_leg_rb, &to.pig_leg_rb, factor);
+    }
+
+    fn bone_index(name: &str) -> Option<usize> {
+        BONE_NAMES.iter().position(|bone| *bone == name)
+    }
+}
+
+impl KeyframeSkeleton for QuadrupedSkeleton {
+    fn bone_mut(&mut self, name: &str) -> Option<&mut Bone> {
+        let index = Self::bone_index(name)?;
+        self.bone_at_mut(index)
+    }
 }
@@ -50,6 +50,14 @@ impl Animation for IdleAnimation {
                 * 0.125,
         );
 
+        // Every so often, the ears flick and settle again
+        let twitch_cycle = (global_time + anim_time) as f32 / 3.0;
+        let ear_twitch = if twitch_cycle.floor().mul(4231.0).sin() > 0.5 {
+            (twitch_cycle.fract() * PI * 6.0).sin() * (1.0 - twitch_cycle.fract()).powf(4.0)
+        } else {
+            0.0
+        };
+
         next.wolf_head_upper.offset = Vec3::new(0.0, 7.5, 15.0 + wave_ultra_slow * 0.4) / 11.0;
         next.wolf_head_upper.ori =
             Quaternion::rotation_z(wolf_look.x) * Quaternion::rotation_x(wolf_look.y);
@@ -78,7 +86,7 @@ impl Animation for IdleAnimation {
         next.wolf_torso_mid.scale = Vec3::one() / 10.5;
 
         next.wolf_ears.offset = Vec3::new(0.0, 0.75, 5.25);
-        next.wolf_ears.ori = Quaternion::rotation_x(0.0 + wave_slow * 0.1);
+        next.wolf_ears.ori = Quaternion::rotation_x(0.0 + wave_slow * 0.1 + ear_twitch * 0.3);
         next.wolf_ears.scale = Vec3::one() * 1.05;
 
         next.wolf_foot_lf.offset = Vec3::new(-5.0, 5.0, 2.5) / 11.0;
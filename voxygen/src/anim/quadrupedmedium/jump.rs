@@ -23,6 +23,8 @@ impl Animation for JumpAnimation {
         let wave = (anim_time as f32 * 14.0).sin();
         let wave_slow = (anim_time as f32 * 3.5 + PI).sin();
         let wave_stop = (anim_time as f32 * 5.0).min(PI / 2.0).sin();
+        // The tail and ears follow the body's leap a moment later
+        let wave_stop_lag = (anim_time as f32 * 5.0 - 0.4).max(0.0).min(PI / 2.0).sin();
 
         next.wolf_head_upper.offset = Vec3::new(0.0, 7.5, 15.0 + wave_stop * 4.8) / 11.0;
         next.wolf_head_upper.ori =
@@ -38,8 +40,8 @@ impl Animation for JumpAnimation {
         next.wolf_head_lower.scale = Vec3::one() * 0.98;
 
         next.wolf_tail.offset = Vec3::new(0.0, -12.0, 8.0) / 11.0;
-        next.wolf_tail.ori =
-            Quaternion::rotation_z(0.0) * Quaternion::rotation_x(wave_slow * -0.25);
+        next.wolf_tail.ori = Quaternion::rotation_z(0.0)
+            * Quaternion::rotation_x(wave_slow * -0.25 + wave_stop_lag * 0.35);
         next.wolf_tail.scale = Vec3::one() / 11.0;
 
         next.wolf_torso_back.offset =
@@ -52,7 +54,7 @@ impl Animation for JumpAnimation {
         next.wolf_torso_mid.scale = Vec3::one() / 10.5;
 
         next.wolf_ears.offset = Vec3::new(0.0, 0.75, 6.25);
-        next.wolf_ears.ori = Quaternion::rotation_x(0.0);
+        next.wolf_ears.ori = Quaternion::rotation_x(wave_stop_lag * -0.4);
         next.wolf_ears.scale = Vec3::one() * 1.05;
 
         next.wolf_foot_lf.offset =
@@ -7,9 +7,12 @@ pub use self::idle::IdleAnimation;
 pub use self::jump::JumpAnimation;
 pub use self::run::RunAnimation;
 
-use super::{Bone, Skeleton};
+use super::{keyframe::KeyframeSkeleton, Bone, Skeleton};
 use crate::render::FigureBoneData;
 
+/// How far, in blocks, wolves travel over a full run cycle of their legs.
+pub const STRIDE_LENGTH: f32 = 2.4;
+
 #[derive(Clone)]
 pub struct QuadrupedMediumSkeleton {
     wolf_head_upper: Bone,
@@ -25,6 +28,21 @@ pub struct QuadrupedMediumSkeleton {
     wolf_foot_rb: Bone,
 }
 
+/// The names of the skeleton's bones, in the order of `compute_matrices`.
+const BONE_NAMES: [&str; 11] = [
+    "wolf_head_upper",
+    "wolf_jaw",
+    "wolf_head_lower",
+    "wolf_tail",
+    "wolf_torso_back",
+    "wolf_torso_mid",
+    "wolf_ears",
+    "wolf_foot_lf",
+    "wolf_foot_rf",
+    "wolf_foot_lb",
+    "wolf_foot_rb",
+];
+
 impl QuadrupedMediumSkeleton {
     pub fn new() -> Self {
         Self {
@@ -41,15 +59,35 @@ impl QuadrupedMediumSkeleton {
             wolf_foot_rb: Bone::default(),
         }
     }
+
+    /// The bone at `index` in the order of `BONE_NAMES`.
+    fn bone_at_mut(&mut self, index: usize) -> Option<&mut Bone> {
+        match index {
+            0 => Some(&mut self.wolf_head_upper),
+            1 => Some(&mut self.wolf_jaw),
+            2 => Some(&mut self.wolf_head_lower),
+            3 => Some(&mut self.wolf_tail),
+            4 => Some(&mut self.wolf_torso_back),
+            5 => Some(&mut self.wolf_torso_mid),
+            6 => Some(&mut self.wolf_ears),
+            7 => Some(&mut self.wolf_foot_lf),
+            8 => Some(&mut self.wolf_foot_rf),
+            9 => Some(&mut self.wolf_foot_lb),
+            10 => Some(&mut self.wolf_foot_rb),
+            _ => None,
+        }
+    }
 }
 
 impl Skeleton for QuadrupedMediumSkeleton {
-    fn compute_matrices(&self) -> [FigureBoneData; 16] {
+    const BONE_COUNT: usize = 11;
+
+    fn fill_matrices(&self, bones: &mut [FigureBoneData]) {
         let ears_mat = self.wolf_ears.compute_base_matrix();
         let head_upper_mat = self.wolf_head_upper.compute_base_matrix();
         let head_lower_mat = self.wolf_head_lower.compute_base_matrix();
 
-        [
+        bones.copy_from_slice(&[
             FigureBoneData::new(head_upper_mat),
             FigureBoneData::new(
                 head_upper_mat * head_lower_mat * self.wolf_jaw.compute_base_matrix(),
@@ -63,12 +101,7 @@ impl Skeleton for QuadrupedMediumSkeleton {
             FigureBoneData::new(self.wolf_foot_rf.compute_base_matrix()),
             FigureBoneData::new(self.wolf_foot_lb.compute_base_matrix()),
             FigureBoneData::new(self.wolf_foot_rb.compute_base_matrix()),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-            FigureBoneData::default(),
-        ]
+        ]);
     }
 
     fn interpolate(&mut self, target: &Self, dt: f32) {
@@ -87,4 +120,37 @@ impl Skeleton for QuadrupedMediumSkeleton {
         self.wolf_foot_lb.interpolate(&target.wolf_foot_lb, dt);
         self.wolf_foot_rb.interpolate(&target.wolf_foot_rb, dt);
     }
+
+    fn blend(&mut self, from: &Self, to: &Self, factor: f32) {
+        self.wolf_head_upper
+            .blend(&from.wolf_head_upper, &to.wolf_head_upper, factor);
+        self.wolf_jaw.blend(&from.wolf_jaw, &to.wolf_jaw, factor);
+        self.wolf_head_lower
+            .blend(&from.wolf_head_lower, &to.wolf_head_lower, factor);
+        self.wolf_tail.blend(&from.wolf_tail, &to.wolf_tail, factor);
+        self.wolf_torso_back
+            .blend(&from.wolf_torso_back, &to.wolf_torso_back, factor);
+        self.wolf_torso_mid
+            .blend(&from.wolf_torso_mid, &to.wolf_torso_mid, factor);
+        self.wolf_ears.blend(&from.wolf_ears, &to.wolf_ears, factor);
+        self.wolf_foot_lf
+            .blend(&from.wolf_foot_lf, &to.wolf_foot_lf, factor);
+        self.wolf_foot_rf
+            .blend(&from.wolf_foot_rf, &to.wolf_foot_rf, factor);
+        self.wolf_foot_lb
+            .blend(&from.wolf_foot_lb, &to.wolf_foot_lb, factor);
+        self.wolf_foot_rb
+            .blend(&from.wolf_foot_rb, &to.wolf_foot_rb, factor);
+    }
+
+    fn bone_index(name: &str) -> Option<usize> {
+        BONE_NAMES.iter().position(|bone| *bone == name)
+    }
+}
+
+impl KeyframeSkeleton for QuadrupedMediumSkeleton {
+    fn bone_mut(&mut self, name: &str) -> Option<&mut Bone> {
+        let index = Self::bone_index(name)?;
+        self.bone_at_mut(index)
+    }
 }
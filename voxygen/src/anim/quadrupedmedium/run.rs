@@ -1,6 +1,6 @@
 use super::{
-    super::{Animation, SkeletonAttr},
-    QuadrupedMediumSkeleton,
+    super::{gallop_factor, leg_phases, stride_rate, stride_reach, Animation, SkeletonAttr},
+    QuadrupedMediumSkeleton, STRIDE_LENGTH,
 };
 use std::{f32::consts::PI, ops::Mul};
 use vek::*;
@@ -13,21 +13,35 @@ impl Animation for RunAnimation {
 
     fn update_skeleton(
         skeleton: &Self::Skeleton,
-        (_velocity, global_time): Self::Dependency,
+        (velocity, global_time): Self::Dependency,
         anim_time: f64,
-        _rate: &mut f32,
+        rate: &mut f32,
         _skeleton_attr: &SkeletonAttr,
     ) -> Self::Skeleton {
         let mut next = (*skeleton).clone();
 
+        // Complete a cycle of the legs every stride, so that planted feet don't slide
+        *rate = stride_rate(velocity, STRIDE_LENGTH, 18.0);
+        let reach = stride_reach(STRIDE_LENGTH, 1.0 / 11.0);
+
         let wave = (anim_time as f32 * 14.0).sin();
         let wave_slow = (anim_time as f32 * 3.5 + PI).sin();
         let wave_slow_cos = (anim_time as f32 * 3.5 + PI).cos();
-        let wave_quick = (anim_time as f32 * 18.0).sin();
         let wave_med = (anim_time as f32 * 12.0).sin();
         let wave_med_cos = (anim_time as f32 * 12.0).cos();
         let wave_quick_cos = (anim_time as f32 * 18.0).cos();
 
+        // How much faster than a trot the wolf is moving, from 0 to 1
+        let gallop = gallop_factor(velocity);
+        let stride = |phase: f32| {
+            let angle = anim_time as f32 * 18.0 + phase * PI * 2.0;
+            (angle.sin(), angle.cos())
+        };
+        let [lf, rf, lb, rb] = leg_phases(velocity);
+        // The tail and ears lag behind the bounce of the body
+        let (tail_lag, _) = stride(-0.2);
+        let (ears_lag, _) = stride(-0.15);
+
         let wolf_look = Vec2::new(
             ((global_time + anim_time) as f32 / 4.0)
                 .floor()
@@ -57,12 +71,13 @@ impl Animation for RunAnimation {
         next.wolf_head_lower.scale = Vec3::one() * 0.98;
 
         next.wolf_tail.offset = Vec3::new(0.0, -12.0, 10.0) / 11.0;
-        next.wolf_tail.ori = Quaternion::rotation_x(wave_quick * 0.18);
+        next.wolf_tail.ori = Quaternion::rotation_x(tail_lag * (0.18 + gallop * 0.12));
         next.wolf_tail.scale = Vec3::one() / 11.0;
 
         next.wolf_torso_back.offset =
             Vec3::new(0.0, -9.5 + wave_quick_cos * 2.2, 13.0 + wave_med * 2.8) / 11.0;
-        next.wolf_torso_back.ori = Quaternion::rotation_x(-0.15 + wave_med_cos * 0.14);
+        next.wolf_torso_back.ori =
+            Quaternion::rotation_x(-0.15 + wave_med_cos * (0.14 + gallop * 0.08));
         next.wolf_torso_back.scale = Vec3::one() / 11.0;
 
         next.wolf_torso_mid.offset =
@@ -71,27 +86,27 @@ impl Animation for RunAnimation {
         next.wolf_torso_mid.scale = Vec3::one() / 10.5;
 
         next.wolf_ears.offset = Vec3::new(0.0, 0.75 + wave * 0.4, 6.25);
-        next.wolf_ears.ori = Quaternion::rotation_x(wave * 0.2);
+        next.wolf_ears.ori = Quaternion::rotation_x(wave * 0.2 - ears_lag * gallop * 0.3);
         next.wolf_ears.scale = Vec3::one() * 1.05;
 
-        next.wolf_foot_lf.offset =
-            Vec3::new(-5.0, 5.0 + wave_quick * 3.0, 7.0 + wave_quick_cos * 4.0) / 11.0;
-        next.wolf_foot_lf.ori = Quaternion::rotation_x(0.0 + wave_quick * 0.8);
+        let (swing, lift) = stride(lf);
+        next.wolf_foot_lf.offset = Vec3::new(-5.0, 5.0 + swing * reach, 7.0 + lift * 4.0) / 11.0;
+        next.wolf_foot_lf.ori = Quaternion::rotation_x(swing * 0.8);
         next.wolf_foot_lf.scale = Vec3::one() / 11.0;
 
-        next.wolf_foot_rf.offset =
-            Vec3::new(5.0, 5.0 - wave_quick_cos * 3.0, 7.0 + wave_quick * 4.0) / 11.0;
-        next.wolf_foot_rf.ori = Quaternion::rotation_x(0.0 - wave_quick_cos * 0.8);
+        let (swing, lift) = stride(rf);
+        next.wolf_foot_rf.offset = Vec3::new(5.0, 5.0 + swing * reach, 7.0 + lift * 4.0) / 11.0;
+        next.wolf_foot_rf.ori = Quaternion::rotation_x(swing * 0.8);
         next.wolf_foot_rf.scale = Vec3::one() / 11.0;
 
-        next.wolf_foot_lb.offset =
-            Vec3::new(-5.0, -10.0 - wave_quick_cos * 3.0, 7.0 + wave_quick * 4.0) / 11.0;
-        next.wolf_foot_lb.ori = Quaternion::rotation_x(0.0 - wave_quick_cos * 0.8);
+        let (swing, lift) = stride(lb);
+        next.wolf_foot_lb.offset = Vec3::new(-5.0, -10.0 + swing * reach, 7.0 + lift * 4.0) / 11.0;
+        next.wolf_foot_lb.ori = Quaternion::rotation_x(swing * 0.8);
         next.wolf_foot_lb.scale = Vec3::one() / 11.0;
 
-        next.wolf_foot_rb.offset =
-            Vec3::new(5.0, -10.0 + wave_quick * 3.0, 7.0 + wave_quick_cos * 4.0) / 11.0;
-        next.wolf_foot_rb.ori = Quaternion::rotation_x(0.0 + wave_quick * 0.8);
+        let (swing, lift) = stride(rb);
+        next.wolf_foot_rb.offset = Vec3::new(5.0, -10.0 + swing * reach, 7.0 + lift * 4.0) / 11.0;
+        next.wolf_foot_rb.ori = Quaternion::rotation_x(swing * 0.8);
         next.wolf_foot_rb.scale = Vec3::one() / 11.0;
 
         next
@@ -31,7 +31,7 @@ use social::{Social, SocialTab};
 use spell::Spell;
 
 use crate::{
-    render::{Consts, Globals, Renderer},
+    render::{AaMode, Consts, Globals, Renderer, ShadowQuality},
     scene::camera::Camera,
     settings::ControlSettings,
     ui::{Ingameable, ScaleMode, Ui},
@@ -97,6 +97,9 @@ widget_ids! {
         loaded_distance,
         time,
 
+        // Profiler
+        profiler,
+
         // Game Version
         version,
 
@@ -156,6 +159,13 @@ pub enum Event {
     ChangeAudioDevice(String),
     ChangeMaxFPS(u32),
     ChangeFOV(u16),
+    ChangeAaMode(AaMode),
+    ChangeRenderScale(f32),
+    ToggleDynamicResolution(bool),
+    ToggleAmbientOcclusion(bool),
+    ChangeShadowQuality(ShadowQuality),
+    ChangeGamma(f32),
+    ToggleBloom(bool),
     CrosshairTransp(f32),
     CrosshairType(CrosshairType),
     ToggleXpBar(XpBar),
@@ -207,6 +217,7 @@ pub struct Show {
     ui: bool,
     help: bool,
     debug: bool,
+    profiler: bool,
     bag: bool,
     social: bool,
     spell: bool,
@@ -397,6 +408,7 @@ impl Hud {
             show: Show {
                 help: false,
                 debug: true,
+                profiler: false,
                 bag: false,
                 esc_menu: false,
                 open_windows: Windows::None,
@@ -647,6 +659,25 @@ impl Hud {
             .set(self.ids.time, ui_widgets);
         }
 
+        // Display how long recent frames have taken in each part of rendering.
+        if self.show.profiler {
+            let timings = global_state.window.renderer().timings();
+            let mut text = String::from("GPU (ms)\n");
+            for (pass, ms) in &timings.gpu {
+                text += &format!("{:?}: {:.2}\n", pass, ms);
+            }
+            text += "\nCPU (ms)\n";
+            for (stage, ms) in &timings.cpu {
+                text += &format!("{}: {:.2}\n", stage.name(), ms);
+            }
+            Text::new(&text)
+                .color(TEXT_COLOR)
+                .mid_left_with_margin_on(ui_widgets.window, 5.0)
+                .font_id(self.fonts.opensans)
+                .font_size(14)
+                .set(self.ids.profiler, ui_widgets);
+        }
+
         // Add Bag-Space Button.
         if self.show.inventory_test_button {
             if Button::image(self.imgs.button)
@@ -815,6 +846,27 @@ impl Hud {
                     settings_window::Event::AdjustFOV(new_fov) => {
                         events.push(Event::ChangeFOV(new_fov));
                     }
+                    settings_window::Event::ChangeAaMode(aa_mode) => {
+                        events.push(Event::ChangeAaMode(aa_mode));
+                    }
+                    settings_window::Event::ChangeRenderScale(scale) => {
+                        events.push(Event::ChangeRenderScale(scale));
+                    }
+                    settings_window::Event::ToggleDynamicResolution(enabled) => {
+                        events.push(Event::ToggleDynamicResolution(enabled));
+                    }
+                    settings_window::Event::ToggleAmbientOcclusion(enabled) => {
+                        events.push(Event::ToggleAmbientOcclusion(enabled));
+                    }
+                    settings_window::Event::ChangeShadowQuality(quality) => {
+                        events.push(Event::ChangeShadowQuality(quality));
+                    }
+                    settings_window::Event::AdjustGamma(gamma) => {
+                        events.push(Event::ChangeGamma(gamma));
+                    }
+                    settings_window::Event::ToggleBloom(enabled) => {
+                        events.push(Event::ToggleBloom(enabled));
+                    }
                 }
             }
         }
@@ -1036,6 +1088,14 @@ impl Hud {
                     self.show.debug = !self.show.debug;
                     true
                 }
+                GameInput::ToggleProfiler => {
+                    self.show.profiler = !self.show.profiler;
+                    global_state
+                        .window
+                        .renderer_mut()
+                        .set_profiling(self.show.profiler);
+                    true
+                }
                 GameInput::ToggleIngameUi => {
                     self.show.ingame = !self.show.ingame;
                     true
@@ -2,6 +2,7 @@ use super::{
     img_ids::Imgs, BarNumbers, CrosshairType, Fonts, ShortcutNumbers, Show, XpBar, TEXT_COLOR,
 };
 use crate::{
+    render::{AaMode, ShadowQuality, MIN_RENDER_SCALE},
     ui::{ImageSlider, ScaleMode, ToggleButton},
     GlobalState,
 };
@@ -12,6 +13,20 @@ use conrod_core::{
 };
 
 const FPS_CHOICES: [u32; 11] = [15, 30, 40, 50, 60, 90, 120, 144, 240, 300, 500];
+const AA_CHOICES: [(AaMode, &str); 5] = [
+    (AaMode::None, "None"),
+    (AaMode::Fxaa, "FXAA"),
+    (AaMode::MsaaX4, "MSAA x4"),
+    (AaMode::MsaaX8, "MSAA x8"),
+    (AaMode::MsaaX16, "MSAA x16"),
+];
+
+const SHADOW_CHOICES: [(ShadowQuality, &str); 4] = [
+    (ShadowQuality::Off, "Off"),
+    (ShadowQuality::Low, "Low"),
+    (ShadowQuality::Medium, "Medium"),
+    (ShadowQuality::High, "High"),
+];
 
 widget_ids! {
     struct Ids {
@@ -76,6 +91,22 @@ widget_ids! {
         fov_slider,
         fov_text,
         fov_value,
+        aa_mode_text,
+        aa_mode_list,
+        render_scale_text,
+        render_scale_slider,
+        render_scale_value,
+        dynamic_resolution_button,
+        dynamic_resolution_label,
+        ambient_occlusion_button,
+        ambient_occlusion_label,
+        shadow_quality_text,
+        shadow_quality_list,
+        gamma_text,
+        gamma_slider,
+        gamma_value,
+        bloom_button,
+        bloom_label,
         audio_volume_slider,
         audio_volume_text,
         audio_device_list,
@@ -151,6 +182,13 @@ pub enum Event {
     AdjustMouseZoom(u32),
     AdjustViewDistance(u32),
     AdjustFOV(u16),
+    ChangeAaMode(AaMode),
+    ChangeRenderScale(f32),
+    ToggleDynamicResolution(bool),
+    ToggleAmbientOcclusion(bool),
+    ChangeShadowQuality(ShadowQuality),
+    AdjustGamma(f32),
+    ToggleBloom(bool),
     AdjustVolume(f32),
     ChangeAudioDevice(String),
     MaximumFPS(u32),
@@ -916,6 +954,7 @@ impl<'a> Widget for SettingsWindow<'a> {
             Toggle FPS and Debug Info\n\
             Take Screenshot\n\
             Toggle Nametags\n\
+            Toggle Frame Profiler\n\
             Toggle Fullscreen\n\
             \n\
             \n\
@@ -995,6 +1034,7 @@ impl<'a> Widget for SettingsWindow<'a> {
                  F3\n\
                  F4\n\
                  F6\n\
+                 F7\n\
                  F11\n\
                  \n\
                  \n\
@@ -1193,6 +1233,190 @@ impl<'a> Widget for SettingsWindow<'a> {
                 .font_id(self.fonts.opensans)
                 .color(TEXT_COLOR)
                 .set(state.ids.fov_value, ui);
+
+            // Anti-Aliasing
+            Text::new("Anti-Aliasing")
+                .down_from(state.ids.fov_slider, 10.0)
+                .font_size(14)
+                .font_id(self.fonts.opensans)
+                .color(TEXT_COLOR)
+                .set(state.ids.aa_mode_text, ui);
+
+            let aa_names = AA_CHOICES
+                .iter()
+                .map(|(_, name)| name.to_string())
+                .collect::<Vec<_>>();
+            let selected = AA_CHOICES
+                .iter()
+                .position(|(mode, _)| *mode == self.global_state.settings.graphics.aa_mode);
+
+            if let Some(clicked) = DropDownList::new(&aa_names, selected)
+                .w_h(104.0, 22.0)
+                .down_from(state.ids.aa_mode_text, 8.0)
+                .label_font_id(self.fonts.opensans)
+                .set(state.ids.aa_mode_list, ui)
+            {
+                events.push(Event::ChangeAaMode(AA_CHOICES[clicked].0));
+            }
+
+            // Render Scale
+            Text::new("Render Scale (%)")
+                .down_from(state.ids.aa_mode_list, 10.0)
+                .font_size(14)
+                .font_id(self.fonts.opensans)
+                .color(TEXT_COLOR)
+                .set(state.ids.render_scale_text, ui);
+
+            let render_scale =
+                (self.global_state.settings.graphics.render_scale * 100.0).round() as u32;
+            if let Some(new_val) = ImageSlider::discrete(
+                render_scale,
+                (MIN_RENDER_SCALE * 100.0) as u32,
+                100,
+                self.imgs.slider_indicator,
+                self.imgs.slider,
+            )
+            .w_h(104.0, 22.0)
+            .down_from(state.ids.render_scale_text, 8.0)
+            .track_breadth(12.0)
+            .slider_length(10.0)
+            .pad_track((5.0, 5.0))
+            .set(state.ids.render_scale_slider, ui)
+            {
+                events.push(Event::ChangeRenderScale(new_val as f32 / 100.0));
+            }
+
+            Text::new(&format!("{}", render_scale))
+                .right_from(state.ids.render_scale_slider, 8.0)
+                .font_size(14)
+                .font_id(self.fonts.opensans)
+                .color(TEXT_COLOR)
+                .set(state.ids.render_scale_value, ui);
+
+            // Dynamic Resolution
+            let dynamic_resolution = ToggleButton::new(
+                self.global_state.settings.graphics.dynamic_resolution,
+                self.imgs.checkbox,
+                self.imgs.checkbox_checked,
+            )
+            .w_h(18.0, 18.0)
+            .down_from(state.ids.render_scale_slider, 10.0)
+            .hover_images(self.imgs.checkbox_mo, self.imgs.checkbox_checked_mo)
+            .press_images(self.imgs.checkbox_press, self.imgs.checkbox_checked)
+            .set(state.ids.dynamic_resolution_button, ui);
+
+            if self.global_state.settings.graphics.dynamic_resolution != dynamic_resolution {
+                events.push(Event::ToggleDynamicResolution(dynamic_resolution));
+            }
+
+            Text::new("Lower Render Scale Under Load")
+                .right_from(state.ids.dynamic_resolution_button, 10.0)
+                .font_size(14)
+                .font_id(self.fonts.opensans)
+                .graphics_for(state.ids.dynamic_resolution_button)
+                .color(TEXT_COLOR)
+                .set(state.ids.dynamic_resolution_label, ui);
+
+            // Ambient Occlusion
+            let ambient_occlusion = ToggleButton::new(
+                self.global_state.settings.graphics.ambient_occlusion,
+                self.imgs.checkbox,
+                self.imgs.checkbox_checked,
+            )
+            .w_h(18.0, 18.0)
+            .down_from(state.ids.dynamic_resolution_button, 10.0)
+            .hover_images(self.imgs.checkbox_mo, self.imgs.checkbox_checked_mo)
+            .press_images(self.imgs.checkbox_press, self.imgs.checkbox_checked)
+            .set(state.ids.ambient_occlusion_button, ui);
+
+            if self.global_state.settings.graphics.ambient_occlusion != ambient_occlusion {
+                events.push(Event::ToggleAmbientOcclusion(ambient_occlusion));
+            }
+
+            Text::new("Ambient Occlusion")
+                .right_from(state.ids.ambient_occlusion_button, 10.0)
+                .font_size(14)
+                .font_id(self.fonts.opensans)
+                .graphics_for(state.ids.ambient_occlusion_button)
+                .color(TEXT_COLOR)
+                .set(state.ids.ambient_occlusion_label, ui);
+
+            // Shadow Quality
+            Text::new("Shadows")
+                .top_left_with_margins_on(state.ids.settings_content, 10.0, 250.0)
+                .font_size(14)
+                .font_id(self.fonts.opensans)
+                .color(TEXT_COLOR)
+                .set(state.ids.shadow_quality_text, ui);
+
+            let shadow_names = SHADOW_CHOICES
+                .iter()
+                .map(|(_, name)| name.to_string())
+                .collect::<Vec<_>>();
+            let selected = SHADOW_CHOICES.iter().position(|(quality, _)| {
+                *quality == self.global_state.settings.graphics.shadow_quality
+            });
+
+            if let Some(clicked) = DropDownList::new(&shadow_names, selected)
+                .w_h(104.0, 22.0)
+                .down_from(state.ids.shadow_quality_text, 8.0)
+                .label_font_id(self.fonts.opensans)
+                .set(state.ids.shadow_quality_list, ui)
+            {
+                events.push(Event::ChangeShadowQuality(SHADOW_CHOICES[clicked].0));
+            }
+
+            // Gamma
+            Text::new("Gamma")
+                .down_from(state.ids.shadow_quality_list, 10.0)
+                .font_size(14)
+                .font_id(self.fonts.opensans)
+                .color(TEXT_COLOR)
+                .set(state.ids.gamma_text, ui);
+
+            let gamma = (self.global_state.settings.graphics.gamma * 10.0).round() as u32;
+            if let Some(new_val) =
+                ImageSlider::discrete(gamma, 5, 20, self.imgs.slider_indicator, self.imgs.slider)
+                    .w_h(104.0, 22.0)
+                    .down_from(state.ids.gamma_text, 8.0)
+                    .track_breadth(12.0)
+                    .slider_length(10.0)
+                    .pad_track((5.0, 5.0))
+                    .set(state.ids.gamma_slider, ui)
+            {
+                events.push(Event::AdjustGamma(new_val as f32 / 10.0));
+            }
+
+            Text::new(&format!("{:.1}", gamma as f32 / 10.0))
+                .right_from(state.ids.gamma_slider, 8.0)
+                .font_size(14)
+                .font_id(self.fonts.opensans)
+                .color(TEXT_COLOR)
+                .set(state.ids.gamma_value, ui);
+
+            // Bloom
+            let bloom = ToggleButton::new(
+                self.global_state.settings.graphics.bloom,
+                self.imgs.checkbox,
+                self.imgs.checkbox_checked,
+            )
+            .w_h(18.0, 18.0)
+            .down_from(state.ids.gamma_slider, 10.0)
+            .hover_images(self.imgs.checkbox_mo, self.imgs.checkbox_checked_mo)
+            .press_images(self.imgs.checkbox_press, self.imgs.checkbox_checked)
+            .set(state.ids.bloom_button, ui);
+
+            if self.global_state.settings.graphics.bloom != bloom {
+                events.push(Event::ToggleBloom(bloom));
+            }
+
+            Text::new("Bloom")
+                .right_from(state.ids.bloom_button, 10.0)
+                .font_size(14)
+                .font_id(self.fonts.opensans)
+                .graphics_for(state.ids.bloom_button)
+                .color(TEXT_COLOR)
+                .set(state.ids.bloom_label, ui);
         }
 
         // 5) Sound Tab -----------------------------------
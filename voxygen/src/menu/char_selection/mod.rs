@@ -20,10 +20,17 @@ pub struct CharSelectionState {
 impl CharSelectionState {
     /// Create a new `CharSelectionState`.
     pub fn new(global_state: &mut GlobalState, client: Rc<RefCell<Client>>) -> Self {
+        let mut scene = Scene::new(global_state.window.renderer_mut());
+        scene.set_post_process(
+            global_state.window.renderer_mut(),
+            global_state.settings.graphics.gamma,
+            global_state.settings.graphics.bloom,
+        );
+
         Self {
             char_selection_ui: CharSelectionUi::new(global_state),
             client,
-            scene: Scene::new(global_state.window.renderer_mut()),
+            scene,
         }
     }
 }
@@ -5,8 +5,9 @@ use crate::{
         Animation, Skeleton, SkeletonAttr,
     },
     render::{
-        create_pp_mesh, create_skybox_mesh, Consts, FigurePipeline, Globals, Light, Model,
-        PostProcessLocals, PostProcessPipeline, Renderer, SkyboxLocals, SkyboxPipeline,
+        create_pp_mesh, create_skybox_mesh, Consts, FigurePipeline, Fog, Globals, Light, Model,
+        PostProcessLocals, PostProcessPipeline, Renderer, Shadow, SkyboxLocals, SkyboxPipeline,
+        MAX_LIGHTS, MAX_SHADOW_CASCADES,
     },
     scene::{
         camera::{Camera, CameraMode},
@@ -36,6 +37,7 @@ struct PostProcess {
 pub struct Scene {
     globals: Consts<Globals>,
     lights: Consts<Light>,
+    shadows: Consts<Shadow>,
     camera: Camera,
 
     skybox: Skybox,
@@ -53,7 +55,13 @@ impl Scene {
 
         Self {
             globals: renderer.create_consts(&[Globals::default()]).unwrap(),
-            lights: renderer.create_consts(&[Light::default(); 32]).unwrap(),
+            lights: renderer
+                .create_consts(&[Light::default(); MAX_LIGHTS])
+                .unwrap(),
+            // Nothing casts shadows here, so every cascade is left unused
+            shadows: renderer
+                .create_consts(&[Shadow::default(); MAX_SHADOW_CASCADES])
+                .unwrap(),
             camera: Camera::new(resolution.x / resolution.y, CameraMode::ThirdPerson),
 
             skybox: Skybox {
@@ -83,6 +91,16 @@ impl Scene {
         &self.globals
     }
 
+    /// Set the gamma that the final image is adjusted by, and whether bright lights bloom.
+    pub fn set_post_process(&mut self, renderer: &mut Renderer, gamma: f32, bloom: bool) {
+        renderer
+            .update_consts(
+                &mut self.postprocess.locals,
+                &[PostProcessLocals::new(gamma, bloom)],
+            )
+            .expect("Failed to update post-processing constants");
+    }
+
     /// Handle an incoming user input event (e.g.: cursor moved, key pressed, window closed).
     ///
     /// If the event is handled, return true.
@@ -117,9 +135,11 @@ impl Scene {
                 CHAR_SELECT_TIME_OF_DAY,
                 55800.0,
                 client.state().get_time(),
-                renderer.get_resolution(),
+                renderer.render_resolution(),
                 0,
                 BlockKind::Air,
+                Fog::default(),
+                Vec2::zero(),
             )],
         ) {
             error!("Renderer failed to update: {:?}", err);
@@ -178,6 +198,7 @@ impl Scene {
             self.figure_state.locals(),
             self.figure_state.bone_consts(),
             &self.lights,
+            &self.shadows,
         );
 
         renderer.render_figure(
@@ -186,6 +207,7 @@ impl Scene {
             self.backdrop_state.locals(),
             self.backdrop_state.bone_consts(),
             &self.lights,
+            &self.shadows,
         );
 
         renderer.render_post_process(
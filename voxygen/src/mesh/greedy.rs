@@ -0,0 +1,213 @@
+use super::vol::create_quad;
+use crate::render::{mesh::Mesh, Pipeline};
+use hashbrown::{HashMap, HashSet};
+use vek::*;
+
+/// What a face looks like. Neighbouring faces can only be merged if these are identical, since
+/// there's nothing but vertex attributes to carry them across the merged quad.
+#[derive(Copy, Clone, PartialEq)]
+struct FaceAttr {
+    col: Rgb<f32>,
+    darkness_ao: Vec4<(f32, f32)>,
+    /// How much the face bends with the wind.
+    sway: f32,
+}
+
+impl FaceAttr {
+    /// Whether the face is lit the same at every corner. Faces that aren't need their own quad so
+    /// that their shading doesn't get stretched across their neighbours.
+    fn is_uniform(&self) -> bool {
+        self.darkness_ao.iter().all(|e| *e == self.darkness_ao[0])
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Face {
+    origin: Vec3<f32>,
+    attr: FaceAttr,
+}
+
+/// The faces lying in one plane, all pointing the same way.
+struct Plane {
+    unit_x: Vec3<f32>,
+    unit_y: Vec3<f32>,
+    norm: Vec3<f32>,
+    faces: HashMap<Vec2<i32>, Face>,
+}
+
+/// Collects the faces of a volume and merges neighbouring faces that look the same into larger
+/// quads, so that flat runs of terrain take a handful of vertices instead of six per voxel.
+pub struct GreedyMesher {
+    planes: HashMap<(Vec3<i32>, i32), Plane>,
+}
+
+impl GreedyMesher {
+    pub fn new() -> Self {
+        Self {
+            planes: HashMap::new(),
+        }
+    }
+
+    /// Add a single voxel face spanning `unit_x` and `unit_y` from `origin`. Both edges must run
+    /// along the positive axes. `sway` is how much the face bends with the wind.
+    pub fn push_face(
+        &mut self,
+        origin: Vec3<f32>,
+        unit_x: Vec3<f32>,
+        unit_y: Vec3<f32>,
+        norm: Vec3<f32>,
+        col: Rgb<f32>,
+        darkness_ao: Vec4<(f32, f32)>,
+        sway: f32,
+    ) {
+        let layer = origin.dot(norm.map(|e| e.abs())) as i32;
+        let plane_pos = Vec2::new(origin.dot(unit_x), origin.dot(unit_y)).map(|e| e as i32);
+
+        self.planes
+            .entry((norm.map(|e| e as i32), layer))
+            .or_insert_with(|| Plane {
+                unit_x,
+                unit_y,
+                norm,
+                faces: HashMap::new(),
+            })
+            .faces
+            .insert(
+                plane_pos,
+                Face {
+                    origin,
+                    attr: FaceAttr {
+                        col,
+                        darkness_ao,
+                        sway,
+                    },
+                },
+            );
+    }
+
+    /// Merge the collected faces and push the resulting quads onto `mesh`. `vcons` is given the
+    /// face's sway after the usual vertex attributes.
+    pub fn build<P: Pipeline>(
+        self,
+        mesh: &mut Mesh<P>,
+        vcons: impl Fn(Vec3<f32>, Vec3<f32>, Rgb<f32>, f32, f32, f32) -> P::Vertex,
+    ) {
+        for plane in self.planes.values() {
+            let mut starts = plane.faces.keys().copied().collect::<Vec<_>>();
+            starts.sort_by_key(|pos| (pos.y, pos.x));
+
+            let mut merged = HashSet::new();
+            for start in starts {
+                if merged.contains(&start) {
+                    continue;
+                }
+                let face = plane.faces[&start];
+
+                // Grow the quad along x as far as it can go, then along y for as many rows as
+                // match it entirely.
+                let mut size = Vec2::one();
+                if face.attr.is_uniform() {
+                    let matches = |pos: Vec2<i32>| {
+                        !merged.contains(&pos)
+                            && plane.faces.get(&pos).map(|other| other.attr) == Some(face.attr)
+                    };
+                    while matches(start + Vec2::new(size.x, 0)) {
+                        size.x += 1;
+                    }
+                    while (0..size.x).all(|x| matches(start + Vec2::new(x, size.y))) {
+                        size.y += 1;
+                    }
+                }
+
+                for y in 0..size.y {
+                    for x in 0..size.x {
+                        merged.insert(start + Vec2::new(x, y));
+                    }
+                }
+
+                let sway = face.attr.sway;
+                mesh.push_quad(create_quad(
+                    face.origin,
+                    plane.unit_x * size.x as f32,
+                    plane.unit_y * size.y as f32,
+                    plane.norm,
+                    face.attr.col,
+                    face.attr.darkness_ao,
+                    &|pos, norm, col, ao, light| vcons(pos, norm, col, ao, light, sway),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::TerrainPipeline;
+
+    type TerrainVertex = <TerrainPipeline as Pipeline>::Vertex;
+
+    fn mesh_faces(faces: &[(Vec3<f32>, Rgb<f32>, f32)]) -> Mesh<TerrainPipeline> {
+        let mut greedy = GreedyMesher::new();
+        for &(origin, col, ao) in faces {
+            greedy.push_face(
+                origin,
+                Vec3::unit_x(),
+                Vec3::unit_y(),
+                Vec3::unit_z(),
+                col,
+                Vec4::broadcast((0.0, ao)),
+                0.0,
+            );
+        }
+
+        let mut mesh = Mesh::new();
+        greedy.build(&mut mesh, |pos, norm, col, ao, light, _| {
+            TerrainVertex::new(pos, norm, col, light * ao)
+        });
+        mesh
+    }
+
+    #[test]
+    fn merges_matching_faces() {
+        let grass = Rgb::new(0.1, 0.6, 0.1);
+        let floor = (0..4)
+            .map(|y| (0..4).map(move |x| (Vec3::new(x as f32, y as f32, 8.0), grass, 1.0)))
+            .flatten()
+            .collect::<Vec<_>>();
+        // A whole flat floor becomes a single quad
+        assert_eq!(mesh_faces(&floor).vertices().len(), 6);
+
+        // A differently coloured face in one corner splits it into three
+        let mut patched = floor.clone();
+        patched[0].1 = Rgb::new(0.5, 0.4, 0.3);
+        assert_eq!(mesh_faces(&patched).vertices().len(), 3 * 6);
+
+        // Faces at different heights never merge
+        let mut stepped = floor.clone();
+        stepped[15].0.z += 1.0;
+        assert_eq!(mesh_faces(&stepped).vertices().len(), 3 * 6);
+    }
+
+    #[test]
+    fn keeps_unevenly_lit_faces_apart() {
+        let mut greedy = GreedyMesher::new();
+        for x in 0..4 {
+            greedy.push_face(
+                Vec3::new(x as f32, 0.0, 0.0),
+                Vec3::unit_x(),
+                Vec3::unit_y(),
+                Vec3::unit_z(),
+                Rgb::one(),
+                Vec4::new((0.0, 1.0), (0.0, 0.5), (0.0, 0.5), (0.0, 1.0)),
+                0.0,
+            );
+        }
+
+        let mut mesh = Mesh::<TerrainPipeline>::new();
+        greedy.build(&mut mesh, |pos, norm, col, ao, light, _| {
+            TerrainVertex::new(pos, norm, col, light * ao)
+        });
+        assert_eq!(mesh.vertices().len(), 4 * 6);
+    }
+}
@@ -1,3 +1,4 @@
+mod greedy;
 pub mod segment;
 pub mod terrain;
 mod vol;
@@ -21,36 +21,48 @@ impl Meshable<FigurePipeline, FigurePipeline> for Segment {
         &self,
         offs: Self::Supplement,
     ) -> (Mesh<Self::Pipeline>, Mesh<Self::TranslucentPipeline>) {
-        let mut mesh = Mesh::new();
+        (generate_glowing_mesh(self, offs, |_| 0.0), Mesh::new())
+    }
+}
 
-        for (pos, vox) in self.full_vol_iter() {
-            if let Some(col) = vox.get_color() {
-                let col = col.map(|e| e as f32 / 255.0);
+/// Generate a figure mesh for a segment where some voxels glow. `glow` gives the amount of glow
+/// for the voxel at each position in the segment.
+pub fn generate_glowing_mesh(
+    segment: &Segment,
+    offs: Vec3<f32>,
+    glow: impl Fn(Vec3<i32>) -> f32,
+) -> Mesh<FigurePipeline> {
+    let mut mesh = Mesh::new();
 
-                vol::push_vox_verts(
-                    &mut mesh,
-                    self,
-                    pos,
-                    offs + pos.map(|e| e as f32),
-                    col,
-                    |origin, norm, col, ao, light| {
-                        FigureVertex::new(
-                            origin,
-                            norm,
-                            linear_to_srgb(srgb_to_linear(col) * ao * light),
-                            0,
-                        )
-                    },
-                    true,
-                    &[[[1.0; 3]; 3]; 3],
-                    |vox| vox.is_empty(),
-                    |vox| !vox.is_empty(),
-                );
-            }
-        }
+    for (pos, vox) in segment.full_vol_iter() {
+        if let Some(col) = vox.get_color() {
+            let col = col.map(|e| e as f32 / 255.0);
+            let glow = glow(pos);
 
-        (mesh, Mesh::new())
+            vol::push_vox_verts(
+                &mut mesh,
+                segment,
+                pos,
+                offs + pos.map(|e| e as f32),
+                col,
+                |origin, norm, col, ao, light| {
+                    FigureVertex::new(
+                        origin,
+                        norm,
+                        linear_to_srgb(srgb_to_linear(col) * ao * light),
+                        0,
+                    )
+                    .with_glow(glow)
+                },
+                true,
+                &[[[1.0; 3]; 3]; 3],
+                |vox| vox.is_empty(),
+                |vox| !vox.is_empty(),
+            );
+        }
     }
+
+    mesh
 }
 
 impl Meshable<SpritePipeline, SpritePipeline> for Segment {
@@ -79,6 +91,7 @@ impl Meshable<SpritePipeline, SpritePipeline> for Segment {
                             origin,
                             norm,
                             linear_to_srgb(srgb_to_linear(col) * ao * light),
+                            Vec2::zero(),
                         )
                     },
                     true,
@@ -1,5 +1,5 @@
 use crate::{
-    mesh::{vol, Meshable},
+    mesh::{greedy::GreedyMesher, vol, Meshable},
     render::{self, FluidPipeline, Mesh, TerrainPipeline},
 };
 use common::{
@@ -16,7 +16,7 @@ type FluidVertex = <FluidPipeline as render::Pipeline>::Vertex;
 fn block_shadow_density(kind: BlockKind) -> (f32, f32) {
     // (density, cap)
     match kind {
-        BlockKind::Normal => (0.085, 0.3),
+        BlockKind::Normal | BlockKind::Leaves => (0.085, 0.3),
         BlockKind::Dense => (0.3, 0.0),
         BlockKind::Water => (0.15, 0.0),
         kind if kind.is_air() => (0.0, 0.0),
@@ -24,19 +24,37 @@ fn block_shadow_density(kind: BlockKind) -> (f32, f32) {
     }
 }
 
+/// How much a block bends with the wind. Only foliage sways.
+fn foliage_sway(block: &Block) -> f32 {
+    match block.kind() {
+        BlockKind::Leaves => 1.0,
+        _ => 0.0,
+    }
+}
+
 impl<V: RectRasterableVol<Vox = Block> + ReadVol + Debug> Meshable<TerrainPipeline, FluidPipeline>
     for VolGrid2d<V>
 {
     type Pipeline = TerrainPipeline;
     type TranslucentPipeline = FluidPipeline;
-    type Supplement = Aabb<i32>;
+    /// The region to mesh, and whether to darken corners and crevices with ambient occlusion.
+    type Supplement = (Aabb<i32>, bool);
 
     fn generate_mesh(
         &self,
-        range: Self::Supplement,
+        (range, ambient_occlusion): Self::Supplement,
     ) -> (Mesh<Self::Pipeline>, Mesh<Self::TranslucentPipeline>) {
-        let mut opaque_mesh = Mesh::new();
-        let mut fluid_mesh = Mesh::new();
+        // Without ambient occlusion, every corner of a face is as open as any other
+        let ao = |darkness_ao: Vec4<(f32, f32)>| {
+            if ambient_occlusion {
+                darkness_ao
+            } else {
+                darkness_ao.map(|(darkness, _)| (darkness, 1.0))
+            }
+        };
+
+        let mut opaque_faces = GreedyMesher::new();
+        let mut fluid_faces = GreedyMesher::new();
 
         for x in range.min.x + 1..range.max.x - 1 {
             for y in range.min.y + 1..range.max.y - 1 {
@@ -49,25 +67,31 @@ impl<V: RectRasterableVol<Vox = Block> + ReadVol + Debug> Meshable<TerrainPipeli
                     let block = self.get(pos).ok();
 
                     // Create mesh polygons
-                    if let Some(col) = block
+                    if let Some((col, sway)) = block
                         .filter(|vox| vox.is_opaque())
-                        .and_then(|vox| vox.get_color())
+                        .and_then(|vox| vox.get_color().map(|col| (col, foliage_sway(vox))))
                     {
                         let col = col.map(|e| e as f32 / 255.0);
 
-                        vol::push_vox_verts(
-                            &mut opaque_mesh,
+                        vol::for_each_vox_face(
                             self,
                             pos,
                             offs,
-                            col,
-                            |pos, norm, col, ao, light| {
-                                TerrainVertex::new(pos, norm, col, light * ao)
-                            },
                             false,
                             &neighbour_light,
                             |vox| !vox.is_opaque(),
                             |vox| vox.is_opaque(),
+                            |origin, unit_x, unit_y, norm, darkness_ao| {
+                                opaque_faces.push_face(
+                                    origin,
+                                    unit_x,
+                                    unit_y,
+                                    norm,
+                                    col,
+                                    ao(darkness_ao),
+                                    sway,
+                                )
+                            },
                         );
                     } else if let Some(col) = block
                         .filter(|vox| vox.is_fluid())
@@ -75,19 +99,25 @@ impl<V: RectRasterableVol<Vox = Block> + ReadVol + Debug> Meshable<TerrainPipeli
                     {
                         let col = col.map(|e| e as f32 / 255.0);
 
-                        vol::push_vox_verts(
-                            &mut fluid_mesh,
+                        vol::for_each_vox_face(
                             self,
                             pos,
                             offs,
-                            col,
-                            |pos, norm, col, ao, light| {
-                                FluidVertex::new(pos, norm, col, light * ao, 0.3)
-                            },
                             false,
                             &neighbour_light,
                             |vox| vox.is_air(),
                             |vox| vox.is_opaque(),
+                            |origin, unit_x, unit_y, norm, darkness_ao| {
+                                fluid_faces.push_face(
+                                    origin,
+                                    unit_x,
+                                    unit_y,
+                                    norm,
+                                    col,
+                                    ao(darkness_ao),
+                                    0.0,
+                                )
+                            },
                         );
                     }
 
@@ -121,6 +151,16 @@ impl<V: RectRasterableVol<Vox = Block> + ReadVol + Debug> Meshable<TerrainPipeli
             }
         }
 
+        // Merge neighbouring faces that look the same to cut down on vertices
+        let mut opaque_mesh = Mesh::new();
+        opaque_faces.build(&mut opaque_mesh, |pos, norm, col, ao, light, sway| {
+            TerrainVertex::new(pos, norm, col, light * ao).with_sway(sway)
+        });
+        let mut fluid_mesh = Mesh::new();
+        fluid_faces.build(&mut fluid_mesh, |pos, norm, col, ao, light, _| {
+            FluidVertex::new(pos, norm, col, light * ao, 0.3)
+        });
+
         (opaque_mesh, fluid_mesh)
     }
 }
@@ -190,3 +230,21 @@ impl<V: BaseVol<Vox = Block> + ReadVol + Debug> Meshable for VolGrid3d<V> {
     }
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_foliage_sways() {
+        let leaves = Block::new(BlockKind::Leaves, Rgb::new(40, 120, 30));
+        let wood = Block::new(BlockKind::Normal, Rgb::new(90, 60, 30));
+        // The surface of the ground, as generated by worldgen
+        let grass = Block::new(BlockKind::Normal, Rgb::new(40, 120, 30));
+
+        assert_eq!(foliage_sway(&leaves), 1.0);
+        assert_eq!(foliage_sway(&wood), 0.0);
+        // The ground stays put, however green it is
+        assert_eq!(foliage_sway(&grass), 0.0);
+    }
+}
@@ -57,8 +57,9 @@ fn get_ao_quad<V: ReadVol>(
         .collect::<Vec4<(f32, f32)>>()
 }
 
-// Utility function
-fn create_quad<P: Pipeline, F: Fn(Vec3<f32>, Vec3<f32>, Rgb<f32>, f32, f32) -> P::Vertex>(
+/// Build the quad for a face spanning `unit_x` and `unit_y` from `origin`, flipping its diagonal
+/// where that makes the AO across it look smoother.
+pub fn create_quad<P: Pipeline, F: Fn(Vec3<f32>, Vec3<f32>, Rgb<f32>, f32, f32) -> P::Vertex>(
     origin: Vec3<f32>,
     unit_x: Vec3<f32>,
     unit_y: Vec3<f32>,
@@ -100,6 +101,40 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
     darknesses: &[[[f32; 3]; 3]; 3],
     should_add: impl Fn(&V::Vox) -> bool,
     is_opaque: impl Fn(&V::Vox) -> bool,
+) {
+    for_each_vox_face(
+        vol,
+        pos,
+        offs,
+        error_makes_face,
+        darknesses,
+        should_add,
+        is_opaque,
+        |origin, unit_x, unit_y, norm, darkness_ao| {
+            mesh.push_quad(create_quad(
+                origin,
+                unit_x,
+                unit_y,
+                norm,
+                col,
+                darkness_ao,
+                &vcons,
+            ))
+        },
+    );
+}
+
+/// Call `f` with each visible face of the voxel at `pos`, giving the corner of the face, the two
+/// edges running from it, its normal, and the darkness and AO at each of its corners.
+pub fn for_each_vox_face<V: ReadVol>(
+    vol: &V,
+    pos: Vec3<i32>,
+    offs: Vec3<f32>,
+    error_makes_face: bool,
+    darknesses: &[[[f32; 3]; 3]; 3],
+    should_add: impl Fn(&V::Vox) -> bool,
+    is_opaque: impl Fn(&V::Vox) -> bool,
+    mut f: impl FnMut(Vec3<f32>, Vec3<f32>, Vec3<f32>, Vec3<f32>, Vec4<(f32, f32)>),
 ) {
     let (x, y, z) = (Vec3::unit_x(), Vec3::unit_y(), Vec3::unit_z());
 
@@ -109,12 +144,11 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
         .map(|v| should_add(v))
         .unwrap_or(error_makes_face)
     {
-        mesh.push_quad(create_quad(
+        f(
             offs,
             Vec3::unit_z(),
             Vec3::unit_y(),
             -Vec3::unit_x(),
-            col,
             get_ao_quad(
                 vol,
                 pos,
@@ -123,8 +157,7 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
                 darknesses,
                 &is_opaque,
             ),
-            &vcons,
-        ));
+        );
     }
     // +x
     if vol
@@ -132,12 +165,11 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
         .map(|v| should_add(v))
         .unwrap_or(error_makes_face)
     {
-        mesh.push_quad(create_quad(
+        f(
             offs + Vec3::unit_x(),
             Vec3::unit_y(),
             Vec3::unit_z(),
             Vec3::unit_x(),
-            col,
             get_ao_quad(
                 vol,
                 pos,
@@ -146,8 +178,7 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
                 darknesses,
                 &is_opaque,
             ),
-            &vcons,
-        ));
+        );
     }
     // -y
     if vol
@@ -155,12 +186,11 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
         .map(|v| should_add(v))
         .unwrap_or(error_makes_face)
     {
-        mesh.push_quad(create_quad(
+        f(
             offs,
             Vec3::unit_x(),
             Vec3::unit_z(),
             -Vec3::unit_y(),
-            col,
             get_ao_quad(
                 vol,
                 pos,
@@ -169,8 +199,7 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
                 darknesses,
                 &is_opaque,
             ),
-            &vcons,
-        ));
+        );
     }
     // +y
     if vol
@@ -178,12 +207,11 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
         .map(|v| should_add(v))
         .unwrap_or(error_makes_face)
     {
-        mesh.push_quad(create_quad(
+        f(
             offs + Vec3::unit_y(),
             Vec3::unit_z(),
             Vec3::unit_x(),
             Vec3::unit_y(),
-            col,
             get_ao_quad(
                 vol,
                 pos,
@@ -192,8 +220,7 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
                 darknesses,
                 &is_opaque,
             ),
-            &vcons,
-        ));
+        );
     }
     // -z
     if vol
@@ -201,12 +228,11 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
         .map(|v| should_add(v))
         .unwrap_or(error_makes_face)
     {
-        mesh.push_quad(create_quad(
+        f(
             offs,
             Vec3::unit_y(),
             Vec3::unit_x(),
             -Vec3::unit_z(),
-            col,
             get_ao_quad(
                 vol,
                 pos,
@@ -215,8 +241,7 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
                 darknesses,
                 &is_opaque,
             ),
-            &vcons,
-        ));
+        );
     }
     // +z
     if vol
@@ -224,12 +249,11 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
         .map(|v| should_add(v))
         .unwrap_or(error_makes_face)
     {
-        mesh.push_quad(create_quad(
+        f(
             offs + Vec3::unit_z(),
             Vec3::unit_x(),
             Vec3::unit_y(),
             Vec3::unit_z(),
-            col,
             get_ao_quad(
                 vol,
                 pos,
@@ -238,7 +262,6 @@ pub fn push_vox_verts<V: ReadVol, P: Pipeline>(
                 darknesses,
                 &is_opaque,
             ),
-            &vcons,
-        ));
+        );
     }
 }
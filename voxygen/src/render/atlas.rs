@@ -0,0 +1,140 @@
+use super::{Pipeline, RenderError, Renderer, Texture};
+use guillotiere::{size2, AllocId, Allocation, AtlasAllocator};
+use image::RgbaImage;
+use vek::*;
+
+/// Where space for an image was allocated in an atlas.
+#[derive(Copy, Clone, Debug)]
+pub struct AtlasRect {
+    id: AllocId,
+    aabr: Aabr<u16>,
+}
+
+impl AtlasRect {
+    /// The pixels of the atlas covered by the image.
+    pub fn aabr(&self) -> Aabr<u16> {
+        self.aabr
+    }
+}
+
+/// Packs rectangles of different sizes into a larger one, using guillotine packing. Space can be
+/// freed again for reuse.
+pub struct Atlas {
+    allocator: AtlasAllocator,
+    size: Vec2<u16>,
+}
+
+impl Atlas {
+    pub fn new(size: Vec2<u16>) -> Self {
+        Self {
+            allocator: AtlasAllocator::new(size2(i32::from(size.x), i32::from(size.y))),
+            size,
+        }
+    }
+
+    /// Find space for a rectangle of the given size, if there's any left.
+    pub fn allocate(&mut self, dims: Vec2<u16>) -> Option<AtlasRect> {
+        self.allocator
+            .allocate(size2(i32::from(dims.x), i32::from(dims.y)))
+            .map(|Allocation { id, rectangle }| AtlasRect {
+                id,
+                aabr: Aabr {
+                    min: Vec2::new(rectangle.min.x as u16, rectangle.min.y as u16),
+                    max: Vec2::new(rectangle.max.x as u16, rectangle.max.y as u16),
+                },
+            })
+    }
+
+    pub fn deallocate(&mut self, rect: AtlasRect) {
+        self.allocator.deallocate(rect.id);
+    }
+
+    /// The texture coordinates covering the given pixels of the atlas.
+    pub fn uv(&self, aabr: Aabr<u16>) -> Aabr<f32> {
+        let size = self.size.map(|e| e as f32);
+        Aabr {
+            min: aabr.min.map2(size, |e, sz| e as f32 / sz),
+            max: aabr.max.map2(size, |e, sz| e as f32 / sz),
+        }
+    }
+}
+
+/// A texture that images can be added to at runtime, so that many of them can be drawn together
+/// without switching textures.
+pub struct TextureAtlas<P: Pipeline> {
+    atlas: Atlas,
+    tex: Texture<P>,
+}
+
+impl<P: Pipeline> TextureAtlas<P> {
+    pub fn new(renderer: &mut Renderer, size: Vec2<u16>) -> Result<Self, RenderError> {
+        Ok(Self {
+            atlas: Atlas::new(size),
+            tex: renderer.create_dynamic_texture(size)?,
+        })
+    }
+
+    /// Upload an image into the atlas, returning where it was put, or `None` if there's no room
+    /// left for it.
+    pub fn insert(
+        &mut self,
+        renderer: &mut Renderer,
+        image: &RgbaImage,
+    ) -> Result<Option<AtlasRect>, RenderError> {
+        let dims = Vec2::new(image.width() as u16, image.height() as u16);
+        let rect = match self.atlas.allocate(dims) {
+            Some(rect) => rect,
+            None => return Ok(None),
+        };
+
+        let pixels = image.pixels().map(|p| p.0).collect::<Vec<_>>();
+        if let Err(err) = renderer.update_texture(
+            &self.tex,
+            rect.aabr.min.into_array(),
+            dims.into_array(),
+            &pixels,
+        ) {
+            self.atlas.deallocate(rect);
+            return Err(err);
+        }
+
+        Ok(Some(rect))
+    }
+
+    /// Free the space taken by an image, for others to be inserted into.
+    pub fn remove(&mut self, rect: AtlasRect) {
+        self.atlas.deallocate(rect);
+    }
+
+    pub fn uv(&self, aabr: Aabr<u16>) -> Aabr<f32> {
+        self.atlas.uv(aabr)
+    }
+
+    pub fn texture(&self) -> &Texture<P> {
+        &self.tex
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atlas_reuses_freed_space() {
+        let mut atlas = Atlas::new(Vec2::new(64, 32));
+        let left = atlas.allocate(Vec2::new(32, 32)).unwrap();
+        let right = atlas.allocate(Vec2::new(32, 32)).unwrap();
+        assert_eq!(
+            left.aabr().size() + right.aabr().size(),
+            Extent2::new(64, 64)
+        );
+        assert!(atlas.allocate(Vec2::new(8, 8)).is_none());
+
+        atlas.deallocate(left);
+        let reused = atlas.allocate(Vec2::new(16, 16)).unwrap();
+        assert_eq!(reused.aabr().min, left.aabr().min);
+
+        let uv = atlas.uv(right.aabr());
+        assert_eq!(uv.size(), Extent2::new(0.5, 1.0));
+    }
+}
@@ -0,0 +1,460 @@
+use super::{
+    gfx_backend,
+    pipelines::{postprocess::AaMode, shadow::ShadowQuality},
+    renderer::{
+        RefractionTex, ShadowDepthFmt, ShadowDepthView, ShadowRes, TgtColorFmt, TgtColorRes,
+        TgtColorView, TgtDepthFmt, TgtDepthView,
+    },
+    RenderError,
+};
+use gfx::{
+    texture::{AaMode as TexAaMode, Kind},
+    traits::{Factory, FactoryExt},
+};
+
+/// Something that passes draw into, for later passes to read from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    ShadowMap,
+    SceneColor,
+    SceneDepth,
+    /// A copy of the scene, made for fluids to see what's behind them.
+    Refraction,
+    Window,
+}
+
+/// A stage of rendering a frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Pass {
+    /// The depth of everything that casts a shadow, as seen by the sun.
+    Shadow,
+    /// Everything solid.
+    Opaque,
+    /// Copy the scene so far for translucent things to refract.
+    Refraction,
+    /// Things that blend with what's behind them (e.g: fluids, particles, weapon trails).
+    Translucent,
+    /// Resolve the scene if it's multisampled, and mipmap it for bloom.
+    Resolve,
+    /// Grade and tonemap the scene onto the window.
+    PostProcess,
+    Ui,
+}
+
+/// How a pass fits into a frame: the targets it reads and writes, and whether the renderer does
+/// its work itself when the frame reaches it, rather than it being made up of draws queued from
+/// outside.
+#[derive(Copy, Clone, Debug)]
+pub struct PassDesc {
+    pub pass: Pass,
+    pub reads: &'static [Target],
+    pub writes: &'static [Target],
+    pub automatic: bool,
+}
+
+/// The passes a frame is made up of, in the order they run, unless something else is asked for.
+pub const DEFAULT_PASSES: [PassDesc; 7] = [
+    PassDesc {
+        pass: Pass::Shadow,
+        reads: &[],
+        writes: &[Target::ShadowMap],
+        automatic: false,
+    },
+    PassDesc {
+        pass: Pass::Opaque,
+        reads: &[Target::ShadowMap],
+        writes: &[Target::SceneColor, Target::SceneDepth],
+        automatic: false,
+    },
+    PassDesc {
+        pass: Pass::Refraction,
+        reads: &[Target::SceneColor],
+        writes: &[Target::Refraction],
+        automatic: true,
+    },
+    PassDesc {
+        pass: Pass::Translucent,
+        reads: &[Target::SceneDepth, Target::Refraction],
+        writes: &[Target::SceneColor],
+        automatic: false,
+    },
+    PassDesc {
+        pass: Pass::Resolve,
+        reads: &[Target::SceneColor],
+        writes: &[Target::SceneColor],
+        automatic: true,
+    },
+    PassDesc {
+        pass: Pass::PostProcess,
+        reads: &[Target::SceneColor],
+        writes: &[Target::Window],
+        automatic: false,
+    },
+    PassDesc {
+        pass: Pass::Ui,
+        reads: &[],
+        writes: &[Target::Window],
+        automatic: false,
+    },
+];
+
+#[derive(Debug)]
+pub enum GraphError {
+    /// The pass reads a target that no pass before it writes.
+    Unwritten(Pass, Target),
+    /// The pass appears more than once.
+    Repeated(Pass),
+}
+
+/// What the targets of a graph are created to suit.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TargetConfig {
+    /// The resolution that the scene is rendered at, before it's scaled up to fill the window.
+    pub scene_dims: (u16, u16),
+    pub aa_mode: AaMode,
+    pub shadow_quality: ShadowQuality,
+}
+
+/// The targets that the passes of a graph draw into and read from. The window is the only target
+/// that isn't here, since it belongs to the window rather than the renderer.
+pub struct Targets {
+    /// `Target::SceneColor` and `Target::SceneDepth`, as drawn to by the passes that write them.
+    pub tgt_color_view: TgtColorView,
+    pub tgt_depth_view: TgtDepthView,
+    /// `Target::SceneColor` as a resource for post-processing, with a full chain of mipmaps so
+    /// that blurred versions of it can be cheaply sampled for bloom.
+    pub tgt_color_res: TgtColorRes,
+    /// When multisampling, the scene is rendered to a separate multisampled target, then resolved
+    /// into `tgt_resolve_view` (the top mipmap of `tgt_color_res`) to be post-processed.
+    pub tgt_msaa_res: Option<TgtColorRes>,
+    pub tgt_resolve_view: TgtColorView,
+
+    /// `Target::Refraction`.
+    pub refraction_tex: RefractionTex,
+    pub refraction_res: TgtColorRes,
+    pub refraction_view: TgtColorView,
+
+    /// `Target::ShadowMap`, with one layer per cascade along with a depth target for each layer.
+    pub shadow_depth_views: Vec<ShadowDepthView>,
+    pub shadow_res: ShadowRes,
+}
+
+/// The passes that make up a frame, in the order they run, along with the targets they draw into.
+/// Draws are queued into whichever pass they belong to, and the frame moves on through the graph
+/// as they come in: the automatic passes in between are run along the way, as long as what they
+/// read has been written this frame.
+pub struct RenderGraph {
+    passes: Vec<PassDesc>,
+    config: TargetConfig,
+    targets: Targets,
+}
+
+impl RenderGraph {
+    pub fn new(
+        factory: &mut gfx_backend::Factory,
+        passes: Vec<PassDesc>,
+        config: TargetConfig,
+    ) -> Result<Self, RenderError> {
+        validate(&passes).map_err(RenderError::GraphError)?;
+
+        let (tgt_color_view, tgt_depth_view, tgt_color_res, tgt_resolve_view, tgt_msaa_res) =
+            create_scene_targets(factory, config.scene_dims, config.aa_mode)?;
+        let (refraction_tex, refraction_res, refraction_view) =
+            create_refraction_target(factory, config.scene_dims)?;
+        let (shadow_depth_views, shadow_res) = create_shadow_maps(
+            factory,
+            config.shadow_quality.resolution(),
+            config.shadow_quality.cascades(),
+        )?;
+
+        Ok(Self {
+            passes,
+            config,
+            targets: Targets {
+                tgt_color_view,
+                tgt_depth_view,
+                tgt_color_res,
+                tgt_msaa_res,
+                tgt_resolve_view,
+
+                refraction_tex,
+                refraction_res,
+                refraction_view,
+
+                shadow_depth_views,
+                shadow_res,
+            },
+        })
+    }
+
+    pub fn passes(&self) -> &[PassDesc] {
+        &self.passes
+    }
+
+    /// Where the pass comes in the frame, if it's part of it at all.
+    pub fn position(&self, pass: Pass) -> Option<usize> {
+        self.passes.iter().position(|p| p.pass == pass)
+    }
+
+    pub fn targets(&self) -> &Targets {
+        &self.targets
+    }
+
+    pub fn targets_mut(&mut self) -> &mut Targets {
+        &mut self.targets
+    }
+
+    /// Recreate whichever targets no longer suit `config`. The new targets are all created before
+    /// any are replaced, so if creating one fails, the graph is left as it was. Targets the size of
+    /// the scene are left alone while it has no area (e.g: when the window is minimised), and are
+    /// recreated once it has some again.
+    pub fn reconfigure(
+        &mut self,
+        factory: &mut gfx_backend::Factory,
+        config: TargetConfig,
+    ) -> Result<(), RenderError> {
+        let has_area = config.scene_dims.0 != 0 && config.scene_dims.1 != 0;
+        let resized = has_area && config.scene_dims != self.config.scene_dims;
+
+        let scene = if resized || (has_area && config.aa_mode != self.config.aa_mode) {
+            Some(create_scene_targets(
+                factory,
+                config.scene_dims,
+                config.aa_mode,
+            )?)
+        } else {
+            None
+        };
+        let refraction = if resized {
+            Some(create_refraction_target(factory, config.scene_dims)?)
+        } else {
+            None
+        };
+        let shadows = if config.shadow_quality != self.config.shadow_quality {
+            Some(create_shadow_maps(
+                factory,
+                config.shadow_quality.resolution(),
+                config.shadow_quality.cascades(),
+            )?)
+        } else {
+            None
+        };
+
+        let targets = &mut self.targets;
+        if let Some((
+            tgt_color_view,
+            tgt_depth_view,
+            tgt_color_res,
+            tgt_resolve_view,
+            tgt_msaa_res,
+        )) = scene
+        {
+            targets.tgt_color_view = tgt_color_view;
+            targets.tgt_depth_view = tgt_depth_view;
+            targets.tgt_color_res = tgt_color_res;
+            targets.tgt_resolve_view = tgt_resolve_view;
+            targets.tgt_msaa_res = tgt_msaa_res;
+            self.config.scene_dims = config.scene_dims;
+            self.config.aa_mode = config.aa_mode;
+        }
+        if let Some((refraction_tex, refraction_res, refraction_view)) = refraction {
+            targets.refraction_tex = refraction_tex;
+            targets.refraction_res = refraction_res;
+            targets.refraction_view = refraction_view;
+        }
+        if let Some((shadow_depth_views, shadow_res)) = shadows {
+            targets.shadow_depth_views = shadow_depth_views;
+            targets.shadow_res = shadow_res;
+            self.config.shadow_quality = config.shadow_quality;
+        }
+
+        Ok(())
+    }
+}
+
+/// Check that no pass appears twice, and that every pass only reads targets that a pass before it
+/// writes.
+pub fn validate(passes: &[PassDesc]) -> Result<(), GraphError> {
+    for (i, desc) in passes.iter().enumerate() {
+        if passes[..i].iter().any(|p| p.pass == desc.pass) {
+            return Err(GraphError::Repeated(desc.pass));
+        }
+        if let Some(target) = desc
+            .reads
+            .iter()
+            .find(|target| !passes[..i].iter().any(|p| p.writes.contains(target)))
+        {
+            return Err(GraphError::Unwritten(desc.pass, *target));
+        }
+    }
+
+    Ok(())
+}
+
+/// Create the targets that the scene is rendered to: the color and depth targets to draw to,
+/// the color target as a resource for post-processing, and, when multisampling, the view that
+/// the multisampled color target is resolved into along with the resource to resolve it from.
+fn create_scene_targets(
+    factory: &mut gfx_backend::Factory,
+    size: (u16, u16),
+    aa_mode: AaMode,
+) -> Result<
+    (
+        TgtColorView,
+        TgtDepthView,
+        TgtColorRes,
+        TgtColorView,
+        Option<TgtColorRes>,
+    ),
+    RenderError,
+> {
+    use gfx::{
+        format::{ChannelType, Formatted, Swizzle},
+        memory::{Bind, Usage},
+    };
+
+    // The color target has a full chain of mipmaps, so that post-processing can cheaply
+    // sample blurred versions of it for bloom.
+    let levels = (size.0.max(size.1) as f32).log2() as u8 + 1;
+    let tex = factory
+        .create_texture::<<TgtColorFmt as Formatted>::Surface>(
+            Kind::D2(size.0, size.1, TexAaMode::Single),
+            levels,
+            Bind::SHADER_RESOURCE | Bind::RENDER_TARGET,
+            Usage::Data,
+            Some(ChannelType::Float),
+        )
+        .map_err(|err| RenderError::CombinedError(err.into()))?;
+    let tgt_color_res = factory
+        .view_texture_as_shader_resource::<TgtColorFmt>(&tex, (0, levels - 1), Swizzle::new())
+        .map_err(|err| RenderError::CombinedError(err.into()))?;
+    let tgt_resolve_view = factory
+        .view_texture_as_render_target::<TgtColorFmt>(&tex, 0, None)
+        .map_err(|err| RenderError::CombinedError(err.into()))?;
+
+    if aa_mode.samples() <= 1 {
+        let tgt_depth_view = factory
+            .create_depth_stencil_view_only::<TgtDepthFmt>(size.0, size.1)
+            .map_err(RenderError::CombinedError)?;
+        return Ok((
+            tgt_resolve_view.clone(),
+            tgt_depth_view,
+            tgt_color_res,
+            tgt_resolve_view,
+            None,
+        ));
+    }
+
+    // Multisampled targets can't have mipmaps or be sampled normally, so the scene is drawn
+    // to these and resolved into the mipmapped target before post-processing.
+    let kind = Kind::D2(size.0, size.1, TexAaMode::Multi(aa_mode.samples()));
+    let msaa_tex = factory
+        .create_texture::<<TgtColorFmt as Formatted>::Surface>(
+            kind,
+            1,
+            Bind::SHADER_RESOURCE | Bind::RENDER_TARGET,
+            Usage::Data,
+            Some(ChannelType::Float),
+        )
+        .map_err(|err| RenderError::CombinedError(err.into()))?;
+    let tgt_msaa_res = factory
+        .view_texture_as_shader_resource::<TgtColorFmt>(&msaa_tex, (0, 0), Swizzle::new())
+        .map_err(|err| RenderError::CombinedError(err.into()))?;
+    let tgt_color_view = factory
+        .view_texture_as_render_target::<TgtColorFmt>(&msaa_tex, 0, None)
+        .map_err(|err| RenderError::CombinedError(err.into()))?;
+    let depth_tex = factory
+        .create_texture::<<TgtDepthFmt as Formatted>::Surface>(
+            kind,
+            1,
+            Bind::DEPTH_STENCIL,
+            Usage::Data,
+            Some(ChannelType::Unorm),
+        )
+        .map_err(|err| RenderError::CombinedError(err.into()))?;
+    let tgt_depth_view = factory
+        .view_texture_as_depth_stencil_trivial::<TgtDepthFmt>(&depth_tex)
+        .map_err(|err| RenderError::CombinedError(err.into()))?;
+
+    Ok((
+        tgt_color_view,
+        tgt_depth_view,
+        tgt_color_res,
+        tgt_resolve_view,
+        Some(tgt_msaa_res),
+    ))
+}
+
+fn create_refraction_target(
+    factory: &mut gfx_backend::Factory,
+    size: (u16, u16),
+) -> Result<(RefractionTex, TgtColorRes, TgtColorView), RenderError> {
+    factory
+        .create_render_target::<TgtColorFmt>(size.0, size.1)
+        .map_err(RenderError::CombinedError)
+}
+
+/// Create a shadow map with one layer per cascade, along with a depth target for each layer.
+fn create_shadow_maps(
+    factory: &mut gfx_backend::Factory,
+    size: u16,
+    layers: usize,
+) -> Result<(Vec<ShadowDepthView>, ShadowRes), RenderError> {
+    use gfx::{
+        format::{ChannelType, Formatted, Swizzle},
+        memory::{Bind, Usage},
+        texture::DepthStencilFlags,
+    };
+
+    let tex = factory
+        .create_texture::<<ShadowDepthFmt as Formatted>::Surface>(
+            Kind::D2Array(size, size, layers as u16, TexAaMode::Single),
+            1,
+            Bind::SHADER_RESOURCE | Bind::DEPTH_STENCIL,
+            Usage::Data,
+            Some(ChannelType::Float),
+        )
+        .map_err(|err| RenderError::CombinedError(err.into()))?;
+    let depth_views = (0..layers as u16)
+        .map(|layer| {
+            factory
+                .view_texture_as_depth_stencil::<ShadowDepthFmt>(
+                    &tex,
+                    0,
+                    Some(layer),
+                    DepthStencilFlags::empty(),
+                )
+                .map_err(|err| RenderError::CombinedError(err.into()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let res = factory
+        .view_texture_as_shader_resource::<ShadowDepthFmt>(&tex, (0, 0), Swizzle::new())
+        .map_err(|err| RenderError::CombinedError(err.into()))?;
+
+    Ok((depth_views, res))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graph_validation() {
+        assert!(validate(&DEFAULT_PASSES).is_ok());
+
+        let desc = |pass| *DEFAULT_PASSES.iter().find(|p| p.pass == pass).unwrap();
+        // Fluids can't refract a scene that hasn't been copied yet
+        match validate(&[
+            desc(Pass::Shadow),
+            desc(Pass::Opaque),
+            desc(Pass::Translucent),
+        ]) {
+            Err(GraphError::Unwritten(Pass::Translucent, Target::Refraction)) => {}
+            _ => panic!("Reading an unwritten target should be rejected"),
+        }
+        match validate(&[desc(Pass::Shadow), desc(Pass::Shadow)]) {
+            Err(GraphError::Repeated(Pass::Shadow)) => {}
+            _ => panic!("Repeated passes should be rejected"),
+        }
+    }
+}
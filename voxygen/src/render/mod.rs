@@ -1,34 +1,64 @@
+pub mod atlas;
 pub mod consts;
+pub mod graph;
 pub mod instances;
 pub mod mesh;
 pub mod model;
 pub mod pipelines;
+pub mod profiler;
 pub mod renderer;
+pub mod scaling;
 pub mod texture;
 mod util;
 
 // Reexports
 pub use self::{
+    atlas::{AtlasRect, TextureAtlas},
     consts::Consts,
     instances::Instances,
     mesh::{Mesh, Quad, Tri},
     model::{DynamicModel, Model},
     pipelines::{
-        figure::{BoneData as FigureBoneData, FigurePipeline, Locals as FigureLocals},
+        figure::{
+            instances_per_batch as figure_instances_per_batch, BoneData as FigureBoneData,
+            FigurePipeline, Instance as FigureInstance, Locals as FigureLocals,
+            MAX_BATCH_BONES as MAX_FIGURE_BATCH_BONES, MAX_BONES as MAX_FIGURE_BONES,
+            MAX_INSTANCES as MAX_FIGURE_INSTANCES,
+        },
         fluid::FluidPipeline,
+        lod::{create_mesh as create_lod_mesh, LodPipeline},
+        outline::{
+            create_block_mesh as create_outline_block_mesh, Locals as OutlineLocals,
+            OutlinePipeline,
+        },
+        pack_lights,
+        particle::{
+            create_mesh as create_particle_mesh, Instance as ParticleInstance, ParticlePipeline,
+        },
         postprocess::{
-            create_mesh as create_pp_mesh, Locals as PostProcessLocals, PostProcessPipeline,
+            create_mesh as create_pp_mesh, AaMode, Locals as PostProcessLocals, PostProcessPipeline,
+        },
+        shadow::{
+            cascade_mat as shadow_cascade_mat, sun_dir, Locals as ShadowLocals, ShadowQuality,
         },
         skybox::{create_mesh as create_skybox_mesh, Locals as SkyboxLocals, SkyboxPipeline},
         sprite::{Instance as SpriteInstance, SpritePipeline},
         terrain::{Locals as TerrainLocals, TerrainPipeline},
+        trail::{TrailPipeline, Vertex as TrailVertex},
         ui::{
             create_quad as create_ui_quad, create_tri as create_ui_tri, Locals as UiLocals,
             Mode as UiMode, UiPipeline,
         },
-        Globals, Light,
+        weather::{
+            create_mesh as create_weather_mesh, Instance as WeatherInstance,
+            Locals as WeatherLocals, WeatherPipeline,
+        },
+        Fog, Globals, Light, Shadow, MAX_LIGHTS, MAX_LIGHT_REACH, MAX_SHADOW_CASCADES,
+        UNBLOCKED_LIGHT_REACH,
     },
-    renderer::{Renderer, TgtColorFmt, TgtDepthFmt, WinColorFmt, WinDepthFmt},
+    profiler::Stage as ProfileStage,
+    renderer::{Renderer, ShadowDepthFmt, TgtColorFmt, TgtDepthFmt, WinColorFmt, WinDepthFmt},
+    scaling::{DynamicResolution, MIN_RENDER_SCALE},
     texture::Texture,
 };
 
@@ -48,6 +78,8 @@ pub enum RenderError {
     IncludeError(glsl_include::Error),
     MappingError(gfx::mapping::Error),
     CopyError(gfx::CopyError<[u16; 3], usize>),
+    TexCopyError(gfx::CopyError<[u16; 3], [u16; 3]>),
+    GraphError(graph::GraphError),
 }
 
 /// Used to represent a specific rendering configuration.
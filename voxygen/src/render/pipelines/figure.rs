@@ -1,6 +1,6 @@
 use super::{
     super::{util::arr_to_mat, Pipeline, TgtColorFmt, TgtDepthFmt},
-    Globals, Light,
+    Globals, Light, Shadow,
 };
 use gfx::{
     self,
@@ -14,14 +14,34 @@ use gfx::{
 };
 use vek::*;
 
+/// The number of bones that the `u_bones` constant buffer of a single figure holds, which
+/// skeletons can't exceed. Only the bones a skeleton actually has are uploaded.
+pub const MAX_BONES: usize = 32;
+/// The number of bones that the `u_bones` constant buffer of an instanced draw call holds, shared
+/// between its figures. This is bounded by the smallest uniform block size GL guarantees (16KiB).
+pub const MAX_BATCH_BONES: usize = 256;
+/// The most figures that can be drawn by a single instanced draw call, if their skeletons are
+/// small enough for that many to fit in `MAX_BATCH_BONES`.
+pub const MAX_INSTANCES: usize = 32;
+
 gfx_defines! {
     vertex Vertex {
         pos: [f32; 3] = "v_pos",
         norm: [f32; 3] = "v_norm",
         col: [f32; 3] = "v_col",
+        glow: f32 = "v_glow",
         bone_idx: u8 = "v_bone_idx",
     }
 
+    vertex Instance {
+        inst_mat0: [f32; 4] = "inst_mat0",
+        inst_mat1: [f32; 4] = "inst_mat1",
+        inst_mat2: [f32; 4] = "inst_mat2",
+        inst_mat3: [f32; 4] = "inst_mat3",
+        inst_col: [f32; 4] = "inst_col",
+        inst_bone_base: u32 = "inst_bone_base",
+    }
+
     constant Locals {
         model_mat: [[f32; 4]; 4] = "model_mat",
         model_col: [f32; 4] = "model_col",
@@ -38,6 +58,24 @@ gfx_defines! {
         globals: gfx::ConstantBuffer<Globals> = "u_globals",
         bones: gfx::ConstantBuffer<BoneData> = "u_bones",
         lights: gfx::ConstantBuffer<Light> = "u_lights",
+        shadows: gfx::ConstantBuffer<Shadow> = "u_shadows",
+
+        shadow_maps: gfx::TextureSampler<f32> = "t_shadow_maps",
+
+        tgt_color: gfx::RenderTarget<TgtColorFmt> = "tgt_color",
+        tgt_depth: gfx::DepthTarget<TgtDepthFmt> = gfx::preset::depth::LESS_EQUAL_WRITE,
+    }
+
+    pipeline instanced_pipe {
+        vbuf: gfx::VertexBuffer<Vertex> = (),
+        ibuf: gfx::InstanceBuffer<Instance> = (),
+
+        globals: gfx::ConstantBuffer<Globals> = "u_globals",
+        bones: gfx::ConstantBuffer<BoneData> = "u_bones",
+        lights: gfx::ConstantBuffer<Light> = "u_lights",
+        shadows: gfx::ConstantBuffer<Shadow> = "u_shadows",
+
+        shadow_maps: gfx::TextureSampler<f32> = "t_shadow_maps",
 
         tgt_color: gfx::RenderTarget<TgtColorFmt> = "tgt_color",
         tgt_depth: gfx::DepthTarget<TgtDepthFmt> = gfx::preset::depth::LESS_EQUAL_WRITE,
@@ -50,10 +88,17 @@ impl Vertex {
             pos: pos.into_array(),
             col: col.into_array(),
             norm: norm.into_array(),
+            glow: 0.0,
             bone_idx,
         }
     }
 
+    /// Make the vertex emit light of its own color, so that it stays visible in the dark.
+    pub fn with_glow(mut self, glow: f32) -> Self {
+        self.glow = glow;
+        self
+    }
+
     pub fn with_bone_idx(mut self, bone_idx: u8) -> Self {
         self.bone_idx = bone_idx;
         self
@@ -75,6 +120,33 @@ impl Default for Locals {
     }
 }
 
+impl Instance {
+    /// The same placement and tint as `Locals`, for a figure drawn as one of many instances.
+    pub fn new(model_mat: Mat4<f32>, col: Rgba<f32>) -> Self {
+        let mat_arr = model_mat.into_col_arrays();
+        Self {
+            inst_mat0: mat_arr[0],
+            inst_mat1: mat_arr[1],
+            inst_mat2: mat_arr[2],
+            inst_mat3: mat_arr[3],
+            inst_col: col.into_array(),
+            inst_bone_base: 0,
+        }
+    }
+
+    /// Pose the instance with the bones starting at `bone_base` in the batch's `u_bones`.
+    pub fn with_bone_base(mut self, bone_base: u32) -> Self {
+        self.inst_bone_base = bone_base;
+        self
+    }
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self::new(Mat4::identity(), Rgba::broadcast(1.0))
+    }
+}
+
 impl BoneData {
     pub fn new(bone_mat: Mat4<f32>) -> Self {
         Self {
@@ -87,8 +159,49 @@ impl BoneData {
     }
 }
 
+/// How many figures with `bone_count` bones each can be drawn by a single instanced draw call.
+pub fn instances_per_batch(bone_count: usize) -> usize {
+    (MAX_BATCH_BONES / bone_count.max(1)).min(MAX_INSTANCES)
+}
+
 pub struct FigurePipeline;
 
 impl Pipeline for FigurePipeline {
     type Vertex = Vertex;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batches_fit_bones() {
+        for bone_count in 1..=MAX_BONES {
+            let instances = instances_per_batch(bone_count);
+            assert!(instances >= 1 && instances <= MAX_INSTANCES);
+            assert!(instances * bone_count <= MAX_BATCH_BONES);
+        }
+        // Small skeletons aren't limited by the space for the largest ones
+        assert_eq!(instances_per_batch(1), MAX_INSTANCES);
+        assert_eq!(instances_per_batch(14), MAX_BATCH_BONES / 14);
+    }
+
+    #[test]
+    fn instance_matches_locals() {
+        let mat = Mat4::translation_3d(Vec3::new(1.0, 2.0, 3.0)) * Mat4::rotation_z(0.5);
+        let col = Rgba::new(0.2, 0.4, 0.6, 1.0);
+        let inst = Instance::new(mat, col);
+        let locals = Locals::new(mat, col);
+
+        assert_eq!(
+            [
+                inst.inst_mat0,
+                inst.inst_mat1,
+                inst.inst_mat2,
+                inst.inst_mat3
+            ],
+            locals.model_mat
+        );
+        assert_eq!(inst.inst_col, locals.model_col);
+    }
+}
@@ -0,0 +1,107 @@
+use super::{
+    super::{Mesh, Pipeline, Quad, TgtColorFmt, TgtDepthFmt},
+    Globals,
+};
+use common::terrain::LodMap;
+use gfx::{
+    self,
+    // Macros
+    gfx_defines,
+    gfx_impl_struct_meta,
+    gfx_pipeline,
+    gfx_pipeline_inner,
+    gfx_vertex_struct_meta,
+};
+use vek::*;
+
+gfx_defines! {
+    vertex Vertex {
+        pos: [f32; 3] = "v_pos",
+        norm: [f32; 3] = "v_norm",
+        col: [f32; 3] = "v_col",
+    }
+
+    pipeline pipe {
+        vbuf: gfx::VertexBuffer<Vertex> = (),
+
+        globals: gfx::ConstantBuffer<Globals> = "u_globals",
+
+        tgt_color: gfx::RenderTarget<TgtColorFmt> = "tgt_color",
+        tgt_depth: gfx::DepthTarget<TgtDepthFmt> = gfx::preset::depth::LESS_EQUAL_WRITE,
+    }
+}
+
+/// A single sheet of terrain covering the whole world, with a vertex at every sample of the LOD
+/// map, in world space.
+pub fn create_mesh(lod: &LodMap) -> Mesh<LodPipeline> {
+    let mut mesh = Mesh::new();
+
+    let size = lod.size();
+    let alt_at = |pos: Vec2<u32>| {
+        lod.get(pos.map2(size, |e, sz| e.min(sz - 1)))
+            .map_or(0.0, |sample| sample.alt as f32)
+    };
+    let vert = |pos: Vec2<u32>| {
+        let sample = lod.get(pos).unwrap();
+        let wpos = lod.wpos(pos).map(|e| e as f32);
+        // The slope of the terrain around the sample, from its neighbours on either side
+        let spacing = lod.spacing() as f32;
+        let dx =
+            alt_at(pos + Vec2::unit_x()) - alt_at(pos.map2(Vec2::unit_x(), |e, d| e.max(d) - d));
+        let dy =
+            alt_at(pos + Vec2::unit_y()) - alt_at(pos.map2(Vec2::unit_y(), |e, d| e.max(d) - d));
+        let norm = Vec3::new(-dx, -dy, spacing * 2.0).normalized();
+
+        Vertex {
+            pos: [wpos.x, wpos.y, sample.alt as f32],
+            norm: norm.into_array(),
+            col: sample.col.map(|e| e as f32 / 255.0).into_array(),
+        }
+    };
+
+    for y in 0..size.y.saturating_sub(1) {
+        for x in 0..size.x.saturating_sub(1) {
+            let pos = Vec2::new(x, y);
+            mesh.push_quad(Quad::new(
+                vert(pos),
+                vert(pos + Vec2::unit_x()),
+                vert(pos + Vec2::one()),
+                vert(pos + Vec2::unit_y()),
+            ));
+        }
+    }
+
+    mesh
+}
+
+/// A coarse heightmap of the distant landscape, drawn beyond the loaded chunks.
+pub struct LodPipeline;
+
+impl Pipeline for LodPipeline {
+    type Vertex = Vertex;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::terrain::LodSample;
+
+    #[test]
+    fn lod_mesh_faces_up() {
+        let lod = LodMap::generate(Vec2::new(4, 3), 128, |pos| LodSample {
+            alt: (pos.x * 20 + pos.y * 5) as u16,
+            col: Rgb::new(0, 255, 0),
+        });
+        let mesh = create_mesh(&lod);
+        assert_eq!(mesh.vertices().len(), 3 * 2 * 6);
+
+        for tri in mesh.vertices().chunks(3) {
+            let [a, b, c] = [tri[0].pos, tri[1].pos, tri[2].pos];
+            let (a, b, c) = (Vec3::from(a), Vec3::from(b), Vec3::from(c));
+            assert!((b - a).cross(c - a).z > 0.0);
+            // The terrain rises along x, so its normals lean back against it
+            let norm = Vec3::from(tri[0].norm);
+            assert!(norm.z > 0.0 && norm.x < 0.0);
+        }
+    }
+}
@@ -1,10 +1,16 @@
 pub mod figure;
 pub mod fluid;
+pub mod lod;
+pub mod outline;
+pub mod particle;
 pub mod postprocess;
+pub mod shadow;
 pub mod skybox;
 pub mod sprite;
 pub mod terrain;
+pub mod trail;
 pub mod ui;
+pub mod weather;
 
 use super::util::arr_to_mat;
 use common::terrain::BlockKind;
@@ -17,6 +23,15 @@ use gfx::{
 };
 use vek::*;
 
+/// The maximum number of lights that the shaders can handle at once.
+pub const MAX_LIGHTS: usize = 32;
+/// How far, in blocks, terrain that stops lights is looked for around them.
+pub const MAX_LIGHT_REACH: f32 = 32.0;
+/// The reach of a light along an axis that nothing stops it along, which leaves it unlimited.
+pub const UNBLOCKED_LIGHT_REACH: f32 = -1.0;
+/// The maximum number of shadow cascades that the shaders can handle at once.
+pub const MAX_SHADOW_CASCADES: usize = 3;
+
 gfx_defines! {
     constant Globals {
         view_mat: [[f32; 4]; 4] = "view_mat",
@@ -30,11 +45,41 @@ gfx_defines! {
         screen_res: [f32; 4] = "screen_res",
         light_count: [u32; 4] = "light_count",
         medium: [u32; 4] = "medium",
+        fog_params: [f32; 4] = "fog_params",
+        wind: [f32; 4] = "wind",
     }
 
     constant Light {
         pos: [f32; 4] = "light_pos",
         col: [f32; 4] = "light_col",
+        // How far the light reaches along each axis before terrain stops it
+        reach_pos: [f32; 4] = "light_reach_pos",
+        reach_neg: [f32; 4] = "light_reach_neg",
+    }
+
+    constant Shadow {
+        shadow_mat: [[f32; 4]; 4] = "shadow_mat",
+        split: [f32; 4] = "shadow_split",
+    }
+}
+
+/// How hazy the air is. Fog takes on the color of the sky, so changes with the time of day.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fog {
+    /// How much of the view is lost to fog per block looked through, at `base_height`.
+    pub density: f32,
+    /// How quickly the fog thins out above `base_height`, and thickens below it.
+    pub height_falloff: f32,
+    pub base_height: f32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            density: 0.003,
+            height_falloff: 0.02,
+            base_height: 0.0,
+        }
     }
 }
 
@@ -51,6 +96,8 @@ impl Globals {
         screen_res: Vec2<u16>,
         light_count: usize,
         medium: BlockKind,
+        fog: Fog,
+        wind: Vec2<f32>,
     ) -> Self {
         Self {
             view_mat: arr_to_mat(view_mat.into_col_array()),
@@ -63,6 +110,8 @@ impl Globals {
             screen_res: Vec4::from(screen_res.map(|e| e as f32)).into_array(),
             light_count: [light_count as u32; 4],
             medium: [if medium.is_fluid() { 1 } else { 0 }; 4],
+            fog_params: [fog.density, fog.height_falloff, fog.base_height, 0.0],
+            wind: [wind.x, wind.y, 0.0, 0.0],
         }
     }
 }
@@ -80,6 +129,8 @@ impl Default for Globals {
             Vec2::new(800, 500),
             0,
             BlockKind::Air,
+            Fog::default(),
+            Vec2::zero(),
         )
     }
 }
@@ -89,8 +140,19 @@ impl Light {
         Self {
             pos: Vec4::from(pos).into_array(),
             col: Rgba::new(col.r, col.g, col.b, strength).into_array(),
+            reach_pos: [UNBLOCKED_LIGHT_REACH; 4],
+            reach_neg: [UNBLOCKED_LIGHT_REACH; 4],
         }
     }
+
+    /// Stop the light at terrain, given how far it reaches along the positive and negative x, y
+    /// and z axes (or `UNBLOCKED_LIGHT_REACH` where nothing stops it). Surfaces further away than
+    /// that in their direction from the light stay dark.
+    pub fn with_reach(mut self, reach_pos: Vec3<f32>, reach_neg: Vec3<f32>) -> Self {
+        self.reach_pos = Vec4::from(reach_pos).into_array();
+        self.reach_neg = Vec4::from(reach_neg).into_array();
+        self
+    }
 }
 
 impl Default for Light {
@@ -98,3 +160,94 @@ impl Default for Light {
         Self::new(Vec3::zero(), Rgb::zero(), 0.0)
     }
 }
+
+impl Shadow {
+    /// A shadow cascade covering everything closer to the camera than `split`, that takes world
+    /// space to the cascade's layer of the shadow map with `shadow_mat`.
+    pub fn new(shadow_mat: Mat4<f32>, split: f32) -> Self {
+        Self {
+            shadow_mat: arr_to_mat(shadow_mat.into_col_array()),
+            split: [split; 4],
+        }
+    }
+}
+
+impl Default for Shadow {
+    /// An unused cascade, which covers nothing.
+    fn default() -> Self {
+        Self::new(Mat4::identity(), 0.0)
+    }
+}
+
+/// Pack a list of lights into the fixed-size array expected by the `u_lights` constant buffer,
+/// padding unused slots with empty lights. Returns the array and the number of active lights.
+///
+/// Lights beyond `MAX_LIGHTS` are dropped, so the list should already be sorted by priority
+/// (e.g: nearest first).
+pub fn pack_lights(lights: &[Light]) -> ([Light; MAX_LIGHTS], u32) {
+    let mut packed = [Light::default(); MAX_LIGHTS];
+    let count = lights.len().min(MAX_LIGHTS);
+    packed[..count].copy_from_slice(&lights[..count]);
+    (packed, count as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_lights_pads_and_truncates() {
+        let light = |x| Light::new(Vec3::new(x, 0.0, 0.0), Rgb::one(), 1.0);
+
+        let (packed, count) = pack_lights(&[light(1.0), light(2.0)]);
+        assert_eq!(count, 2);
+        assert_eq!(packed[1], light(2.0));
+        assert_eq!(packed[2], Light::default());
+
+        // Already nearest first, as the scene hands them over
+        let lights = (0..MAX_LIGHTS + 8)
+            .map(|i| light(i as f32))
+            .collect::<Vec<_>>();
+        let (packed, count) = pack_lights(&lights);
+        assert_eq!(count as usize, MAX_LIGHTS);
+        assert_eq!(&packed[..], &lights[..MAX_LIGHTS]);
+    }
+
+    #[test]
+    fn globals_fog() {
+        let fog = Fog {
+            density: 0.01,
+            height_falloff: 0.5,
+            base_height: 140.0,
+        };
+        let globals = Globals::new(
+            Mat4::identity(),
+            Mat4::identity(),
+            Vec3::zero(),
+            Vec3::zero(),
+            0.0,
+            0.0,
+            0.0,
+            Vec2::new(800, 500),
+            0,
+            BlockKind::Air,
+            fog,
+            Vec2::zero(),
+        );
+        assert_eq!(globals.fog_params, [0.01, 0.5, 140.0, 0.0]);
+    }
+
+    #[test]
+    fn light_reach() {
+        // Nothing stops lights until told otherwise
+        let light = Light::new(Vec3::zero(), Rgb::one(), 1.0);
+        assert_eq!(light.reach_pos[0], UNBLOCKED_LIGHT_REACH);
+        assert_eq!(light.reach_neg[2], UNBLOCKED_LIGHT_REACH);
+
+        let light = light.with_reach(Vec3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 5.0, 6.0));
+        assert_eq!(&light.reach_pos[..3], &[1.0, 2.0, 3.0]);
+        assert_eq!(&light.reach_neg[..3], &[4.0, 5.0, 6.0]);
+        // Where the light is and how bright it is don't change
+        assert_eq!(light.pos, Light::new(Vec3::zero(), Rgb::one(), 1.0).pos);
+    }
+}
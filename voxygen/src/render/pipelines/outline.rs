@@ -0,0 +1,144 @@
+use super::{
+    super::{util::arr_to_mat, Mesh, Pipeline, Quad, TgtColorFmt, TgtDepthFmt},
+    figure, Globals,
+};
+use gfx::{
+    self,
+    gfx_constant_struct_meta,
+    // Macros
+    gfx_defines,
+    gfx_impl_struct_meta,
+    gfx_pipeline,
+    gfx_pipeline_inner,
+    gfx_vertex_struct_meta,
+    state::ColorMask,
+};
+use vek::*;
+
+gfx_defines! {
+    vertex Vertex {
+        pos: [f32; 3] = "v_pos",
+    }
+
+    constant Locals {
+        outline_mat: [[f32; 4]; 4] = "outline_mat",
+        outline_col: [f32; 4] = "outline_col",
+        // x: how far, in blocks, figure outlines extend beyond their surface
+        outline_params: [f32; 4] = "outline_params",
+    }
+
+    // Figures are drawn again with their surface pushed outwards and only their back faces
+    // left, so that just a rim of them shows around the figure itself.
+    pipeline figure_pipe {
+        vbuf: gfx::VertexBuffer<figure::Vertex> = (),
+
+        locals: gfx::ConstantBuffer<figure::Locals> = "u_locals",
+        bones: gfx::ConstantBuffer<figure::BoneData> = "u_bones",
+        outline_locals: gfx::ConstantBuffer<Locals> = "u_outline",
+        globals: gfx::ConstantBuffer<Globals> = "u_globals",
+
+        tgt_color: gfx::BlendTarget<TgtColorFmt> = ("tgt_color", ColorMask::all(), gfx::preset::blend::ALPHA),
+        tgt_depth: gfx::DepthTarget<TgtDepthFmt> = gfx::preset::depth::LESS_EQUAL_TEST,
+    }
+
+    pipeline block_pipe {
+        vbuf: gfx::VertexBuffer<Vertex> = (),
+
+        outline_locals: gfx::ConstantBuffer<Locals> = "u_outline",
+        globals: gfx::ConstantBuffer<Globals> = "u_globals",
+
+        tgt_color: gfx::BlendTarget<TgtColorFmt> = ("tgt_color", ColorMask::all(), gfx::preset::blend::ALPHA),
+        tgt_depth: gfx::DepthTarget<TgtDepthFmt> = gfx::preset::depth::LESS_EQUAL_TEST,
+    }
+}
+
+impl Locals {
+    pub fn new(outline_mat: Mat4<f32>, col: Rgba<f32>, width: f32) -> Self {
+        Self {
+            outline_mat: arr_to_mat(outline_mat.into_col_array()),
+            outline_col: col.into_array(),
+            outline_params: [width, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+impl Default for Locals {
+    fn default() -> Self {
+        Self::new(Mat4::identity(), Rgba::broadcast(1.0), 0.0)
+    }
+}
+
+/// Highlights drawn around whatever the player is targeting.
+pub struct OutlinePipeline;
+
+impl Pipeline for OutlinePipeline {
+    type Vertex = Vertex;
+}
+
+/// A wireframe of the unit cube, with each of its edges a bar `thickness` blocks across.
+pub fn create_block_mesh(thickness: f32) -> Mesh<OutlinePipeline> {
+    let mut mesh = Mesh::new();
+
+    let half = thickness / 2.0;
+    for axis in 0..3 {
+        for corner in 0..4 {
+            let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+            let mut min = Vec3::zero();
+            let mut max = Vec3::zero();
+            min[axis] = -half;
+            max[axis] = 1.0 + half;
+            min[u] = (corner & 1) as f32 - half;
+            max[u] = (corner & 1) as f32 + half;
+            min[v] = (corner >> 1) as f32 - half;
+            max[v] = (corner >> 1) as f32 + half;
+
+            push_box(&mut mesh, min, max);
+        }
+    }
+
+    mesh
+}
+
+#[rustfmt::skip]
+fn push_box(mesh: &mut Mesh<OutlinePipeline>, min: Vec3<f32>, max: Vec3<f32>) {
+    let v = |x: f32, y: f32, z: f32| Vertex { pos: [x, y, z] };
+    let (a, b) = (min, max);
+
+    // -x
+    mesh.push_quad(Quad::new(v(a.x, a.y, a.z), v(a.x, b.y, a.z), v(a.x, b.y, b.z), v(a.x, a.y, b.z)));
+    // +x
+    mesh.push_quad(Quad::new(v(b.x, a.y, b.z), v(b.x, b.y, b.z), v(b.x, b.y, a.z), v(b.x, a.y, a.z)));
+    // -y
+    mesh.push_quad(Quad::new(v(b.x, a.y, a.z), v(a.x, a.y, a.z), v(a.x, a.y, b.z), v(b.x, a.y, b.z)));
+    // +y
+    mesh.push_quad(Quad::new(v(b.x, b.y, b.z), v(a.x, b.y, b.z), v(a.x, b.y, a.z), v(b.x, b.y, a.z)));
+    // -z
+    mesh.push_quad(Quad::new(v(a.x, a.y, a.z), v(b.x, a.y, a.z), v(b.x, b.y, a.z), v(a.x, b.y, a.z)));
+    // +z
+    mesh.push_quad(Quad::new(v(a.x, b.y, b.z), v(b.x, b.y, b.z), v(b.x, a.y, b.z), v(a.x, a.y, b.z)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_mesh_traces_every_edge() {
+        let mesh = create_block_mesh(0.1);
+        // 12 edges, each a box of 6 quads
+        assert_eq!(mesh.vertices().len(), 12 * 6 * 6);
+        assert!(mesh
+            .vertices()
+            .iter()
+            .all(|v| v.pos.iter().all(|e| *e > -0.06 && *e < 1.06)));
+
+        // Every corner of the cube is covered by some edge
+        for corner in 0..8 {
+            let pos = Vec3::new(corner & 1, (corner >> 1) & 1, corner >> 2).map(|e| e as f32);
+            assert!(mesh
+                .vertices()
+                .iter()
+                .any(|v| Vec3::from(v.pos).distance(pos) < 0.1));
+        }
+    }
+}
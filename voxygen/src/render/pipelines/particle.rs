@@ -0,0 +1,164 @@
+use super::{
+    super::{Mesh, Pipeline, Quad, TgtColorFmt, TgtDepthFmt},
+    Globals, Light,
+};
+use gfx::{
+    self,
+    // Macros
+    gfx_defines,
+    gfx_impl_struct_meta,
+    gfx_pipeline,
+    gfx_pipeline_inner,
+    gfx_vertex_struct_meta,
+    state::ColorMask,
+};
+use vek::*;
+
+gfx_defines! {
+    vertex Vertex {
+        pos: [f32; 3] = "v_pos",
+        norm: [f32; 3] = "v_norm",
+    }
+
+    vertex Instance {
+        inst_pos: [f32; 3] = "inst_pos",
+        inst_vel: [f32; 3] = "inst_vel",
+        inst_col: [f32; 4] = "inst_col",
+        inst_time: f32 = "inst_time",
+        inst_lifespan: f32 = "inst_lifespan",
+        inst_size: f32 = "inst_size",
+        inst_gravity: f32 = "inst_gravity",
+    }
+
+    pipeline pipe {
+        vbuf: gfx::VertexBuffer<Vertex> = (),
+        ibuf: gfx::InstanceBuffer<Instance> = (),
+
+        globals: gfx::ConstantBuffer<Globals> = "u_globals",
+        lights: gfx::ConstantBuffer<Light> = "u_lights",
+
+        tgt_color: gfx::BlendTarget<TgtColorFmt> = ("tgt_color", ColorMask::all(), gfx::preset::blend::ALPHA),
+        tgt_depth: gfx::DepthTarget<TgtDepthFmt> = gfx::preset::depth::LESS_EQUAL_TEST,
+    }
+}
+
+impl Instance {
+    /// A particle spawned at `pos` at the given time (as in `Globals::tick`), moving at `vel` and
+    /// pulled down by `gravity` until it fades away after `lifespan` seconds. Everything after
+    /// that is worked out on the GPU, so particles never need updating once they're spawned.
+    pub fn new(
+        pos: Vec3<f32>,
+        vel: Vec3<f32>,
+        col: Rgba<f32>,
+        time: f64,
+        lifespan: f32,
+        size: f32,
+        gravity: f32,
+    ) -> Self {
+        Self {
+            inst_pos: pos.into_array(),
+            inst_vel: vel.into_array(),
+            inst_col: col.into_array(),
+            inst_time: time as f32,
+            inst_lifespan: lifespan,
+            inst_size: size,
+            inst_gravity: gravity,
+        }
+    }
+
+    /// Where the particle is `age` seconds after it was spawned.
+    pub fn pos_at(&self, age: f32) -> Vec3<f32> {
+        Vec3::from(self.inst_pos) + Vec3::from(self.inst_vel) * age
+            - Vec3::unit_z() * 0.5 * self.inst_gravity * age * age
+    }
+
+    /// Whether the particle has faded away by the given time.
+    pub fn is_expired(&self, time: f64) -> bool {
+        time as f32 - self.inst_time > self.inst_lifespan
+    }
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self::new(Vec3::zero(), Vec3::zero(), Rgba::zero(), 0.0, 0.0, 0.0, 0.0)
+    }
+}
+
+/// The shape every particle is drawn with: a unit cube centred on the origin, with its faces
+/// pointing outwards.
+pub fn create_mesh() -> Mesh<ParticlePipeline> {
+    let mut mesh = Mesh::new();
+
+    let normals = [
+        -Vec3::unit_x(),
+        Vec3::unit_x(),
+        -Vec3::unit_y(),
+        Vec3::unit_y(),
+        -Vec3::unit_z(),
+        Vec3::unit_z(),
+    ];
+    for &norm in normals.iter() {
+        // Two edges of the face, chosen so that their cross product points along the normal
+        let u = Vec3::new(norm.z, norm.x, norm.y);
+        let v = Vec3::new(norm.y, norm.z, norm.x).map(|e: f32| e.abs());
+        let vert = |pos: Vec3<f32>| Vertex {
+            pos: (pos * 0.5).into_array(),
+            norm: norm.into_array(),
+        };
+
+        mesh.push_quad(Quad::new(
+            vert(norm - u - v),
+            vert(norm + u - v),
+            vert(norm + u + v),
+            vert(norm - u + v),
+        ));
+    }
+
+    mesh
+}
+
+/// Small, short-lived, GPU-animated cubes (e.g: sparks, dust, leaves, blood).
+pub struct ParticlePipeline;
+
+impl Pipeline for ParticlePipeline {
+    type Vertex = Vertex;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cube_faces_outwards() {
+        let mesh = create_mesh();
+        assert_eq!(mesh.vertices().len(), 36);
+
+        for tri in mesh.vertices().chunks(3) {
+            let [a, b, c] = [tri[0].pos, tri[1].pos, tri[2].pos];
+            let (a, b, c) = (Vec3::from(a), Vec3::from(b), Vec3::from(c));
+            let norm = Vec3::from(tri[0].norm);
+            // Counter-clockwise winding when seen from outside, facing away from the centre
+            assert!((b - a).cross(c - a).dot(norm) > 0.0);
+            assert!(a.dot(norm) > 0.0);
+        }
+    }
+
+    #[test]
+    fn particle_motion() {
+        let particle = Instance::new(
+            Vec3::zero(),
+            Vec3::new(1.0, 0.0, 2.0),
+            Rgba::one(),
+            10.0,
+            1.5,
+            0.2,
+            4.0,
+        );
+        assert_eq!(particle.pos_at(0.0), Vec3::zero());
+        // Thrown up, then pulled back down
+        assert_eq!(particle.pos_at(1.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(!particle.is_expired(11.0));
+        assert!(particle.is_expired(12.0));
+    }
+}
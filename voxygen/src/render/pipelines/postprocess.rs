@@ -1,5 +1,5 @@
 use super::{
-    super::{Mesh, Pipeline, Tri, WinColorFmt, WinDepthFmt},
+    super::{Mesh, Pipeline, TgtColorFmt, Tri, WinColorFmt, WinDepthFmt},
     Globals,
 };
 use gfx::{
@@ -12,6 +12,7 @@ use gfx::{
     gfx_pipeline_inner,
     gfx_vertex_struct_meta,
 };
+use serde_derive::{Deserialize, Serialize};
 
 gfx_defines! {
     vertex Vertex {
@@ -19,7 +20,8 @@ gfx_defines! {
     }
 
     constant Locals {
-        nul: [f32; 4] = "nul",
+        // x = gamma, y = bloom (0 or 1)
+        post_params: [f32; 4] = "post_params",
     }
 
     pipeline pipe {
@@ -28,16 +30,72 @@ gfx_defines! {
         locals: gfx::ConstantBuffer<Locals> = "u_locals",
         globals: gfx::ConstantBuffer<Globals> = "u_globals",
 
-        src_sampler: gfx::TextureSampler<<WinColorFmt as gfx::format::Formatted>::View> = "src_color",
+        src_sampler: gfx::TextureSampler<<TgtColorFmt as gfx::format::Formatted>::View> = "src_color",
 
         tgt_color: gfx::RenderTarget<WinColorFmt> = "tgt_color",
         tgt_depth: gfx::DepthTarget<WinDepthFmt> = gfx::preset::depth::PASS_TEST,
     }
+
+    pipeline resolve_pipe {
+        vbuf: gfx::VertexBuffer<Vertex> = (),
+
+        src_sampler: gfx::TextureSampler<<TgtColorFmt as gfx::format::Formatted>::View> = "src_color",
+
+        tgt_color: gfx::RenderTarget<TgtColorFmt> = "tgt_color",
+    }
+}
+
+/// How the jagged edges of geometry are smoothed out.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AaMode {
+    None,
+    /// Blur edges found in the final image during post-processing. Cheap, but softens the image.
+    Fxaa,
+    /// Render the scene with several samples per pixel, and average them.
+    MsaaX4,
+    MsaaX8,
+    MsaaX16,
+}
+
+impl AaMode {
+    /// How many samples are taken for each pixel of the scene.
+    pub fn samples(&self) -> u8 {
+        match self {
+            AaMode::None | AaMode::Fxaa => 1,
+            AaMode::MsaaX4 => 4,
+            AaMode::MsaaX8 => 8,
+            AaMode::MsaaX16 => 16,
+        }
+    }
+
+    /// The definitions that tell the shaders which anti-aliasing to apply, as
+    /// `anti-aliasing.glsl`.
+    pub fn shader_defines(&self) -> String {
+        format!(
+            "#define FXAA {}\n#define MSAA_SAMPLES {}\n",
+            if *self == AaMode::Fxaa { 1 } else { 0 },
+            self.samples()
+        )
+    }
+}
+
+impl Default for AaMode {
+    fn default() -> Self {
+        AaMode::Fxaa
+    }
 }
 
 impl Locals {
+    /// Post-processing that brightens or darkens the image by `gamma` (where 1.0 leaves it as it
+    /// is), and optionally lets bright lights bloom into their surroundings.
+    pub fn new(gamma: f32, bloom: bool) -> Self {
+        Self {
+            post_params: [gamma, if bloom { 1.0 } else { 0.0 }, 0.0, 0.0],
+        }
+    }
+
     pub fn default() -> Self {
-        Self { nul: [0.0; 4] }
+        Self::new(1.0, true)
     }
 }
 
@@ -66,3 +124,19 @@ pub fn create_mesh() -> Mesh<PostProcessPipeline> {
 
     mesh
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aa_mode_defines() {
+        assert_eq!(AaMode::None.samples(), 1);
+        assert_eq!(AaMode::MsaaX8.samples(), 8);
+        assert!(AaMode::Fxaa.shader_defines().contains("#define FXAA 1"));
+        assert!(AaMode::MsaaX4.shader_defines().contains("#define FXAA 0"));
+        assert!(AaMode::MsaaX4
+            .shader_defines()
+            .contains("#define MSAA_SAMPLES 4"));
+    }
+}
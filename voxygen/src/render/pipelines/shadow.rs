@@ -0,0 +1,160 @@
+use super::{
+    super::{util::arr_to_mat, ShadowDepthFmt, TerrainLocals},
+    figure, terrain, Globals,
+};
+use gfx::{
+    self,
+    gfx_constant_struct_meta,
+    // Macros
+    gfx_defines,
+    gfx_impl_struct_meta,
+    gfx_pipeline,
+    gfx_pipeline_inner,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use vek::*;
+
+gfx_defines! {
+    constant Locals {
+        shadow_mat: [[f32; 4]; 4] = "shadow_mat",
+    }
+
+    // Depth-only passes, rendering terrain and figures from the sun into a layer of the shadow
+    // map.
+    pipeline terrain_pipe {
+        vbuf: gfx::VertexBuffer<terrain::Vertex> = (),
+
+        locals: gfx::ConstantBuffer<TerrainLocals> = "u_locals",
+        globals: gfx::ConstantBuffer<Globals> = "u_globals",
+        shadow_locals: gfx::ConstantBuffer<Locals> = "u_shadow_locals",
+
+        tgt_depth: gfx::DepthTarget<ShadowDepthFmt> = gfx::preset::depth::LESS_EQUAL_WRITE,
+    }
+
+    pipeline figure_pipe {
+        vbuf: gfx::VertexBuffer<figure::Vertex> = (),
+
+        locals: gfx::ConstantBuffer<figure::Locals> = "u_locals",
+        bones: gfx::ConstantBuffer<figure::BoneData> = "u_bones",
+        shadow_locals: gfx::ConstantBuffer<Locals> = "u_shadow_locals",
+
+        tgt_depth: gfx::DepthTarget<ShadowDepthFmt> = gfx::preset::depth::LESS_EQUAL_WRITE,
+    }
+}
+
+impl Locals {
+    pub fn new(shadow_mat: Mat4<f32>) -> Self {
+        Self {
+            shadow_mat: arr_to_mat(shadow_mat.into_col_array()),
+        }
+    }
+}
+
+impl Default for Locals {
+    fn default() -> Self {
+        Self::new(Mat4::identity())
+    }
+}
+
+/// How detailed shadows are, trading the resolution of the shadow map and the number of cascades
+/// it's split into for speed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShadowQuality {
+    Off,
+    Low,
+    Medium,
+    High,
+}
+
+impl ShadowQuality {
+    /// The width and height, in texels, of each cascade of the shadow map.
+    pub fn resolution(&self) -> u16 {
+        match self {
+            ShadowQuality::Off => 1,
+            ShadowQuality::Low => 1024,
+            ShadowQuality::Medium => 2048,
+            ShadowQuality::High => 4096,
+        }
+    }
+
+    /// How far, in blocks, from the camera each cascade reaches, nearest first. Nearer cascades
+    /// cover less ground with the same resolution, so are sharper.
+    pub fn cascade_splits(&self) -> &'static [f32] {
+        match self {
+            ShadowQuality::Off => &[],
+            ShadowQuality::Low => &[48.0],
+            ShadowQuality::Medium => &[24.0, 96.0],
+            ShadowQuality::High => &[16.0, 64.0, 192.0],
+        }
+    }
+
+    /// The number of layers the shadow map needs. Even without shadows, there's always one to
+    /// bind.
+    pub fn cascades(&self) -> usize {
+        self.cascade_splits().len().max(1)
+    }
+}
+
+impl Default for ShadowQuality {
+    fn default() -> Self {
+        ShadowQuality::Medium
+    }
+}
+
+/// The direction sunlight travels in at the given time of day. This must match `get_sun_dir` in
+/// the shaders.
+pub fn sun_dir(time_of_day: f64) -> Vec3<f32> {
+    const TIME_FACTOR: f64 = (PI * 2.0) / (3600.0 * 24.0);
+
+    let sun_angle_rad = (time_of_day * TIME_FACTOR) as f32;
+    Vec3::new(sun_angle_rad.sin(), 0.0, sun_angle_rad.cos())
+}
+
+/// How far towards the sun from the edge of a cascade things can be and still cast shadows into it.
+const SHADOW_CASTER_REACH: f32 = 256.0;
+
+/// The matrix taking world space to a shadow cascade, looking along `sun_dir` at everything
+/// within `radius` blocks of `center`.
+pub fn cascade_mat(sun_dir: Vec3<f32>, center: Vec3<f32>, radius: f32) -> Mat4<f32> {
+    // Back far enough away that anything between the sun and the cascade can cast shadows into it
+    let back = radius + SHADOW_CASTER_REACH;
+    let eye = center - sun_dir * back;
+    // Sunlight never travels along the y axis, so it can always be used as up
+    let view_mat = Mat4::look_at_rh(eye, center, Vec3::unit_y());
+    let proj_mat = Mat4::orthographic_rh_no(FrustumPlanes {
+        left: -radius,
+        right: radius,
+        bottom: -radius,
+        top: radius,
+        near: 0.0,
+        far: back + radius * 2.0,
+    });
+
+    proj_mat * view_mat
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cascade_projection() {
+        let center = Vec3::new(100.0, 200.0, 50.0);
+        // Mid-afternoon, with the sun sinking towards the horizon
+        let sun = sun_dir(3600.0 * 15.0);
+        let mat = cascade_mat(sun, center, 32.0);
+        let project = |pos: Vec3<f32>| {
+            let pos = mat * Vec4::from_point(pos);
+            Vec3::from(pos) / pos.w
+        };
+
+        // The cascade is centred on its center, and whatever's closer to the sun is in front
+        assert!(Vec2::from(project(center)).magnitude() < 0.001);
+        assert!(project(center - sun * 8.0).z < project(center).z);
+        // Tall things well outside the cascade, towards the sun, still fall within its depth range
+        assert!(project(center - sun * (32.0 + SHADOW_CASTER_REACH * 0.9)).z > -1.0);
+        // The edge of the cascade is at the edge of the map
+        assert!(project(center + Vec3::unit_y() * 32.0).y.abs() > 0.999);
+    }
+}
@@ -2,6 +2,8 @@ use super::{
     super::{util::arr_to_mat, Pipeline, TgtColorFmt, TgtDepthFmt},
     Globals, Light,
 };
+use crate::scene::camera::aabb_in_frustum;
+use common::util::{linear_to_srgb, srgb_to_linear};
 use gfx::{
     self,
     // Macros
@@ -19,6 +21,7 @@ gfx_defines! {
         pos: [f32; 3] = "v_pos",
         norm: [f32; 3] = "v_norm",
         col: [f32; 3] = "v_col",
+        uv: [f32; 2] = "v_uv",
     }
 
     vertex Instance {
@@ -28,6 +31,9 @@ gfx_defines! {
         inst_mat3: [f32; 4] = "inst_mat3",
         inst_col: [f32; 3] = "inst_col",
         inst_wind_sway: f32 = "inst_wind_sway",
+        inst_wind_phase: f32 = "inst_wind_phase",
+        inst_wind_twist: f32 = "inst_wind_twist",
+        inst_fade: f32 = "inst_fade",
     }
 
     pipeline pipe {
@@ -37,23 +43,56 @@ gfx_defines! {
         globals: gfx::ConstantBuffer<Globals> = "u_globals",
         lights: gfx::ConstantBuffer<Light> = "u_lights",
 
+        tex: gfx::TextureSampler<[f32; 4]> = "u_tex",
+
         tgt_color: gfx::BlendTarget<TgtColorFmt> = ("tgt_color", ColorMask::all(), gfx::preset::blend::ALPHA),
         tgt_depth: gfx::DepthTarget<TgtDepthFmt> = gfx::preset::depth::LESS_EQUAL_WRITE,
     }
 }
 
 impl Vertex {
-    pub fn new(pos: Vec3<f32>, norm: Vec3<f32>, col: Rgb<f32>) -> Self {
+    /// `uv` is the texture coordinate of the vertex. Untextured sprites should use `(0, 0)` with a
+    /// plain white texture, so that they're colored by `col` alone.
+    pub fn new(pos: Vec3<f32>, norm: Vec3<f32>, col: Rgb<f32>, uv: Vec2<f32>) -> Self {
         Self {
             pos: pos.into_array(),
             col: col.into_array(),
             norm: norm.into_array(),
+            uv: uv.into_array(),
         }
     }
 }
 
 impl Instance {
-    pub fn new(mat: Mat4<f32>, col: Rgb<f32>, wind_sway: f32) -> Self {
+    /// `wind_phase` offsets the sway of this instance so that neighbouring sprites don't move in
+    /// unison. `fade` is multiplied into the output alpha, where 0.0 is invisible and 1.0 is
+    /// opaque.
+    pub fn new(mat: Mat4<f32>, col: Rgb<f32>, wind_sway: f32, wind_phase: f32, fade: f32) -> Self {
+        Self::new_fluttering(mat, col, wind_sway, wind_phase, 0.0, fade)
+    }
+
+    /// Like `new`, but with the instance color darkened by a baked ambient occlusion factor
+    /// (e.g: for sprites in the shade of a tree).
+    pub fn new_shaded(
+        mat: Mat4<f32>,
+        col: Rgb<f32>,
+        wind_sway: f32,
+        wind_phase: f32,
+        ao: f32,
+        fade: f32,
+    ) -> Self {
+        Self::new(mat, apply_instance_ao(col, ao), wind_sway, wind_phase, fade)
+    }
+
+    /// Like `new`, but the sprite also twists about its up axis in the wind.
+    pub fn new_fluttering(
+        mat: Mat4<f32>,
+        col: Rgb<f32>,
+        wind_sway: f32,
+        wind_phase: f32,
+        wind_twist: f32,
+        fade: f32,
+    ) -> Self {
         let mat_arr = arr_to_mat(mat.into_col_array());
         Self {
             inst_mat0: mat_arr[0],
@@ -62,13 +101,58 @@ impl Instance {
             inst_mat3: mat_arr[3],
             inst_col: col.into_array(),
             inst_wind_sway: wind_sway,
+            inst_wind_phase: wind_phase,
+            inst_wind_twist: wind_twist,
+            inst_fade: fade,
         }
     }
+
+    /// Where the instance is placed in the world.
+    pub fn pos(&self) -> Vec3<f32> {
+        Vec3::new(self.inst_mat3[0], self.inst_mat3[1], self.inst_mat3[2])
+    }
+
+    pub fn set_fade(&mut self, fade: f32) {
+        self.inst_fade = fade;
+    }
+}
+
+/// Darken an instance color by an ambient occlusion factor in linear space, where 1.0 is fully
+/// lit and 0.0 is fully occluded.
+pub fn apply_instance_ao(base_col: Rgb<f32>, ao: f32) -> Rgb<f32> {
+    linear_to_srgb(srgb_to_linear(base_col) * ao.min(1.0).max(0.0))
+}
+
+/// Filter sprite instances down to those that are both within `max_dist` of the camera and
+/// intersect the view frustum, treating each instance as a cube reaching `radius` from its
+/// position.
+pub fn cull_instances(
+    instances: &[Instance],
+    view_proj: Mat4<f32>,
+    cam_pos: Vec3<f32>,
+    max_dist: f32,
+    radius: f32,
+) -> Vec<Instance> {
+    instances
+        .iter()
+        .filter(|inst| {
+            let pos = inst.pos();
+            pos.distance_squared(cam_pos) < (max_dist + radius).powf(2.0)
+                && aabb_in_frustum(
+                    view_proj,
+                    Aabb {
+                        min: pos - radius,
+                        max: pos + radius,
+                    },
+                )
+        })
+        .cloned()
+        .collect()
 }
 
 impl Default for Instance {
     fn default() -> Self {
-        Self::new(Mat4::identity(), Rgb::broadcast(1.0), 0.0)
+        Self::new(Mat4::identity(), Rgb::broadcast(1.0), 0.0, 0.0, 1.0)
     }
 }
 
@@ -77,3 +161,74 @@ pub struct SpritePipeline;
 impl Pipeline for SpritePipeline {
     type Vertex = Vertex;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vertex_uv() {
+        let vert = Vertex::new(
+            Vec3::zero(),
+            Vec3::unit_z(),
+            Rgb::one(),
+            Vec2::new(0.25, 0.75),
+        );
+        assert_eq!(vert.uv, [0.25, 0.75]);
+    }
+
+    #[test]
+    fn wind_twist() {
+        let fluttering = Instance::new_fluttering(Mat4::identity(), Rgb::one(), 0.1, 0.0, 0.3, 1.0);
+        assert_eq!(fluttering.inst_wind_twist, 0.3);
+        assert_eq!(Instance::default().inst_wind_twist, 0.0);
+    }
+
+    #[test]
+    fn wind_phase() {
+        let phased = Instance::new(Mat4::identity(), Rgb::one(), 1.0, 2.5, 1.0);
+        assert_eq!(phased.inst_wind_phase, 2.5);
+        assert_eq!(Instance::default().inst_wind_phase, 0.0);
+    }
+
+    #[test]
+    fn fade() {
+        let faded = Instance::new(Mat4::identity(), Rgb::one(), 0.0, 0.0, 0.4);
+        assert_eq!(faded.inst_fade, 0.4);
+        assert_eq!(Instance::default().inst_fade, 1.0);
+    }
+
+    #[test]
+    fn cull() {
+        use std::f32::consts::PI;
+
+        // Camera at the origin looking down -z
+        let view_proj = Mat4::perspective_rh_no(PI / 2.0, 1.0, 0.1, 1000.0);
+        let at =
+            |pos: Vec3<f32>| Instance::new(Mat4::translation_3d(pos), Rgb::one(), 0.0, 0.0, 1.0);
+        let visible = at(Vec3::new(0.0, 0.0, -10.0));
+        let instances = [
+            visible,
+            // Behind the camera
+            at(Vec3::new(0.0, 0.0, 10.0)),
+            // Off to the side
+            at(Vec3::new(100.0, 0.0, -10.0)),
+            // In the frustum, but too far away
+            at(Vec3::new(0.0, 0.0, -50.0)),
+        ];
+
+        let survivors = cull_instances(&instances, view_proj, Vec3::zero(), 20.0, 1.0);
+        assert_eq!(survivors, vec![visible]);
+    }
+
+    #[test]
+    fn instance_ao() {
+        let col = Rgb::new(0.8, 0.5, 0.2);
+        let lit = apply_instance_ao(col, 1.0);
+        assert!((lit - col).map(|e| e.abs()).reduce_partial_max() < 1e-5);
+        assert_eq!(apply_instance_ao(col, 0.0), Rgb::zero());
+
+        let shaded = Instance::new_shaded(Mat4::identity(), col, 0.0, 0.0, 0.5, 1.0);
+        assert!(shaded.inst_col[0] < col.r && shaded.inst_col[0] > 0.0);
+    }
+}
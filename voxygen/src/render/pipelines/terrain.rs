@@ -1,6 +1,6 @@
 use super::{
     super::{Pipeline, TgtColorFmt, TgtDepthFmt},
-    Globals, Light,
+    Globals, Light, Shadow,
 };
 use gfx::{
     self,
@@ -19,6 +19,7 @@ gfx_defines! {
     vertex Vertex {
         pos_norm: u32 = "v_pos_norm",
         col_light: u32 = "v_col_light",
+        sway: u8 = "v_sway",
     }
 
     constant Locals {
@@ -31,6 +32,9 @@ gfx_defines! {
         locals: gfx::ConstantBuffer<Locals> = "u_locals",
         globals: gfx::ConstantBuffer<Globals> = "u_globals",
         lights: gfx::ConstantBuffer<Light> = "u_lights",
+        shadows: gfx::ConstantBuffer<Shadow> = "u_shadows",
+
+        shadow_maps: gfx::TextureSampler<f32> = "t_shadow_maps",
 
         tgt_color: gfx::RenderTarget<TgtColorFmt> = "tgt_color",
         tgt_depth: gfx::DepthTarget<TgtDepthFmt> = gfx::preset::depth::LESS_EQUAL_WRITE,
@@ -58,8 +62,16 @@ impl Vertex {
                 | ((col.g.mul(255.0) as u32) & 0xFF) << 16
                 | ((col.b.mul(255.0) as u32) & 0xFF) << 24
                 | ((light.mul(255.0) as u32) & 0xFF) << 0,
+            sway: 0,
         }
     }
+
+    /// Make the vertex bend with the wind, where 0.0 is rigid and 1.0 sways as much as foliage
+    /// ever does.
+    pub fn with_sway(mut self, sway: f32) -> Self {
+        self.sway = (sway.max(0.0).min(1.0) * 255.0) as u8;
+        self
+    }
 }
 
 impl Locals {
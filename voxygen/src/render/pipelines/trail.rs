@@ -0,0 +1,48 @@
+use super::{
+    super::{Pipeline, TgtColorFmt, TgtDepthFmt},
+    Globals,
+};
+use gfx::{
+    self,
+    // Macros
+    gfx_defines,
+    gfx_impl_struct_meta,
+    gfx_pipeline,
+    gfx_pipeline_inner,
+    gfx_vertex_struct_meta,
+    state::ColorMask,
+};
+use vek::*;
+
+gfx_defines! {
+    vertex Vertex {
+        pos: [f32; 3] = "v_pos",
+        col: [f32; 4] = "v_col",
+    }
+
+    pipeline pipe {
+        vbuf: gfx::VertexBuffer<Vertex> = (),
+
+        globals: gfx::ConstantBuffer<Globals> = "u_globals",
+
+        tgt_color: gfx::BlendTarget<TgtColorFmt> = ("tgt_color", ColorMask::all(), gfx::preset::blend::ALPHA),
+        tgt_depth: gfx::DepthTarget<TgtDepthFmt> = gfx::preset::depth::LESS_EQUAL_TEST,
+    }
+}
+
+impl Vertex {
+    /// A vertex of a trail, in world space.
+    pub fn new(pos: Vec3<f32>, col: Rgba<f32>) -> Self {
+        Self {
+            pos: pos.into_array(),
+            col: col.into_array(),
+        }
+    }
+}
+
+/// Translucent ribbons left behind by things moving quickly (e.g: swung weapons).
+pub struct TrailPipeline;
+
+impl Pipeline for TrailPipeline {
+    type Vertex = Vertex;
+}
@@ -0,0 +1,106 @@
+use super::{
+    super::{Mesh, Pipeline, Quad, TgtColorFmt, TgtDepthFmt},
+    Globals,
+};
+use gfx::{
+    self,
+    gfx_constant_struct_meta,
+    // Macros
+    gfx_defines,
+    gfx_impl_struct_meta,
+    gfx_pipeline,
+    gfx_pipeline_inner,
+    gfx_vertex_struct_meta,
+    state::ColorMask,
+};
+use vek::*;
+
+gfx_defines! {
+    vertex Vertex {
+        // x: across the drop, from -0.5 to 0.5, y: along it, from 0.0 to 1.0
+        pos: [f32; 2] = "v_pos",
+    }
+
+    vertex Instance {
+        // Where the drop falls from within the box around the camera, from 0.0 to 1.0 along each axis
+        inst_pos: [f32; 3] = "inst_pos",
+    }
+
+    constant Locals {
+        weather_col: [f32; 4] = "weather_col",
+        // xyz: the size of the box around the camera that drops fall within, in blocks
+        weather_box: [f32; 4] = "weather_box",
+        // x: how fast drops fall, y: how wide they are, z: how long they are, w: how far they
+        // drift from side to side as they fall
+        weather_drops: [f32; 4] = "weather_drops",
+    }
+
+    pipeline pipe {
+        vbuf: gfx::VertexBuffer<Vertex> = (),
+        ibuf: gfx::InstanceBuffer<Instance> = (),
+
+        locals: gfx::ConstantBuffer<Locals> = "u_locals",
+        globals: gfx::ConstantBuffer<Globals> = "u_globals",
+
+        tgt_color: gfx::BlendTarget<TgtColorFmt> = ("tgt_color", ColorMask::all(), gfx::preset::blend::ALPHA),
+        tgt_depth: gfx::DepthTarget<TgtDepthFmt> = gfx::preset::depth::LESS_EQUAL_TEST,
+    }
+}
+
+impl Instance {
+    pub fn new(pos: Vec3<f32>) -> Self {
+        Self {
+            inst_pos: pos.into_array(),
+        }
+    }
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self::new(Vec3::zero())
+    }
+}
+
+impl Locals {
+    pub fn new(
+        col: Rgba<f32>,
+        box_size: Vec3<f32>,
+        fall_speed: f32,
+        drop_size: Vec2<f32>,
+        drift: f32,
+    ) -> Self {
+        Self {
+            weather_col: col.into_array(),
+            weather_box: Vec4::from(box_size).into_array(),
+            weather_drops: [fall_speed, drop_size.x, drop_size.y, drift],
+        }
+    }
+}
+
+impl Default for Locals {
+    fn default() -> Self {
+        Self::new(Rgba::zero(), Vec3::one(), 0.0, Vec2::zero(), 0.0)
+    }
+}
+
+/// Rain and snow, falling on the GPU through a box that follows the camera around.
+pub struct WeatherPipeline;
+
+impl Pipeline for WeatherPipeline {
+    type Vertex = Vertex;
+}
+
+/// The shape every drop is drawn with, before it's stretched and turned to face the camera.
+pub fn create_mesh() -> Mesh<WeatherPipeline> {
+    let mut mesh = Mesh::new();
+
+    #[rustfmt::skip]
+    mesh.push_quad(Quad::new(
+        Vertex { pos: [-0.5, 0.0] },
+        Vertex { pos: [ 0.5, 0.0] },
+        Vertex { pos: [ 0.5, 1.0] },
+        Vertex { pos: [-0.5, 1.0] },
+    ));
+
+    mesh
+}
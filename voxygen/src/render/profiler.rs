@@ -0,0 +1,308 @@
+use super::{gfx_backend, graph::Pass};
+use gfx_gl as gl;
+use std::{collections::VecDeque, time::Duration};
+
+/// How much of each new frame's timings goes into the averages shown, so that they're steady
+/// enough to read.
+const SMOOTHING: f32 = 0.05;
+/// How many frames of GPU timestamps can be waiting on the GPU at once. Beyond this, the oldest
+/// are given up on.
+const MAX_PENDING_FRAMES: usize = 4;
+
+/// Parts of the frame whose CPU time is worth keeping an eye on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stage {
+    Terrain,
+    Figures,
+    Sprites,
+    Ui,
+    MeshUpload,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 5] = [
+        Stage::Terrain,
+        Stage::Figures,
+        Stage::Sprites,
+        Stage::Ui,
+        Stage::MeshUpload,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Terrain => "Terrain",
+            Stage::Figures => "Figures",
+            Stage::Sprites => "Sprites",
+            Stage::Ui => "UI",
+            Stage::MeshUpload => "Mesh uploads",
+        }
+    }
+}
+
+/// How long recent frames have spent, on average, in each pass on the GPU and in each stage on
+/// the CPU, in milliseconds.
+#[derive(Clone, Debug, Default)]
+pub struct FrameTimings {
+    pub gpu: Vec<(Pass, f32)>,
+    pub cpu: Vec<(Stage, f32)>,
+}
+
+/// Times the stages of each frame on the CPU, and, using timestamp queries, each of its passes
+/// on the GPU. GPU timings arrive a few frames late, since the GPU runs behind the CPU.
+pub struct Profiler {
+    enabled: bool,
+    /// Whether the GPU can record timestamps (OpenGL 3.3 or `GL_ARB_timer_query`). Without them,
+    /// passes are never timed on the GPU.
+    timestamps_supported: bool,
+    /// Whether to time each frame's passes on the GPU even when not profiling, for dynamic
+    /// resolution to judge how hard the GPU is working.
+    gpu_timing: bool,
+
+    cpu: [Duration; 5],
+    /// Time spent in stages nested within the one currently being timed, so that it isn't
+    /// counted twice.
+    nested: Duration,
+
+    /// The timestamps queried at the start of each pass of the current frame, and of the frames
+    /// still waiting on the GPU. Each frame ends with a timestamp that doesn't start a pass.
+    gpu: Vec<(Option<Pass>, gl::types::GLuint)>,
+    pending: VecDeque<Vec<(Option<Pass>, gl::types::GLuint)>>,
+    free_queries: Vec<gl::types::GLuint>,
+
+    timings: FrameTimings,
+    /// How long, in milliseconds, the GPU spent on the passes of the latest frame it's finished,
+    /// if that hasn't been taken yet.
+    gpu_frame_time: Option<f32>,
+}
+
+impl Profiler {
+    pub fn new(timestamps_supported: bool) -> Self {
+        Self {
+            enabled: false,
+            timestamps_supported,
+            gpu_timing: false,
+
+            cpu: [Duration::default(); 5],
+            nested: Duration::default(),
+
+            gpu: Vec::new(),
+            pending: VecDeque::new(),
+            free_queries: Vec::new(),
+
+            timings: FrameTimings::default(),
+            gpu_frame_time: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !self.times_gpu() {
+            self.free_queries
+                .extend(self.gpu.drain(..).map(|(_, query)| query));
+        }
+        self.timings = FrameTimings::default();
+    }
+
+    /// Whether each frame's passes are being timed on the GPU.
+    pub fn times_gpu(&self) -> bool {
+        self.timestamps_supported && (self.enabled || self.gpu_timing)
+    }
+
+    pub fn set_gpu_timing(&mut self, enabled: bool) {
+        self.gpu_timing = enabled;
+        self.gpu_frame_time = None;
+    }
+
+    pub fn timings(&self) -> &FrameTimings {
+        &self.timings
+    }
+
+    /// Take how long, in milliseconds, the GPU spent on the passes of the latest frame it's
+    /// finished, if it's finished one since this was last taken.
+    pub fn take_gpu_frame_time(&mut self) -> Option<f32> {
+        self.gpu_frame_time.take()
+    }
+
+    /// Begin timing a stage, returning the time spent in the stages this one is nested in so far.
+    pub fn begin_cpu(&mut self) -> Duration {
+        std::mem::replace(&mut self.nested, Duration::default())
+    }
+
+    /// Finish timing a stage that took `elapsed` in all, given what `begin_cpu` returned.
+    pub fn end_cpu(&mut self, stage: Stage, elapsed: Duration, outer_nested: Duration) {
+        let own = elapsed.checked_sub(self.nested).unwrap_or_default();
+        self.cpu[stage as usize] += own;
+        self.nested = outer_nested + elapsed;
+    }
+
+    /// Query the time at which the GPU starts `pass`. Everything queued before this should
+    /// already have been flushed to the device.
+    pub fn begin_gpu(&mut self, device: &mut gfx_backend::Device, pass: Pass) {
+        let query = self.query_timestamp(device);
+        self.gpu.push((Some(pass), query));
+    }
+
+    /// Finish the frame, after everything queued for it has been flushed to the device, and take
+    /// in the timings of any earlier frames the GPU has since finished.
+    pub fn end_frame(&mut self, device: &mut gfx_backend::Device) {
+        if self.enabled {
+            let cpu = Stage::ALL
+                .iter()
+                .map(|stage| (*stage, as_ms(self.cpu[*stage as usize])))
+                .collect::<Vec<_>>();
+            smooth(&mut self.timings.cpu, &cpu);
+        }
+        if !self.gpu.is_empty() {
+            let query = self.query_timestamp(device);
+            self.gpu.push((None, query));
+            self.pending
+                .push_back(std::mem::replace(&mut self.gpu, Vec::new()));
+        }
+        self.cpu = [Duration::default(); 5];
+        self.nested = Duration::default();
+
+        while self.pending.len() > MAX_PENDING_FRAMES {
+            if let Some(frame) = self.pending.pop_front() {
+                self.free_queries
+                    .extend(frame.into_iter().map(|(_, query)| query));
+            }
+        }
+
+        while let Some(frame) = self.pending.front() {
+            let times = match read_timestamps(device, frame) {
+                Some(times) => times,
+                None => break,
+            };
+            let gpu = frame
+                .iter()
+                .zip(times.windows(2))
+                .filter_map(|((pass, _), window)| {
+                    pass.map(|pass| (pass, window[1].saturating_sub(window[0]) as f32 / 1.0e6))
+                })
+                .collect::<Vec<_>>();
+            self.gpu_frame_time = Some(gpu.iter().map(|(_, ms)| ms).sum());
+            if self.enabled {
+                smooth(&mut self.timings.gpu, &gpu);
+            }
+
+            if let Some(frame) = self.pending.pop_front() {
+                self.free_queries
+                    .extend(frame.into_iter().map(|(_, query)| query));
+            }
+        }
+    }
+
+    #[allow(unsafe_code)]
+    fn query_timestamp(&mut self, device: &mut gfx_backend::Device) -> gl::types::GLuint {
+        let mut query = self.free_queries.pop().unwrap_or(0);
+        // Safety: timestamps are only queried when `timestamps_supported` says the context has
+        // `glQueryCounter`, and `query` is either one generated by us earlier or freshly generated
+        // here. Nothing gfx tracks is touched.
+        unsafe {
+            device.with_gl(|gl| {
+                if query == 0 {
+                    gl.GenQueries(1, &mut query);
+                }
+                gl.QueryCounter(query, gl::TIMESTAMP);
+            });
+        }
+        query
+    }
+}
+
+/// The GPU times, in nanoseconds, recorded by the timestamp queries of a frame, if the GPU has
+/// got far enough to have recorded all of them.
+#[allow(unsafe_code)]
+fn read_timestamps(
+    device: &mut gfx_backend::Device,
+    frame: &[(Option<Pass>, gl::types::GLuint)],
+) -> Option<Vec<u64>> {
+    let mut times = None;
+    // Safety: every query in `frame` was generated and issued by `query_timestamp`, and each
+    // result is written into a local of the size GL expects for it.
+    unsafe {
+        device.with_gl(|gl| {
+            // Queries complete in order, so if the last is done then so are the rest
+            let mut available: gl::types::GLint = 0;
+            if let Some((_, last)) = frame.last() {
+                gl.GetQueryObjectiv(*last, gl::QUERY_RESULT_AVAILABLE, &mut available);
+            }
+            if available != 0 {
+                times = Some(
+                    frame
+                        .iter()
+                        .map(|(_, query)| {
+                            let mut time: gl::types::GLuint64 = 0;
+                            gl.GetQueryObjectui64v(*query, gl::QUERY_RESULT, &mut time);
+                            time
+                        })
+                        .collect(),
+                );
+            }
+        });
+    }
+    times
+}
+
+fn as_ms(duration: Duration) -> f32 {
+    duration.as_secs() as f32 * 1000.0 + duration.subsec_nanos() as f32 / 1.0e6
+}
+
+/// Blend the latest frame's timings into the running averages. Anything missing from the frame
+/// took no time in it.
+fn smooth<K: Copy + PartialEq>(averages: &mut Vec<(K, f32)>, frame: &[(K, f32)]) {
+    for (key, avg) in averages.iter_mut() {
+        let ms = frame
+            .iter()
+            .find(|(k, _)| k == key)
+            .map_or(0.0, |(_, ms)| *ms);
+        *avg += (ms - *avg) * SMOOTHING;
+    }
+    for (key, ms) in frame {
+        if !averages.iter().any(|(k, _)| k == key) {
+            averages.push((*key, *ms));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timings_are_smoothed() {
+        let mut averages = Vec::new();
+        smooth(&mut averages, &[(Stage::Terrain, 2.0)]);
+        assert_eq!(averages, vec![(Stage::Terrain, 2.0)]);
+
+        // A spike only nudges the average, and new stages start where they are
+        smooth(&mut averages, &[(Stage::Terrain, 22.0), (Stage::Ui, 1.0)]);
+        assert_eq!(averages, vec![(Stage::Terrain, 3.0), (Stage::Ui, 1.0)]);
+
+        // Stages that don't happen in a frame fall away
+        smooth(&mut averages, &[]);
+        assert!(averages[0].1 < 3.0 && averages[1].1 < 1.0);
+    }
+
+    #[test]
+    fn nested_stages_are_counted_once() {
+        let mut profiler = Profiler::new(false);
+        let outer = profiler.begin_cpu();
+        let inner = profiler.begin_cpu();
+        profiler.end_cpu(Stage::MeshUpload, Duration::from_millis(3), inner);
+        profiler.end_cpu(Stage::Figures, Duration::from_millis(10), outer);
+
+        assert_eq!(
+            profiler.cpu[Stage::MeshUpload as usize],
+            Duration::from_millis(3)
+        );
+        assert_eq!(
+            profiler.cpu[Stage::Figures as usize],
+            Duration::from_millis(7)
+        );
+    }
+}
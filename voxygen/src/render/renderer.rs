@@ -1,10 +1,16 @@
 use super::{
     consts::Consts,
     gfx_backend,
+    graph::{self, Pass, RenderGraph, Target, TargetConfig},
     instances::Instances,
     mesh::Mesh,
     model::{DynamicModel, Model},
-    pipelines::{figure, fluid, postprocess, skybox, sprite, terrain, ui, Globals, Light},
+    pipelines::{
+        figure, fluid, lod, outline, particle, postprocess, postprocess::AaMode, shadow,
+        shadow::ShadowQuality, skybox, sprite, terrain, trail, ui, weather, Globals, Light, Shadow,
+    },
+    profiler::{FrameTimings, Profiler, Stage},
+    scaling::{scaled_dims, MIN_RENDER_SCALE},
     texture::Texture,
     Pipeline, RenderError,
 };
@@ -12,14 +18,17 @@ use common::assets::{self, watch::ReloadIndicator};
 use gfx::{
     self,
     handle::Sampler,
+    texture::{FilterMethod, SamplerInfo, WrapMode},
     traits::{Device, Factory, FactoryExt},
 };
 use glsl_include::Context as IncludeContext;
-use log::error;
+use log::{error, warn};
+use std::time::Instant;
 use vek::*;
 
-/// Represents the format of the pre-processed color target.
-pub type TgtColorFmt = gfx::format::Srgba8;
+/// Represents the format of the pre-processed color target. It's floating point, so that colors
+/// brighter than white survive until they're tonemapped during post-processing.
+pub type TgtColorFmt = gfx::format::Rgba16F;
 /// Represents the format of the pre-processed depth target.
 pub type TgtDepthFmt = gfx::format::Depth;
 
@@ -28,6 +37,9 @@ pub type WinColorFmt = gfx::format::Srgba8;
 /// Represents the format of the window's depth target.
 pub type WinDepthFmt = gfx::format::Depth;
 
+/// Represents the format of the shadow map.
+pub type ShadowDepthFmt = gfx::format::Depth32F;
+
 /// A handle to a pre-processed color target.
 pub type TgtColorView = gfx::handle::RenderTargetView<gfx_backend::Resources, TgtColorFmt>;
 /// A handle to a pre-processed depth target.
@@ -38,11 +50,23 @@ pub type WinColorView = gfx::handle::RenderTargetView<gfx_backend::Resources, Wi
 /// A handle to a window depth target.
 pub type WinDepthView = gfx::handle::DepthStencilView<gfx_backend::Resources, WinDepthFmt>;
 
+/// A handle to a single cascade of the shadow map.
+pub type ShadowDepthView = gfx::handle::DepthStencilView<gfx_backend::Resources, ShadowDepthFmt>;
+
 /// A handle to a render color target as a resource.
 pub type TgtColorRes = gfx::handle::ShaderResourceView<
     gfx_backend::Resources,
     <TgtColorFmt as gfx::format::Formatted>::View,
 >;
+/// A copy of the render color target, made for things that need to see what's behind them.
+pub type RefractionTex =
+    gfx::handle::Texture<gfx_backend::Resources, <TgtColorFmt as gfx::format::Formatted>::Surface>;
+
+/// A handle to every cascade of the shadow map as a resource.
+pub type ShadowRes = gfx::handle::ShaderResourceView<
+    gfx_backend::Resources,
+    <ShadowDepthFmt as gfx::format::Formatted>::View,
+>;
 
 /// A type that encapsulates rendering state. `Renderer` is central to Voxygen's rendering
 /// subsystem and contains any state necessary to interact with the GPU, along with pipeline state
@@ -55,20 +79,27 @@ pub struct Renderer {
     win_color_view: WinColorView,
     win_depth_view: WinDepthView,
 
-    tgt_color_view: TgtColorView,
-    tgt_depth_view: TgtDepthView,
-
-    tgt_color_res: TgtColorRes,
+    /// How much of the window's resolution the scene is rendered at. Post-processing scales it up
+    /// to fill the window.
+    render_scale: f32,
+    aa_mode: AaMode,
 
     sampler: Sampler<gfx_backend::Resources>,
 
-    skybox_pipeline: GfxPipeline<skybox::pipe::Init<'static>>,
-    figure_pipeline: GfxPipeline<figure::pipe::Init<'static>>,
-    terrain_pipeline: GfxPipeline<terrain::pipe::Init<'static>>,
-    fluid_pipeline: GfxPipeline<fluid::pipe::Init<'static>>,
-    sprite_pipeline: GfxPipeline<sprite::pipe::Init<'static>>,
-    ui_pipeline: GfxPipeline<ui::pipe::Init<'static>>,
-    postprocess_pipeline: GfxPipeline<postprocess::pipe::Init<'static>>,
+    shadow_quality: ShadowQuality,
+    shadow_sampler: Sampler<gfx_backend::Resources>,
+
+    pipelines: Pipelines,
+    /// The passes of a frame and the targets they draw into, how far through them the current
+    /// frame has got, and the targets that have been drawn to so far this frame.
+    graph: RenderGraph,
+    pass: Option<usize>,
+    written: Vec<Target>,
+
+    profiler: Profiler,
+
+    /// Covers the whole screen, for passes that process every pixel of a target.
+    screen_model: Model<postprocess::PostProcessPipeline>,
 
     shader_reload_indicator: ReloadIndicator,
 }
@@ -80,24 +111,40 @@ impl Renderer {
         mut factory: gfx_backend::Factory,
         win_color_view: WinColorView,
         win_depth_view: WinDepthView,
+        shadow_quality: ShadowQuality,
+        aa_mode: AaMode,
+        render_scale: f32,
     ) -> Result<Self, RenderError> {
         let mut shader_reload_indicator = ReloadIndicator::new();
 
-        let (
-            skybox_pipeline,
-            figure_pipeline,
-            terrain_pipeline,
-            fluid_pipeline,
-            sprite_pipeline,
-            ui_pipeline,
-            postprocess_pipeline,
-        ) = create_pipelines(&mut factory, &mut shader_reload_indicator)?;
+        // Timestamp queries are only core from OpenGL 3.3, and the context asked for is 3.2
+        let timestamps_supported =
+            device
+                .get_info()
+                .is_version_or_extension_supported(3, 3, "GL_ARB_timer_query");
+        if !timestamps_supported {
+            warn!("GPU timestamps aren't supported, so passes won't be timed on the GPU");
+        }
+
+        let pipelines = create_pipelines(&mut factory, aa_mode, &mut shader_reload_indicator)?;
 
+        let render_scale = render_scale.max(MIN_RENDER_SCALE).min(1.0);
         let dims = win_color_view.get_dimensions();
-        let (tgt_color_view, tgt_depth_view, tgt_color_res) =
-            Self::create_rt_views(&mut factory, (dims.0, dims.1))?;
+        let graph = RenderGraph::new(
+            &mut factory,
+            graph::DEFAULT_PASSES.to_vec(),
+            TargetConfig {
+                scene_dims: scaled_dims((dims.0, dims.1), render_scale),
+                aa_mode,
+                shadow_quality,
+            },
+        )?;
+
+        let screen_model = Model::new(&mut factory, &postprocess::create_mesh());
 
         let sampler = factory.create_sampler_linear();
+        let shadow_sampler =
+            factory.create_sampler(SamplerInfo::new(FilterMethod::Scale, WrapMode::Clamp));
 
         Ok(Self {
             device,
@@ -107,19 +154,21 @@ impl Renderer {
             win_color_view,
             win_depth_view,
 
-            tgt_color_view,
-            tgt_depth_view,
-
-            tgt_color_res,
+            render_scale,
+            aa_mode,
             sampler,
 
-            skybox_pipeline,
-            figure_pipeline,
-            terrain_pipeline,
-            fluid_pipeline,
-            sprite_pipeline,
-            ui_pipeline,
-            postprocess_pipeline,
+            shadow_quality,
+            shadow_sampler,
+
+            pipelines,
+            graph,
+            pass: None,
+            written: Vec::new(),
+
+            profiler: Profiler::new(timestamps_supported),
+
+            screen_model,
 
             shader_reload_indicator,
         })
@@ -128,7 +177,8 @@ impl Renderer {
     /// Get references to the internal render target views that get rendered to before post-processing.
     #[allow(dead_code)]
     pub fn tgt_views(&self) -> (&TgtColorView, &TgtDepthView) {
-        (&self.tgt_color_view, &self.tgt_depth_view)
+        let targets = self.graph.targets();
+        (&targets.tgt_color_view, &targets.tgt_depth_view)
     }
 
     /// Get references to the internal render target views that get displayed directly by the window.
@@ -140,7 +190,8 @@ impl Renderer {
     /// Get mutable references to the internal render target views that get rendered to before post-processing.
     #[allow(dead_code)]
     pub fn tgt_views_mut(&mut self) -> (&mut TgtColorView, &mut TgtDepthView) {
-        (&mut self.tgt_color_view, &mut self.tgt_depth_view)
+        let targets = self.graph.targets_mut();
+        (&mut targets.tgt_color_view, &mut targets.tgt_depth_view)
     }
 
     /// Get mutable references to the internal render target views that get displayed directly by the window.
@@ -149,33 +200,89 @@ impl Renderer {
         (&mut self.win_color_view, &mut self.win_depth_view)
     }
 
-    /// Resize internal render targets to match window render target dimensions.
+    /// Resize internal render targets to match window render target dimensions, scaled by the
+    /// render scale.
     pub fn on_resize(&mut self) -> Result<(), RenderError> {
-        let dims = self.win_color_view.get_dimensions();
+        let config = self.target_config(self.aa_mode);
+        self.graph.reconfigure(&mut self.factory, config)
+    }
 
-        // Avoid panics when creating texture with w,h of 0,0.
-        if dims.0 != 0 && dims.1 != 0 {
-            let (tgt_color_view, tgt_depth_view, tgt_color_res) =
-                Self::create_rt_views(&mut self.factory, (dims.0, dims.1))?;
-            self.tgt_color_res = tgt_color_res;
-            self.tgt_color_view = tgt_color_view;
-            self.tgt_depth_view = tgt_depth_view;
+    /// Switch to anti-aliasing edges in a different way, recreating the render targets and
+    /// shaders to suit it.
+    pub fn set_aa_mode(&mut self, aa_mode: AaMode) -> Result<(), RenderError> {
+        if aa_mode == self.aa_mode {
+            return Ok(());
         }
 
+        // Create both the targets and the pipelines for the new mode before replacing either, so
+        // that failing to create one leaves the renderer consistently in the old mode
+        let pipelines = create_pipelines(
+            &mut self.factory,
+            aa_mode,
+            &mut self.shader_reload_indicator,
+        )?;
+        let config = self.target_config(aa_mode);
+        self.graph.reconfigure(&mut self.factory, config)?;
+
+        self.pipelines = pipelines;
+        self.aa_mode = aa_mode;
+
         Ok(())
     }
 
-    fn create_rt_views(
-        factory: &mut gfx_device_gl::Factory,
-        size: (u16, u16),
-    ) -> Result<(TgtColorView, TgtDepthView, TgtColorRes), RenderError> {
-        let (_, tgt_color_res, tgt_color_view) = factory
-            .create_render_target::<TgtColorFmt>(size.0, size.1)
-            .map_err(RenderError::CombinedError)?;;
-        let tgt_depth_view = factory
-            .create_depth_stencil_view_only::<TgtDepthFmt>(size.0, size.1)
-            .map_err(RenderError::CombinedError)?;;
-        Ok((tgt_color_view, tgt_depth_view, tgt_color_res))
+    /// What the graph's targets need to suit to render the window's scene with `aa_mode`. While
+    /// the window has no area, neither does the scene.
+    fn target_config(&self, aa_mode: AaMode) -> TargetConfig {
+        let dims = self.win_color_view.get_dimensions();
+        // Avoid panics when creating texture with w,h of 0,0.
+        let scene_dims = if dims.0 != 0 && dims.1 != 0 {
+            scaled_dims((dims.0, dims.1), self.render_scale)
+        } else {
+            (0, 0)
+        };
+
+        TargetConfig {
+            scene_dims,
+            aa_mode,
+            shadow_quality: self.shadow_quality,
+        }
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Render the scene at a different fraction of the window's resolution, from
+    /// `MIN_RENDER_SCALE` to 1.0, recreating the render targets at the new size.
+    pub fn set_render_scale(&mut self, render_scale: f32) -> Result<(), RenderError> {
+        let render_scale = render_scale.max(MIN_RENDER_SCALE).min(1.0);
+        if render_scale == self.render_scale {
+            return Ok(());
+        }
+
+        self.render_scale = render_scale;
+        self.on_resize()
+    }
+
+    /// Get the quality that shadows are rendered at.
+    pub fn shadow_quality(&self) -> ShadowQuality {
+        self.shadow_quality
+    }
+
+    /// Render shadows at a different quality, recreating the shadow map to suit.
+    pub fn set_shadow_quality(&mut self, shadow_quality: ShadowQuality) -> Result<(), RenderError> {
+        if shadow_quality == self.shadow_quality {
+            return Ok(());
+        }
+
+        let config = TargetConfig {
+            shadow_quality,
+            ..self.target_config(self.aa_mode)
+        };
+        self.graph.reconfigure(&mut self.factory, config)?;
+        self.shadow_quality = shadow_quality;
+
+        Ok(())
     }
 
     /// Get the resolution of the render target.
@@ -186,37 +293,188 @@ impl Renderer {
         )
     }
 
+    /// Get the resolution that the scene is rendered at, before it's scaled up to fill the window.
+    pub fn render_resolution(&self) -> Vec2<u16> {
+        Vec2::new(
+            self.graph.targets().tgt_color_view.get_dimensions().0,
+            self.graph.targets().tgt_color_view.get_dimensions().1,
+        )
+    }
+
     /// Queue the clearing of the depth target ready for a new frame to be rendered.
     pub fn clear(&mut self) {
-        self.encoder.clear_depth(&self.tgt_depth_view, 1.0);
+        self.encoder
+            .clear_depth(&self.graph.targets().tgt_depth_view, 1.0);
         self.encoder.clear_depth(&self.win_depth_view, 1.0);
+        for shadow_depth_view in &self.graph.targets().shadow_depth_views {
+            self.encoder.clear_depth(shadow_depth_view, 1.0);
+        }
+    }
+
+    /// Move the frame on to `pass`, running the automatic passes before it whose results will be
+    /// used. Drawing into a pass that the frame has already moved beyond is a mistake, caught in
+    /// debug builds. Otherwise the draws are still made, but land on top of whatever the passes
+    /// since have done.
+    fn enter_pass(&mut self, pass: Pass) {
+        let to = match self.graph.position(pass) {
+            Some(to) => to,
+            None => return,
+        };
+        let from = self.pass.map_or(0, |current| current + 1);
+        debug_assert!(
+            to >= from || Some(to) == self.pass,
+            "Drew into {:?} after the frame had moved beyond it",
+            pass
+        );
+        if to < from {
+            return;
+        }
+
+        for i in from..to {
+            let auto = self.graph.passes()[i];
+            // An automatic pass is only worth running if what it reads has been drawn and what it
+            // writes is read by the pass being entered or another automatic pass along the way
+            let passes = &self.graph.passes()[i + 1..=to];
+            let is_needed = auto.automatic
+                && auto.reads.iter().all(|t| self.written.contains(t))
+                && passes
+                    .iter()
+                    .filter(|p| p.pass == pass || p.automatic)
+                    .any(|p| p.reads.iter().any(|t| auto.writes.contains(t)));
+            if is_needed {
+                self.begin_gpu_pass(auto.pass);
+                self.run_pass(auto.pass);
+                self.written.extend_from_slice(auto.writes);
+            }
+        }
+
+        self.begin_gpu_pass(pass);
+        self.written
+            .extend_from_slice(self.graph.passes()[to].writes);
+        self.pass = Some(to);
+    }
+
+    /// When timing the GPU, mark where it starts on `pass`. Everything queued so far has to be
+    /// sent to the GPU first, so that the mark lands between the passes.
+    fn begin_gpu_pass(&mut self, pass: Pass) {
+        if self.profiler.times_gpu() {
+            self.encoder.flush(&mut self.device);
+            self.profiler.begin_gpu(&mut self.device, pass);
+        }
+    }
+
+    /// Run `f`, counting the CPU time it takes towards `stage` when profiling.
+    pub fn profile<R>(&mut self, stage: Stage, f: impl FnOnce(&mut Self) -> R) -> R {
+        if !self.profiler.is_enabled() {
+            return f(self);
+        }
+
+        let outer_nested = self.profiler.begin_cpu();
+        let start = Instant::now();
+        let result = f(self);
+        self.profiler.end_cpu(stage, start.elapsed(), outer_nested);
+        result
+    }
+
+    /// Start or stop timing each frame's passes and stages.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profiler.set_enabled(enabled);
+    }
+
+    /// Get how long recent frames have spent in each of their passes and stages, if profiling.
+    pub fn timings(&self) -> &FrameTimings {
+        self.profiler.timings()
+    }
+
+    /// Start or stop timing each frame's passes on the GPU, whether or not profiling.
+    pub fn set_gpu_timing(&mut self, enabled: bool) {
+        self.profiler.set_gpu_timing(enabled);
+    }
+
+    /// Take how long, in milliseconds, the GPU spent on the latest frame it's finished, if it's
+    /// being timed and has finished one since this was last taken.
+    pub fn take_gpu_frame_time(&mut self) -> Option<f32> {
+        self.profiler.take_gpu_frame_time()
+    }
+
+    /// Queue the work of an automatic pass.
+    fn run_pass(&mut self, pass: Pass) {
+        use gfx::memory::Typed;
+
+        match pass {
+            Pass::Refraction => {
+                if let Err(err) = self.capture_refraction() {
+                    warn!(
+                        "Failed to capture the scene for translucent things to refract: {:?}",
+                        err
+                    );
+                }
+            }
+            Pass::Resolve => {
+                if let Some(tgt_msaa_res) = self.graph.targets().tgt_msaa_res.clone() {
+                    let tgt_resolve_view = self.graph.targets().tgt_resolve_view.clone();
+                    self.resolve_msaa(tgt_msaa_res, tgt_resolve_view);
+                }
+
+                // Bloom is sampled from the blurrier mipmaps of the rendered scene
+                self.encoder
+                    .generate_mipmap_raw(self.graph.targets().tgt_color_res.raw());
+            }
+            _ => {}
+        }
+    }
+
+    /// Queue a copy of everything rendered so far this frame, for translucent things rendered
+    /// afterwards to refract.
+    fn capture_refraction(&mut self) -> Result<(), RenderError> {
+        use gfx::{format::Formatted, memory::Typed};
+
+        // A multisampled target can't be copied directly, so it's resolved into the copy instead
+        if let Some(tgt_msaa_res) = self.graph.targets().tgt_msaa_res.clone() {
+            let refraction_view = self.graph.targets().refraction_view.clone();
+            self.resolve_msaa(tgt_msaa_res, refraction_view);
+            return Ok(());
+        }
+
+        let (width, height, _, _) = self.graph.targets().tgt_color_view.get_dimensions();
+        let info = gfx::texture::RawImageInfo {
+            xoffset: 0,
+            yoffset: 0,
+            zoffset: 0,
+            width,
+            height,
+            depth: 0,
+            format: TgtColorFmt::get_format(),
+            mipmap: 0,
+        };
+        self.encoder
+            .copy_texture_to_texture_raw(
+                self.graph.targets().tgt_color_view.raw().get_texture(),
+                None,
+                info,
+                self.graph.targets().refraction_tex.raw(),
+                None,
+                info,
+            )
+            .map_err(RenderError::TexCopyError)
     }
 
     /// Perform all queued draw calls for this frame and clean up discarded items.
     pub fn flush(&mut self) {
         self.encoder.flush(&mut self.device);
+        self.profiler.end_frame(&mut self.device);
         self.device.cleanup();
+        self.pass = None;
+        self.written.clear();
 
         // If the shaders files were changed attempt to recreate the shaders
         if self.shader_reload_indicator.reloaded() {
-            match create_pipelines(&mut self.factory, &mut self.shader_reload_indicator) {
-                Ok((
-                    skybox_pipeline,
-                    figure_pipeline,
-                    terrain_pipeline,
-                    fluid_pipeline,
-                    sprite_pipeline,
-                    ui_pipeline,
-                    postprocess_pipeline,
-                )) => {
-                    self.skybox_pipeline = skybox_pipeline;
-                    self.figure_pipeline = figure_pipeline;
-                    self.terrain_pipeline = terrain_pipeline;
-                    self.fluid_pipeline = fluid_pipeline;
-                    self.sprite_pipeline = sprite_pipeline;
-                    self.ui_pipeline = ui_pipeline;
-                    self.postprocess_pipeline = postprocess_pipeline;
-                }
+            match create_pipelines(
+                &mut self.factory,
+                self.aa_mode,
+                &mut self.shader_reload_indicator,
+            ) {
+                Ok(pipelines) => self.pipelines = pipelines,
                 Err(e) => error!(
                     "Could not recreate shaders from assets due to an error: {:#?}",
                     e
@@ -254,9 +512,20 @@ impl Renderer {
         Ok(instances)
     }
 
+    /// Update a set of instances with the provided values.
+    pub fn update_instances<T: Copy + gfx::traits::Pod>(
+        &mut self,
+        instances: &mut Instances<T>,
+        vals: &[T],
+    ) -> Result<(), RenderError> {
+        instances.update(&mut self.encoder, vals)
+    }
+
     /// Create a new model from the provided mesh.
     pub fn create_model<P: Pipeline>(&mut self, mesh: &Mesh<P>) -> Result<Model<P>, RenderError> {
-        Ok(Model::new(&mut self.factory, mesh))
+        Ok(self.profile(Stage::MeshUpload, |renderer| {
+            Model::new(&mut renderer.factory, mesh)
+        }))
     }
 
     /// Create a new dynamic model with the specified size.
@@ -274,7 +543,9 @@ impl Renderer {
         mesh: &Mesh<P>,
         offset: usize,
     ) -> Result<(), RenderError> {
-        model.update(&mut self.encoder, mesh, offset)
+        self.profile(Stage::MeshUpload, |renderer| {
+            model.update(&mut renderer.encoder, mesh, offset)
+        })
     }
 
     /// Return the maximum supported texture size.
@@ -365,6 +636,8 @@ impl Renderer {
         globals: &Consts<Globals>,
         locals: &Consts<skybox::Locals>,
     ) {
+        self.enter_pass(Pass::Opaque);
+
         self.encoder.draw(
             &gfx::Slice {
                 start: model.vertex_range().start,
@@ -373,13 +646,13 @@ impl Renderer {
                 instances: None,
                 buffer: gfx::IndexBuffer::Auto,
             },
-            &self.skybox_pipeline.pso,
+            &self.pipelines.skybox.pso,
             &skybox::pipe::Data {
                 vbuf: model.vbuf.clone(),
                 locals: locals.buf.clone(),
                 globals: globals.buf.clone(),
-                tgt_color: self.tgt_color_view.clone(),
-                tgt_depth: self.tgt_depth_view.clone(),
+                tgt_color: self.graph.targets().tgt_color_view.clone(),
+                tgt_depth: self.graph.targets().tgt_depth_view.clone(),
             },
         );
     }
@@ -392,7 +665,10 @@ impl Renderer {
         locals: &Consts<figure::Locals>,
         bones: &Consts<figure::BoneData>,
         lights: &Consts<Light>,
+        shadows: &Consts<Shadow>,
     ) {
+        self.enter_pass(Pass::Opaque);
+
         self.encoder.draw(
             &gfx::Slice {
                 start: model.vertex_range().start,
@@ -401,15 +677,60 @@ impl Renderer {
                 instances: None,
                 buffer: gfx::IndexBuffer::Auto,
             },
-            &self.figure_pipeline.pso,
+            &self.pipelines.figure.pso,
             &figure::pipe::Data {
                 vbuf: model.vbuf.clone(),
                 locals: locals.buf.clone(),
                 globals: globals.buf.clone(),
                 bones: bones.buf.clone(),
                 lights: lights.buf.clone(),
-                tgt_color: self.tgt_color_view.clone(),
-                tgt_depth: self.tgt_depth_view.clone(),
+                shadows: shadows.buf.clone(),
+                shadow_maps: (
+                    self.graph.targets().shadow_res.clone(),
+                    self.shadow_sampler.clone(),
+                ),
+                tgt_color: self.graph.targets().tgt_color_view.clone(),
+                tgt_depth: self.graph.targets().tgt_depth_view.clone(),
+            },
+        );
+    }
+
+    /// Queue the rendering of `count` figures sharing the provided model in the upcoming frame,
+    /// each placed by its instance and posed by the run of `bones` starting at its bone base.
+    pub fn render_figures(
+        &mut self,
+        model: &Model<figure::FigurePipeline>,
+        globals: &Consts<Globals>,
+        instances: &Instances<figure::Instance>,
+        bones: &Consts<figure::BoneData>,
+        count: usize,
+        lights: &Consts<Light>,
+        shadows: &Consts<Shadow>,
+    ) {
+        self.enter_pass(Pass::Opaque);
+
+        self.encoder.draw(
+            &gfx::Slice {
+                start: model.vertex_range().start,
+                end: model.vertex_range().end,
+                base_vertex: 0,
+                instances: Some((count.min(instances.count()) as u32, 0)),
+                buffer: gfx::IndexBuffer::Auto,
+            },
+            &self.pipelines.figure_instanced.pso,
+            &figure::instanced_pipe::Data {
+                vbuf: model.vbuf.clone(),
+                ibuf: instances.ibuf.clone(),
+                globals: globals.buf.clone(),
+                bones: bones.buf.clone(),
+                lights: lights.buf.clone(),
+                shadows: shadows.buf.clone(),
+                shadow_maps: (
+                    self.graph.targets().shadow_res.clone(),
+                    self.shadow_sampler.clone(),
+                ),
+                tgt_color: self.graph.targets().tgt_color_view.clone(),
+                tgt_depth: self.graph.targets().tgt_depth_view.clone(),
             },
         );
     }
@@ -421,7 +742,10 @@ impl Renderer {
         globals: &Consts<Globals>,
         locals: &Consts<terrain::Locals>,
         lights: &Consts<Light>,
+        shadows: &Consts<Shadow>,
     ) {
+        self.enter_pass(Pass::Opaque);
+
         self.encoder.draw(
             &gfx::Slice {
                 start: model.vertex_range().start,
@@ -430,19 +754,93 @@ impl Renderer {
                 instances: None,
                 buffer: gfx::IndexBuffer::Auto,
             },
-            &self.terrain_pipeline.pso,
+            &self.pipelines.terrain.pso,
             &terrain::pipe::Data {
                 vbuf: model.vbuf.clone(),
                 locals: locals.buf.clone(),
                 globals: globals.buf.clone(),
                 lights: lights.buf.clone(),
-                tgt_color: self.tgt_color_view.clone(),
-                tgt_depth: self.tgt_depth_view.clone(),
+                shadows: shadows.buf.clone(),
+                shadow_maps: (
+                    self.graph.targets().shadow_res.clone(),
+                    self.shadow_sampler.clone(),
+                ),
+                tgt_color: self.graph.targets().tgt_color_view.clone(),
+                tgt_depth: self.graph.targets().tgt_depth_view.clone(),
             },
         );
     }
 
-    /// Queue the rendering of the provided terrain chunk model in the upcoming frame.
+    /// Queue the rendering of the provided terrain chunk model into a cascade of the shadow map.
+    pub fn render_terrain_shadow(
+        &mut self,
+        model: &Model<terrain::TerrainPipeline>,
+        globals: &Consts<Globals>,
+        locals: &Consts<terrain::Locals>,
+        shadow_locals: &Consts<shadow::Locals>,
+        cascade: usize,
+    ) {
+        self.enter_pass(Pass::Shadow);
+
+        let tgt_depth = match self.graph.targets().shadow_depth_views.get(cascade) {
+            Some(view) => view.clone(),
+            None => return,
+        };
+        self.encoder.draw(
+            &gfx::Slice {
+                start: model.vertex_range().start,
+                end: model.vertex_range().end,
+                base_vertex: 0,
+                instances: None,
+                buffer: gfx::IndexBuffer::Auto,
+            },
+            &self.pipelines.shadow_terrain.pso,
+            &shadow::terrain_pipe::Data {
+                vbuf: model.vbuf.clone(),
+                locals: locals.buf.clone(),
+                globals: globals.buf.clone(),
+                shadow_locals: shadow_locals.buf.clone(),
+                tgt_depth,
+            },
+        );
+    }
+
+    /// Queue the rendering of the provided figure model into a cascade of the shadow map.
+    pub fn render_figure_shadow(
+        &mut self,
+        model: &Model<figure::FigurePipeline>,
+        locals: &Consts<figure::Locals>,
+        bones: &Consts<figure::BoneData>,
+        shadow_locals: &Consts<shadow::Locals>,
+        cascade: usize,
+    ) {
+        self.enter_pass(Pass::Shadow);
+
+        let tgt_depth = match self.graph.targets().shadow_depth_views.get(cascade) {
+            Some(view) => view.clone(),
+            None => return,
+        };
+        self.encoder.draw(
+            &gfx::Slice {
+                start: model.vertex_range().start,
+                end: model.vertex_range().end,
+                base_vertex: 0,
+                instances: None,
+                buffer: gfx::IndexBuffer::Auto,
+            },
+            &self.pipelines.shadow_figure.pso,
+            &shadow::figure_pipe::Data {
+                vbuf: model.vbuf.clone(),
+                locals: locals.buf.clone(),
+                bones: bones.buf.clone(),
+                shadow_locals: shadow_locals.buf.clone(),
+                tgt_depth,
+            },
+        );
+    }
+
+    /// Queue the rendering of the provided fluid chunk model in the upcoming frame. Fluids refract
+    /// whatever opaque things were rendered before them.
     pub fn render_fluid_chunk(
         &mut self,
         model: &Model<fluid::FluidPipeline>,
@@ -450,6 +848,8 @@ impl Renderer {
         locals: &Consts<terrain::Locals>,
         lights: &Consts<Light>,
     ) {
+        self.enter_pass(Pass::Translucent);
+
         self.encoder.draw(
             &gfx::Slice {
                 start: model.vertex_range().start,
@@ -458,14 +858,18 @@ impl Renderer {
                 instances: None,
                 buffer: gfx::IndexBuffer::Auto,
             },
-            &self.fluid_pipeline.pso,
+            &self.pipelines.fluid.pso,
             &fluid::pipe::Data {
                 vbuf: model.vbuf.clone(),
                 locals: locals.buf.clone(),
                 globals: globals.buf.clone(),
                 lights: lights.buf.clone(),
-                tgt_color: self.tgt_color_view.clone(),
-                tgt_depth: self.tgt_depth_view.clone(),
+                refraction: (
+                    self.graph.targets().refraction_res.clone(),
+                    self.sampler.clone(),
+                ),
+                tgt_color: self.graph.targets().tgt_color_view.clone(),
+                tgt_depth: self.graph.targets().tgt_depth_view.clone(),
             },
         );
     }
@@ -474,10 +878,13 @@ impl Renderer {
     pub fn render_sprites(
         &mut self,
         model: &Model<sprite::SpritePipeline>,
+        tex: &Texture<sprite::SpritePipeline>,
         globals: &Consts<Globals>,
         instances: &Instances<sprite::Instance>,
         lights: &Consts<Light>,
     ) {
+        self.enter_pass(Pass::Opaque);
+
         self.encoder.draw(
             &gfx::Slice {
                 start: model.vertex_range().start,
@@ -486,14 +893,182 @@ impl Renderer {
                 instances: Some((instances.count() as u32, 0)),
                 buffer: gfx::IndexBuffer::Auto,
             },
-            &self.sprite_pipeline.pso,
+            &self.pipelines.sprite.pso,
             &sprite::pipe::Data {
                 vbuf: model.vbuf.clone(),
                 ibuf: instances.ibuf.clone(),
                 globals: globals.buf.clone(),
                 lights: lights.buf.clone(),
-                tgt_color: self.tgt_color_view.clone(),
-                tgt_depth: self.tgt_depth_view.clone(),
+                tex: (tex.srv.clone(), tex.sampler.clone()),
+                tgt_color: self.graph.targets().tgt_color_view.clone(),
+                tgt_depth: self.graph.targets().tgt_depth_view.clone(),
+            },
+        );
+    }
+
+    /// Queue the rendering of the provided LOD terrain model in the upcoming frame.
+    pub fn render_lod(&mut self, model: &Model<lod::LodPipeline>, globals: &Consts<Globals>) {
+        self.enter_pass(Pass::Opaque);
+
+        self.encoder.draw(
+            &gfx::Slice {
+                start: model.vertex_range().start,
+                end: model.vertex_range().end,
+                base_vertex: 0,
+                instances: None,
+                buffer: gfx::IndexBuffer::Auto,
+            },
+            &self.pipelines.lod.pso,
+            &lod::pipe::Data {
+                vbuf: model.vbuf.clone(),
+                globals: globals.buf.clone(),
+                tgt_color: self.graph.targets().tgt_color_view.clone(),
+                tgt_depth: self.graph.targets().tgt_depth_view.clone(),
+            },
+        );
+    }
+
+    /// Queue the rendering of the provided trail model in the upcoming frame.
+    pub fn render_trail(&mut self, model: &Model<trail::TrailPipeline>, globals: &Consts<Globals>) {
+        self.enter_pass(Pass::Translucent);
+
+        self.encoder.draw(
+            &gfx::Slice {
+                start: model.vertex_range().start,
+                end: model.vertex_range().end,
+                base_vertex: 0,
+                instances: None,
+                buffer: gfx::IndexBuffer::Auto,
+            },
+            &self.pipelines.trail.pso,
+            &trail::pipe::Data {
+                vbuf: model.vbuf.clone(),
+                globals: globals.buf.clone(),
+                tgt_color: self.graph.targets().tgt_color_view.clone(),
+                tgt_depth: self.graph.targets().tgt_depth_view.clone(),
+            },
+        );
+    }
+
+    /// Queue the rendering of the first `count` of the provided drops of rain or snow in the
+    /// upcoming frame.
+    pub fn render_weather(
+        &mut self,
+        model: &Model<weather::WeatherPipeline>,
+        globals: &Consts<Globals>,
+        locals: &Consts<weather::Locals>,
+        instances: &Instances<weather::Instance>,
+        count: usize,
+    ) {
+        self.enter_pass(Pass::Translucent);
+
+        self.encoder.draw(
+            &gfx::Slice {
+                start: model.vertex_range().start,
+                end: model.vertex_range().end,
+                base_vertex: 0,
+                instances: Some((count.min(instances.count()) as u32, 0)),
+                buffer: gfx::IndexBuffer::Auto,
+            },
+            &self.pipelines.weather.pso,
+            &weather::pipe::Data {
+                vbuf: model.vbuf.clone(),
+                ibuf: instances.ibuf.clone(),
+                locals: locals.buf.clone(),
+                globals: globals.buf.clone(),
+                tgt_color: self.graph.targets().tgt_color_view.clone(),
+                tgt_depth: self.graph.targets().tgt_depth_view.clone(),
+            },
+        );
+    }
+
+    /// Queue the rendering of an outline around the provided figure model in the upcoming frame.
+    pub fn render_figure_outline(
+        &mut self,
+        model: &Model<figure::FigurePipeline>,
+        globals: &Consts<Globals>,
+        locals: &Consts<figure::Locals>,
+        bones: &Consts<figure::BoneData>,
+        outline_locals: &Consts<outline::Locals>,
+    ) {
+        self.enter_pass(Pass::Translucent);
+
+        self.encoder.draw(
+            &gfx::Slice {
+                start: model.vertex_range().start,
+                end: model.vertex_range().end,
+                base_vertex: 0,
+                instances: None,
+                buffer: gfx::IndexBuffer::Auto,
+            },
+            &self.pipelines.outline_figure.pso,
+            &outline::figure_pipe::Data {
+                vbuf: model.vbuf.clone(),
+                locals: locals.buf.clone(),
+                bones: bones.buf.clone(),
+                outline_locals: outline_locals.buf.clone(),
+                globals: globals.buf.clone(),
+                tgt_color: self.graph.targets().tgt_color_view.clone(),
+                tgt_depth: self.graph.targets().tgt_depth_view.clone(),
+            },
+        );
+    }
+
+    /// Queue the rendering of the provided block wireframe in the upcoming frame.
+    pub fn render_block_outline(
+        &mut self,
+        model: &Model<outline::OutlinePipeline>,
+        globals: &Consts<Globals>,
+        outline_locals: &Consts<outline::Locals>,
+    ) {
+        self.enter_pass(Pass::Translucent);
+
+        self.encoder.draw(
+            &gfx::Slice {
+                start: model.vertex_range().start,
+                end: model.vertex_range().end,
+                base_vertex: 0,
+                instances: None,
+                buffer: gfx::IndexBuffer::Auto,
+            },
+            &self.pipelines.outline_block.pso,
+            &outline::block_pipe::Data {
+                vbuf: model.vbuf.clone(),
+                outline_locals: outline_locals.buf.clone(),
+                globals: globals.buf.clone(),
+                tgt_color: self.graph.targets().tgt_color_view.clone(),
+                tgt_depth: self.graph.targets().tgt_depth_view.clone(),
+            },
+        );
+    }
+
+    /// Queue the rendering of the first `count` of the provided particles in the upcoming frame.
+    pub fn render_particles(
+        &mut self,
+        model: &Model<particle::ParticlePipeline>,
+        globals: &Consts<Globals>,
+        instances: &Instances<particle::Instance>,
+        count: usize,
+        lights: &Consts<Light>,
+    ) {
+        self.enter_pass(Pass::Translucent);
+
+        self.encoder.draw(
+            &gfx::Slice {
+                start: model.vertex_range().start,
+                end: model.vertex_range().end,
+                base_vertex: 0,
+                instances: Some((count.min(instances.count()) as u32, 0)),
+                buffer: gfx::IndexBuffer::Auto,
+            },
+            &self.pipelines.particle.pso,
+            &particle::pipe::Data {
+                vbuf: model.vbuf.clone(),
+                ibuf: instances.ibuf.clone(),
+                globals: globals.buf.clone(),
+                lights: lights.buf.clone(),
+                tgt_color: self.graph.targets().tgt_color_view.clone(),
+                tgt_depth: self.graph.targets().tgt_depth_view.clone(),
             },
         );
     }
@@ -507,6 +1082,8 @@ impl Renderer {
         globals: &Consts<Globals>,
         locals: &Consts<ui::Locals>,
     ) {
+        self.enter_pass(Pass::Ui);
+
         let Aabr { min, max } = scissor;
         self.encoder.draw(
             &gfx::Slice {
@@ -516,7 +1093,7 @@ impl Renderer {
                 instances: None,
                 buffer: gfx::IndexBuffer::Auto,
             },
-            &self.ui_pipeline.pso,
+            &self.pipelines.ui.pso,
             &ui::pipe::Data {
                 vbuf: model.vbuf.clone(),
                 scissor: gfx::Rect {
@@ -540,6 +1117,8 @@ impl Renderer {
         globals: &Consts<Globals>,
         locals: &Consts<postprocess::Locals>,
     ) {
+        self.enter_pass(Pass::PostProcess);
+
         self.encoder.draw(
             &gfx::Slice {
                 start: model.vertex_range().start,
@@ -548,39 +1127,119 @@ impl Renderer {
                 instances: None,
                 buffer: gfx::IndexBuffer::Auto,
             },
-            &self.postprocess_pipeline.pso,
+            &self.pipelines.postprocess.pso,
             &postprocess::pipe::Data {
                 vbuf: model.vbuf.clone(),
                 locals: locals.buf.clone(),
                 globals: globals.buf.clone(),
-                src_sampler: (self.tgt_color_res.clone(), self.sampler.clone()),
+                src_sampler: (
+                    self.graph.targets().tgt_color_res.clone(),
+                    self.sampler.clone(),
+                ),
                 tgt_color: self.win_color_view.clone(),
                 tgt_depth: self.win_depth_view.clone(),
             },
         )
     }
+
+    /// Average the samples of each pixel of a multisampled target into a plain one.
+    fn resolve_msaa(&mut self, src: TgtColorRes, dst: TgtColorView) {
+        self.encoder.draw(
+            &gfx::Slice {
+                start: self.screen_model.vertex_range().start,
+                end: self.screen_model.vertex_range().end,
+                base_vertex: 0,
+                instances: None,
+                buffer: gfx::IndexBuffer::Auto,
+            },
+            &self.pipelines.resolve.pso,
+            &postprocess::resolve_pipe::Data {
+                vbuf: self.screen_model.vbuf.clone(),
+                src_sampler: (src, self.sampler.clone()),
+                tgt_color: dst,
+            },
+        )
+    }
 }
 
 struct GfxPipeline<P: gfx::pso::PipelineInit> {
     pso: gfx::pso::PipelineState<gfx_backend::Resources, P::Meta>,
 }
 
+/// Declares the pipelines used to render, each with the vertex and fragment shaders and the face
+/// culling that it's built with. This generates `Pipelines` to hold them, which builds them all at
+/// once.
+macro_rules! pipelines {
+    ($(
+        $(#[$attr:meta])*
+        $name:ident: $($pipe:ident)::+ = ($vert:literal, $frag:literal, $cull:ident),
+    )*) => {
+        struct Pipelines {
+            $($(#[$attr])* $name: GfxPipeline<$($pipe)::+::Init<'static>>,)*
+        }
+
+        impl Pipelines {
+            fn new(
+                factory: &mut gfx_backend::Factory,
+                include_ctx: &IncludeContext,
+                shader_reload_indicator: &mut ReloadIndicator,
+            ) -> Result<Self, RenderError> {
+                Ok(Self {
+                    $($name: create_pipeline(
+                        factory,
+                        $($pipe)::+::new(),
+                        &assets::load_watched::<String>(
+                            concat!("voxygen.shaders.", $vert),
+                            shader_reload_indicator,
+                        )
+                        .unwrap(),
+                        &assets::load_watched::<String>(
+                            concat!("voxygen.shaders.", $frag),
+                            shader_reload_indicator,
+                        )
+                        .unwrap(),
+                        include_ctx,
+                        gfx::state::CullFace::$cull,
+                    )?,)*
+                })
+            }
+        }
+    };
+}
+
+pipelines! {
+    skybox: skybox::pipe = ("skybox-vert", "skybox-frag", Back),
+    /// The distant landscape.
+    lod: lod::pipe = ("lod-vert", "lod-frag", Back),
+    figure: figure::pipe = ("figure-vert", "figure-frag", Back),
+    /// Many figures sharing a model at once.
+    figure_instanced: figure::instanced_pipe = ("figure-instanced-vert", "figure-frag", Back),
+    terrain: terrain::pipe = ("terrain-vert", "terrain-frag", Back),
+    fluid: fluid::pipe = ("fluid-vert", "fluid-frag", Nothing),
+    sprite: sprite::pipe = ("sprite-vert", "sprite-frag", Back),
+    trail: trail::pipe = ("trail-vert", "trail-frag", Nothing),
+    particle: particle::pipe = ("particle-vert", "particle-frag", Back),
+    weather: weather::pipe = ("weather-vert", "weather-frag", Nothing),
+    /// Highlights around whatever the player is targeting. Figure outlines only keep their back
+    /// faces, so that the figure itself covers all but the rim of them.
+    outline_figure: outline::figure_pipe = ("outline-figure-vert", "outline-frag", Front),
+    outline_block: outline::block_pipe = ("outline-block-vert", "outline-frag", Nothing),
+    /// Terrain and figures drawn into the shadow map. Culling is left off so that thin geometry
+    /// still casts shadows.
+    shadow_terrain: shadow::terrain_pipe = ("shadow-terrain-vert", "shadow-frag", Nothing),
+    shadow_figure: shadow::figure_pipe = ("shadow-figure-vert", "shadow-frag", Nothing),
+    ui: ui::pipe = ("ui-vert", "ui-frag", Back),
+    postprocess: postprocess::pipe = ("postprocess-vert", "postprocess-frag", Back),
+    /// Resolves the multisampled scene.
+    resolve: postprocess::resolve_pipe = ("resolve-vert", "resolve-frag", Back),
+}
+
 /// Creates all the pipelines used to render.
 fn create_pipelines(
     factory: &mut gfx_backend::Factory,
+    aa_mode: AaMode,
     shader_reload_indicator: &mut ReloadIndicator,
-) -> Result<
-    (
-        GfxPipeline<skybox::pipe::Init<'static>>,
-        GfxPipeline<figure::pipe::Init<'static>>,
-        GfxPipeline<terrain::pipe::Init<'static>>,
-        GfxPipeline<fluid::pipe::Init<'static>>,
-        GfxPipeline<sprite::pipe::Init<'static>>,
-        GfxPipeline<ui::pipe::Init<'static>>,
-        GfxPipeline<postprocess::pipe::Init<'static>>,
-    ),
-    RenderError,
-> {
+) -> Result<Pipelines, RenderError> {
     let globals =
         assets::load_watched::<String>("voxygen.shaders.include.globals", shader_reload_indicator)
             .unwrap();
@@ -596,6 +1255,12 @@ fn create_pipelines(
     let random =
         assets::load_watched::<String>("voxygen.shaders.include.random", shader_reload_indicator)
             .unwrap();
+    let shadow =
+        assets::load_watched::<String>("voxygen.shaders.include.shadow", shader_reload_indicator)
+            .unwrap();
+    let sway =
+        assets::load_watched::<String>("voxygen.shaders.include.sway", shader_reload_indicator)
+            .unwrap();
 
     let mut include_ctx = IncludeContext::new();
     include_ctx.include("globals.glsl", &globals);
@@ -603,106 +1268,12 @@ fn create_pipelines(
     include_ctx.include("light.glsl", &light);
     include_ctx.include("srgb.glsl", &srgb);
     include_ctx.include("random.glsl", &random);
+    include_ctx.include("shadow.glsl", &shadow);
+    include_ctx.include("sway.glsl", &sway);
+    let anti_aliasing = aa_mode.shader_defines();
+    include_ctx.include("anti-aliasing.glsl", &anti_aliasing);
 
-    // Construct a pipeline for rendering skyboxes
-    let skybox_pipeline = create_pipeline(
-        factory,
-        skybox::pipe::new(),
-        &assets::load_watched::<String>("voxygen.shaders.skybox-vert", shader_reload_indicator)
-            .unwrap(),
-        &assets::load_watched::<String>("voxygen.shaders.skybox-frag", shader_reload_indicator)
-            .unwrap(),
-        &include_ctx,
-        gfx::state::CullFace::Back,
-    )?;
-
-    // Construct a pipeline for rendering figures
-    let figure_pipeline = create_pipeline(
-        factory,
-        figure::pipe::new(),
-        &assets::load_watched::<String>("voxygen.shaders.figure-vert", shader_reload_indicator)
-            .unwrap(),
-        &assets::load_watched::<String>("voxygen.shaders.figure-frag", shader_reload_indicator)
-            .unwrap(),
-        &include_ctx,
-        gfx::state::CullFace::Back,
-    )?;
-
-    // Construct a pipeline for rendering terrain
-    let terrain_pipeline = create_pipeline(
-        factory,
-        terrain::pipe::new(),
-        &assets::load_watched::<String>("voxygen.shaders.terrain-vert", shader_reload_indicator)
-            .unwrap(),
-        &assets::load_watched::<String>("voxygen.shaders.terrain-frag", shader_reload_indicator)
-            .unwrap(),
-        &include_ctx,
-        gfx::state::CullFace::Back,
-    )?;
-
-    // Construct a pipeline for rendering fluids
-    let fluid_pipeline = create_pipeline(
-        factory,
-        fluid::pipe::new(),
-        &assets::load_watched::<String>("voxygen.shaders.fluid-vert", shader_reload_indicator)
-            .unwrap(),
-        &assets::load_watched::<String>("voxygen.shaders.fluid-frag", shader_reload_indicator)
-            .unwrap(),
-        &include_ctx,
-        gfx::state::CullFace::Nothing,
-    )?;
-
-    // Construct a pipeline for rendering sprites
-    let sprite_pipeline = create_pipeline(
-        factory,
-        sprite::pipe::new(),
-        &assets::load_watched::<String>("voxygen.shaders.sprite-vert", shader_reload_indicator)
-            .unwrap(),
-        &assets::load_watched::<String>("voxygen.shaders.sprite-frag", shader_reload_indicator)
-            .unwrap(),
-        &include_ctx,
-        gfx::state::CullFace::Back,
-    )?;
-
-    // Construct a pipeline for rendering UI elements
-    let ui_pipeline = create_pipeline(
-        factory,
-        ui::pipe::new(),
-        &assets::load_watched::<String>("voxygen.shaders.ui-vert", shader_reload_indicator)
-            .unwrap(),
-        &assets::load_watched::<String>("voxygen.shaders.ui-frag", shader_reload_indicator)
-            .unwrap(),
-        &include_ctx,
-        gfx::state::CullFace::Back,
-    )?;
-
-    // Construct a pipeline for rendering our post-processing
-    let postprocess_pipeline = create_pipeline(
-        factory,
-        postprocess::pipe::new(),
-        &assets::load_watched::<String>(
-            "voxygen.shaders.postprocess-vert",
-            shader_reload_indicator,
-        )
-        .unwrap(),
-        &assets::load_watched::<String>(
-            "voxygen.shaders.postprocess-frag",
-            shader_reload_indicator,
-        )
-        .unwrap(),
-        &include_ctx,
-        gfx::state::CullFace::Back,
-    )?;
-
-    Ok((
-        skybox_pipeline,
-        figure_pipeline,
-        terrain_pipeline,
-        fluid_pipeline,
-        sprite_pipeline,
-        ui_pipeline,
-        postprocess_pipeline,
-    ))
+    Pipelines::new(factory, &include_ctx, shader_reload_indicator)
 }
 
 /// Create a new pipeline from the provided vertex shader and fragment shader.
@@ -0,0 +1,100 @@
+/// The lowest fraction of the window's resolution that the scene can be rendered at.
+pub const MIN_RENDER_SCALE: f32 = 0.5;
+/// How much the render scale changes by at a time.
+const SCALE_STEP: f32 = 0.1;
+/// How many frames to wait after changing the render scale before changing it again, so that the
+/// last change has had time to show in the GPU's frame times.
+const SETTLE_FRAMES: u32 = 30;
+/// How much of each new frame's time goes into the average that's judged.
+const SMOOTHING: f32 = 0.1;
+/// Frames have to take the GPU this much longer than the target for the render scale to be
+/// lowered, or be this much quicker for it to be raised again, so that it doesn't flicker back
+/// and forth.
+const LOWER_ABOVE: f32 = 1.2;
+const RAISE_BELOW: f32 = 0.75;
+
+/// Lowers the resolution that the scene is rendered at while the GPU takes longer over frames
+/// than it should, and raises it again once it speeds back up.
+pub struct DynamicResolution {
+    avg_gpu_time: f32,
+    since_change: u32,
+}
+
+impl DynamicResolution {
+    pub fn new() -> Self {
+        Self {
+            avg_gpu_time: 0.0,
+            since_change: 0,
+        }
+    }
+
+    /// Take in how long, in milliseconds, the GPU spent on the latest frame it's finished,
+    /// returning the render scale to switch to if it's time for a change. The scale is never
+    /// raised above `max_scale`, the one chosen in the settings.
+    pub fn update(
+        &mut self,
+        gpu_time: f32,
+        target_gpu_time: f32,
+        scale: f32,
+        max_scale: f32,
+    ) -> Option<f32> {
+        self.avg_gpu_time += (gpu_time - self.avg_gpu_time) * SMOOTHING;
+        self.since_change += 1;
+        if self.since_change < SETTLE_FRAMES {
+            return None;
+        }
+
+        let new_scale = if self.avg_gpu_time > target_gpu_time * LOWER_ABOVE {
+            (scale - SCALE_STEP).max(MIN_RENDER_SCALE)
+        } else if self.avg_gpu_time < target_gpu_time * RAISE_BELOW {
+            (scale + SCALE_STEP).min(max_scale)
+        } else {
+            scale
+        };
+
+        if (new_scale - scale).abs() > 0.001 {
+            self.since_change = 0;
+            Some(new_scale)
+        } else {
+            None
+        }
+    }
+}
+
+/// The size of the scene's render targets, for a window of the given size.
+pub fn scaled_dims(dims: (u16, u16), scale: f32) -> (u16, u16) {
+    let scale = |e: u16| ((f32::from(e) * scale).round() as u16).max(1);
+    (scale(dims.0), scale(dims.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_follows_gpu_time() {
+        let mut resolution = DynamicResolution::new();
+        let mut scale = 1.0;
+        let mut run = |gpu_time: f32, scale: &mut f32| {
+            for _ in 0..SETTLE_FRAMES * 20 {
+                if let Some(new_scale) = resolution.update(gpu_time, 12.0, *scale, 0.9) {
+                    *scale = new_scale;
+                }
+            }
+        };
+
+        // Slow frames lower the scale as far as it goes, but no further
+        run(40.0, &mut scale);
+        assert!((scale - MIN_RENDER_SCALE).abs() < 0.001);
+
+        // Quick frames raise it back up to the chosen scale
+        run(4.0, &mut scale);
+        assert!((scale - 0.9).abs() < 0.001);
+    }
+
+    #[test]
+    fn scaled_dims_never_vanish() {
+        assert_eq!(scaled_dims((1920, 1080), 0.5), (960, 540));
+        assert_eq!(scaled_dims((1, 1), 0.5), (1, 1));
+    }
+}
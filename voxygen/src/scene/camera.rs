@@ -1,6 +1,5 @@
 use client::Client;
 use common::vol::{ReadVol, Vox};
-use frustum_query::frustum::Frustum;
 use std::f32::consts::PI;
 use vek::*;
 
@@ -100,15 +99,6 @@ impl Camera {
         (view_mat, proj_mat, cam_pos)
     }
 
-    pub fn frustum(&self, client: &Client) -> Frustum {
-        let (view_mat, proj_mat, _) = self.compute_dependents(client);
-
-        Frustum::from_modelview_and_projection(
-            &view_mat.into_col_array(),
-            &proj_mat.into_col_array(),
-        )
-    }
-
     /// Rotate the camera about its focus by the given delta, limiting the input accordingly.
     pub fn rotate_by(&mut self, delta: Vec3<f32>) {
         // Wrap camera yaw
@@ -255,3 +245,73 @@ impl Camera {
         self.mode
     }
 }
+
+/// Whether any part of `aabb` could be seen by a camera with the given view-projection matrix.
+/// Boxes just outside the corners of the frustum can still pass, but anything entirely on the far
+/// side of one of its planes never does.
+pub fn aabb_in_frustum(view_proj: Mat4<f32>, aabb: Aabb<f32>) -> bool {
+    let m = view_proj.into_col_array();
+    let row = |i: usize| Vec4::new(m[i], m[4 + i], m[8 + i], m[12 + i]);
+    // The planes bounding clip space, facing inwards
+    let planes = [
+        row(3) + row(0),
+        row(3) - row(0),
+        row(3) + row(1),
+        row(3) - row(1),
+        row(3) + row(2),
+        row(3) - row(2),
+    ];
+
+    planes.iter().all(|plane| {
+        // The corner of the box that lies furthest in front of the plane
+        let corner = Vec3::new(
+            if plane.x > 0.0 {
+                aabb.max.x
+            } else {
+                aabb.min.x
+            },
+            if plane.y > 0.0 {
+                aabb.max.y
+            } else {
+                aabb.min.y
+            },
+            if plane.z > 0.0 {
+                aabb.max.z
+            } else {
+                aabb.min.z
+            },
+        );
+        Vec3::from(*plane).dot(corner) + plane.w >= 0.0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frustum_culling() {
+        // Looking down the negative z axis from the origin
+        let view_proj = Mat4::perspective_rh_no(PI / 2.0, 1.0, NEAR_PLANE, 100.0);
+        let cube = |center: Vec3<f32>| Aabb {
+            min: center - 1.0,
+            max: center + 1.0,
+        };
+
+        assert!(aabb_in_frustum(view_proj, cube(Vec3::new(0.0, 0.0, -10.0))));
+        // Straddling the edge of the view
+        assert!(aabb_in_frustum(
+            view_proj,
+            cube(Vec3::new(10.5, 0.0, -10.0))
+        ));
+        assert!(!aabb_in_frustum(view_proj, cube(Vec3::new(0.0, 0.0, 10.0))));
+        assert!(!aabb_in_frustum(
+            view_proj,
+            cube(Vec3::new(20.0, 0.0, -10.0))
+        ));
+        assert!(!aabb_in_frustum(
+            view_proj,
+            cube(Vec3::new(0.0, 0.0, -200.0))
+        ));
+    }
+}
@@ -11,8 +11,10 @@ use common::{
 use hashbrown::HashMap;
 use std::mem::{discriminant, Discriminant};
 
+/// Everything that decides what a figure's model looks like. Figures with the same key share a
+/// model, and so can be drawn together.
 #[derive(PartialEq, Eq, Hash, Clone)]
-enum FigureKey {
+pub enum FigureKey {
     Simple(Body),
     Complex(
         Body,
@@ -23,11 +25,31 @@ enum FigureKey {
 }
 
 #[derive(PartialEq, Eq, Hash, Clone)]
-struct CharacterStateCacheKey {
+pub struct CharacterStateCacheKey {
     movement: Discriminant<MovementState>,
     action: Discriminant<ActionState>,
 }
 
+impl FigureKey {
+    pub fn new(
+        body: Body,
+        equipment: Option<&Equipment>,
+        camera_mode: CameraMode,
+        character_state: Option<&CharacterState>,
+    ) -> Self {
+        if equipment.is_some() {
+            FigureKey::Complex(
+                body,
+                equipment.cloned(),
+                camera_mode,
+                character_state.map(|cs| CharacterStateCacheKey::from(cs)),
+            )
+        } else {
+            FigureKey::Simple(body)
+        }
+    }
+}
+
 impl From<&CharacterState> for CharacterStateCacheKey {
     fn from(cs: &CharacterState) -> Self {
         Self {
@@ -59,16 +81,7 @@ impl FigureModelCache {
         camera_mode: CameraMode,
         character_state: Option<&CharacterState>,
     ) -> &(Model<FigurePipeline>, SkeletonAttr) {
-        let key = if equipment.is_some() {
-            FigureKey::Complex(
-                body,
-                equipment.cloned(),
-                camera_mode,
-                character_state.map(|cs| CharacterStateCacheKey::from(cs)),
-            )
-        } else {
-            FigureKey::Simple(body)
-        };
+        let key = FigureKey::new(body, equipment, camera_mode, character_state);
 
         match self.models.get_mut(&key) {
             Some((_model, last_used)) => {
@@ -81,92 +94,111 @@ impl FigureModelCache {
                         {
                             let humanoid_head_spec =
                                 HumHeadSpec::load_watched(&mut self.manifest_indicator);
+                            let armor_spec = ArmorSpec::load_watched(&mut self.manifest_indicator);
+                            let weapon_spec =
+                                WeaponSpec::load_watched(&mut self.manifest_indicator);
+                            let weapon_drawn = character_state
+                                .map(|cs| {
+                                    cs.action.is_attack()
+                                        || cs.action.is_block()
+                                        || cs.action.is_wield()
+                                })
+                                .unwrap_or_default();
                             let bone_meshes = match body {
-                                Body::Humanoid(body) => [
-                                    match camera_mode {
-                                        CameraMode::ThirdPerson => {
-                                            Some(humanoid_head_spec.mesh_head(
-                                                body.race,
-                                                body.body_type,
-                                                body.hair_color,
-                                                body.hair_style,
-                                                body.beard,
-                                                body.eye_color,
-                                                body.skin,
-                                                body.eyebrows,
-                                                body.accessory,
-                                            ))
-                                        }
-                                        CameraMode::FirstPerson => None,
-                                    },
-                                    match camera_mode {
-                                        CameraMode::ThirdPerson => Some(mesh_chest(body.chest)),
-                                        CameraMode::FirstPerson => None,
-                                    },
-                                    match camera_mode {
-                                        CameraMode::ThirdPerson => Some(mesh_belt(body.belt)),
-                                        CameraMode::FirstPerson => None,
-                                    },
-                                    match camera_mode {
-                                        CameraMode::ThirdPerson => Some(mesh_pants(body.pants)),
-                                        CameraMode::FirstPerson => None,
-                                    },
-                                    if camera_mode == CameraMode::FirstPerson
-                                        && character_state
+                                Body::Humanoid(body) => match camera_mode {
+                                    // Only the hands and weapon are seen in first person, in the
+                                    // order of the `FirstPersonSkeleton`'s bones
+                                    CameraMode::FirstPerson => {
+                                        let rolling = character_state
+                                            .map(|cs| cs.movement.is_roll())
+                                            .unwrap_or_default();
+                                        [
+                                            if rolling {
+                                                None
+                                            } else {
+                                                Some(mesh_left_hand(body.hand, body.body_type))
+                                            },
+                                            if rolling {
+                                                None
+                                            } else {
+                                                Some(mesh_right_hand(body.hand, body.body_type))
+                                            },
+                                            if weapon_drawn {
+                                                Some(weapon_spec.mesh_main(
+                                                    equipment.and_then(|e| e.main.as_ref()),
+                                                ))
+                                            } else {
+                                                None
+                                            },
+                                            None,
+                                            None,
+                                            None,
+                                            None,
+                                            None,
+                                            None,
+                                            None,
+                                            None,
+                                            None,
+                                            None,
+                                            None,
+                                            None,
+                                            None,
+                                        ]
+                                    }
+                                    CameraMode::ThirdPerson => [
+                                        Some(humanoid_head_spec.mesh_head(
+                                            body.race,
+                                            body.body_type,
+                                            body.hair_color,
+                                            body.hair_style,
+                                            body.beard,
+                                            body.eye_color,
+                                            body.skin,
+                                            body.eyebrows,
+                                            body.accessory,
+                                        )),
+                                        Some(armor_spec.mesh_chest(body.chest)),
+                                        Some(armor_spec.mesh_belt(body.belt, body.body_type)),
+                                        Some(armor_spec.mesh_pants(body.pants)),
+                                        Some(mesh_left_hand(body.hand, body.body_type)),
+                                        if character_state
                                             .map(|cs| cs.movement.is_roll())
                                             .unwrap_or_default()
-                                    {
-                                        None
-                                    } else {
-                                        Some(mesh_left_hand(body.hand))
-                                    },
-                                    if character_state
-                                        .map(|cs| cs.movement.is_roll())
-                                        .unwrap_or_default()
-                                    {
-                                        None
-                                    } else {
-                                        Some(mesh_right_hand(body.hand))
-                                    },
-                                    match camera_mode {
-                                        CameraMode::ThirdPerson => Some(mesh_left_foot(body.foot)),
-                                        CameraMode::FirstPerson => None,
-                                    },
-                                    match camera_mode {
-                                        CameraMode::ThirdPerson => Some(mesh_right_foot(body.foot)),
-                                        CameraMode::FirstPerson => None,
-                                    },
-                                    if camera_mode != CameraMode::FirstPerson
-                                        || character_state
-                                            .map(|cs| {
-                                                cs.action.is_attack()
-                                                    || cs.action.is_block()
-                                                    || cs.action.is_wield()
-                                            })
-                                            .unwrap_or_default()
-                                    {
-                                        Some(mesh_main(equipment.and_then(|e| e.main.as_ref())))
-                                    } else {
-                                        None
-                                    },
-                                    match camera_mode {
-                                        CameraMode::ThirdPerson => {
-                                            Some(mesh_left_shoulder(body.shoulder))
-                                        }
-                                        CameraMode::FirstPerson => None,
-                                    },
-                                    match camera_mode {
-                                        CameraMode::ThirdPerson => {
-                                            Some(mesh_right_shoulder(body.shoulder))
-                                        }
-                                        CameraMode::FirstPerson => None,
-                                    },
-                                    Some(mesh_draw()),
-                                    None,
-                                    None,
-                                    None,
-                                    None,
-                                ],
+                                        {
+                                            None
+                                        } else {
+                                            Some(mesh_right_hand(body.hand, body.body_type))
+                                        },
+                                        Some(armor_spec.mesh_left_foot(body.foot, body.body_type)),
+                                        Some(armor_spec.mesh_right_foot(body.foot, body.body_type)),
+                                        // Sheathed weapons are placed by the animations, and
+                                        // drawn ones are held in the right hand
+                                        if weapon_drawn {
+                                            None
+                                        } else {
+                                            Some(
+                                                weapon_spec.mesh_main(
+                                                    equipment.and_then(|e| e.main.as_ref()),
+                                                ),
+                                            )
+                                        },
+                                        Some(armor_spec.mesh_left_shoulder(body.shoulder)),
+                                        Some(armor_spec.mesh_right_shoulder(body.shoulder)),
+                                        Some(mesh_draw()),
+                                        None,
+                                        if weapon_drawn {
+                                            Some(
+                                                weapon_spec.mesh_main(
+                                                    equipment.and_then(|e| e.main.as_ref()),
+                                                ),
+                                            )
+                                        } else {
+                                            None
+                                        },
+                                        None,
+                                        None,
+                                    ],
+                                },
                                 Body::Quadruped(body) => [
                                     Some(mesh_pig_head(body.head)),
                                     Some(mesh_pig_chest(body.chest)),
@@ -203,6 +235,42 @@ impl FigureModelCache {
                                     None,
                                     None,
                                 ],
+                                Body::BirdMedium(body) => [
+                                    Some(mesh_bird_torso(body.torso)),
+                                    Some(mesh_bird_head(body.head)),
+                                    Some(mesh_bird_tail(body.tail)),
+                                    Some(mesh_bird_wing_l(body.wing_l)),
+                                    Some(mesh_bird_wing_r(body.wing_r)),
+                                    Some(mesh_bird_leg_l(body.leg_l)),
+                                    Some(mesh_bird_leg_r(body.leg_r)),
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                ],
+                                Body::FishSmall(body) => [
+                                    Some(mesh_fish_head(body.species)),
+                                    Some(mesh_fish_body_front(body.species)),
+                                    Some(mesh_fish_body_back(body.species)),
+                                    Some(mesh_fish_tail(body.species)),
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                    None,
+                                ],
                                 Body::Object(object) => [
                                     Some(mesh_object(object)),
                                     None,
@@ -250,11 +318,18 @@ impl FigureModelCache {
         &self.models[&key].0
     }
 
+    /// The model for `key`, if it was created by `get_or_create_model` and hasn't been cleaned up
+    /// since.
+    pub fn get_model(&self, key: &FigureKey) -> Option<&(Model<FigurePipeline>, SkeletonAttr)> {
+        self.models.get(key).map(|(model, _)| model)
+    }
+
     pub fn clean(&mut self, tick: u64) {
         // Check for reloaded manifests
         // TODO: maybe do this in a different function, maintain?
         if self.manifest_indicator.reloaded() {
             self.models.clear();
+            clear_figure_cache();
         }
         // TODO: Don't hard-code this.
         self.models
@@ -0,0 +1,154 @@
+use crate::anim::{
+    character::{CharacterSkeleton, L_FOOT_BONE, R_FOOT_BONE},
+    keyframe::KeyframeSkeleton,
+    Skeleton,
+};
+use common::{terrain::TerrainGrid, vol::ReadVol};
+use vek::*;
+
+/// How far, in blocks, a foot reaches up or down to meet the ground.
+pub const MAX_FOOT_SHIFT: f32 = 0.6;
+/// How quickly feet settle onto new ground, per second.
+const SETTLE_RATE: f32 = 12.0;
+
+/// Vertical adjustments, in blocks, that plant a character's feet on uneven ground.
+///
+/// The character rig has no knees, so the chain from the hips to each foot is solved by lowering
+/// the hips (the torso) until the lower foot reaches its ground, then raising the other foot to
+/// meet its own.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FootPlacement {
+    pub l_foot: f32,
+    pub r_foot: f32,
+    pub torso: f32,
+}
+
+impl FootPlacement {
+    /// Plant the feet on the ground below them, given as heights relative to the character's
+    /// position.
+    pub fn solve(l_ground: f32, r_ground: f32) -> Self {
+        let l_ground = l_ground.min(MAX_FOOT_SHIFT).max(-MAX_FOOT_SHIFT);
+        let r_ground = r_ground.min(MAX_FOOT_SHIFT).max(-MAX_FOOT_SHIFT);
+        // The hips are only ever lowered, since raising them would leave both feet in the air
+        let torso = l_ground.min(r_ground).min(0.0);
+        Self {
+            l_foot: l_ground - torso,
+            r_foot: r_ground - torso,
+            torso,
+        }
+    }
+
+    /// Move towards `target` over time, so that feet don't snap between steps.
+    pub fn approach(&mut self, target: Self, dt: f32) {
+        let factor = 1.0 - (-dt * SETTLE_RATE).exp();
+        self.l_foot += (target.l_foot - self.l_foot) * factor;
+        self.r_foot += (target.r_foot - self.r_foot) * factor;
+        self.torso += (target.torso - self.torso) * factor;
+    }
+
+    /// Shift the torso and feet of an animated `skeleton`, drawn at `figure_scale` blocks per
+    /// unit of figure space.
+    pub fn apply<S: KeyframeSkeleton>(&self, skeleton: &mut S, figure_scale: f32) {
+        let torso_scale = match skeleton.bone_mut("torso") {
+            Some(torso) => {
+                torso.offset.z += self.torso / figure_scale;
+                torso.scale.z
+            }
+            None => return,
+        };
+        // Feet are posed in the space of the torso, so are scaled by it too
+        let foot_scale = figure_scale * torso_scale;
+        if let Some(foot) = skeleton.bone_mut("l_foot") {
+            foot.offset.z += self.l_foot / foot_scale;
+        }
+        if let Some(foot) = skeleton.bone_mut("r_foot") {
+            foot.offset.z += self.r_foot / foot_scale;
+        }
+    }
+}
+
+/// Where the left and right feet of a character at `pos` facing `ori` are over the ground, at
+/// the height of the character's position.
+pub fn foot_positions(
+    skeleton: &CharacterSkeleton,
+    pos: Vec3<f32>,
+    ori: Vec3<f32>,
+    figure_scale: f32,
+) -> [Vec3<f32>; 2] {
+    let matrices = skeleton.compute_matrices();
+    let rotation = Quaternion::rotation_z(-ori.x.atan2(ori.y));
+    let foot_pos = |bone: usize| {
+        let col = matrices[bone].bone_mat[3];
+        let offset = rotation * (Vec3::new(col[0], col[1], 0.0) * figure_scale);
+        pos + offset
+    };
+    [foot_pos(L_FOOT_BONE), foot_pos(R_FOOT_BONE)]
+}
+
+/// The height of the ground at `pos` relative to it, looking no further than `MAX_FOOT_SHIFT`
+/// up or down.
+pub fn ground_height(terrain: &TerrainGrid, pos: Vec3<f32>) -> f32 {
+    let from = pos + Vec3::unit_z() * MAX_FOOT_SHIFT;
+    let to = pos - Vec3::unit_z() * MAX_FOOT_SHIFT;
+    match terrain.ray(from, to).ignore_error().cast() {
+        (dist, Ok(Some(_))) => MAX_FOOT_SHIFT - dist,
+        _ => -MAX_FOOT_SHIFT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn foot_placement() {
+        assert_eq!(FootPlacement::solve(0.0, 0.0), FootPlacement::default());
+
+        // Stepping down a slope lowers the hips to the lower foot
+        let placement = FootPlacement::solve(0.0, -0.4);
+        assert_eq!(placement.torso, -0.4);
+        assert_eq!(placement.l_foot, 0.4);
+        assert_eq!(placement.r_foot, 0.0);
+
+        // Feet don't reach too far
+        let placement = FootPlacement::solve(2.0, -2.0);
+        assert_eq!(placement.torso, -MAX_FOOT_SHIFT);
+        assert_eq!(placement.l_foot, 2.0 * MAX_FOOT_SHIFT);
+
+        // The feet end up on their ground once applied
+        let mut skeleton = CharacterSkeleton::neutral();
+        let before = foot_heights(&skeleton);
+        FootPlacement::solve(0.2, -0.3).apply(&mut skeleton, 0.8);
+        let after = foot_heights(&skeleton);
+        assert!((after[0] - before[0] - 0.2 / 0.8).abs() < 1e-4);
+        assert!((after[1] - before[1] + 0.3 / 0.8).abs() < 1e-4);
+    }
+
+    #[test]
+    fn foot_placement_does_not_build_up() {
+        let placement = FootPlacement::solve(0.0, -0.4);
+        let pose = CharacterSkeleton::neutral();
+        let mut skeleton = CharacterSkeleton::neutral();
+        let mut torso_heights = Vec::new();
+        for _ in 0..240 {
+            // As each frame does, plant the feet in the pose before smoothing towards it
+            let mut target = pose.clone();
+            placement.apply(&mut target, 0.8);
+            skeleton.interpolate(&target, 1.0 / 60.0);
+            torso_heights.push(skeleton.bone_mut("torso").unwrap().offset.z);
+        }
+
+        // The hips settle where they were planted, and stay there
+        let planted = pose.clone().bone_mut("torso").unwrap().offset.z + placement.torso / 0.8;
+        assert!((torso_heights[239] - planted).abs() < 1e-3);
+        assert!((torso_heights[239] - torso_heights[179]).abs() < 1e-3);
+    }
+
+    fn foot_heights(skeleton: &CharacterSkeleton) -> [f32; 2] {
+        let matrices = skeleton.compute_matrices();
+        [
+            matrices[L_FOOT_BONE].bone_mat[3][2],
+            matrices[R_FOOT_BONE].bone_mat[3][2],
+        ]
+    }
+}
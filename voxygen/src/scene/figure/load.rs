@@ -1,24 +1,28 @@
 use crate::{
-    mesh::Meshable,
+    mesh::{segment::generate_glowing_mesh, Meshable},
     render::{FigurePipeline, Mesh},
 };
 use common::{
     assets::{self, watch::ReloadIndicator, Asset},
     comp::{
+        bird_medium, fish_small,
         humanoid::{
-            Accessory, Beard, Belt, BodyType, Chest, EyeColor, Eyebrows, Foot, HairStyle, Hand,
-            Pants, Race, Shoulder,
+            self, Accessory, Beard, Belt, BodyType, Chest, EyeColor, Eyebrows, Foot, HairStyle,
+            Hand, Pants, Race, Shoulder,
         },
         item::Tool,
         object, quadruped, quadruped_medium, Item,
     },
-    figure::{DynaUnionizer, MatSegment, Material, Segment},
+    figure::{mat_cell::MatCell, DynaUnionizer, MatSegment, Material, Segment},
+    vol::IntoFullVolIterator,
 };
 use dot_vox::DotVoxData;
 use hashbrown::HashMap;
+use lazy_static::lazy_static;
 use log::{error, warn};
+use parking_lot::Mutex;
 use serde_derive::{Deserialize, Serialize};
-use std::{fs::File, io::BufReader, sync::Arc};
+use std::{fmt::Debug, fs::File, hash::Hash, io::BufReader, sync::Arc};
 use vek::*;
 
 fn load_segment(mesh_name: &str) -> Segment {
@@ -42,8 +46,60 @@ fn graceful_load_mat_segment(mesh_name: &str) -> MatSegment {
     MatSegment::from(graceful_load_vox(mesh_name).as_ref())
 }
 
+/// The name of the model of `mesh_name` specific to `body_type` (e.g: `figure.body.hand_female`)
+/// if one exists, falling back to the model shared by all body types otherwise.
+fn body_type_mesh_name(mesh_name: &str, body_type: BodyType) -> String {
+    MESH_NAME_CACHE
+        .lock()
+        .entry((mesh_name.to_string(), body_type))
+        .or_insert_with(|| {
+            let suffix = match body_type {
+                BodyType::Female => "_female",
+                BodyType::Male => "_male",
+            };
+            let specific_name = [mesh_name, suffix].concat();
+            let full_specifier: String = ["voxygen.voxel.", &specific_name].concat();
+            match assets::load::<DotVoxData>(&full_specifier) {
+                Ok(_) => specific_name,
+                Err(_) => mesh_name.to_string(),
+            }
+        })
+        .clone()
+}
+
+lazy_static! {
+    /// Names chosen by `body_type_mesh_name`, so that missing body type specific models aren't
+    /// looked for on disk every time.
+    static ref MESH_NAME_CACHE: Mutex<HashMap<(String, BodyType), String>> =
+        Mutex::new(HashMap::new());
+    /// Meshes generated by `load_mesh`, keyed by mesh name and the bit patterns of the offset
+    /// (offsets may be fractional, so they can't simply be truncated).
+    static ref MESH_CACHE: Mutex<HashMap<(String, [u32; 3]), Mesh<FigurePipeline>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Forget all cached figure meshes, so that they're regenerated from reloaded assets.
+pub fn clear_figure_cache() {
+    MESH_CACHE.lock().clear();
+    MESH_NAME_CACHE.lock().clear();
+}
+
 pub fn load_mesh(mesh_name: &str, position: Vec3<f32>) -> Mesh<FigurePipeline> {
-    Meshable::<FigurePipeline, FigurePipeline>::generate_mesh(&load_segment(mesh_name), position).0
+    let key = (
+        mesh_name.to_string(),
+        position.map(|e| e.to_bits()).into_array(),
+    );
+    if let Some(mesh) = MESH_CACHE.lock().get(&key) {
+        return mesh.clone();
+    }
+
+    let mesh = Meshable::<FigurePipeline, FigurePipeline>::generate_mesh(
+        &load_segment(mesh_name),
+        position,
+    )
+    .0;
+    MESH_CACHE.lock().insert(key, mesh.clone());
+    mesh
 }
 
 fn color_segment(
@@ -63,33 +119,129 @@ fn color_segment(
     })
 }
 
+/// How much a voxel of the given material glows, where `eye_glow` is the glow of the figure's eyes.
+fn material_glow(mat: Material, eye_glow: f32) -> f32 {
+    match mat {
+        Material::EyeLight | Material::EyeDark => eye_glow,
+        Material::Skin | Material::Hair | Material::EyeWhite => 0.0,
+    }
+}
+
+/// The glowing voxels of a segment, at the positions they take when the segment is placed at
+/// `offset` within a larger volume.
+fn glowing_voxels(
+    mat_segment: &MatSegment,
+    offset: Vec3<i32>,
+    eye_glow: f32,
+) -> Vec<(Vec3<i32>, f32)> {
+    mat_segment
+        .full_vol_iter()
+        .filter_map(|(pos, cell)| match cell {
+            MatCell::Mat(mat) => Some((offset + pos, material_glow(*mat, eye_glow))),
+            _ => None,
+        })
+        .filter(|(_, glow)| *glow > 0.0)
+        .collect()
+}
+
+/// The grey level that greyscale voxel models are assumed to be authored at.
+const BASE_GREY: f32 = 178.0;
+
 fn recolor_greys(segment: Segment, color: Rgb<u8>) -> Segment {
-    use common::util::{linear_to_srgb, srgb_to_linear};
+    recolor_greys_tol(segment, color, 0)
+}
+
+/// Like `recolor_greys`, but also treating voxels as grey when their channels differ by at most
+/// `tol`, to catch slightly off-grey voxels left over from editing or palette quantization. The
+/// average of the channels is used as the grey level of such voxels.
+pub fn recolor_greys_tol(segment: Segment, color: Rgb<u8>, tol: u8) -> Segment {
+    recolor_greys_base(segment, color, BASE_GREY, tol)
+}
+
+/// Like `recolor_greys_tol`, but using the average grey level of the segment as the base level,
+/// for models that weren't authored around `BASE_GREY`.
+fn recolor_greys_auto(segment: Segment, color: Rgb<u8>, tol: u8) -> Segment {
+    let base_grey = average_grey(&segment).unwrap_or(BASE_GREY);
+    recolor_greys_base(segment, color, base_grey, tol)
+}
+
+/// Tint the grey voxels (those whose channels differ by at most `tol`) of a segment with `color`,
+/// where voxels at the `base_grey` level end up exactly `color` and lighter or darker greys are
+/// scaled accordingly.
+///
+/// The tinting is done in OKLCh: the lightness of each grey (relative to `base_grey`) scales the
+/// lightness of `color`, while its hue is kept as is, so that the shading of the template remains
+/// readable even for saturated colors.
+fn recolor_greys_base(segment: Segment, color: Rgb<u8>, base_grey: f32, tol: u8) -> Segment {
+    use common::util::{
+        linear_to_oklab, linear_to_srgb, oklab_to_linear, oklab_to_oklch, oklch_to_oklab,
+        srgb_to_linear,
+    };
+
+    let lightness = |grey: f32| linear_to_oklab(srgb_to_linear(Rgb::broadcast(grey / 255.0))).x;
+    let base_lightness = lightness(base_grey);
+    let target = oklab_to_oklch(linear_to_oklab(srgb_to_linear(
+        color.map(|e| e as f32 / 255.0),
+    )));
 
     segment.map_rgb(|rgb| {
-        const BASE_GREY: f32 = 178.0;
-        if rgb.r == rgb.g && rgb.g == rgb.b {
-            let c1 = srgb_to_linear(rgb.map(|e| e as f32 / BASE_GREY));
-            let c2 = srgb_to_linear(color.map(|e| e as f32 / 255.0));
+        let max = rgb.r.max(rgb.g).max(rgb.b);
+        let min = rgb.r.min(rgb.g).min(rgb.b);
+        if max - min <= tol {
+            let grey = (rgb.r as f32 + rgb.g as f32 + rgb.b as f32) / 3.0;
+            let ratio = lightness(grey) / base_lightness;
+            // Darker shades are also less saturated, as they would be under real lighting
+            let lch = Vec3::new(target.x * ratio, target.y * ratio.min(1.0), target.z);
 
-            linear_to_srgb(c1 * c2).map(|e| (e.min(1.0).max(0.0) * 255.0) as u8)
+            linear_to_srgb(oklab_to_linear(oklch_to_oklab(lch)).map(|e| e.min(1.0).max(0.0)))
+                .map(|e| (e.min(1.0).max(0.0) * 255.0) as u8)
         } else {
             rgb
         }
     })
 }
 
+/// Replace each voxel whose color exactly matches one of the source colors in `mapping` with the
+/// paired target color, e.g: to dye the flat marker colors of a two-tone template independently.
+/// Voxels that match no source color are left untouched.
+pub fn recolor_palette(segment: Segment, mapping: &[(Rgb<u8>, Rgb<u8>)]) -> Segment {
+    segment.map_rgb(|rgb| {
+        mapping
+            .iter()
+            .find(|(src, _)| *src == rgb)
+            .map(|(_, dst)| *dst)
+            .unwrap_or(rgb)
+    })
+}
+
+/// The average level of the grey voxels in a segment, if it has any.
+fn average_grey(segment: &Segment) -> Option<f32> {
+    let (sum, count) = segment
+        .full_vol_iter()
+        .filter_map(|(_, cell)| cell.get_color())
+        .filter(|rgb| rgb.r == rgb.g && rgb.g == rgb.b)
+        .fold((0.0, 0), |(sum, count), rgb| {
+            (sum + rgb.r as f32, count + 1)
+        });
+
+    if count > 0 {
+        Some(sum / count as f32)
+    } else {
+        None
+    }
+}
+
 #[derive(Serialize, Deserialize)]
-struct VoxSpec(String, [i32; 3]); // All offsets should be relative to an initial origin that doesn't change when combining segments
-                                  // All reliant on humanoid::Race and humanoid::BodyType
+struct VoxSpec<T>(String, [T; 3]); // All offsets should be relative to an initial origin that doesn't change when combining segments
+                                   // All reliant on humanoid::Race and humanoid::BodyType
 #[derive(Serialize, Deserialize)]
 struct HumHeadSubSpec {
     offset: [f32; 3], // Should be relative to initial origin
-    head: VoxSpec,
-    eyes: VoxSpec,
-    hair: HashMap<HairStyle, Option<VoxSpec>>,
-    beard: HashMap<Beard, Option<VoxSpec>>,
-    accessory: HashMap<Accessory, Option<VoxSpec>>,
+    head: VoxSpec<i32>,
+    eyes: VoxSpec<i32>,
+    hair: HashMap<HairStyle, Option<VoxSpec<i32>>>,
+    beard: HashMap<Beard, Option<VoxSpec<i32>>>,
+    accessory: HashMap<Accessory, Option<VoxSpec<i32>>>,
 }
 #[derive(Serialize, Deserialize)]
 pub struct HumHeadSpec(HashMap<(Race, BodyType), HumHeadSubSpec>);
@@ -166,6 +318,11 @@ impl HumHeadSpec {
             }
         };
 
+        let glowing = glowing_voxels(&bare_head, spec.head.1.into(), race.eye_glow())
+            .into_iter()
+            .chain(glowing_voxels(&eyes, spec.eyes.1.into(), race.eye_glow()))
+            .collect::<HashMap<_, _>>();
+
         let (head, origin_offset) = DynaUnionizer::new()
             .add(
                 color_segment(bare_head, skin_rgb, hair_rgb, eye_color),
@@ -180,150 +337,384 @@ impl HumHeadSpec {
             .maybe_add(accessory)
             .unify();
 
-        Meshable::<FigurePipeline, FigurePipeline>::generate_mesh(
+        generate_glowing_mesh(
             &head,
             Vec3::from(spec.offset) + origin_offset.map(|e| e as f32 * -1.0),
+            |pos| glowing.get(&(pos - origin_offset)).cloned().unwrap_or(0.0),
         )
-        .0
     }
 }
 
-pub fn mesh_chest(chest: Chest) -> Mesh<FigurePipeline> {
-    let color = match chest {
-        Chest::Blue => (28, 66, 109),
-        Chest::Brown => (54, 30, 26),
-        Chest::Dark => (24, 19, 17),
-        Chest::Green => (49, 95, 59),
-        Chest::Orange => (148, 52, 33),
-    };
+/// How far from grey the voxels of armor templates can be while still being dyed.
+const DYE_TOLERANCE: u8 = 4;
 
+/// Load a greyscale armor template and dye it with `dye`.
+fn dyed_segment(base: &str, dye: Rgb<u8>) -> Segment {
+    recolor_greys_tol(graceful_load_segment(base), dye, DYE_TOLERANCE)
+}
+
+/// Mesh a chest armor segment over the bare chest.
+fn mesh_chest_armor(armor: Segment, offset: Vec3<f32>) -> Mesh<FigurePipeline> {
     let bare_chest = graceful_load_segment("figure.body.chest");
-    let chest_armor = graceful_load_segment("armor.chest.grayscale");
     let chest = DynaUnionizer::new()
         .add(bare_chest, Vec3::new(0, 0, 0))
-        .add(
-            recolor_greys(chest_armor, Rgb::from(color)),
-            Vec3::new(0, 0, 0),
-        )
+        .add(armor, Vec3::new(0, 0, 0))
         .unify()
         .0;
 
-    Meshable::<FigurePipeline, FigurePipeline>::generate_mesh(&chest, Vec3::new(-6.0, -3.5, 0.0)).0
+    Meshable::<FigurePipeline, FigurePipeline>::generate_mesh(&chest, offset).0
 }
 
-pub fn mesh_belt(belt: Belt) -> Mesh<FigurePipeline> {
-    load_mesh(
-        match belt {
-            //Belt::Default => "figure/body/belt_male",
-            Belt::Dark => "armor.belt.belt_dark",
-        },
-        Vec3::new(-5.0, -3.5, 0.0),
-    )
+/// Mesh the greyscale chest armor template `base` dyed with an arbitrary color, e.g: one picked
+/// in the character creator.
+pub fn mesh_chest_dyed(base: &str, dye: Rgb<u8>) -> Mesh<FigurePipeline> {
+    let offset = armor_offset(&ArmorSpec::load().chest, base);
+    mesh_chest_armor(dyed_segment(base, dye), offset)
 }
 
-pub fn mesh_pants(pants: Pants) -> Mesh<FigurePipeline> {
-    let color = match pants {
-        Pants::Blue => (28, 66, 109),
-        Pants::Brown => (54, 30, 26),
-        Pants::Dark => (24, 19, 17),
-        Pants::Green => (49, 95, 59),
-        Pants::Orange => (148, 52, 33),
-    };
-
-    let pants_segment = recolor_greys(
-        graceful_load_segment("armor.pants.grayscale"),
-        Rgb::from(color),
-    );
-
+/// Mesh the greyscale pants template `base` dyed with an arbitrary color.
+pub fn mesh_pants_dyed(base: &str, dye: Rgb<u8>) -> Mesh<FigurePipeline> {
     Meshable::<FigurePipeline, FigurePipeline>::generate_mesh(
-        &pants_segment,
-        Vec3::new(-5.0, -3.5, 0.0),
+        &dyed_segment(base, dye),
+        armor_offset(&ArmorSpec::load().pants, base),
     )
     .0
 }
 
-pub fn mesh_left_hand(hand: Hand) -> Mesh<FigurePipeline> {
-    load_mesh(
-        match hand {
-            Hand::Default => "figure.body.hand",
-        },
-        Vec3::new(-2.0, -2.5, -2.0),
-    )
+/// Mesh the greyscale shoulder template `base` (for either side) dyed with an arbitrary color.
+pub fn mesh_shoulder_dyed(base: &str, dye: Rgb<u8>) -> Mesh<FigurePipeline> {
+    let offset = found_offset(
+        ArmorSpec::load()
+            .shoulder
+            .values()
+            .filter_map(|spec| spec.as_ref())
+            .find(|spec| spec.left == base || spec.right == base)
+            .map(|spec| Vec3::from(spec.offset)),
+        base,
+    );
+    Meshable::<FigurePipeline, FigurePipeline>::generate_mesh(&dyed_segment(base, dye), offset).0
 }
 
-pub fn mesh_right_hand(hand: Hand) -> Mesh<FigurePipeline> {
-    load_mesh(
-        match hand {
-            Hand::Default => "figure.body.hand",
-        },
-        Vec3::new(-2.0, -2.5, -2.0),
-    )
+/// A piece of armor, optionally dyed by recoloring the greys of its model.
+#[derive(Serialize, Deserialize)]
+struct ArmorVoxSpec {
+    vox_spec: VoxSpec<f32>,
+    dye: Option<[u8; 3]>,
+    /// Dye the model relative to its own average grey rather than `BASE_GREY`, for templates
+    /// authored at a lighter or darker grey.
+    #[serde(default)]
+    auto_base: bool,
+}
+/// A piece of armor with a model for each side, which share one offset and dye so that they
+/// stay in sync.
+#[derive(Serialize, Deserialize)]
+struct SidedArmorVoxSpec {
+    left: String,
+    right: String,
+    offset: [f32; 3],
+    dye: Option<[u8; 3]>,
+    #[serde(default)]
+    auto_base: bool,
+}
+/// The models used for each variant of each armor slot. Feet use the same model on both sides.
+#[derive(Serialize, Deserialize)]
+pub struct ArmorSpec {
+    chest: HashMap<Chest, ArmorVoxSpec>,
+    belt: HashMap<Belt, ArmorVoxSpec>,
+    pants: HashMap<Pants, ArmorVoxSpec>,
+    foot: HashMap<Foot, ArmorVoxSpec>,
+    shoulder: HashMap<Shoulder, Option<SidedArmorVoxSpec>>,
 }
 
-pub fn mesh_left_foot(foot: Foot) -> Mesh<FigurePipeline> {
-    load_mesh(
-        match foot {
-            Foot::Dark => "armor.foot.foot_dark",
-        },
-        Vec3::new(-2.5, -3.5, -9.0),
-    )
+impl Asset for ArmorSpec {
+    const ENDINGS: &'static [&'static str] = &["ron"];
+    fn parse(buf_reader: BufReader<File>) -> Result<Self, assets::Error> {
+        Ok(ron::de::from_reader(buf_reader).expect("Error parsing armor spec"))
+    }
 }
 
-pub fn mesh_right_foot(foot: Foot) -> Mesh<FigurePipeline> {
-    load_mesh(
-        match foot {
-            Foot::Dark => "armor.foot.foot_dark",
-        },
-        Vec3::new(-2.5, -3.5, -9.0),
+impl SidedArmorVoxSpec {
+    fn side(&self, mesh_name: &str) -> ArmorVoxSpec {
+        ArmorVoxSpec {
+            vox_spec: VoxSpec(mesh_name.to_string(), self.offset),
+            dye: self.dye,
+            auto_base: self.auto_base,
+        }
+    }
+}
+
+impl ArmorVoxSpec {
+    fn segment(&self) -> Segment {
+        self.segment_named(&self.vox_spec.0)
+    }
+
+    fn mesh(&self) -> Mesh<FigurePipeline> {
+        self.mesh_named(&self.vox_spec.0)
+    }
+
+    /// Like `mesh`, but using the model specific to `body_type` if one exists.
+    fn mesh_for(&self, body_type: BodyType) -> Mesh<FigurePipeline> {
+        self.mesh_named(&body_type_mesh_name(&self.vox_spec.0, body_type))
+    }
+
+    fn segment_named(&self, mesh_name: &str) -> Segment {
+        match self.dye {
+            Some(dye) if self.auto_base => recolor_greys_auto(
+                graceful_load_segment(mesh_name),
+                Rgb::from(dye),
+                DYE_TOLERANCE,
+            ),
+            Some(dye) => dyed_segment(mesh_name, Rgb::from(dye)),
+            None => graceful_load_segment(mesh_name),
+        }
+    }
+
+    fn mesh_named(&self, mesh_name: &str) -> Mesh<FigurePipeline> {
+        let offset = Vec3::from(self.vox_spec.1);
+        match self.dye {
+            Some(_) => {
+                Meshable::<FigurePipeline, FigurePipeline>::generate_mesh(
+                    &self.segment_named(mesh_name),
+                    offset,
+                )
+                .0
+            }
+            // Undyed armor can be shared with the mesh cache
+            None => load_mesh(mesh_name, offset),
+        }
+    }
+}
+
+/// The offset that the manifest places the model `base` at, for meshing it with a custom dye.
+fn armor_offset<K>(specs: &HashMap<K, ArmorVoxSpec>, base: &str) -> Vec3<f32> {
+    found_offset(
+        specs
+            .values()
+            .find(|spec| spec.vox_spec.0 == base)
+            .map(|spec| Vec3::from(spec.vox_spec.1)),
+        base,
     )
 }
 
-pub fn mesh_main(item: Option<&Item>) -> Mesh<FigurePipeline> {
-    if let Some(item) = item {
-        let (name, offset) = match item {
-            Item::Tool { kind, .. } => match kind {
-                Tool::Sword => ("weapon.sword.rusty_2h", Vec3::new(-1.5, -6.5, -4.0)),
-                Tool::Axe => ("weapon.axe.rusty_2h", Vec3::new(-1.5, -5.0, -4.0)),
-                Tool::Hammer => ("weapon.hammer.rusty_2h", Vec3::new(-2.5, -5.5, -4.0)),
-                Tool::Daggers => ("weapon.hammer.rusty_2h", Vec3::new(-2.5, -5.5, -4.0)),
-                Tool::SwordShield => ("weapon.axe.rusty_2h", Vec3::new(-2.5, -6.5, -2.0)),
-                Tool::Bow => ("weapon.hammer.rusty_2h", Vec3::new(-2.5, -5.5, -4.0)),
-                Tool::Staff => ("weapon.axe.rusty_2h", Vec3::new(-2.5, -6.5, -2.0)),
-            },
-            Item::Debug(_) => ("weapon.debug_wand", Vec3::new(-1.5, -9.5, -4.0)),
-            _ => return Mesh::new(),
+/// The offset found in the manifest for the model `base`, or no offset (with a warning) if no
+/// armor specification uses it.
+fn found_offset(offset: Option<Vec3<f32>>, base: &str) -> Vec3<f32> {
+    offset.unwrap_or_else(|| {
+        warn!("No armor specification uses the model {:?}", base);
+        Vec3::zero()
+    })
+}
+
+fn armor_spec<'a, K: Eq + Hash + Debug>(
+    specs: &'a HashMap<K, ArmorVoxSpec>,
+    key: &K,
+) -> Option<&'a ArmorVoxSpec> {
+    let spec = specs.get(key);
+    if spec.is_none() {
+        warn!("No armor specification exists for {:?}", key);
+    }
+    spec
+}
+
+impl ArmorSpec {
+    pub fn load_watched(indicator: &mut ReloadIndicator) -> Arc<Self> {
+        assets::load_watched::<Self>("voxygen.voxel.armor_manifest", indicator).unwrap()
+    }
+
+    fn load() -> Arc<Self> {
+        assets::load_expect::<Self>("voxygen.voxel.armor_manifest")
+    }
+
+    pub fn mesh_chest(&self, chest: Chest) -> Mesh<FigurePipeline> {
+        let spec = match armor_spec(&self.chest, &chest) {
+            Some(spec) => spec,
+            None => return load_mesh("not_found", Vec3::new(-5.0, -5.0, -2.5)),
         };
-        load_mesh(name, offset)
-    } else {
-        Mesh::new()
+
+        mesh_chest_armor(spec.segment(), Vec3::from(spec.vox_spec.1))
+    }
+
+    pub fn mesh_belt(&self, belt: Belt, body_type: BodyType) -> Mesh<FigurePipeline> {
+        armor_spec(&self.belt, &belt)
+            .map(|spec| spec.mesh_for(body_type))
+            .unwrap_or_else(|| load_mesh("not_found", Vec3::new(-5.0, -5.0, -2.5)))
+    }
+
+    pub fn mesh_pants(&self, pants: Pants) -> Mesh<FigurePipeline> {
+        armor_spec(&self.pants, &pants)
+            .map(|spec| spec.mesh())
+            .unwrap_or_else(|| load_mesh("not_found", Vec3::new(-5.0, -5.0, -2.5)))
+    }
+
+    pub fn mesh_left_foot(&self, foot: Foot, body_type: BodyType) -> Mesh<FigurePipeline> {
+        armor_spec(&self.foot, &foot)
+            .map(|spec| spec.mesh_for(body_type))
+            .unwrap_or_else(|| load_mesh("not_found", Vec3::new(-5.0, -5.0, -2.5)))
+    }
+
+    pub fn mesh_right_foot(&self, foot: Foot, body_type: BodyType) -> Mesh<FigurePipeline> {
+        self.mesh_left_foot(foot, body_type)
+    }
+
+    fn mesh_shoulder(
+        &self,
+        shoulder: Shoulder,
+        side: impl Fn(&SidedArmorVoxSpec) -> &str,
+    ) -> Mesh<FigurePipeline> {
+        match self.shoulder.get(&shoulder) {
+            Some(Some(spec)) => spec.side(side(spec)).mesh(),
+            Some(None) => Mesh::new(),
+            None => {
+                warn!("No armor specification exists for {:?}", shoulder);
+                load_mesh("not_found", Vec3::new(-5.0, -5.0, -2.5))
+            }
+        }
+    }
+
+    pub fn mesh_left_shoulder(&self, shoulder: Shoulder) -> Mesh<FigurePipeline> {
+        self.mesh_shoulder(shoulder, |spec| spec.left.as_str())
+    }
+
+    pub fn mesh_right_shoulder(&self, shoulder: Shoulder) -> Mesh<FigurePipeline> {
+        self.mesh_shoulder(shoulder, |spec| spec.right.as_str())
     }
 }
 
-pub fn mesh_left_shoulder(shoulder: Shoulder) -> Mesh<FigurePipeline> {
+// Offset shared by the left and right hands, so that they stay in sync.
+const HAND_OFFSET: Vec3<f32> = Vec3 {
+    x: -2.0,
+    y: -2.5,
+    z: -2.0,
+};
+
+pub fn mesh_left_hand(hand: Hand, body_type: BodyType) -> Mesh<FigurePipeline> {
     load_mesh(
-        match shoulder {
-            Shoulder::None => return Mesh::new(),
-            Shoulder::Brown1 => "armor.shoulder.shoulder_l_brown",
-        },
-        Vec3::new(-2.5, -3.5, -1.5),
+        &body_type_mesh_name(
+            match hand {
+                Hand::Default => "figure.body.hand",
+            },
+            body_type,
+        ),
+        HAND_OFFSET,
     )
 }
 
-pub fn mesh_right_shoulder(shoulder: Shoulder) -> Mesh<FigurePipeline> {
+pub fn mesh_right_hand(hand: Hand, body_type: BodyType) -> Mesh<FigurePipeline> {
     load_mesh(
-        match shoulder {
-            Shoulder::None => return Mesh::new(),
-            Shoulder::Brown1 => "armor.shoulder.shoulder_r_brown",
-        },
-        Vec3::new(-2.5, -3.5, -1.5),
+        &body_type_mesh_name(
+            match hand {
+                Hand::Default => "figure.body.hand",
+            },
+            body_type,
+        ),
+        HAND_OFFSET,
     )
 }
 
+/// The models used for each kind of tool, offset relative to the hand that holds them.
+#[derive(Serialize, Deserialize)]
+pub struct WeaponSpec(HashMap<Tool, VoxSpec<f32>>);
+
+impl Asset for WeaponSpec {
+    const ENDINGS: &'static [&'static str] = &["ron"];
+    fn parse(buf_reader: BufReader<File>) -> Result<Self, assets::Error> {
+        Ok(ron::de::from_reader(buf_reader).expect("Error parsing weapon spec"))
+    }
+}
+
+impl WeaponSpec {
+    pub fn load_watched(indicator: &mut ReloadIndicator) -> Arc<Self> {
+        assets::load_watched::<Self>("voxygen.voxel.weapon_manifest", indicator).unwrap()
+    }
+
+    pub fn mesh_main(&self, item: Option<&Item>) -> Mesh<FigurePipeline> {
+        if let Some(item) = item {
+            let (name, offset) = match item {
+                Item::Tool { kind, .. } => match self.0.get(kind) {
+                    Some(spec) => (spec.0.as_str(), Vec3::from(spec.1)),
+                    None => {
+                        warn!("No weapon specification exists for {:?}", kind);
+                        return load_mesh("not_found", Vec3::new(-5.0, -5.0, -2.5));
+                    }
+                },
+                Item::Debug(_) => ("weapon.debug_wand", Vec3::new(-1.5, -9.5, -4.0)),
+                _ => return Mesh::new(),
+            };
+            load_mesh(name, offset)
+        } else {
+            Mesh::new()
+        }
+    }
+}
+
 // TODO: Inventory
 pub fn mesh_draw() -> Mesh<FigurePipeline> {
     load_mesh("object.glider", Vec3::new(-26.0, -26.0, -5.0))
 }
 
+/// A complete description of a humanoid figure's appearance, so that NPC templates can be
+/// authored as data.
+#[derive(Serialize, Deserialize)]
+pub struct FigureSpec {
+    pub body: humanoid::Body,
+    pub tool: Option<Tool>,
+}
+
+impl Asset for FigureSpec {
+    const ENDINGS: &'static [&'static str] = &["ron"];
+    fn parse(buf_reader: BufReader<File>) -> Result<Self, assets::Error> {
+        Ok(ron::de::from_reader(buf_reader).expect("Error parsing figure spec"))
+    }
+}
+
+/// The meshes for each part of a humanoid figure.
+pub struct FigureMeshes {
+    pub head: Mesh<FigurePipeline>,
+    pub chest: Mesh<FigurePipeline>,
+    pub belt: Mesh<FigurePipeline>,
+    pub pants: Mesh<FigurePipeline>,
+    pub l_hand: Mesh<FigurePipeline>,
+    pub r_hand: Mesh<FigurePipeline>,
+    pub l_foot: Mesh<FigurePipeline>,
+    pub r_foot: Mesh<FigurePipeline>,
+    pub main: Mesh<FigurePipeline>,
+    pub l_shoulder: Mesh<FigurePipeline>,
+    pub r_shoulder: Mesh<FigurePipeline>,
+    pub draw: Mesh<FigurePipeline>,
+}
+
+pub fn build_figure(spec: &FigureSpec) -> FigureMeshes {
+    let body = &spec.body;
+    let main = spec.tool.map(|kind| Item::Tool { kind, power: 0 });
+    let armor_spec = assets::load_expect::<ArmorSpec>("voxygen.voxel.armor_manifest");
+
+    FigureMeshes {
+        head: assets::load_expect::<HumHeadSpec>("voxygen.voxel.humanoid_head_manifest").mesh_head(
+            body.race,
+            body.body_type,
+            body.hair_color,
+            body.hair_style,
+            body.beard,
+            body.eye_color,
+            body.skin,
+            body.eyebrows,
+            body.accessory,
+        ),
+        chest: armor_spec.mesh_chest(body.chest),
+        belt: armor_spec.mesh_belt(body.belt, body.body_type),
+        pants: armor_spec.mesh_pants(body.pants),
+        l_hand: mesh_left_hand(body.hand, body.body_type),
+        r_hand: mesh_right_hand(body.hand, body.body_type),
+        l_foot: armor_spec.mesh_left_foot(body.foot, body.body_type),
+        r_foot: armor_spec.mesh_right_foot(body.foot, body.body_type),
+        main: assets::load_expect::<WeaponSpec>("voxygen.voxel.weapon_manifest")
+            .mesh_main(main.as_ref()),
+        l_shoulder: armor_spec.mesh_left_shoulder(body.shoulder),
+        r_shoulder: armor_spec.mesh_right_shoulder(body.shoulder),
+        draw: mesh_draw(),
+    }
+}
+
 //pub fn mesh_right_equip(hand: Hand) -> Mesh<FigurePipeline> {
 //    load_mesh(
 //        match hand {
@@ -487,10 +878,117 @@ pub fn mesh_wolf_foot_rb(foot_rb: quadruped_medium::FootRB) -> Mesh<FigurePipeli
     )
 }
 
-pub fn mesh_object(obj: object::Body) -> Mesh<FigurePipeline> {
+//////
+pub fn mesh_bird_torso(torso: bird_medium::Torso) -> Mesh<FigurePipeline> {
+    load_mesh(
+        match torso {
+            bird_medium::Torso::Default => "npc.bird.bird_torso",
+        },
+        Vec3::new(-3.0, -4.0, -3.0),
+    )
+}
+
+pub fn mesh_bird_head(head: bird_medium::Head) -> Mesh<FigurePipeline> {
+    load_mesh(
+        match head {
+            bird_medium::Head::Default => "npc.bird.bird_head",
+        },
+        Vec3::new(-2.0, -1.0, -1.0),
+    )
+}
+
+pub fn mesh_bird_tail(tail: bird_medium::Tail) -> Mesh<FigurePipeline> {
+    load_mesh(
+        match tail {
+            bird_medium::Tail::Default => "npc.bird.bird_tail",
+        },
+        Vec3::new(-2.0, -5.0, 0.0),
+    )
+}
+
+pub fn mesh_bird_wing_l(wing_l: bird_medium::WingL) -> Mesh<FigurePipeline> {
+    load_mesh(
+        match wing_l {
+            bird_medium::WingL::Default => "npc.bird.bird_wing_l",
+        },
+        Vec3::new(-8.0, -3.0, 0.0),
+    )
+}
+
+pub fn mesh_bird_wing_r(wing_r: bird_medium::WingR) -> Mesh<FigurePipeline> {
+    load_mesh(
+        match wing_r {
+            bird_medium::WingR::Default => "npc.bird.bird_wing_r",
+        },
+        Vec3::new(0.0, -3.0, 0.0),
+    )
+}
+
+pub fn mesh_bird_leg_l(leg_l: bird_medium::LegL) -> Mesh<FigurePipeline> {
+    load_mesh(
+        match leg_l {
+            bird_medium::LegL::Default => "npc.bird.bird_leg",
+        },
+        Vec3::new(-1.5, -1.5, -4.0),
+    )
+}
+
+pub fn mesh_bird_leg_r(leg_r: bird_medium::LegR) -> Mesh<FigurePipeline> {
+    load_mesh(
+        match leg_r {
+            bird_medium::LegR::Default => "npc.bird.bird_leg",
+        },
+        Vec3::new(-1.5, -1.5, -4.0),
+    )
+}
+
+//////
+pub fn mesh_fish_head(species: fish_small::Species) -> Mesh<FigurePipeline> {
+    let (name, offset) = match species {
+        fish_small::Species::Carp => ("npc.fish.fish_carp_head", Vec3::new(-1.5, 0.0, -2.5)),
+        fish_small::Species::Trout => ("npc.fish.fish_trout_head", Vec3::new(-1.5, 0.0, -2.0)),
+        fish_small::Species::Eel => ("npc.fish.fish_eel_head", Vec3::new(-1.0, 0.0, -1.0)),
+    };
+    load_mesh(name, offset)
+}
+
+pub fn mesh_fish_body_front(species: fish_small::Species) -> Mesh<FigurePipeline> {
+    let (name, offset) = match species {
+        fish_small::Species::Carp => ("npc.fish.fish_carp_body_front", Vec3::new(-2.0, -3.0, -3.0)),
+        fish_small::Species::Trout => (
+            "npc.fish.fish_trout_body_front",
+            Vec3::new(-1.5, -3.0, -2.0),
+        ),
+        fish_small::Species::Eel => ("npc.fish.fish_eel_body_front", Vec3::new(-1.0, -3.0, -1.0)),
+    };
+    load_mesh(name, offset)
+}
+
+pub fn mesh_fish_body_back(species: fish_small::Species) -> Mesh<FigurePipeline> {
+    let (name, offset) = match species {
+        fish_small::Species::Carp => ("npc.fish.fish_carp_body_back", Vec3::new(-1.5, -6.0, -2.5)),
+        fish_small::Species::Trout => {
+            ("npc.fish.fish_trout_body_back", Vec3::new(-1.0, -6.0, -1.5))
+        }
+        fish_small::Species::Eel => ("npc.fish.fish_eel_body_back", Vec3::new(-1.0, -6.0, -1.0)),
+    };
+    load_mesh(name, offset)
+}
+
+pub fn mesh_fish_tail(species: fish_small::Species) -> Mesh<FigurePipeline> {
+    let (name, offset) = match species {
+        fish_small::Species::Carp => ("npc.fish.fish_carp_tail", Vec3::new(-0.5, -4.0, -3.0)),
+        fish_small::Species::Trout => ("npc.fish.fish_trout_tail", Vec3::new(-0.5, -4.0, -2.0)),
+        fish_small::Species::Eel => ("npc.fish.fish_eel_tail", Vec3::new(-0.5, -4.0, -1.5)),
+    };
+    load_mesh(name, offset)
+}
+
+/// The model and offset used for an object body.
+pub fn object_vox_spec(obj: object::Body) -> (&'static str, Vec3<f32>) {
     use object::Body;
 
-    let (name, offset) = match obj {
+    match obj {
         Body::Bomb => ("object.bomb", Vec3::new(-5.5, -5.5, 0.0)),
         Body::Scarecrow => ("object.scarecrow", Vec3::new(-9.5, -4.0, 0.0)),
         Body::Cauldron => ("object.cauldron", Vec3::new(-10.0, -10.0, 0.0)),
@@ -544,6 +1042,621 @@ pub fn mesh_object(obj: object::Body) -> Mesh<FigurePipeline> {
             Vec3::new(-21.0, -21.0, -0.5),
         ),
         Body::Pouch => ("object.pouch", Vec3::new(-5.5, -4.5, 0.0)),
-    };
+    }
+}
+
+/// The model and offset of every object body, e.g: for prewarming meshes or previewing spawns.
+pub fn all_object_specs() -> Vec<(object::Body, &'static str, Vec3<f32>)> {
+    object::ALL_OBJECTS
+        .iter()
+        .map(|obj| {
+            let (name, offset) = object_vox_spec(*obj);
+            (*obj, name, offset)
+        })
+        .collect()
+}
+
+pub fn mesh_object(obj: object::Body) -> Mesh<FigurePipeline> {
+    let (name, offset) = object_vox_spec(obj);
     load_mesh(name, offset)
 }
+
+/// Like `mesh_object`, but with the grey voxels of the object recolored with `tint`, so that
+/// color variants can be generated from a single base model.
+pub fn mesh_object_tinted(base: object::Body, tint: Rgb<u8>) -> Mesh<FigurePipeline> {
+    let (name, offset) = object_vox_spec(base);
+    let segment = recolor_greys(graceful_load_segment(name), tint);
+    Meshable::<FigurePipeline, FigurePipeline>::generate_mesh(&segment, offset).0
+}
+
+/// Like `mesh_object`, but with the object turned about the vertical axis by `yaw` radians so
+/// that furniture can be placed facing different directions. Multiples of `PI / 2` give the four
+/// cardinal directions.
+pub fn mesh_object_facing(obj: object::Body, yaw: f32) -> Mesh<FigurePipeline> {
+    let rot = Mat3::rotation_z(yaw);
+    let mut mesh = Mesh::new();
+    mesh.push_mesh_map(&mesh_object(obj), |mut vert| {
+        vert.pos = (rot * Vec3::from(vert.pos)).into_array();
+        vert.norm = (rot * Vec3::from(vert.norm)).into_array();
+        vert
+    });
+    mesh
+}
+
+/// Reduce the colors of a figure mesh to at most `max_colors` shared palette entries so that
+/// many figures can be packed into a single palette texture.
+///
+/// Returns the remapped mesh, its palette, and the palette index of each of its vertices. Colors
+/// are grouped by median cut in CIELAB, with each group represented by whichever of its colors
+/// is closest to its mean, and every color then goes to the palette entry with the smallest
+/// CIEDE2000 difference from it.
+pub fn quantize_colors(
+    mesh: &Mesh<FigurePipeline>,
+    max_colors: usize,
+) -> (Mesh<FigurePipeline>, Vec<Rgb<f32>>, Vec<usize>) {
+    use common::util::{delta_e_2000, linear_to_cielab, srgb_to_linear};
+    use std::cmp::Ordering;
+
+    let to_rgb = |col: [f32; 3]| Rgb::new(col[0], col[1], col[2]);
+    let key = |col: [f32; 3]| [col[0].to_bits(), col[1].to_bits(), col[2].to_bits()];
+
+    // Collect unique colors, in CIELAB, along with how many vertices use them
+    let mut colors: Vec<(Rgb<f32>, Vec3<f32>, f32)> = Vec::new();
+    let mut color_indices: HashMap<[u32; 3], usize> = HashMap::new();
+    for vert in mesh.vertices() {
+        let i = *color_indices.entry(key(vert.col)).or_insert_with(|| {
+            let col = to_rgb(vert.col);
+            colors.push((col, linear_to_cielab(srgb_to_linear(col)), 0.0));
+            colors.len() - 1
+        });
+        colors[i].2 += 1.0;
+    }
+    if colors.is_empty() {
+        return (Mesh::new(), Vec::new(), Vec::new());
+    }
+
+    // Keep splitting whichever group of colors spans the widest range along some axis, at the
+    // (vertex weighted) median along that axis, until there are enough groups
+    let mut groups = vec![(0..colors.len()).collect::<Vec<_>>()];
+    while groups.len() < max_colors.max(1) {
+        let widest = groups
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| group.len() > 1)
+            .flat_map(|(i, group)| {
+                let colors = &colors;
+                (0..3).map(move |axis| {
+                    let (min, max) = group.iter().fold(
+                        (std::f32::INFINITY, std::f32::NEG_INFINITY),
+                        |(min, max), c| (min.min(colors[*c].1[axis]), max.max(colors[*c].1[axis])),
+                    );
+                    (i, axis, max - min)
+                })
+            })
+            .max_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(Ordering::Equal));
+        let (i, axis) = match widest {
+            Some((i, axis, _)) => (i, axis),
+            None => break,
+        };
+
+        let mut group = groups.swap_remove(i);
+        group.sort_by(|a, b| {
+            colors[*a].1[axis]
+                .partial_cmp(&colors[*b].1[axis])
+                .unwrap_or(Ordering::Equal)
+        });
+        let half_weight = group.iter().map(|c| colors[*c].2).sum::<f32>() / 2.0;
+        let mut weight = 0.0;
+        let split = group
+            .iter()
+            .position(|c| {
+                weight += colors[*c].2;
+                weight >= half_weight
+            })
+            .map_or(1, |i| i + 1)
+            .min(group.len() - 1)
+            .max(1);
+        let upper = group.split_off(split);
+        groups.push(group);
+        groups.push(upper);
+    }
+
+    let palette_colors = groups
+        .iter()
+        .filter_map(|group| {
+            let weight = group.iter().map(|c| colors[*c].2).sum::<f32>();
+            let mean = group
+                .iter()
+                .fold(Vec3::zero(), |mean, c| mean + colors[*c].1 * colors[*c].2)
+                / weight;
+            group.iter().cloned().min_by(|a, b| {
+                delta_e_2000(mean, colors[*a].1)
+                    .partial_cmp(&delta_e_2000(mean, colors[*b].1))
+                    .unwrap_or(Ordering::Equal)
+            })
+        })
+        .collect::<Vec<_>>();
+    let palette = palette_colors
+        .iter()
+        .map(|c| colors[*c].0)
+        .collect::<Vec<_>>();
+
+    // The palette entry each unique color ends up as
+    let nearest = colors
+        .iter()
+        .map(|(_, lab, _)| {
+            (0..palette_colors.len())
+                .min_by(|a, b| {
+                    delta_e_2000(*lab, colors[palette_colors[*a]].1)
+                        .partial_cmp(&delta_e_2000(*lab, colors[palette_colors[*b]].1))
+                        .unwrap_or(Ordering::Equal)
+                })
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    let indices = mesh
+        .vertices()
+        .iter()
+        .map(|vert| nearest[color_indices[&key(vert.col)]])
+        .collect::<Vec<_>>();
+    let mut quantized = Mesh::new();
+    quantized.push_mesh_map(mesh, |mut vert| {
+        vert.col = palette[nearest[color_indices[&key(vert.col)]]].into_array();
+        vert
+    });
+
+    (quantized, palette, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_figure_from_spec() {
+        let spec: FigureSpec = ron::de::from_str(
+            "(
+                body: (
+                    race: Human,
+                    body_type: Male,
+                    chest: Blue,
+                    belt: Dark,
+                    pants: Green,
+                    hand: Default,
+                    foot: Dark,
+                    shoulder: Brown1,
+                    hair_style: Temp1,
+                    beard: None,
+                    eyebrows: Yup,
+                    accessory: Nothing,
+                    hair_color: 0,
+                    skin: 0,
+                    eye_color: 0,
+                ),
+                tool: Some(Sword),
+            )",
+        )
+        .unwrap();
+        let meshes = build_figure(&spec);
+
+        for part in [
+            &meshes.head,
+            &meshes.chest,
+            &meshes.belt,
+            &meshes.pants,
+            &meshes.l_hand,
+            &meshes.r_hand,
+            &meshes.l_foot,
+            &meshes.r_foot,
+            &meshes.main,
+            &meshes.l_shoulder,
+            &meshes.r_shoulder,
+            &meshes.draw,
+        ]
+        .iter()
+        {
+            assert!(!part.vertices().is_empty());
+        }
+    }
+
+    #[test]
+    fn quantize_to_palette() {
+        use crate::render::pipelines::figure::Vertex;
+        use common::util::{delta_e_2000, linear_to_cielab, srgb_to_linear};
+
+        let mut mesh = Mesh::new();
+        for i in 0..32 {
+            let col = Rgb::new(i as f32 / 31.0, 0.5, 1.0 - i as f32 / 31.0);
+            mesh.push(Vertex::new(Vec3::zero(), Vec3::unit_z(), col, 0));
+            // Repeated colors share a palette entry
+            mesh.push(Vertex::new(Vec3::unit_x(), Vec3::unit_z(), col, 0));
+        }
+
+        let (quantized, palette, indices) = quantize_colors(&mesh, 8);
+        assert!(palette.len() <= 8);
+        assert_eq!(indices.len(), mesh.vertices().len());
+
+        let mut distinct = quantized
+            .vertices()
+            .iter()
+            .map(|vert| {
+                [
+                    vert.col[0].to_bits(),
+                    vert.col[1].to_bits(),
+                    vert.col[2].to_bits(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        distinct.sort();
+        distinct.dedup();
+        assert!(distinct.len() <= 8);
+
+        let lab =
+            |col: [f32; 3]| linear_to_cielab(srgb_to_linear(Rgb::new(col[0], col[1], col[2])));
+        for ((orig, quant), index) in mesh
+            .vertices()
+            .iter()
+            .zip(quantized.vertices())
+            .zip(indices.iter())
+        {
+            assert_eq!(quant.col, palette[*index].into_array());
+            let err = delta_e_2000(lab(orig.col), lab(quant.col));
+            assert!(err < 10.0, "quantization error too large: {}", err);
+        }
+        assert!(indices.chunks(2).all(|pair| pair[0] == pair[1]));
+
+        // Meshes that already have few enough colors are left as they are
+        let (unchanged, palette, _) = quantize_colors(&mesh, 64);
+        assert_eq!(palette.len(), 32);
+        assert!(unchanged
+            .vertices()
+            .iter()
+            .zip(mesh.vertices())
+            .all(|(a, b)| a.col == b.col));
+    }
+
+    #[test]
+    fn recolor_grey_base_level() {
+        use common::{figure::cell::Cell, vol::ReadVol};
+
+        let grey = Rgb::new(100, 100, 100);
+        let color = Rgb::new(200, 150, 100);
+        let segment = Segment::filled(Vec3::new(2, 2, 2), Cell::new(grey), ());
+        let recolored = |segment: Segment| segment.get(Vec3::zero()).unwrap().get_color().unwrap();
+
+        // The default base level is unchanged
+        assert_eq!(
+            recolored(recolor_greys(segment.clone(), color)),
+            recolored(recolor_greys_base(segment.clone(), color, BASE_GREY, 0))
+        );
+
+        // A lower base level brightens the result
+        let dark = recolored(recolor_greys_base(segment.clone(), color, 200.0, 0));
+        let bright = recolored(recolor_greys_base(segment.clone(), color, 100.0, 0));
+        assert!(bright.r > dark.r && bright.g > dark.g && bright.b > dark.b);
+
+        // Detecting the base from the segment reproduces the target color
+        assert_eq!(average_grey(&segment), Some(100.0));
+        let auto = recolored(recolor_greys_auto(segment, color, 0));
+        assert!(auto
+            .map2(color, |a, b| (a as i32 - b as i32).abs() <= 1)
+            .reduce_and());
+
+        // Armor opts into it in the manifest
+        let spec: ArmorVoxSpec = ron::de::from_str(
+            r#"(vox_spec: ("armor.chest.grayscale", (0.0, 0.0, 0.0)), dye: None)"#,
+        )
+        .unwrap();
+        assert!(!spec.auto_base);
+        let spec: ArmorVoxSpec = ron::de::from_str(
+            r#"(vox_spec: ("armor.chest.grayscale", (0.0, 0.0, 0.0)), dye: Some((28, 66, 109)), auto_base: true)"#,
+        )
+        .unwrap();
+        assert!(spec.auto_base);
+    }
+
+    #[test]
+    fn object_facing() {
+        use std::f32::consts::PI;
+
+        let bounds = |mesh: &Mesh<FigurePipeline>| {
+            mesh.vertices().iter().fold(
+                (
+                    Vec2::broadcast(std::f32::MAX),
+                    Vec2::broadcast(std::f32::MIN),
+                ),
+                |(min, max), vert| {
+                    let pos = Vec2::new(vert.pos[0], vert.pos[1]);
+                    (Vec2::partial_min(min, pos), Vec2::partial_max(max, pos))
+                },
+            )
+        };
+
+        let (min, max) = bounds(&mesh_object(object::Body::Chest));
+        let (rot_min, rot_max) = bounds(&mesh_object_facing(object::Body::Chest, PI / 2.0));
+
+        // Turning a quarter turn anticlockwise maps (x, y) to (-y, x)
+        assert!(rot_min.distance(Vec2::new(-max.y, min.x)) < 1e-3);
+        assert!(rot_max.distance(Vec2::new(-min.y, max.x)) < 1e-3);
+    }
+
+    #[test]
+    fn paired_part_offsets() {
+        let min_corner = |mesh: Mesh<FigurePipeline>| {
+            mesh.vertices()
+                .iter()
+                .fold(Vec3::broadcast(std::f32::MAX), |min, vert| {
+                    Vec3::partial_min(min, Vec3::from(vert.pos))
+                })
+        };
+
+        let armor_spec = assets::load_expect::<ArmorSpec>("voxygen.voxel.armor_manifest");
+        assert_eq!(
+            min_corner(armor_spec.mesh_left_foot(Foot::Dark, BodyType::Male)),
+            min_corner(armor_spec.mesh_right_foot(Foot::Dark, BodyType::Male))
+        );
+        assert_eq!(
+            min_corner(mesh_left_hand(Hand::Default, BodyType::Female)),
+            min_corner(mesh_right_hand(Hand::Default, BodyType::Female))
+        );
+    }
+
+    #[test]
+    fn object_assets_load() {
+        let specs = all_object_specs();
+        assert_eq!(specs.len(), object::ALL_OBJECTS.len());
+        for (obj, name, _) in specs {
+            let full_specifier: String = ["voxygen.voxel.", name].concat();
+            assert!(
+                assets::load::<DotVoxData>(&full_specifier).is_ok(),
+                "Missing model for {:?}: {}",
+                obj,
+                full_specifier
+            );
+        }
+    }
+
+    #[test]
+    fn tinted_objects() {
+        let red = mesh_object_tinted(object::Body::Anvil, Rgb::new(200, 30, 30));
+        let blue = mesh_object_tinted(object::Body::Anvil, Rgb::new(30, 30, 200));
+
+        assert_eq!(red.vertices().len(), blue.vertices().len());
+        assert!(red
+            .vertices()
+            .iter()
+            .zip(blue.vertices())
+            .any(|(a, b)| a.col != b.col));
+    }
+
+    #[test]
+    fn recolor_keeps_shading() {
+        use common::{
+            figure::cell::Cell,
+            vol::{ReadVol, WriteVol},
+        };
+
+        let mut segment = Segment::filled(Vec3::new(3, 1, 1), Cell::new(Rgb::broadcast(120)), ());
+        segment
+            .set(Vec3::new(1, 0, 0), Cell::new(Rgb::broadcast(178)))
+            .unwrap();
+        segment
+            .set(Vec3::new(2, 0, 0), Cell::new(Rgb::broadcast(230)))
+            .unwrap();
+
+        let blue = Rgb::new(28, 66, 200);
+        let dyed = recolor_greys(segment, blue);
+        let col = |x| dyed.get(Vec3::new(x, 0, 0)).unwrap().get_color().unwrap();
+        let (shadow, mid, highlight) = (col(0), col(1), col(2));
+
+        // Shadows and highlights stay distinct, and everything stays blue
+        assert!(shadow.b < mid.b && mid.b < highlight.b);
+        assert!(shadow.map(|e| e as u32).sum() < highlight.map(|e| e as u32).sum());
+        for col in [shadow, mid, highlight].iter() {
+            assert!(col.b > col.r && col.b > col.g);
+        }
+    }
+
+    #[test]
+    fn recolor_by_palette() {
+        use common::{
+            figure::cell::Cell,
+            vol::{ReadVol, WriteVol},
+        };
+
+        let (cloth, trim, other) = (
+            Rgb::new(255, 0, 255),
+            Rgb::new(0, 255, 255),
+            Rgb::new(90, 60, 30),
+        );
+        let mut segment = Segment::filled(Vec3::new(3, 1, 1), Cell::new(cloth), ());
+        segment.set(Vec3::new(1, 0, 0), Cell::new(trim)).unwrap();
+        segment.set(Vec3::new(2, 0, 0), Cell::new(other)).unwrap();
+
+        let (red, gold) = (Rgb::new(150, 20, 20), Rgb::new(200, 170, 40));
+        let dyed = recolor_palette(segment, &[(cloth, red), (trim, gold)]);
+        let col = |x| dyed.get(Vec3::new(x, 0, 0)).unwrap().get_color().unwrap();
+        assert_eq!(col(0), red);
+        assert_eq!(col(1), gold);
+        assert_eq!(col(2), other);
+    }
+
+    #[test]
+    fn recolor_near_greys() {
+        use common::{
+            figure::cell::Cell,
+            vol::{ReadVol, WriteVol},
+        };
+
+        let mut segment = Segment::filled(Vec3::new(3, 1, 1), Cell::new(Rgb::broadcast(178)), ());
+        segment
+            .set(Vec3::new(1, 0, 0), Cell::new(Rgb::new(176, 178, 180)))
+            .unwrap();
+        segment
+            .set(Vec3::new(2, 0, 0), Cell::new(Rgb::new(170, 178, 190)))
+            .unwrap();
+
+        let color = Rgb::new(148, 52, 33);
+        let col = |segment: &Segment, x| segment.get(Vec3::new(x, 0, 0)).unwrap().get_color();
+
+        // Without a tolerance, only exact greys are recolored
+        let exact = recolor_greys(segment.clone(), color);
+        assert_eq!(col(&exact, 1), Some(Rgb::new(176, 178, 180)));
+
+        // Near-greys are recolored by their average level, while colored voxels are left alone
+        let tolerant = recolor_greys_tol(segment, color, 4);
+        assert_eq!(col(&tolerant, 1), col(&tolerant, 0));
+        assert_eq!(col(&tolerant, 2), Some(Rgb::new(170, 178, 190)));
+    }
+
+    #[test]
+    fn glowing_eyes() {
+        use common::{comp::humanoid::Race, vol::WriteVol};
+
+        let eye_glow = Race::Undead.eye_glow();
+        assert!(material_glow(Material::EyeLight, eye_glow) > 0.0);
+        assert_eq!(material_glow(Material::Skin, eye_glow), 0.0);
+        assert_eq!(
+            material_glow(Material::EyeLight, Race::Human.eye_glow()),
+            0.0
+        );
+
+        // A skin voxel next to an eye voxel
+        let mut head = MatSegment::filled(Vec3::new(2, 1, 1), MatCell::None, ());
+        head.set(Vec3::new(0, 0, 0), MatCell::Mat(Material::Skin))
+            .unwrap();
+        head.set(Vec3::new(1, 0, 0), MatCell::Mat(Material::EyeLight))
+            .unwrap();
+        let glowing = glowing_voxels(&head, Vec3::zero(), eye_glow)
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let segment = head.to_segment(|_| Rgb::broadcast(128));
+        let mesh = generate_glowing_mesh(&segment, Vec3::zero(), |pos| {
+            glowing.get(&pos).cloned().unwrap_or(0.0)
+        });
+
+        // Vertices on the shared x = 1 plane may belong to either voxel
+        for vert in mesh.vertices() {
+            if vert.pos[0] < 1.0 {
+                assert_eq!(vert.glow, 0.0);
+            } else if vert.pos[0] > 1.0 {
+                assert!(vert.glow > 0.0);
+            }
+        }
+        assert!(mesh.vertices().iter().any(|vert| vert.glow > 0.0));
+    }
+
+    #[test]
+    fn mesh_cache() {
+        // An offset no other test uses, so that they can't interfere
+        let offset = Vec3::new(-3.25, -2.0, 0.5);
+        let key = (
+            "object.anvil".to_string(),
+            offset.map(|e| e.to_bits()).into_array(),
+        );
+
+        let first = load_mesh("object.anvil", offset);
+        assert!(MESH_CACHE.lock().contains_key(&key));
+        let second = load_mesh("object.anvil", offset);
+        assert_eq!(first.vertices(), second.vertices());
+
+        clear_figure_cache();
+        assert!(!MESH_CACHE.lock().contains_key(&key));
+    }
+
+    #[test]
+    fn weapon_manifest_covers_tools() {
+        let spec = assets::load_expect::<WeaponSpec>("voxygen.voxel.weapon_manifest");
+        for tool in [
+            Tool::Daggers,
+            Tool::SwordShield,
+            Tool::Sword,
+            Tool::Axe,
+            Tool::Hammer,
+            Tool::Bow,
+            Tool::Staff,
+        ]
+        .iter()
+        {
+            assert!(spec.0.contains_key(tool), "{:?} has no model", tool);
+        }
+    }
+
+    #[test]
+    fn armor_manifest_covers_variants() {
+        let spec = assets::load_expect::<ArmorSpec>("voxygen.voxel.armor_manifest");
+        assert!(humanoid::ALL_CHESTS
+            .iter()
+            .all(|c| spec.chest.contains_key(c)));
+        assert!(humanoid::ALL_BELTS
+            .iter()
+            .all(|b| spec.belt.contains_key(b)));
+        assert!(humanoid::ALL_PANTS
+            .iter()
+            .all(|p| spec.pants.contains_key(p)));
+        assert!(humanoid::ALL_FEET.iter().all(|f| spec.foot.contains_key(f)));
+        assert!(humanoid::ALL_SHOULDERS
+            .iter()
+            .all(|s| spec.shoulder.contains_key(s)));
+
+        // Dyed armor matches the colors that used to be hard-coded
+        assert_eq!(spec.chest[&Chest::Blue].dye, Some([28, 66, 109]));
+        assert_eq!(spec.pants[&Pants::Orange].dye, Some([148, 52, 33]));
+    }
+
+    #[test]
+    fn body_type_meshes() {
+        // Body type specific models are used where they exist...
+        assert_eq!(
+            body_type_mesh_name("figure.body.belt", BodyType::Male),
+            "figure.body.belt_male"
+        );
+        assert_eq!(
+            body_type_mesh_name("figure.body.belt", BodyType::Female),
+            "figure.body.belt_female"
+        );
+        // ...and the shared model otherwise
+        assert_eq!(
+            body_type_mesh_name("figure.body.hand", BodyType::Female),
+            "figure.body.hand"
+        );
+    }
+
+    #[test]
+    fn custom_dyes() {
+        let armor_spec = assets::load_expect::<ArmorSpec>("voxygen.voxel.armor_manifest");
+
+        // The manifest's dyes go through the same path as custom ones
+        assert_eq!(
+            mesh_chest_dyed("armor.chest.grayscale", Rgb::new(28, 66, 109)).vertices(),
+            armor_spec.mesh_chest(Chest::Blue).vertices()
+        );
+        assert_eq!(
+            mesh_pants_dyed("armor.pants.grayscale", Rgb::new(148, 52, 33)).vertices(),
+            armor_spec.mesh_pants(Pants::Orange).vertices()
+        );
+
+        // Dyed shoulders sit where the manifest places them
+        let positions = |mesh: Mesh<FigurePipeline>| {
+            mesh.vertices()
+                .iter()
+                .map(|vert| vert.pos)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            positions(mesh_shoulder_dyed(
+                "armor.shoulder.shoulder_l_brown",
+                Rgb::new(28, 66, 109)
+            )),
+            positions(armor_spec.mesh_left_shoulder(Shoulder::Brown1))
+        );
+
+        let pink = mesh_pants_dyed("armor.pants.grayscale", Rgb::new(230, 100, 180));
+        assert!(pink
+            .vertices()
+            .iter()
+            .zip(armor_spec.mesh_pants(Pants::Blue).vertices())
+            .any(|(a, b)| a.col != b.col));
+    }
+}
@@ -1,21 +1,42 @@
 mod cache;
+mod ik;
 mod load;
+mod trail;
 
-pub use cache::FigureModelCache;
+pub use cache::{FigureKey, FigureModelCache};
 pub use load::load_mesh; // TODO: Don't make this public.
 
+use self::{ik::FootPlacement, trail::WeaponTrail};
+
 use crate::{
     anim::{
-        self, character::CharacterSkeleton, object::ObjectSkeleton, quadruped::QuadrupedSkeleton,
-        quadrupedmedium::QuadrupedMediumSkeleton, Animation, Skeleton,
+        self,
+        bird_medium::{BirdMediumSkeleton, LAND_DURATION},
+        character::{
+            look::EYE_HEIGHT, AttackSequence, CharacterSkeleton, HeadLook, DEATH_DURATION,
+            R_HAND_WEAPON_BONE,
+        },
+        first_person::FirstPersonSkeleton,
+        fish_small::FishSmallSkeleton,
+        keyframe::{KeyframeAnimation, KeyframeAnimationData},
+        object::ObjectSkeleton,
+        quadruped::QuadrupedSkeleton,
+        quadrupedmedium::QuadrupedMediumSkeleton,
+        Animation, AnimationBlender, Skeleton, FIGURE_SCALE,
+    },
+    render::{
+        figure_instances_per_batch, Consts, FigureBoneData, FigureInstance, FigureLocals, Globals,
+        Instances, Light, OutlineLocals, Renderer, Shadow, ShadowLocals, MAX_FIGURE_BATCH_BONES,
+        MAX_FIGURE_BONES, MAX_FIGURE_INSTANCES,
     },
-    render::{Consts, FigureBoneData, FigureLocals, Globals, Light, Renderer},
-    scene::camera::{Camera, CameraMode},
+    scene::camera::{aabb_in_frustum, Camera, CameraMode},
 };
 use client::Client;
 use common::{
+    assets::watch::ReloadIndicator,
     comp::{
-        ActionState::*, Body, CharacterState, Last, MovementState::*, Ori, Pos, Scale, Stats, Vel,
+        ActionState::*, Body, CharacterState, Emote, Item, Last, Mounting, MovementState::*, Ori,
+        Pos, Scale, Stats, Vel,
     },
     terrain::TerrainChunk,
     vol::RectRasterableVol,
@@ -23,26 +44,76 @@ use common::{
 use hashbrown::HashMap;
 use log::debug;
 use specs::{Entity as EcsEntity, Join};
+use std::{f32::consts::FRAC_PI_2, sync::Arc};
 use vek::*;
 
 const DAMAGE_FADE_COEFFICIENT: f64 = 5.0;
+/// How long, in seconds, figures take to crossfade to a new movement or action animation.
+const MOVEMENT_FADE_DURATION: f32 = 0.2;
+const ACTION_FADE_DURATION: f32 = 0.1;
+/// The speed below which creatures are animated as standing still, even if trying to move.
+const MIN_RUN_SPEED: f32 = 0.5;
+/// How far away, in blocks, characters notice others and turn their heads towards them.
+const LOOK_RANGE: f32 = 8.0;
+/// How high, in blocks, figures that roll onto their side when they die are lifted to rest on it.
+const COLLAPSE_LIFT: f32 = 0.4;
+
+/// The GPU buffers for one instanced draw call of up to `MAX_FIGURE_INSTANCES` figures sharing a
+/// model, whose bones share `MAX_FIGURE_BATCH_BONES`.
+struct InstanceBatch {
+    instances: Instances<FigureInstance>,
+    bones: Consts<FigureBoneData>,
+}
+
+impl InstanceBatch {
+    fn new(renderer: &mut Renderer) -> Self {
+        Self {
+            instances: renderer
+                .create_instances(&[FigureInstance::default(); MAX_FIGURE_INSTANCES])
+                .unwrap(),
+            bones: renderer
+                .create_consts(&[FigureBoneData::default(); MAX_FIGURE_BATCH_BONES])
+                .unwrap(),
+        }
+    }
+}
 
 pub struct FigureMgr {
     model_cache: FigureModelCache,
+    anim_indicator: ReloadIndicator,
+    wave_animation: Option<Arc<KeyframeAnimationData>>,
     character_states: HashMap<EcsEntity, FigureState<CharacterSkeleton>>,
+    character_looks: HashMap<EcsEntity, HeadLook>,
+    character_feet: HashMap<EcsEntity, FootPlacement>,
+    character_trails: HashMap<EcsEntity, WeaponTrail>,
+    /// The player's hands and weapon, as seen in first person.
+    first_person_state: Option<FigureState<FirstPersonSkeleton>>,
     quadruped_states: HashMap<EcsEntity, FigureState<QuadrupedSkeleton>>,
     quadruped_medium_states: HashMap<EcsEntity, FigureState<QuadrupedMediumSkeleton>>,
+    bird_medium_states: HashMap<EcsEntity, FigureState<BirdMediumSkeleton>>,
+    fish_small_states: HashMap<EcsEntity, FigureState<FishSmallSkeleton>>,
     object_states: HashMap<EcsEntity, FigureState<ObjectSkeleton>>,
+    /// Reused from frame to frame, and grown whenever a frame needs more draw calls than before.
+    instance_batches: Vec<InstanceBatch>,
 }
 
 impl FigureMgr {
     pub fn new() -> Self {
         Self {
             model_cache: FigureModelCache::new(),
+            anim_indicator: ReloadIndicator::new(),
+            wave_animation: None,
             character_states: HashMap::new(),
+            character_looks: HashMap::new(),
+            character_feet: HashMap::new(),
+            character_trails: HashMap::new(),
+            first_person_state: None,
             quadruped_states: HashMap::new(),
             quadruped_medium_states: HashMap::new(),
+            bird_medium_states: HashMap::new(),
+            fish_small_states: HashMap::new(),
             object_states: HashMap::new(),
+            instance_batches: Vec::new(),
         }
     }
 
@@ -50,7 +121,7 @@ impl FigureMgr {
         self.model_cache.clean(tick);
     }
 
-    pub fn maintain(&mut self, renderer: &mut Renderer, client: &Client) {
+    pub fn maintain(&mut self, renderer: &mut Renderer, client: &Client, camera: &Camera) {
         let time = client.state().get_time();
         let tick = client.get_tick();
         let ecs = client.state().ecs();
@@ -61,8 +132,31 @@ impl FigureMgr {
             .read_storage::<Pos>()
             .get(client.entity())
             .map_or(Vec3::zero(), |pos| pos.0);
+        // The eyes of every figure, for characters to look at
+        let mut look_targets = LookTargets::default();
+        for (entity, pos, _) in (
+            &ecs.entities(),
+            &ecs.read_storage::<Pos>(),
+            &ecs.read_storage::<Body>(),
+        )
+            .join()
+        {
+            look_targets.insert(entity, pos.0 + Vec3::unit_z() * EYE_HEIGHT);
+        }
+
+        if self.wave_animation.is_none() || self.anim_indicator.reloaded() {
+            self.wave_animation = KeyframeAnimationData::load_watched(
+                "voxygen.anim.character.wave",
+                &mut self.anim_indicator,
+            )
+            .ok();
+        }
+        // Start afresh each time the camera switches to first person
+        if camera.get_mode() != CameraMode::FirstPerson {
+            self.first_person_state = None;
+        }
 
-        for (entity, pos, vel, ori, scale, body, character, last_character, stats) in (
+        for (entity, pos, vel, ori, scale, body, character, last_character, stats, emote) in (
             &ecs.entities(),
             &ecs.read_storage::<Pos>(),
             &ecs.read_storage::<Vel>(),
@@ -72,6 +166,7 @@ impl FigureMgr {
             ecs.read_storage::<CharacterState>().maybe(),
             ecs.read_storage::<Last<CharacterState>>().maybe(),
             ecs.read_storage::<Stats>().maybe(),
+            ecs.read_storage::<Emote>().maybe(),
         )
             .join()
         {
@@ -87,6 +182,8 @@ impl FigureMgr {
                 match body {
                     Body::Humanoid(_) => {
                         self.character_states.remove(&entity);
+                        self.character_looks.remove(&entity);
+                        self.character_feet.remove(&entity);
                     }
                     Body::Quadruped(_) => {
                         self.quadruped_states.remove(&entity);
@@ -94,6 +191,12 @@ impl FigureMgr {
                     Body::QuadrupedMedium(_) => {
                         self.quadruped_medium_states.remove(&entity);
                     }
+                    Body::BirdMedium(_) => {
+                        self.bird_medium_states.remove(&entity);
+                    }
+                    Body::FishSmall(_) => {
+                        self.fish_small_states.remove(&entity);
+                    }
                     Body::Object(_) => {
                         self.object_states.remove(&entity);
                     }
@@ -143,9 +246,38 @@ impl FigureMgr {
 
                     if !character.is_same_movement(&last_character.0) {
                         state.movement_time = 0.0;
+                        state.blender.start_fade(MOVEMENT_FADE_DURATION);
                     }
+                    let tool = stats.and_then(|stats| match &stats.equipment.main {
+                        Some(Item::Tool { kind, .. }) => Some(*kind),
+                        _ => None,
+                    });
+                    let attack = &mut state.attack;
+
                     if !character.is_same_action(&last_character.0) {
                         state.action_time = 0.0;
+                        state.blender.start_fade(ACTION_FADE_DURATION);
+                        if let (Attack { .. }, Some(tool)) = (character.action, tool) {
+                            attack.start(tool);
+                        }
+                    }
+                    attack.update(dt);
+                    // Jumping and falling share a movement state, but switch animations at the
+                    // top of the jump
+                    let falling = character.movement == Jump && vel.0.z < 0.0;
+                    if falling != state.falling {
+                        state.falling = falling;
+                        if character.movement == Jump {
+                            state.movement_time = 0.0;
+                            state.blender.start_fade(MOVEMENT_FADE_DURATION);
+                        }
+                    }
+                    // Emotes only play while standing still, and start over when they change
+                    let emote = emote.copied().filter(|_| character.movement == Stand);
+                    if emote != state.emote {
+                        state.emote = emote;
+                        state.movement_time = 0.0;
+                        state.blender.start_fade(MOVEMENT_FADE_DURATION);
                     }
 
                     let target_base = match &character.movement {
@@ -163,6 +295,13 @@ impl FigureMgr {
                             &mut movement_animation_rate,
                             skeleton_attr,
                         ),
+                        Jump if state.falling => anim::character::FallAnimation::update_skeleton(
+                            &CharacterSkeleton::new(),
+                            (vel.0, time),
+                            state.movement_time,
+                            &mut movement_animation_rate,
+                            skeleton_attr,
+                        ),
                         Jump => anim::character::JumpAnimation::update_skeleton(
                             &CharacterSkeleton::new(),
                             time,
@@ -186,7 +325,7 @@ impl FigureMgr {
                         ),
                         Swim => anim::character::SwimAnimation::update_skeleton(
                             &CharacterSkeleton::new(),
-                            (vel.0.magnitude(), ori.0.magnitude(), time),
+                            vel.0,
                             state.movement_time,
                             &mut movement_animation_rate,
                             skeleton_attr,
@@ -206,8 +345,29 @@ impl FigureMgr {
                             skeleton_attr,
                         ),
                     };
+                    let target_base = match (emote, &self.wave_animation) {
+                        (Some(Emote::Wave), Some(wave)) => {
+                            KeyframeAnimation::<CharacterSkeleton>::update_skeleton(
+                                &target_base,
+                                wave.clone(),
+                                state.movement_time,
+                                &mut movement_animation_rate,
+                                skeleton_attr,
+                            )
+                        }
+                        (Some(Emote::Dance), _) => {
+                            anim::character::DanceAnimation::update_skeleton(
+                                &CharacterSkeleton::new(),
+                                (),
+                                state.movement_time,
+                                &mut movement_animation_rate,
+                                skeleton_attr,
+                            )
+                        }
+                        _ => target_base,
+                    };
 
-                    let target_bones = match (&character.movement, &character.action) {
+                    let mut target_bones = match (&character.movement, &character.action) {
                         (Stand, Wield { .. }) => anim::character::CidleAnimation::update_skeleton(
                             &target_base,
                             time,
@@ -224,16 +384,27 @@ impl FigureMgr {
                                 skeleton_attr,
                             )
                         }
-                        (_, Attack { .. }) => anim::character::AttackAnimation::update_skeleton(
-                            &target_base,
-                            time,
-                            state.action_time,
-                            &mut action_animation_rate,
-                            skeleton_attr,
-                        ),
+                        (_, Attack { .. }) => match (tool, attack.phase()) {
+                            (Some(tool), Some((phase, progress))) => {
+                                anim::character::SwingAnimation::update_skeleton(
+                                    &target_base,
+                                    (tool, phase, progress),
+                                    state.action_time,
+                                    &mut action_animation_rate,
+                                    skeleton_attr,
+                                )
+                            }
+                            _ => anim::character::AttackAnimation::update_skeleton(
+                                &target_base,
+                                time,
+                                state.action_time,
+                                &mut action_animation_rate,
+                                skeleton_attr,
+                            ),
+                        },
                         (_, Wield { .. }) => anim::character::WieldAnimation::update_skeleton(
                             &target_base,
-                            (vel.0.magnitude(), time),
+                            (tool, vel.0.magnitude(), time),
                             state.action_time,
                             &mut action_animation_rate,
                             skeleton_attr,
@@ -247,8 +418,86 @@ impl FigureMgr {
                         ),
                         _ => target_base,
                     };
+                    // Drawn weapons are held in the hand, and follow it
+                    if character.action.is_attack()
+                        || character.action.is_block()
+                        || character.action.is_wield()
+                    {
+                        target_bones.hold_weapon(tool, skeleton_attr);
+                    }
+                    // The dead collapse and lie still, whatever they were doing
+                    let dead_time = time_since_death(stats);
+                    if dead_time.is_some() != state.dead {
+                        state.dead = dead_time.is_some();
+                        state.blender.start_fade(MOVEMENT_FADE_DURATION);
+                    }
+                    let target_bones = match dead_time {
+                        Some(dead_time) => anim::character::DyingAnimation::update_skeleton(
+                            &CharacterSkeleton::new(),
+                            (),
+                            dead_time,
+                            &mut movement_animation_rate,
+                            skeleton_attr,
+                        ),
+                        None => target_bones,
+                    };
+                    // Crossfade between animations
+                    let mut target_bones = state.blender.update(target_bones, dt);
+
+                    // Plant the feet on uneven ground. This adjusts the pose being smoothed
+                    // towards rather than the smoothed one, so that it doesn't pile up over frames
+                    let figure_scale = FIGURE_SCALE * scale;
+                    let target_feet = match character.movement {
+                        Stand | Run if dead_time.is_none() => {
+                            let terrain = client.state().terrain();
+                            let [l_foot, r_foot] =
+                                ik::foot_positions(&target_bones, pos.0, ori.0, figure_scale);
+                            FootPlacement::solve(
+                                ik::ground_height(&terrain, l_foot),
+                                ik::ground_height(&terrain, r_foot),
+                            )
+                        }
+                        _ => FootPlacement::default(),
+                    };
+                    let feet = self
+                        .character_feet
+                        .entry(entity)
+                        .or_insert_with(FootPlacement::default);
+                    feet.approach(target_feet, dt);
+                    feet.apply(&mut target_bones, figure_scale);
+
+                    // Smooth the bones towards the pose
                     state.skeleton.interpolate(&target_bones, dt);
 
+                    let look_target = if dead_time.is_some() {
+                        None
+                    } else if entity == client.entity() {
+                        // The player looks wherever the camera does
+                        let cam_ori = camera.get_orientation();
+                        Some(
+                            camera.get_focus_pos()
+                                + Vec3::new(
+                                    cam_ori.x.sin() * cam_ori.y.cos(),
+                                    cam_ori.x.cos() * cam_ori.y.cos(),
+                                    -cam_ori.y.sin(),
+                                ) * LOOK_RANGE,
+                        )
+                    } else {
+                        // Others look at whoever is closest
+                        look_targets.nearest(entity, pos.0)
+                    };
+                    let look = self
+                        .character_looks
+                        .entry(entity)
+                        .or_insert_with(HeadLook::default);
+                    look.approach(
+                        look_target
+                            .map(|target| HeadLook::towards(pos.0, ori.0, target))
+                            .unwrap_or_default(),
+                        dt,
+                    );
+                    state.skeleton.set_look(*look);
+
                     state.update(
                         renderer,
                         pos.0,
@@ -259,6 +508,82 @@ impl FigureMgr {
                         movement_animation_rate,
                         action_animation_rate,
                     );
+
+                    // Swung weapons leave a trail behind them, except in the player's own hands
+                    // in first person, where the weapon isn't where the body holds it
+                    let first_person =
+                        entity == client.entity() && camera.get_mode() == CameraMode::FirstPerson;
+                    let weapon_mat = if character.action.is_attack()
+                        && tool.is_some()
+                        && !first_person
+                    {
+                        Some(
+                            state.model_mat
+                                * Mat4::from_col_arrays(state.bones[R_HAND_WEAPON_BONE].bone_mat),
+                        )
+                    } else {
+                        None
+                    };
+                    let trail = self
+                        .character_trails
+                        .entry(entity)
+                        .or_insert_with(WeaponTrail::new);
+                    trail.update(weapon_mat, dt);
+                    trail.update_model(renderer);
+
+                    // In first person, the player sees only their own hands and weapon
+                    if first_person {
+                        let state = self.first_person_state.get_or_insert_with(|| {
+                            FigureState::new(renderer, FirstPersonSkeleton::new())
+                        });
+                        let mut first_person_rate = 1.0;
+
+                        if !character.is_same_action(&last_character.0) {
+                            state.action_time = 0.0;
+                            state.blender.start_fade(ACTION_FADE_DURATION);
+                        }
+
+                        let target_bones = match character.action {
+                            Attack { .. } => anim::first_person::AttackAnimation::update_skeleton(
+                                &FirstPersonSkeleton::new(),
+                                (),
+                                state.action_time,
+                                &mut first_person_rate,
+                                skeleton_attr,
+                            ),
+                            Block { .. } => anim::first_person::BlockAnimation::update_skeleton(
+                                &FirstPersonSkeleton::new(),
+                                (),
+                                state.action_time,
+                                &mut first_person_rate,
+                                skeleton_attr,
+                            ),
+                            _ => anim::first_person::IdleAnimation::update_skeleton(
+                                &FirstPersonSkeleton::new(),
+                                Vec2::<f32>::from(vel.0).magnitude(),
+                                state.movement_time,
+                                &mut first_person_rate,
+                                skeleton_attr,
+                            ),
+                        };
+                        let target_bones = state.blender.update(target_bones, dt);
+                        state.skeleton.interpolate(&target_bones, dt);
+
+                        let cam_ori = camera.get_orientation();
+                        state.skeleton.set_pitch(cam_ori.y);
+                        // Stay fixed to the view, rather than trailing behind it
+                        state.pos = camera.get_focus_pos();
+                        state.update(
+                            renderer,
+                            camera.get_focus_pos(),
+                            Vec3::new(cam_ori.x.sin(), cam_ori.x.cos(), 0.0),
+                            scale,
+                            col,
+                            dt,
+                            1.0,
+                            first_person_rate,
+                        );
+                    }
                 }
                 Body::Quadruped(_) => {
                     let state = self
@@ -276,20 +601,23 @@ impl FigureMgr {
                     }
 
                     let target_base = match character.movement {
-                        Stand => anim::quadruped::IdleAnimation::update_skeleton(
+                        // Gaits are chosen by speed, so only run when actually moving
+                        Run if vel.0.magnitude() > MIN_RUN_SPEED => {
+                            anim::quadruped::RunAnimation::update_skeleton(
+                                &QuadrupedSkeleton::new(),
+                                (vel.0.magnitude(), time),
+                                state.movement_time,
+                                &mut movement_animation_rate,
+                                skeleton_attr,
+                            )
+                        }
+                        Stand | Run => anim::quadruped::IdleAnimation::update_skeleton(
                             &QuadrupedSkeleton::new(),
                             time,
                             state.movement_time,
                             &mut movement_animation_rate,
                             skeleton_attr,
                         ),
-                        Run => anim::quadruped::RunAnimation::update_skeleton(
-                            &QuadrupedSkeleton::new(),
-                            (vel.0.magnitude(), time),
-                            state.movement_time,
-                            &mut movement_animation_rate,
-                            skeleton_attr,
-                        ),
                         Jump => anim::quadruped::JumpAnimation::update_skeleton(
                             &QuadrupedSkeleton::new(),
                             (vel.0.magnitude(), time),
@@ -303,6 +631,8 @@ impl FigureMgr {
                     };
 
                     state.skeleton.interpolate(&target_base, dt);
+                    state.collapse = time_since_death(stats)
+                        .map_or(0.0, |time| smoothstep(0.0, DEATH_DURATION, time as f32));
                     state.update(
                         renderer,
                         pos.0,
@@ -332,20 +662,23 @@ impl FigureMgr {
                     }
 
                     let target_base = match character.movement {
-                        Stand => anim::quadrupedmedium::IdleAnimation::update_skeleton(
+                        // Gaits are chosen by speed, so only run when actually moving
+                        Run if vel.0.magnitude() > MIN_RUN_SPEED => {
+                            anim::quadrupedmedium::RunAnimation::update_skeleton(
+                                &QuadrupedMediumSkeleton::new(),
+                                (vel.0.magnitude(), time),
+                                state.movement_time,
+                                &mut movement_animation_rate,
+                                skeleton_attr,
+                            )
+                        }
+                        Stand | Run => anim::quadrupedmedium::IdleAnimation::update_skeleton(
                             &QuadrupedMediumSkeleton::new(),
                             time,
                             state.movement_time,
                             &mut movement_animation_rate,
                             skeleton_attr,
                         ),
-                        Run => anim::quadrupedmedium::RunAnimation::update_skeleton(
-                            &QuadrupedMediumSkeleton::new(),
-                            (vel.0.magnitude(), time),
-                            state.movement_time,
-                            &mut movement_animation_rate,
-                            skeleton_attr,
-                        ),
                         Jump => anim::quadrupedmedium::JumpAnimation::update_skeleton(
                             &QuadrupedMediumSkeleton::new(),
                             (vel.0.magnitude(), time),
@@ -359,6 +692,101 @@ impl FigureMgr {
                     };
 
                     state.skeleton.interpolate(&target_base, dt);
+                    state.collapse = time_since_death(stats)
+                        .map_or(0.0, |time| smoothstep(0.0, DEATH_DURATION, time as f32));
+                    state.update(
+                        renderer,
+                        pos.0,
+                        ori.0,
+                        scale,
+                        col,
+                        dt,
+                        movement_animation_rate,
+                        action_animation_rate,
+                    );
+                }
+                Body::BirdMedium(_) => {
+                    let state = self
+                        .bird_medium_states
+                        .entry(entity)
+                        .or_insert_with(|| FigureState::new(renderer, BirdMediumSkeleton::new()));
+
+                    let (character, last_character) = match (character, last_character) {
+                        (Some(c), Some(l)) => (c, l),
+                        _ => continue,
+                    };
+
+                    if !character.is_same_movement(&last_character.0) {
+                        state.movement_time = 0.0;
+                    }
+                    // Birds fly whenever they're off the ground, and settle after touching down
+                    let flying = |character: &CharacterState| match character.movement {
+                        Jump | Glide => true,
+                        _ => false,
+                    };
+                    if flying(&last_character.0) && !flying(character) {
+                        state.landing = true;
+                    }
+                    if flying(character) || state.movement_time > LAND_DURATION as f64 {
+                        state.landing = false;
+                    }
+
+                    let target_base = match character.movement {
+                        Jump | Glide => anim::bird_medium::FlyAnimation::update_skeleton(
+                            &BirdMediumSkeleton::new(),
+                            vel.0,
+                            state.movement_time,
+                            &mut movement_animation_rate,
+                            skeleton_attr,
+                        ),
+                        _ if state.landing => anim::bird_medium::LandAnimation::update_skeleton(
+                            &BirdMediumSkeleton::new(),
+                            (),
+                            state.movement_time,
+                            &mut movement_animation_rate,
+                            skeleton_attr,
+                        ),
+                        _ => anim::bird_medium::IdleAnimation::update_skeleton(
+                            &BirdMediumSkeleton::new(),
+                            time,
+                            state.movement_time,
+                            &mut movement_animation_rate,
+                            skeleton_attr,
+                        ),
+                    };
+
+                    state.skeleton.interpolate(&target_base, dt);
+                    state.collapse = time_since_death(stats)
+                        .map_or(0.0, |time| smoothstep(0.0, DEATH_DURATION, time as f32));
+                    state.update(
+                        renderer,
+                        pos.0,
+                        ori.0,
+                        scale,
+                        col,
+                        dt,
+                        movement_animation_rate,
+                        action_animation_rate,
+                    );
+                }
+                Body::FishSmall(_) => {
+                    let state = self
+                        .fish_small_states
+                        .entry(entity)
+                        .or_insert_with(|| FigureState::new(renderer, FishSmallSkeleton::new()));
+
+                    // Fish are always swimming, whatever their movement state
+                    let target_base = anim::fish_small::SwimAnimation::update_skeleton(
+                        &FishSmallSkeleton::new(),
+                        vel.0,
+                        state.movement_time,
+                        &mut movement_animation_rate,
+                        skeleton_attr,
+                    );
+
+                    state.skeleton.interpolate(&target_base, dt);
+                    state.collapse = time_since_death(stats)
+                        .map_or(0.0, |time| smoothstep(0.0, DEATH_DURATION, time as f32));
                     state.update(
                         renderer,
                         pos.0,
@@ -391,13 +819,41 @@ impl FigureMgr {
             }
         }
 
+        // Riders sit on a bone of their mount, and are carried along with it
+        for (entity, mounting, scale) in (
+            &ecs.entities(),
+            &ecs.read_storage::<Mounting>(),
+            ecs.read_storage::<Scale>().maybe(),
+        )
+            .join()
+        {
+            let seat = ecs.entity_from_uid(mounting.0.id()).and_then(|mount| {
+                ecs.read_storage::<Body>()
+                    .get(mount)
+                    .and_then(|body| self.mount_seat(mount, *body))
+            });
+            if let (Some(seat), Some(state)) = (seat, self.character_states.get_mut(&entity)) {
+                state.attach(renderer, seat, scale.map_or(1.0, |s| s.0));
+            }
+        }
+
         // Clear states that have dead entities.
         self.character_states
             .retain(|entity, _| ecs.entities().is_alive(*entity));
+        self.character_looks
+            .retain(|entity, _| ecs.entities().is_alive(*entity));
+        self.character_feet
+            .retain(|entity, _| ecs.entities().is_alive(*entity));
+        self.character_trails
+            .retain(|entity, _| ecs.entities().is_alive(*entity));
         self.quadruped_states
             .retain(|entity, _| ecs.entities().is_alive(*entity));
         self.quadruped_medium_states
             .retain(|entity, _| ecs.entities().is_alive(*entity));
+        self.bird_medium_states
+            .retain(|entity, _| ecs.entities().is_alive(*entity));
+        self.fish_small_states
+            .retain(|entity, _| ecs.entities().is_alive(*entity));
         self.object_states
             .retain(|entity, _| ecs.entities().is_alive(*entity));
     }
@@ -408,18 +864,24 @@ impl FigureMgr {
         client: &mut Client,
         globals: &Consts<Globals>,
         lights: &Consts<Light>,
+        shadows: &Consts<Shadow>,
         camera: &Camera,
     ) {
         let tick = client.get_tick();
         let ecs = client.state().ecs();
 
-        let frustum = camera.frustum(client);
+        let (view_mat, proj_mat, _) = camera.compute_dependents(client);
+        let view_proj = proj_mat * view_mat;
 
         let character_state_storage = client
             .state()
             .read_storage::<common::comp::CharacterState>();
         let character_state = character_state_storage.get(client.entity());
 
+        // Figures sharing a model are gathered up, to be drawn together
+        let mut batches: HashMap<FigureKey, (Vec<FigureInstance>, Vec<FigureBoneData>)> =
+            HashMap::new();
+
         for (entity, _, _, _, body, stats, _) in (
             &ecs.entities(),
             &ecs.read_storage::<Pos>(),
@@ -432,42 +894,178 @@ impl FigureMgr {
             .join()
             // Don't render figures outside of frustum (camera viewport, max draw distance is farplane)
             .filter(|(_, pos, _, _, _, _, scale)| {
-                frustum.sphere_intersecting(
-                    &pos.0.x,
-                    &pos.0.y,
-                    &pos.0.z,
-                    &(scale.unwrap_or(&Scale(1.0)).0 * 2.0),
-                )
+                aabb_in_frustum(view_proj, figure_bounds(pos.0, scale.map_or(1.0, |s| s.0)))
             })
-            // Don't render dead entities
-            .filter(|(_, _, _, _, _, stats, _)| stats.map_or(true, |s| !s.is_dead))
         {
-            if let Some((locals, bone_consts)) = match body {
+            let is_player = entity == client.entity();
+
+            if let Some((instance, bones)) = match body {
+                Body::Humanoid(_) if is_player && camera.get_mode() == CameraMode::FirstPerson => {
+                    self.first_person_state
+                        .as_ref()
+                        .map(|state| (state.instance(), state.bones()))
+                }
                 Body::Humanoid(_) => self
                     .character_states
                     .get(&entity)
-                    .map(|state| (state.locals(), state.bone_consts())),
+                    .map(|state| (state.instance(), state.bones())),
                 Body::Quadruped(_) => self
                     .quadruped_states
                     .get(&entity)
-                    .map(|state| (state.locals(), state.bone_consts())),
+                    .map(|state| (state.instance(), state.bones())),
                 Body::QuadrupedMedium(_) => self
                     .quadruped_medium_states
                     .get(&entity)
-                    .map(|state| (state.locals(), state.bone_consts())),
+                    .map(|state| (state.instance(), state.bones())),
+                Body::BirdMedium(_) => self
+                    .bird_medium_states
+                    .get(&entity)
+                    .map(|state| (state.instance(), state.bones())),
+                Body::FishSmall(_) => self
+                    .fish_small_states
+                    .get(&entity)
+                    .map(|state| (state.instance(), state.bones())),
                 Body::Object(_) => self
                     .object_states
                     .get(&entity)
-                    .map(|state| (state.locals(), state.bone_consts())),
+                    .map(|state| (state.instance(), state.bones())),
             } {
-                let is_player = entity == client.entity();
-
                 let player_camera_mode = if is_player {
                     camera.get_mode()
                 } else {
                     CameraMode::default()
                 };
+                let character_state = if is_player { character_state } else { None };
+
+                self.model_cache.get_or_create_model(
+                    renderer,
+                    *body,
+                    stats.map(|s| &s.equipment),
+                    tick,
+                    player_camera_mode,
+                    character_state,
+                );
+
+                let batch = batches
+                    .entry(FigureKey::new(
+                        *body,
+                        stats.map(|s| &s.equipment),
+                        player_camera_mode,
+                        character_state,
+                    ))
+                    .or_insert_with(|| (Vec::new(), Vec::new()));
+                batch.0.push(instance);
+                batch.1.extend_from_slice(bones);
+            } else {
+                debug!("Body has no saved figure");
+            }
+        }
 
+        let mut batch_idx = 0;
+        for (key, (instances, bones)) in batches {
+            let model = match self.model_cache.get_model(&key) {
+                Some((model, _)) => model,
+                None => continue,
+            };
+
+            // Figures sharing a model share a skeleton, so each has the same number of bones
+            let bone_count = bones.len() / instances.len();
+            let per_batch = figure_instances_per_batch(bone_count);
+            for (instances, bones) in instances
+                .chunks(per_batch)
+                .zip(bones.chunks(per_batch * bone_count))
+            {
+                let instances = instances
+                    .iter()
+                    .enumerate()
+                    .map(|(i, instance)| instance.with_bone_base((i * bone_count) as u32))
+                    .collect::<Vec<_>>();
+
+                if batch_idx == self.instance_batches.len() {
+                    self.instance_batches.push(InstanceBatch::new(renderer));
+                }
+                let batch = &mut self.instance_batches[batch_idx];
+                batch_idx += 1;
+
+                renderer
+                    .update_instances(&mut batch.instances, &instances)
+                    .expect("Failed to update figure instances");
+                renderer
+                    .update_consts(&mut batch.bones, bones)
+                    .expect("Failed to update figure bones");
+                renderer.render_figures(
+                    model,
+                    globals,
+                    &batch.instances,
+                    &batch.bones,
+                    instances.len(),
+                    lights,
+                    shadows,
+                );
+            }
+        }
+    }
+
+    /// Render every figure that could cast a shadow into a cascade of the shadow map. The player
+    /// always casts the shadow of their whole body, even in first person.
+    pub fn render_shadows(
+        &mut self,
+        renderer: &mut Renderer,
+        client: &mut Client,
+        shadow_locals: &Consts<ShadowLocals>,
+        cascade: usize,
+        shadow_mat: Mat4<f32>,
+    ) {
+        let tick = client.get_tick();
+        let ecs = client.state().ecs();
+
+        let character_state_storage = client
+            .state()
+            .read_storage::<common::comp::CharacterState>();
+        let character_state = character_state_storage.get(client.entity());
+
+        for (entity, _, body, stats, _) in (
+            &ecs.entities(),
+            &ecs.read_storage::<Pos>(),
+            &ecs.read_storage::<Body>(),
+            ecs.read_storage::<Stats>().maybe(),
+            ecs.read_storage::<Scale>().maybe(),
+        )
+            .join()
+            // The cascade reaches back towards the sun, so this also keeps figures that shade it
+            // from outside of it
+            .filter(|(_, pos, _, _, scale)| {
+                aabb_in_frustum(shadow_mat, figure_bounds(pos.0, scale.map_or(1.0, |s| s.0)))
+            })
+        {
+            let is_player = entity == client.entity();
+
+            if let Some((locals, bone_consts)) = match body {
+                Body::Humanoid(_) => self
+                    .character_states
+                    .get(&entity)
+                    .map(|state| (state.locals(), state.bone_consts())),
+                Body::Quadruped(_) => self
+                    .quadruped_states
+                    .get(&entity)
+                    .map(|state| (state.locals(), state.bone_consts())),
+                Body::QuadrupedMedium(_) => self
+                    .quadruped_medium_states
+                    .get(&entity)
+                    .map(|state| (state.locals(), state.bone_consts())),
+                Body::BirdMedium(_) => self
+                    .bird_medium_states
+                    .get(&entity)
+                    .map(|state| (state.locals(), state.bone_consts())),
+                Body::FishSmall(_) => self
+                    .fish_small_states
+                    .get(&entity)
+                    .map(|state| (state.locals(), state.bone_consts())),
+                Body::Object(_) => self
+                    .object_states
+                    .get(&entity)
+                    .map(|state| (state.locals(), state.bone_consts())),
+            } {
                 let model = &self
                     .model_cache
                     .get_or_create_model(
@@ -475,14 +1073,100 @@ impl FigureMgr {
                         *body,
                         stats.map(|s| &s.equipment),
                         tick,
-                        player_camera_mode,
+                        CameraMode::ThirdPerson,
                         if is_player { character_state } else { None },
                     )
                     .0;
 
-                renderer.render_figure(model, globals, locals, bone_consts, lights);
-            } else {
-                debug!("Body has no saved figure");
+                renderer.render_figure_shadow(model, locals, bone_consts, shadow_locals, cascade);
+            }
+        }
+    }
+
+    /// Render an outline around the figure of `entity`, to show that the player is targeting it.
+    pub fn render_outline(
+        &mut self,
+        renderer: &mut Renderer,
+        client: &Client,
+        entity: EcsEntity,
+        globals: &Consts<Globals>,
+        outline_locals: &Consts<OutlineLocals>,
+    ) {
+        let tick = client.get_tick();
+        let ecs = client.state().ecs();
+
+        let body = match ecs.read_storage::<Body>().get(entity) {
+            Some(body) => *body,
+            None => return,
+        };
+        let stats_storage = ecs.read_storage::<Stats>();
+        let stats = stats_storage.get(entity);
+
+        if let Some((locals, bone_consts)) = match body {
+            Body::Humanoid(_) => self
+                .character_states
+                .get(&entity)
+                .map(|state| (state.locals(), state.bone_consts())),
+            Body::Quadruped(_) => self
+                .quadruped_states
+                .get(&entity)
+                .map(|state| (state.locals(), state.bone_consts())),
+            Body::QuadrupedMedium(_) => self
+                .quadruped_medium_states
+                .get(&entity)
+                .map(|state| (state.locals(), state.bone_consts())),
+            Body::BirdMedium(_) => self
+                .bird_medium_states
+                .get(&entity)
+                .map(|state| (state.locals(), state.bone_consts())),
+            Body::FishSmall(_) => self
+                .fish_small_states
+                .get(&entity)
+                .map(|state| (state.locals(), state.bone_consts())),
+            Body::Object(_) => self
+                .object_states
+                .get(&entity)
+                .map(|state| (state.locals(), state.bone_consts())),
+        } {
+            // Only other entities can be targeted, so the model is the one they're rendered with
+            let model = &self
+                .model_cache
+                .get_or_create_model(
+                    renderer,
+                    body,
+                    stats.map(|s| &s.equipment),
+                    tick,
+                    CameraMode::default(),
+                    None,
+                )
+                .0;
+
+            renderer.render_figure_outline(model, globals, locals, bone_consts, outline_locals);
+        }
+    }
+
+    /// The world space matrix of the bone that riders of `mount` sit on, if it can be ridden.
+    fn mount_seat(&self, mount: EcsEntity, body: Body) -> Option<Mat4<f32>> {
+        let bone = body.mount_bone()?;
+        match body {
+            Body::Quadruped(_) => self
+                .quadruped_states
+                .get(&mount)
+                .and_then(|state| state.bone_matrix(bone)),
+            Body::QuadrupedMedium(_) => self
+                .quadruped_medium_states
+                .get(&mount)
+                .and_then(|state| state.bone_matrix(bone)),
+            _ => None,
+        }
+    }
+
+    /// Render the trails of swung weapons. These are translucent, so should be rendered after
+    /// everything opaque.
+    pub fn render_trails(&self, renderer: &mut Renderer, globals: &Consts<Globals>) {
+        for trail in self.character_trails.values() {
+            if let Some(model) = trail.model() {
+                renderer.render_trail(&model, globals);
             }
         }
     }
@@ -490,28 +1174,62 @@ impl FigureMgr {
 
 pub struct FigureState<S: Skeleton> {
     bone_consts: Consts<FigureBoneData>,
+    /// What was last uploaded to `bone_consts`, kept for drawing the figure instanced.
+    bones: Vec<FigureBoneData>,
     locals: Consts<FigureLocals>,
     movement_time: f64,
     action_time: f64,
     skeleton: S,
+    blender: AnimationBlender<S>,
+    falling: bool,
+    /// Whether a flying figure has just touched down, and is settling on the ground.
+    landing: bool,
+    emote: Option<Emote>,
+    /// How far the figure is through its windup, swing and recovery, for figures that attack.
+    attack: AttackSequence,
+    dead: bool,
+    /// How far a dead figure without a dying animation has rolled onto its side, from 0 to 1.
+    collapse: f32,
     pos: Vec3<f32>,
     ori: Vec3<f32>,
     last_ori: Vec3<f32>,
+    /// Takes the figure's space to world space, as of the last update.
+    model_mat: Mat4<f32>,
+    col: Rgba<f32>,
 }
 
-impl<S: Skeleton> FigureState<S> {
+impl<S: Skeleton + Clone> FigureState<S> {
     pub fn new(renderer: &mut Renderer, skeleton: S) -> Self {
+        assert!(
+            S::BONE_COUNT <= MAX_FIGURE_BONES,
+            "Skeletons can't have more than {} bones",
+            MAX_FIGURE_BONES
+        );
+        // Only the skeleton's own bones are uploaded, the rest of the buffer is never read
+        let bones = skeleton.compute_matrices();
+        let mut bone_consts = renderer
+            .create_consts(&[FigureBoneData::default(); MAX_FIGURE_BONES])
+            .unwrap();
+        renderer.update_consts(&mut bone_consts, &bones).unwrap();
         Self {
-            bone_consts: renderer
-                .create_consts(&skeleton.compute_matrices())
-                .unwrap(),
+            bone_consts,
+            bones,
             locals: renderer.create_consts(&[FigureLocals::default()]).unwrap(),
             movement_time: 0.0,
             action_time: 0.0,
             skeleton,
+            blender: AnimationBlender::new(),
+            falling: false,
+            landing: false,
+            emote: None,
+            attack: AttackSequence::new(),
+            dead: false,
+            collapse: 0.0,
             pos: Vec3::zero(),
             ori: Vec3::zero(),
             last_ori: Vec3::zero(),
+            model_mat: Mat4::identity(),
+            col: Rgba::broadcast(1.0),
         }
     }
 
@@ -541,18 +1259,46 @@ impl<S: Skeleton> FigureState<S> {
         self.action_time += (dt * action_rate) as f64;
 
         let mat = Mat4::<f32>::identity()
-            * Mat4::translation_3d(self.pos)
+            * Mat4::translation_3d(
+                self.pos + Vec3::unit_z() * self.collapse * COLLAPSE_LIFT * scale,
+            )
             * Mat4::rotation_z(-ori.x.atan2(ori.y))
-            * Mat4::scaling_3d(Vec3::from(0.8 * scale));
+            * Mat4::rotation_y(self.collapse * FRAC_PI_2)
+            * Mat4::scaling_3d(Vec3::from(FIGURE_SCALE * scale));
+
+        self.model_mat = mat;
+        self.col = col;
 
         let locals = FigureLocals::new(mat, col);
         renderer.update_consts(&mut self.locals, &[locals]).unwrap();
 
+        self.skeleton.fill_matrices(&mut self.bones);
         renderer
-            .update_consts(&mut self.bone_consts, &self.skeleton.compute_matrices())
+            .update_consts(&mut self.bone_consts, &self.bones)
             .unwrap();
     }
 
+    /// Place the figure on `parent`, a bone matrix in world space, instead of at its own position
+    /// and orientation. The bone's scale is ignored, so that the figure keeps its own size.
+    pub fn attach(&mut self, renderer: &mut Renderer, parent: Mat4<f32>, scale: f32) {
+        let mut parent = parent;
+        parent.cols.x = parent.cols.x.normalized();
+        parent.cols.y = parent.cols.y.normalized();
+        parent.cols.z = parent.cols.z.normalized();
+
+        self.pos = Vec3::from(parent.cols.w);
+        self.model_mat = parent * Mat4::scaling_3d(Vec3::from(FIGURE_SCALE * scale));
+
+        let locals = FigureLocals::new(self.model_mat, self.col);
+        renderer.update_consts(&mut self.locals, &[locals]).unwrap();
+    }
+
+    /// The world space matrix of the bone with the given name, as of the last update.
+    pub fn bone_matrix(&self, name: &str) -> Option<Mat4<f32>> {
+        S::bone_index(name)
+            .map(|index| self.model_mat * Mat4::from_col_arrays(self.bones[index].bone_mat))
+    }
+
     pub fn locals(&self) -> &Consts<FigureLocals> {
         &self.locals
     }
@@ -561,7 +1307,123 @@ impl<S: Skeleton> FigureState<S> {
         &self.bone_consts
     }
 
+    /// The figure's placement and tint, as of the last update, for drawing it instanced.
+    pub fn instance(&self) -> FigureInstance {
+        FigureInstance::new(self.model_mat, self.col)
+    }
+
+    pub fn bones(&self) -> &[FigureBoneData] {
+        &self.bones
+    }
+
     pub fn skeleton_mut(&mut self) -> &mut S {
         &mut self.skeleton
     }
 }
+
+/// The eyes of figures, bucketed by which `LOOK_RANGE` wide column of the world they're in, so
+/// that finding something for a character to look at only means checking the columns around it.
+#[derive(Default)]
+struct LookTargets(HashMap<Vec2<i32>, Vec<(EcsEntity, Vec3<f32>)>>);
+
+impl LookTargets {
+    fn column(pos: Vec3<f32>) -> Vec2<i32> {
+        Vec2::from(pos).map(|e: f32| (e / LOOK_RANGE).floor() as i32)
+    }
+
+    fn insert(&mut self, entity: EcsEntity, eyes: Vec3<f32>) {
+        self.0
+            .entry(Self::column(eyes))
+            .or_insert_with(Vec::new)
+            .push((entity, eyes));
+    }
+
+    /// The nearest eyes within `LOOK_RANGE` of `pos`, other than those of `entity` itself.
+    fn nearest(&self, entity: EcsEntity, pos: Vec3<f32>) -> Option<Vec3<f32>> {
+        let column = Self::column(pos);
+        (-1..=1)
+            .flat_map(|x| (-1..=1).map(move |y| column + Vec2::new(x, y)))
+            .filter_map(|column| self.0.get(&column))
+            .flatten()
+            .filter(|(other, _)| *other != entity)
+            .map(|(_, target)| *target)
+            .filter(|target| target.distance_squared(pos) < LOOK_RANGE.powi(2))
+            .min_by(|a, b| {
+                a.distance_squared(pos)
+                    .partial_cmp(&b.distance_squared(pos))
+                    .unwrap()
+            })
+    }
+}
+
+/// How long ago, in seconds, a dead figure died, or `None` if it's alive.
+fn time_since_death(stats: Option<&Stats>) -> Option<f64> {
+    stats.filter(|stats| stats.is_dead).map(|stats| {
+        stats
+            .health
+            .last_change
+            .map_or(DEATH_DURATION as f64, |(_, time, _)| time)
+    })
+}
+
+/// A box around everything a figure at `pos` might draw. Figures stand on their position, so their
+/// bounds mostly extend upwards from it.
+fn figure_bounds(pos: Vec3<f32>, scale: f32) -> Aabb<f32> {
+    Aabb {
+        min: pos - Vec3::new(2.0, 2.0, 0.0) * scale,
+        max: pos + Vec3::new(2.0, 2.0, 4.0) * scale,
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).min(1.0).max(0.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Opacity of a figure at the given camera distance, fading out smoothly between the `start`
+/// and `end` distances in the same way as sprites.
+pub fn distance_alpha(distance: f32, start: f32, end: f32) -> f32 {
+    1.0 - smoothstep(start, end, distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_fade() {
+        assert_eq!(distance_alpha(0.0, 50.0, 100.0), 1.0);
+        assert_eq!(distance_alpha(50.0, 50.0, 100.0), 1.0);
+        assert_eq!(distance_alpha(100.0, 50.0, 100.0), 0.0);
+        assert_eq!(distance_alpha(250.0, 50.0, 100.0), 0.0);
+        assert!((distance_alpha(75.0, 50.0, 100.0) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn nearest_look_target() {
+        let world = specs::World::new();
+        let (looker, near, far, distant) = (
+            world.entities().create(),
+            world.entities().create(),
+            world.entities().create(),
+            world.entities().create(),
+        );
+        let pos = Vec3::new(LOOK_RANGE - 0.5, 0.0, 0.0);
+
+        let mut targets = LookTargets::default();
+        targets.insert(looker, pos);
+        targets.insert(far, pos + Vec3::new(-LOOK_RANGE * 0.9, 0.0, 0.0));
+        targets.insert(distant, pos + Vec3::new(0.0, LOOK_RANGE * 1.5, 0.0));
+        assert_eq!(
+            targets.nearest(looker, pos),
+            Some(pos + Vec3::new(-LOOK_RANGE * 0.9, 0.0, 0.0))
+        );
+
+        // Across the edge of the column, but closer
+        targets.insert(near, pos + Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(
+            targets.nearest(looker, pos),
+            Some(pos + Vec3::new(1.0, 0.0, 0.0))
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use crate::render::{DynamicModel, Mesh, Model, Quad, Renderer, TrailPipeline, TrailVertex};
+use std::collections::VecDeque;
+use vek::*;
+
+/// How many frames of a weapon's movement its trail spans.
+const TRAIL_LENGTH: usize = 16;
+/// How long, in seconds, each part of a trail takes to fade away.
+const TRAIL_LIFETIME: f32 = 0.15;
+/// How opaque a trail is where it's freshest.
+const TRAIL_OPACITY: f32 = 0.5;
+/// How far along the blade, which runs along the y axis of the weapon bone, the trail starts and
+/// ends.
+const BLADE_BASE: f32 = 4.0;
+const BLADE_TIP: f32 = 16.0;
+
+struct Sample {
+    base: Vec3<f32>,
+    tip: Vec3<f32>,
+    age: f32,
+}
+
+/// A translucent ribbon that follows the blade of a swung weapon, sampled from the weapon bone
+/// each frame and fading away behind it.
+pub struct WeaponTrail {
+    samples: VecDeque<Sample>,
+    model: Option<DynamicModel<TrailPipeline>>,
+    len: usize,
+}
+
+impl WeaponTrail {
+    pub fn new() -> Self {
+        Self {
+            samples: VecDeque::with_capacity(TRAIL_LENGTH),
+            model: None,
+            len: 0,
+        }
+    }
+
+    /// Age the trail, and extend it to where the weapon is now if it's being swung. `weapon_mat`
+    /// takes the weapon bone's space to world space.
+    pub fn update(&mut self, weapon_mat: Option<Mat4<f32>>, dt: f32) {
+        for sample in self.samples.iter_mut() {
+            sample.age += dt;
+        }
+        while self
+            .samples
+            .front()
+            .map_or(false, |sample| sample.age > TRAIL_LIFETIME)
+        {
+            self.samples.pop_front();
+        }
+
+        if let Some(mat) = weapon_mat {
+            if self.samples.len() == TRAIL_LENGTH {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(Sample {
+                base: Vec3::from(mat * Vec4::from_point(Vec3::unit_y() * BLADE_BASE)),
+                tip: Vec3::from(mat * Vec4::from_point(Vec3::unit_y() * BLADE_TIP)),
+                age: 0.0,
+            });
+        }
+    }
+
+    /// The ribbon between each consecutive pair of samples, fading out towards the hilt and with
+    /// age.
+    fn mesh(&self) -> Mesh<TrailPipeline> {
+        let mut mesh = Mesh::new();
+        let col = |age: f32, opacity: f32| {
+            Rgba::new(
+                1.0,
+                1.0,
+                1.0,
+                (1.0 - age / TRAIL_LIFETIME).max(0.0) * opacity,
+            )
+        };
+
+        for (a, b) in self.samples.iter().zip(self.samples.iter().skip(1)) {
+            mesh.push_quad(Quad::new(
+                TrailVertex::new(a.base, col(a.age, 0.0)),
+                TrailVertex::new(b.base, col(b.age, 0.0)),
+                TrailVertex::new(b.tip, col(b.age, TRAIL_OPACITY)),
+                TrailVertex::new(a.tip, col(a.age, TRAIL_OPACITY)),
+            ));
+        }
+
+        mesh
+    }
+
+    /// Send the trail to the GPU, ready to be rendered.
+    pub fn update_model(&mut self, renderer: &mut Renderer) {
+        let mesh = self.mesh();
+        self.len = mesh.vertices().len();
+        if self.len == 0 {
+            return;
+        }
+
+        if self.model.is_none() {
+            self.model = renderer.create_dynamic_model((TRAIL_LENGTH - 1) * 6).ok();
+        }
+        if let Some(model) = &self.model {
+            renderer.update_model(model, &mesh, 0).unwrap();
+        }
+    }
+
+    /// The part of the trail's model in use, if there's anything to render.
+    pub fn model(&self) -> Option<Model<TrailPipeline>> {
+        self.model
+            .as_ref()
+            .filter(|_| self.len > 0)
+            .map(|model| model.submodel(0..self.len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trail_fades() {
+        let mut trail = WeaponTrail::new();
+        for i in 0..TRAIL_LENGTH * 2 {
+            trail.update(Some(Mat4::translation_3d(Vec3::unit_x() * i as f32)), 0.001);
+        }
+        // The trail holds only the most recent samples, joined by a quad each
+        assert_eq!(trail.samples.len(), TRAIL_LENGTH);
+        assert_eq!(trail.mesh().vertices().len(), (TRAIL_LENGTH - 1) * 6);
+
+        // Once the swing ends, the trail fades away
+        trail.update(None, TRAIL_LIFETIME * 2.0);
+        assert_eq!(trail.mesh().vertices().len(), 0);
+    }
+}
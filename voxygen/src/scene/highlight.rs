@@ -0,0 +1,200 @@
+use super::{camera::Camera, figure::FigureMgr};
+use crate::render::{
+    create_outline_block_mesh, Consts, Globals, Model, OutlineLocals, OutlinePipeline, Renderer,
+};
+use client::Client;
+use common::{comp, vol::ReadVol};
+use specs::{Entity as EcsEntity, Join};
+use std::cmp::Ordering;
+use vek::*;
+
+/// How far beyond the camera's focus, in blocks, the player can reach.
+const REACH: f32 = 5.0;
+/// How far away blocks can be built on. This must match the range used for building.
+const BUILD_RANGE: f32 = 100.0;
+
+const BLOCK_OUTLINE_COL: Rgba<f32> = Rgba {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 0.6,
+};
+/// How thick, in blocks, the edges of the block wireframe are.
+const BLOCK_OUTLINE_THICKNESS: f32 = 0.03;
+
+const ENTITY_OUTLINE_COL: Rgba<f32> = Rgba {
+    r: 1.0,
+    g: 0.9,
+    b: 0.6,
+    a: 0.8,
+};
+/// How far, in blocks, the outline around entities extends beyond them.
+const ENTITY_OUTLINE_WIDTH: f32 = 0.05;
+
+/// Something the player is looking at, and that they could interact with or hit.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Target {
+    Block(Vec3<i32>),
+    Entity(EcsEntity),
+}
+
+/// Highlights whatever is under the crosshair: a wireframe around blocks, and an outline around
+/// entities.
+pub struct Highlight {
+    target: Option<Target>,
+
+    block_model: Model<OutlinePipeline>,
+    block_locals: Consts<OutlineLocals>,
+    entity_locals: Consts<OutlineLocals>,
+}
+
+impl Highlight {
+    pub fn new(renderer: &mut Renderer) -> Self {
+        Self {
+            target: None,
+
+            block_model: renderer
+                .create_model(&create_outline_block_mesh(BLOCK_OUTLINE_THICKNESS))
+                .unwrap(),
+            block_locals: renderer.create_consts(&[OutlineLocals::default()]).unwrap(),
+            entity_locals: renderer
+                .create_consts(&[OutlineLocals::new(
+                    Mat4::identity(),
+                    ENTITY_OUTLINE_COL,
+                    ENTITY_OUTLINE_WIDTH,
+                )])
+                .unwrap(),
+        }
+    }
+
+    /// Find what the player is targeting, preferring whichever of the nearest block and entity
+    /// along the camera's line of sight is closer.
+    pub fn maintain(&mut self, renderer: &mut Renderer, client: &Client, camera: &Camera) {
+        let (view_mat, _, cam_pos) = camera.compute_dependents(client);
+        let cam_dir: Vec3<f32> = Vec3::from(view_mat.inverted() * -Vec4::unit_z());
+        let reach = camera.get_distance() + REACH;
+
+        let ecs = client.state().ecs();
+        let can_build = ecs
+            .read_storage::<comp::CanBuild>()
+            .get(client.entity())
+            .is_some();
+        let block_range = if can_build { BUILD_RANGE } else { reach };
+
+        let block = match client
+            .state()
+            .terrain()
+            .ray(cam_pos, cam_pos + cam_dir * block_range)
+            .cast()
+        {
+            (dist, Ok(Some(_))) => Some((
+                dist,
+                Target::Block((cam_pos + cam_dir * dist).map(|e| e.floor() as i32)),
+            )),
+            _ => None,
+        };
+
+        let entity = (
+            &ecs.entities(),
+            &ecs.read_storage::<comp::Pos>(),
+            &ecs.read_storage::<comp::Body>(),
+            ecs.read_storage::<comp::Scale>().maybe(),
+        )
+            .join()
+            .filter(|(entity, _, _, _)| *entity != client.entity())
+            .filter_map(|(entity, pos, _, scale)| {
+                // Figures stand on their position, so are roughly a sphere sitting on it
+                let scale = scale.map_or(1.0, |s| s.0);
+                ray_sphere(cam_pos, cam_dir, pos.0 + Vec3::unit_z() * scale, scale)
+                    .filter(|dist| *dist <= reach)
+                    .map(|dist| (dist, Target::Entity(entity)))
+            })
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        let target = match (block, entity) {
+            (Some(block), Some(entity)) => Some(if entity.0 < block.0 { entity } else { block }),
+            (block, entity) => block.or(entity),
+        }
+        .map(|(_, target)| target);
+
+        if target != self.target {
+            if let Some(Target::Block(pos)) = target {
+                renderer
+                    .update_consts(
+                        &mut self.block_locals,
+                        &[OutlineLocals::new(
+                            Mat4::translation_3d(pos.map(|e| e as f32)),
+                            BLOCK_OUTLINE_COL,
+                            0.0,
+                        )],
+                    )
+                    .expect("Failed to update block outline constants");
+            }
+        }
+        self.target = target;
+    }
+
+    pub fn render(
+        &self,
+        renderer: &mut Renderer,
+        client: &Client,
+        figure_mgr: &mut FigureMgr,
+        globals: &Consts<Globals>,
+    ) {
+        match self.target {
+            Some(Target::Block(_)) => {
+                renderer.render_block_outline(&self.block_model, globals, &self.block_locals)
+            }
+            Some(Target::Entity(entity)) => {
+                figure_mgr.render_outline(renderer, client, entity, globals, &self.entity_locals)
+            }
+            None => {}
+        }
+    }
+}
+
+/// How far along the ray from `origin` in the direction `dir` (which should be normalized) it
+/// first meets the sphere, if it does at all.
+fn ray_sphere(origin: Vec3<f32>, dir: Vec3<f32>, center: Vec3<f32>, radius: f32) -> Option<f32> {
+    let to_center = center - origin;
+    let along = to_center.dot(dir);
+    let dist_sq = to_center.magnitude_squared() - along * along;
+    if dist_sq > radius * radius {
+        return None;
+    }
+
+    let half_chord = (radius * radius - dist_sq).sqrt();
+    if along + half_chord < 0.0 {
+        // The sphere is behind the ray
+        None
+    } else {
+        Some((along - half_chord).max(0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_sphere_intersection() {
+        let hit = ray_sphere(Vec3::zero(), Vec3::unit_x(), Vec3::new(5.0, 0.0, 0.0), 1.0);
+        assert_eq!(hit, Some(4.0));
+
+        // Passing by, or pointing away
+        assert_eq!(
+            ray_sphere(Vec3::zero(), Vec3::unit_x(), Vec3::new(5.0, 2.0, 0.0), 1.0),
+            None
+        );
+        assert_eq!(
+            ray_sphere(Vec3::zero(), -Vec3::unit_x(), Vec3::new(5.0, 0.0, 0.0), 1.0),
+            None
+        );
+
+        // From inside the sphere, it's hit straight away
+        assert_eq!(
+            ray_sphere(Vec3::zero(), Vec3::unit_x(), Vec3::zero(), 1.0),
+            Some(0.0)
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use crate::render::{create_lod_mesh, Consts, Globals, LodPipeline, Model, Renderer};
+use client::Client;
+
+/// The distant landscape beyond the loaded chunks, drawn from the coarse heightmap of the world
+/// that the server sends on connecting.
+pub struct Lod {
+    model: Option<Model<LodPipeline>>,
+}
+
+impl Lod {
+    pub fn new() -> Self {
+        Self { model: None }
+    }
+
+    pub fn maintain(&mut self, renderer: &mut Renderer, client: &Client) {
+        // The heightmap never changes, so it only needs meshing once
+        if self.model.is_none() {
+            self.model = Some(
+                renderer
+                    .create_model(&create_lod_mesh(client.lod()))
+                    .expect("Failed to upload LOD terrain mesh to the GPU"),
+            );
+        }
+    }
+
+    pub fn render(&self, renderer: &mut Renderer, globals: &Consts<Globals>) {
+        if let Some(model) = &self.model {
+            renderer.render_lod(model, globals);
+        }
+    }
+}
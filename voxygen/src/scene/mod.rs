@@ -1,26 +1,36 @@
 pub mod camera;
 pub mod figure;
+pub mod highlight;
+pub mod lod;
+pub mod particle;
 pub mod sound;
 pub mod terrain;
+pub mod weather;
 
 use self::{
     camera::{Camera, CameraMode},
     figure::FigureMgr,
+    highlight::Highlight,
+    lod::Lod,
+    particle::ParticleMgr,
     sound::SoundMgr,
     terrain::Terrain,
+    weather::WeatherMgr,
 };
 use crate::{
     audio::AudioFrontend,
     render::{
-        create_pp_mesh, create_skybox_mesh, Consts, Globals, Light, Model, PostProcessLocals,
-        PostProcessPipeline, Renderer, SkyboxLocals, SkyboxPipeline,
+        create_pp_mesh, create_skybox_mesh, pack_lights, shadow_cascade_mat, sun_dir, Consts, Fog,
+        Globals, Light, Model, PostProcessLocals, PostProcessPipeline, ProfileStage, Renderer,
+        Shadow, ShadowLocals, SkyboxLocals, SkyboxPipeline, MAX_LIGHTS, MAX_LIGHT_REACH,
+        MAX_SHADOW_CASCADES, UNBLOCKED_LIGHT_REACH,
     },
     window::Event,
 };
 use client::Client;
 use common::{
     comp,
-    terrain::{BlockKind, TerrainChunk},
+    terrain::{BlockKind, TerrainChunk, TerrainGrid},
     vol::ReadVol,
 };
 use specs::Join;
@@ -29,9 +39,48 @@ use vek::*;
 // TODO: Don't hard-code this.
 const CURSOR_PAN_SCALE: f32 = 0.005;
 
-const MAX_LIGHT_COUNT: usize = 32;
 const LIGHT_DIST_RADIUS: f32 = 64.0; // The distance beyond which lights may not be visible
 
+/// How quickly, per second, the height that fog settles towards catches up with the ground under
+/// the camera. Neighbouring chunks sit at different heights, so this keeps the fog from jumping
+/// between them.
+const FOG_SETTLE_RATE: f32 = 0.5;
+
+/// The wind blowing across the world at the given time, in blocks per second. Its direction
+/// wanders slowly, and it picks up and dies down in gusts.
+fn wind_at(time: f64) -> Vec2<f32> {
+    let angle = ((time * 0.003).sin() * std::f64::consts::PI) as f32;
+    let strength = (1.5 + (time * 0.21).sin() + 0.5 * (time * 0.67).sin()) as f32;
+    Vec2::new(angle.cos(), angle.sin()) * strength
+}
+
+/// How far light from `pos` travels along the positive and negative x, y and z axes before an
+/// opaque block stops it, or `UNBLOCKED_LIGHT_REACH` along axes without one nearby.
+fn light_reach(terrain: &TerrainGrid, pos: Vec3<f32>) -> (Vec3<f32>, Vec3<f32>) {
+    let reach = |dir: Vec3<f32>| match terrain
+        .ray(pos, pos + dir * MAX_LIGHT_REACH)
+        .ignore_error()
+        .until(|block| block.is_opaque())
+        .cast()
+    {
+        (dist, Ok(Some(_))) => dist,
+        _ => UNBLOCKED_LIGHT_REACH,
+    };
+
+    (
+        Vec3::new(
+            reach(Vec3::unit_x()),
+            reach(Vec3::unit_y()),
+            reach(Vec3::unit_z()),
+        ),
+        Vec3::new(
+            reach(-Vec3::unit_x()),
+            reach(-Vec3::unit_y()),
+            reach(-Vec3::unit_z()),
+        ),
+    )
+}
+
 struct Skybox {
     model: Model<SkyboxPipeline>,
     locals: Consts<SkyboxLocals>,
@@ -45,15 +94,26 @@ struct PostProcess {
 pub struct Scene {
     globals: Consts<Globals>,
     lights: Consts<Light>,
+    shadows: Consts<Shadow>,
+    shadow_locals: Vec<Consts<ShadowLocals>>,
+    /// The matrix taking world space into each shadow cascade, as of the last `maintain`.
+    shadow_mats: [Mat4<f32>; MAX_SHADOW_CASCADES],
+    /// The height that fog settles down towards, following the ground around the camera. `None`
+    /// until the camera's chunk has loaded.
+    fog_base_height: Option<f32>,
     camera: Camera,
 
     skybox: Skybox,
     postprocess: PostProcess,
     terrain: Terrain<TerrainChunk>,
+    lod: Lod,
     loaded_distance: f32,
 
     figure_mgr: FigureMgr,
+    particle_mgr: ParticleMgr,
     sound_mgr: SoundMgr,
+    weather_mgr: WeatherMgr,
+    highlight: Highlight,
 }
 
 impl Scene {
@@ -63,7 +123,17 @@ impl Scene {
 
         Self {
             globals: renderer.create_consts(&[Globals::default()]).unwrap(),
-            lights: renderer.create_consts(&[Light::default(); 32]).unwrap(),
+            lights: renderer
+                .create_consts(&[Light::default(); MAX_LIGHTS])
+                .unwrap(),
+            shadows: renderer
+                .create_consts(&[Shadow::default(); MAX_SHADOW_CASCADES])
+                .unwrap(),
+            shadow_locals: (0..MAX_SHADOW_CASCADES)
+                .map(|_| renderer.create_consts(&[ShadowLocals::default()]).unwrap())
+                .collect(),
+            shadow_mats: [Mat4::identity(); MAX_SHADOW_CASCADES],
+            fog_base_height: None,
             camera: Camera::new(resolution.x / resolution.y, CameraMode::ThirdPerson),
 
             skybox: Skybox {
@@ -77,9 +147,13 @@ impl Scene {
                     .unwrap(),
             },
             terrain: Terrain::new(renderer),
+            lod: Lod::new(),
             loaded_distance: 0.0,
             figure_mgr: FigureMgr::new(),
+            particle_mgr: ParticleMgr::new(renderer),
             sound_mgr: SoundMgr::new(),
+            weather_mgr: WeatherMgr::new(renderer),
+            highlight: Highlight::new(renderer),
         }
     }
 
@@ -98,6 +172,21 @@ impl Scene {
         &mut self.camera
     }
 
+    /// Set the gamma that the final image is adjusted by, and whether bright lights bloom.
+    pub fn set_post_process(&mut self, renderer: &mut Renderer, gamma: f32, bloom: bool) {
+        renderer
+            .update_consts(
+                &mut self.postprocess.locals,
+                &[PostProcessLocals::new(gamma, bloom)],
+            )
+            .expect("Failed to update post-processing constants");
+    }
+
+    /// Set whether terrain is meshed with ambient occlusion.
+    pub fn set_ambient_occlusion(&mut self, ambient_occlusion: bool) {
+        self.terrain.set_ambient_occlusion(ambient_occlusion);
+    }
+
     /// Handle an incoming user input event (e.g.: cursor moved, key pressed, window closed).
     ///
     /// If the event is handled, return true.
@@ -203,11 +292,52 @@ impl Scene {
         lights.sort_by_key(|light| {
             Vec3::from(Vec4::from(light.pos)).distance_squared(player_pos) as i32
         });
-        lights.truncate(MAX_LIGHT_COUNT);
+        // Stop the nearest lights at the terrain around them, so they don't shine through walls
+        for light in lights.iter_mut().take(MAX_LIGHTS) {
+            let (reach_pos, reach_neg) =
+                light_reach(&client.state().terrain(), Vec3::from(Vec4::from(light.pos)));
+            *light = light.with_reach(reach_pos, reach_neg);
+        }
+        let (lights, light_count) = pack_lights(&lights);
         renderer
             .update_consts(&mut self.lights, &lights)
             .expect("Failed to update light constants");
 
+        // Update shadow constants, fitting each cascade around the camera's focus
+        let sun_dir = sun_dir(client.state().get_time_of_day());
+        let focus_pos = self.camera.get_focus_pos();
+        let splits = renderer.shadow_quality().cascade_splits();
+        let mut shadows = [Shadow::default(); MAX_SHADOW_CASCADES];
+        for (cascade, &split) in splits.iter().enumerate() {
+            let shadow_mat = shadow_cascade_mat(sun_dir, focus_pos, split);
+            shadows[cascade] = Shadow::new(shadow_mat, split);
+            self.shadow_mats[cascade] = shadow_mat;
+            renderer
+                .update_consts(
+                    &mut self.shadow_locals[cascade],
+                    &[ShadowLocals::new(shadow_mat)],
+                )
+                .expect("Failed to update shadow constants");
+        }
+        renderer
+            .update_consts(&mut self.shadows, &shadows)
+            .expect("Failed to update shadow constants");
+
+        // Settle the fog towards the base of the chunk the camera is in
+        let chunk_base = {
+            let terrain = client.state().terrain();
+            terrain
+                .get_key(terrain.pos_key(cam_pos.map(|e| e.floor() as i32)))
+                .map(|chunk| chunk.get_min_z() as f32)
+        };
+        if let Some(base_z) = chunk_base {
+            let settle = (client.state().get_delta_time() * FOG_SETTLE_RATE).min(1.0);
+            self.fog_base_height = Some(
+                self.fog_base_height
+                    .map_or(base_z, |height| height + (base_z - height) * settle),
+            );
+        }
+
         // Update global constants.
         renderer
             .update_consts(
@@ -220,14 +350,19 @@ impl Scene {
                     self.loaded_distance,
                     client.state().get_time_of_day(),
                     client.state().get_time(),
-                    renderer.get_resolution(),
-                    lights.len(),
+                    renderer.render_resolution(),
+                    light_count as usize,
                     client
                         .state()
                         .terrain()
                         .get(cam_pos.map(|e| e.floor() as i32))
                         .map(|b| b.kind())
                         .unwrap_or(BlockKind::Air),
+                    Fog {
+                        base_height: self.fog_base_height.unwrap_or(0.0),
+                        ..Fog::default()
+                    },
+                    wind_at(client.state().get_time()),
                 )],
             )
             .expect("Failed to update global constants");
@@ -242,30 +377,78 @@ impl Scene {
             proj_mat,
         );
 
+        // Maintain the distant landscape.
+        self.lod.maintain(renderer, client);
+
         // Maintain the figures.
-        self.figure_mgr.maintain(renderer, client);
+        self.figure_mgr.maintain(renderer, client, &self.camera);
 
         // Remove unused figures.
         self.figure_mgr.clean(client.get_tick());
 
+        // Maintain the particles.
+        self.particle_mgr
+            .maintain(renderer, client, self.camera.get_focus_pos());
+
+        // Maintain the rain and snow around the camera.
+        self.weather_mgr.maintain(renderer, client, cam_pos);
+
+        // Find what the player is targeting.
+        self.highlight.maintain(renderer, client, &self.camera);
+
         // Maintain audio
         self.sound_mgr.maintain(audio, client);
     }
 
     /// Render the scene using the provided `Renderer`.
     pub fn render(&mut self, renderer: &mut Renderer, client: &mut Client) {
+        // Render the shadow map first, since terrain and figures need it to be lit.
+        for cascade in 0..renderer.shadow_quality().cascade_splits().len() {
+            let shadow_locals = &self.shadow_locals[cascade];
+            let shadow_mat = self.shadow_mats[cascade];
+            self.terrain.render_shadows(
+                renderer,
+                &self.globals,
+                shadow_locals,
+                cascade,
+                shadow_mat,
+            );
+            self.figure_mgr
+                .render_shadows(renderer, client, shadow_locals, cascade, shadow_mat);
+        }
+
         // Render the skybox first (it appears over everything else so must be rendered first).
         renderer.render_skybox(&self.skybox.model, &self.globals, &self.skybox.locals);
 
+        // Render the distant landscape behind everything that's loaded.
+        self.lod.render(renderer, &self.globals);
+
         // Render terrain and figures.
-        self.figure_mgr
-            .render(renderer, client, &self.globals, &self.lights, &self.camera);
+        renderer.profile(ProfileStage::Figures, |renderer| {
+            self.figure_mgr.render(
+                renderer,
+                client,
+                &self.globals,
+                &self.lights,
+                &self.shadows,
+                &self.camera,
+            )
+        });
         self.terrain.render(
             renderer,
             &self.globals,
             &self.lights,
+            &self.shadows,
             self.camera.get_focus_pos(),
         );
+        self.terrain
+            .render_translucent(renderer, &self.globals, &self.lights);
+        self.highlight
+            .render(renderer, client, &mut self.figure_mgr, &self.globals);
+        self.particle_mgr
+            .render(renderer, &self.globals, &self.lights);
+        self.figure_mgr.render_trails(renderer, &self.globals);
+        self.weather_mgr.render(renderer, &self.globals);
 
         renderer.render_post_process(
             &self.postprocess.model,
@@ -0,0 +1,273 @@
+use crate::render::{
+    create_particle_mesh, Consts, Globals, Instances, Light, Model, ParticleInstance,
+    ParticlePipeline, Renderer,
+};
+use client::Client;
+use common::{
+    comp::{object, Body, HealthSource, PhysicsState, Pos, Stats, Vel},
+    terrain::BlockKind,
+    vol::ReadVol,
+};
+use hashbrown::HashMap;
+use rand::{thread_rng, Rng};
+use specs::{Entity as EcsEntity, Join};
+use vek::*;
+
+/// The most particles that can be alive at once. Beyond this, new particles are dropped.
+const MAX_PARTICLES: usize = 4096;
+
+/// How fast, in blocks per second, something has to move along the ground to kick up dust.
+const DUST_MIN_SPEED: f32 = 2.0;
+/// How many puffs of dust are kicked up per block travelled.
+const DUST_PER_BLOCK: f32 = 0.8;
+/// How many drops of blood burst out of something when it's hit.
+const BLOOD_PER_HIT: usize = 12;
+/// How many sparks rise from a campfire each second.
+const SPARKS_PER_SECOND: f32 = 15.0;
+/// How many places around the camera are checked each second for a leaf to fall from, and how
+/// far away they can be.
+const LEAF_CHECKS_PER_SECOND: f32 = 8.0;
+const LEAF_RADIUS: f32 = 24.0;
+/// How many blocks of leaves deep a canopy can be for leaves to fall from underneath it.
+const MAX_CANOPY_DEPTH: usize = 16;
+
+/// Spawns particles for things happening in the world (e.g: footsteps, hits, campfires, falling
+/// leaves). Particles move and fade on the GPU, so once spawned they only need to be forgotten
+/// once they've expired.
+pub struct ParticleMgr {
+    particles: Vec<ParticleInstance>,
+    /// How long ago each entity was last hurt, as of the last frame, to tell when it's hurt again.
+    last_hurt: HashMap<EcsEntity, f64>,
+
+    model: Model<ParticlePipeline>,
+    instances: Instances<ParticleInstance>,
+}
+
+impl ParticleMgr {
+    pub fn new(renderer: &mut Renderer) -> Self {
+        Self {
+            particles: Vec::new(),
+            last_hurt: HashMap::new(),
+
+            model: renderer.create_model(&create_particle_mesh()).unwrap(),
+            instances: renderer
+                .create_instances(&[ParticleInstance::default(); MAX_PARTICLES])
+                .unwrap(),
+        }
+    }
+
+    pub fn maintain(&mut self, renderer: &mut Renderer, client: &Client, focus_pos: Vec3<f32>) {
+        let time = client.state().get_time();
+        let dt = client.state().get_delta_time();
+        let ecs = client.state().ecs();
+        let terrain = client.state().terrain();
+        let mut rng = thread_rng();
+
+        self.particles.retain(|p| !p.is_expired(time));
+        let mut spawned = Vec::new();
+
+        // Dust kicked up by anything moving along the ground
+        for (pos, vel, physics) in (
+            &ecs.read_storage::<Pos>(),
+            &ecs.read_storage::<Vel>(),
+            &ecs.read_storage::<PhysicsState>(),
+        )
+            .join()
+            .filter(|(_, _, physics)| physics.on_ground && !physics.in_fluid)
+        {
+            let speed = Vec2::<f32>::from(vel.0).magnitude();
+            if speed < DUST_MIN_SPEED {
+                continue;
+            }
+
+            let col = terrain
+                .get((pos.0 - Vec3::unit_z() * 0.5).map(|e| e.floor() as i32))
+                .ok()
+                .and_then(|block| block.get_color())
+                .map_or(Rgb::new(0.5, 0.4, 0.3), |col| col.map(|e| e as f32 / 255.0));
+            for _ in 0..spawn_count(&mut rng, speed * DUST_PER_BLOCK, dt) {
+                spawned.push(ParticleInstance::new(
+                    pos.0 + Vec3::new(rng.gen_range(-0.3, 0.3), rng.gen_range(-0.3, 0.3), 0.1),
+                    Vec3::new(
+                        -vel.0.x * 0.1 + rng.gen_range(-0.5, 0.5),
+                        -vel.0.y * 0.1 + rng.gen_range(-0.5, 0.5),
+                        rng.gen_range(0.5, 1.2),
+                    ),
+                    Rgba::from_opaque(col),
+                    time,
+                    0.6,
+                    0.12,
+                    2.0,
+                ));
+            }
+        }
+
+        // Blood bursting out of anything that's just been hit
+        for (entity, pos, stats) in (
+            &ecs.entities(),
+            &ecs.read_storage::<Pos>(),
+            &ecs.read_storage::<Stats>(),
+        )
+            .join()
+        {
+            let hurt = match stats.health.last_change {
+                Some((amount, since, HealthSource::Attack { .. })) if amount < 0 => since,
+                _ => continue,
+            };
+            // How long ago the hit was only ever grows, so if it's shrunk there's been another
+            let is_new = self
+                .last_hurt
+                .insert(entity, hurt)
+                .map_or(hurt <= dt as f64, |last| hurt < last);
+            if !is_new {
+                continue;
+            }
+
+            for _ in 0..BLOOD_PER_HIT {
+                spawned.push(ParticleInstance::new(
+                    pos.0 + Vec3::unit_z(),
+                    Vec3::new(
+                        rng.gen_range(-3.0, 3.0),
+                        rng.gen_range(-3.0, 3.0),
+                        rng.gen_range(1.0, 4.0),
+                    ),
+                    Rgba::new(0.6, 0.0, 0.0, 1.0),
+                    time,
+                    0.8,
+                    0.1,
+                    25.0,
+                ));
+            }
+        }
+        self.last_hurt
+            .retain(|entity, _| ecs.entities().is_alive(*entity));
+
+        // Sparks rising from campfires
+        for (pos, _) in (&ecs.read_storage::<Pos>(), &ecs.read_storage::<Body>())
+            .join()
+            .filter(|(_, body)| **body == Body::Object(object::Body::Campfire))
+        {
+            for _ in 0..spawn_count(&mut rng, SPARKS_PER_SECOND, dt) {
+                spawned.push(ParticleInstance::new(
+                    pos.0 + Vec3::new(rng.gen_range(-0.3, 0.3), rng.gen_range(-0.3, 0.3), 0.3),
+                    Vec3::new(
+                        rng.gen_range(-0.3, 0.3),
+                        rng.gen_range(-0.3, 0.3),
+                        rng.gen_range(1.5, 3.0),
+                    ),
+                    Rgba::new(1.0, 0.6, 0.1, 1.0),
+                    time,
+                    rng.gen_range(0.8, 1.6),
+                    0.06,
+                    -0.5,
+                ));
+            }
+        }
+
+        // Leaves drifting down from the underside of tree canopies
+        for _ in 0..spawn_count(&mut rng, LEAF_CHECKS_PER_SECOND, dt) {
+            let column = Vec2::from(focus_pos)
+                + Vec2::new(
+                    rng.gen_range(-LEAF_RADIUS, LEAF_RADIUS),
+                    rng.gen_range(-LEAF_RADIUS, LEAF_RADIUS),
+                );
+            let top = Vec3::new(column.x, column.y, focus_pos.z + LEAF_RADIUS);
+            let dist = match terrain
+                .ray(top, top - Vec3::unit_z() * LEAF_RADIUS * 2.0)
+                .ignore_error()
+                .until(|block| block.is_opaque())
+                .cast()
+            {
+                (dist, Ok(Some(_))) => dist,
+                _ => continue,
+            };
+            let leaf_col = |pos: Vec3<i32>| {
+                terrain
+                    .get(pos)
+                    .ok()
+                    .filter(|block| block.kind() == BlockKind::Leaves)
+                    .and_then(|block| block.get_color())
+            };
+
+            // Follow the leaves down to the underside of the canopy
+            let mut block_pos = (top - Vec3::unit_z() * (dist + 0.5)).map(|e| e.floor() as i32);
+            let col = match leaf_col(block_pos) {
+                Some(col) => col,
+                None => continue,
+            };
+            for _ in 0..MAX_CANOPY_DEPTH {
+                if leaf_col(block_pos - Vec3::unit_z()).is_none() {
+                    break;
+                }
+                block_pos -= Vec3::unit_z();
+            }
+            let hangs_over_air = terrain
+                .get(block_pos - Vec3::unit_z())
+                .map_or(false, |block| !block.is_opaque());
+
+            if hangs_over_air {
+                spawned.push(ParticleInstance::new(
+                    block_pos.map(|e| e as f32) + Vec3::new(0.5, 0.5, -0.5),
+                    Vec3::new(
+                        0.5 + rng.gen_range(-0.3, 0.3),
+                        0.3 + rng.gen_range(-0.3, 0.3),
+                        -0.8,
+                    ),
+                    Rgba::from_opaque(col.map(|e| e as f32 / 255.0)),
+                    time,
+                    6.0,
+                    0.15,
+                    0.0,
+                ));
+            }
+        }
+
+        let room = MAX_PARTICLES - self.particles.len();
+        self.particles.extend(spawned.into_iter().take(room));
+
+        renderer
+            .update_instances(&mut self.instances, &self.particles)
+            .expect("Failed to update particle instances");
+    }
+
+    pub fn render(
+        &self,
+        renderer: &mut Renderer,
+        globals: &Consts<Globals>,
+        lights: &Consts<Light>,
+    ) {
+        if !self.particles.is_empty() {
+            renderer.render_particles(
+                &self.model,
+                globals,
+                &self.instances,
+                self.particles.len(),
+                lights,
+            );
+        }
+    }
+}
+
+/// How many things happening `rate` times a second happen over `dt` seconds, rounding fractions
+/// up or down at random so that the rate holds on average.
+fn spawn_count(rng: &mut impl Rng, rate: f32, dt: f32) -> usize {
+    let expected = rate * dt;
+    let extra = rng.gen::<f32>() < expected.fract();
+    expected.floor() as usize + extra as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_rate() {
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            let count = spawn_count(&mut rng, 15.0, 0.1);
+            assert!(count == 1 || count == 2);
+        }
+        assert_eq!(spawn_count(&mut rng, 0.0, 0.1), 0);
+        assert_eq!(spawn_count(&mut rng, 10.0, 0.5), 5);
+    }
+}
@@ -1,9 +1,11 @@
 use crate::{
     mesh::Meshable,
     render::{
-        Consts, FluidPipeline, Globals, Instances, Light, Mesh, Model, Renderer, SpriteInstance,
-        SpritePipeline, TerrainLocals, TerrainPipeline,
+        Consts, FluidPipeline, Globals, Instances, Light, Mesh, Model, ProfileStage, Renderer,
+        Shadow, ShadowLocals, SpriteInstance, SpritePipeline, TerrainLocals, TerrainPipeline,
+        Texture,
     },
+    scene::camera::aabb_in_frustum,
 };
 
 use client::Client;
@@ -16,11 +18,25 @@ use common::{
 };
 use crossbeam::channel;
 use dot_vox::DotVoxData;
-use frustum_query::frustum::Frustum;
 use hashbrown::HashMap;
-use std::{f32, fmt::Debug, i32, marker::PhantomData, ops::Mul, time::Duration};
+use std::{f32, fmt::Debug, i32, marker::PhantomData};
 use vek::*;
 
+/// The most chunks that can be waiting for a meshing worker at once. Keeping the queue short means
+/// that chunks which become more urgent (e.g: because the player turned around) don't end up
+/// stuck behind a backlog of others.
+const MAX_QUEUED_MESH_JOBS: usize = 4;
+/// Roughly how many vertices of finished chunk meshes get uploaded to the GPU each frame. Uploads
+/// block, so spreading them out avoids hitches when many chunks finish meshing at once.
+const MESH_UPLOAD_BUDGET: usize = 65536;
+/// How far from the focus, horizontally, sprites are drawn.
+const SPRITE_RENDER_DISTANCE: f32 = 128.0;
+/// Sprites fade out over this distance as they approach `SPRITE_RENDER_DISTANCE`, rather than
+/// vanishing all at once.
+const SPRITE_FADE_DISTANCE: f32 = 16.0;
+/// How far the focus has to move before the fade of sprites near the edge is recomputed.
+const SPRITE_FADE_STEP: f32 = 1.0;
+
 struct TerrainChunk {
     // GPU data
     opaque_model: Model<TerrainPipeline>,
@@ -28,6 +44,12 @@ struct TerrainChunk {
     sprite_instances: HashMap<(BlockKind, usize), Instances<SpriteInstance>>,
     locals: Consts<TerrainLocals>,
 
+    // CPU copies of the sprite instances, so that their fade can be updated as the focus moves
+    sprite_data: HashMap<(BlockKind, usize), Vec<SpriteInstance>>,
+    /// Where the focus was when the fade of the chunk's sprites was last computed, if any of them
+    /// are faded.
+    sprite_fade_focus: Option<Vec2<f32>>,
+
     visible: bool,
     z_bounds: (f32, f32),
 }
@@ -134,15 +156,29 @@ fn sprite_config_for(kind: BlockKind) -> Option<SpriteConfig> {
     }
 }
 
+/// How opaque sprites at the given horizontal distance from the focus are drawn.
+fn sprite_fade(dist: f32) -> f32 {
+    ((SPRITE_RENDER_DISTANCE - dist) / SPRITE_FADE_DISTANCE)
+        .min(1.0)
+        .max(0.0)
+}
+
+/// Scales a sprite's base wind sway by how exposed its region is to the wind.
+fn scale_wind(base_sway: f32, region_strength: f32) -> f32 {
+    base_sway * region_strength.max(0.0)
+}
+
 /// Function executed by worker threads dedicated to chunk meshing.
 fn mesh_worker<V: BaseVol<Vox = Block> + RectRasterableVol + ReadVol + Debug>(
     pos: Vec2<i32>,
     z_bounds: (f32, f32),
+    wind_strength: f32,
     started_tick: u64,
     volume: <VolGrid2d<V> as SampleVol<Aabr<i32>>>::Sample,
     range: Aabb<i32>,
+    ambient_occlusion: bool,
 ) -> MeshWorkerResponse {
-    let (opaque_mesh, fluid_mesh) = volume.generate_mesh(range);
+    let (opaque_mesh, fluid_mesh) = volume.generate_mesh((range, ambient_occlusion));
     MeshWorkerResponse {
         pos,
         z_bounds,
@@ -170,7 +206,11 @@ fn mesh_worker<V: BaseVol<Vox = Block> + RectRasterableVol + ReadVol + Debug>(
                                         wpos.map(|e| e as f32) + Vec3::new(0.5, 0.5, 0.0),
                                     ),
                                 Rgb::broadcast(1.0),
-                                cfg.wind_sway,
+                                scale_wind(cfg.wind_sway, wind_strength),
+                                // Scatter the sway phase so that neighbouring sprites don't
+                                // move in unison
+                                (seed as u32 % 628) as f32 * 0.01,
+                                1.0,
                             );
 
                             instances
@@ -199,6 +239,12 @@ pub struct Terrain<V: RectRasterableVol> {
 
     // GPU data
     sprite_models: HashMap<(BlockKind, usize), Model<SpritePipeline>>,
+    // Plain white texture for sprites that aren't textured
+    sprite_tex: Texture<SpritePipeline>,
+
+    ambient_occlusion: bool,
+    // Whether every loaded chunk needs meshing again (e.g: because the meshing settings changed)
+    remesh_all: bool,
 
     phantom: PhantomData<V>,
 }
@@ -209,6 +255,12 @@ impl<V: RectRasterableVol> Terrain<V> {
         // worker threads that are meshing chunks.
         let (send, recv) = channel::unbounded();
 
+        let sprite_tex = renderer
+            .create_texture(&image::DynamicImage::ImageRgba8(
+                image::RgbaImage::from_pixel(1, 1, image::Rgba([255; 4])),
+            ))
+            .unwrap();
+
         let mut make_model = |s, offset| {
             renderer
                 .create_model(
@@ -598,10 +650,22 @@ impl<V: RectRasterableVol> Terrain<V> {
             ]
             .into_iter()
             .collect(),
+            sprite_tex,
+            ambient_occlusion: true,
+            remesh_all: false,
             phantom: PhantomData,
         }
     }
 
+    /// Set whether chunks are meshed with ambient occlusion. Chunks that have already been meshed
+    /// are meshed again to match.
+    pub fn set_ambient_occlusion(&mut self, ambient_occlusion: bool) {
+        if self.ambient_occlusion != ambient_occlusion {
+            self.ambient_occlusion = ambient_occlusion;
+            self.remesh_all = true;
+        }
+    }
+
     /// Maintain terrain data. To be called once per tick.
     pub fn maintain(
         &mut self,
@@ -665,6 +729,20 @@ impl<V: RectRasterableVol> Terrain<V> {
             }
         }
 
+        if self.remesh_all {
+            for &pos in self.chunks.keys() {
+                self.mesh_todo.insert(
+                    pos,
+                    ChunkMeshState {
+                        pos,
+                        started_tick: current_tick,
+                        active_worker: None,
+                    },
+                );
+            }
+            self.remesh_all = false;
+        }
+
         // Add the chunks belonging to recently changed blocks to the list of chunks to be meshed
         for pos in client
             .state()
@@ -710,19 +788,31 @@ impl<V: RectRasterableVol> Terrain<V> {
             self.mesh_todo.remove(pos);
         }
 
-        for todo in self
+        // Mesh the chunks nearest to the player first, since they're the most noticeable.
+        let focus_chunk = client
+            .state()
+            .terrain()
+            .pos_key(focus_pos.map(|e| e.floor() as i32));
+        let mut todo_order = self
             .mesh_todo
-            .values_mut()
+            .values()
             .filter(|todo| {
                 todo.active_worker
                     .map(|worker_tick| worker_tick < todo.started_tick)
                     .unwrap_or(true)
             })
-            .min_by_key(|todo| todo.active_worker.unwrap_or(todo.started_tick))
-        {
-            if client.thread_pool().queued_jobs() > 0 {
+            .map(|todo| todo.pos)
+            .collect::<Vec<_>>();
+        todo_order.sort_by_key(|pos| (*pos - focus_chunk).map(|e| e * e).sum());
+
+        for pos in todo_order {
+            if client.thread_pool().queued_jobs() >= MAX_QUEUED_MESH_JOBS {
                 break;
             }
+            let todo = match self.mesh_todo.get_mut(&pos) {
+                Some(todo) => todo,
+                None => continue,
+            };
 
             // Find the area of the terrain we want. Because meshing needs to compute things like
             // ambient occlusion and edge elision, we also need the borders of the chunk's
@@ -742,7 +832,7 @@ impl<V: RectRasterableVol> Terrain<V> {
                 Ok(sample) => sample,
                 // Either this chunk or its neighbours doesn't yet exist, so we keep it in the
                 // queue to be processed at a later date when we have its neighbours.
-                Err(VolGrid2dError::NoSuchChunk) => return,
+                Err(VolGrid2dError::NoSuchChunk) => continue,
                 _ => panic!("Unhandled edge case"),
             };
 
@@ -759,9 +849,18 @@ impl<V: RectRasterableVol> Terrain<V> {
                 max: Vec3::from(aabr.max) + Vec3::unit_z() * (max_z + 1),
             };
 
+            // Sheltered regions sway less than open ones
+            let wind_strength = client
+                .state()
+                .terrain()
+                .get_key(todo.pos)
+                .map(|chunk| chunk.meta().biome().wind_strength())
+                .unwrap_or(1.0);
+
             // Clone various things so that they can be moved into the thread.
             let send = self.mesh_send_tmp.clone();
             let pos = todo.pos;
+            let ambient_occlusion = self.ambient_occlusion;
 
             // Queue the worker thread.
             let started_tick = todo.started_tick;
@@ -769,22 +868,33 @@ impl<V: RectRasterableVol> Terrain<V> {
                 let _ = send.send(mesh_worker(
                     pos,
                     (min_z as f32, max_z as f32),
+                    wind_strength,
                     started_tick,
                     volume,
                     aabb,
+                    ambient_occlusion,
                 ));
             });
             todo.active_worker = Some(todo.started_tick);
         }
 
-        // Receive a chunk mesh from a worker thread and upload it to the GPU, then store it.
-        // Only pull out one chunk per frame to avoid an unacceptable amount of blocking lag due
-        // to the GPU upload. That still gives us a 60 chunks / second budget to play with.
-        if let Ok(response) = self.mesh_recv.recv_timeout(Duration::new(0, 0)) {
+        // Receive chunk meshes from the worker threads and upload them to the GPU, then store
+        // them. Only upload as many as fit into the budget each frame to avoid an unacceptable
+        // amount of blocking lag due to the GPU upload; the rest wait in the channel until the
+        // next frame.
+        let mut uploaded_verts = 0;
+        while uploaded_verts < MESH_UPLOAD_BUDGET {
+            let response = match self.mesh_recv.try_recv() {
+                Ok(response) => response,
+                Err(_) => break,
+            };
+
             match self.mesh_todo.get(&response.pos) {
                 // It's the mesh we want, insert the newly finished model into the terrain model
                 // data structure (convert the mesh to a model first of course).
                 Some(todo) if response.started_tick <= todo.started_tick => {
+                    uploaded_verts += response.opaque_mesh.vertices().len()
+                        + response.fluid_mesh.vertices().len();
                     self.chunks.insert(
                         response.pos,
                         TerrainChunk {
@@ -796,11 +906,11 @@ impl<V: RectRasterableVol> Terrain<V> {
                                 .expect("Failed to upload chunk mesh to the GPU!"),
                             sprite_instances: response
                                 .sprite_instances
-                                .into_iter()
+                                .iter()
                                 .map(|(kind, instances)| {
                                     (
-                                        kind,
-                                        renderer.create_instances(&instances).expect(
+                                        *kind,
+                                        renderer.create_instances(instances).expect(
                                             "Failed to upload chunk sprite instances to the GPU!",
                                         ),
                                     )
@@ -816,6 +926,8 @@ impl<V: RectRasterableVol> Terrain<V> {
                                     .into_array(),
                                 }])
                                 .expect("Failed to upload chunk locals to the GPU!"),
+                            sprite_data: response.sprite_instances,
+                            sprite_fade_focus: None,
                             visible: false,
                             z_bounds: response.z_bounds,
                         },
@@ -831,11 +943,7 @@ impl<V: RectRasterableVol> Terrain<V> {
             }
         }
 
-        // Construct view frustum
-        let frustum = Frustum::from_modelview_and_projection(
-            &view_mat.into_col_array(),
-            &proj_mat.into_col_array(),
-        );
+        let view_proj = proj_mat * view_mat;
 
         // Update chunk visibility
         let chunk_sz = V::RECT_SIZE.x as f32;
@@ -848,24 +956,55 @@ impl<V: RectRasterableVol> Terrain<V> {
                 < loaded_distance.powf(2.0);
 
             // Ensure the chunk is within the view frustrum
-            let chunk_mid = Vec3::new(
-                chunk_pos.x + chunk_sz / 2.0,
-                chunk_pos.y + chunk_sz / 2.0,
-                (chunk.z_bounds.0 + chunk.z_bounds.1) * 0.5,
-            );
-            let chunk_radius = ((chunk.z_bounds.1 - chunk.z_bounds.0) / 2.0)
-                .max(chunk_sz / 2.0)
-                .powf(2.0)
-                .mul(2.0)
-                .sqrt();
-            let in_frustum = frustum.sphere_intersecting(
-                &chunk_mid.x,
-                &chunk_mid.y,
-                &chunk_mid.z,
-                &chunk_radius,
+            let in_frustum = aabb_in_frustum(
+                view_proj,
+                Aabb {
+                    min: Vec3::new(chunk_pos.x, chunk_pos.y, chunk.z_bounds.0),
+                    max: Vec3::new(
+                        chunk_pos.x + chunk_sz,
+                        chunk_pos.y + chunk_sz,
+                        chunk.z_bounds.1,
+                    ),
+                },
             );
 
             chunk.visible = in_range && in_frustum;
+
+            // Fade out the sprites nearing the edge of the sprite render distance
+            let focus_2d = Vec2::from(focus_pos);
+            let sprite_dist = focus_2d.distance(nearest_in_chunk);
+            let farthest_in_chunk = Vec2::new(
+                if focus_2d.x < chunk_pos.x + chunk_sz / 2.0 {
+                    chunk_pos.x + chunk_sz
+                } else {
+                    chunk_pos.x
+                },
+                if focus_2d.y < chunk_pos.y + chunk_sz / 2.0 {
+                    chunk_pos.y + chunk_sz
+                } else {
+                    chunk_pos.y
+                },
+            );
+            let needs_fade = focus_2d.distance(farthest_in_chunk)
+                > SPRITE_RENDER_DISTANCE - SPRITE_FADE_DISTANCE;
+            let fade_outdated = match chunk.sprite_fade_focus {
+                Some(last_focus) => !needs_fade || last_focus.distance(focus_2d) > SPRITE_FADE_STEP,
+                None => needs_fade,
+            };
+            if sprite_dist < SPRITE_RENDER_DISTANCE && fade_outdated {
+                for (kind, data) in &mut chunk.sprite_data {
+                    for instance in data.iter_mut() {
+                        instance
+                            .set_fade(sprite_fade(Vec2::from(instance.pos()).distance(focus_2d)));
+                    }
+                    if let Some(instances) = chunk.sprite_instances.get_mut(kind) {
+                        renderer
+                            .update_instances(instances, data)
+                            .expect("Failed to update chunk sprite instances");
+                    }
+                }
+                chunk.sprite_fade_focus = if needs_fade { Some(focus_2d) } else { None };
+            }
         }
     }
 
@@ -874,42 +1013,128 @@ impl<V: RectRasterableVol> Terrain<V> {
         renderer: &mut Renderer,
         globals: &Consts<Globals>,
         lights: &Consts<Light>,
+        shadows: &Consts<Shadow>,
         focus_pos: Vec3<f32>,
     ) {
         // Opaque
-        for (_, chunk) in &self.chunks {
-            if chunk.visible {
-                renderer.render_terrain_chunk(&chunk.opaque_model, globals, &chunk.locals, lights);
+        renderer.profile(ProfileStage::Terrain, |renderer| {
+            for (_, chunk) in &self.chunks {
+                if chunk.visible {
+                    renderer.render_terrain_chunk(
+                        &chunk.opaque_model,
+                        globals,
+                        &chunk.locals,
+                        lights,
+                        shadows,
+                    );
+                }
             }
-        }
+        });
 
         // Terrain sprites
-        for (pos, chunk) in &self.chunks {
-            if chunk.visible {
-                const SPRITE_RENDER_DISTANCE: f32 = 128.0;
-
-                let chunk_center =
-                    pos.map2(V::RECT_SIZE, |e, sz: u32| (e as f32 + 0.5) * sz as f32);
-                if Vec2::from(focus_pos).distance_squared(chunk_center)
-                    < SPRITE_RENDER_DISTANCE * SPRITE_RENDER_DISTANCE
-                {
-                    for (kind, instances) in &chunk.sprite_instances {
-                        renderer.render_sprites(
-                            &self.sprite_models[&kind],
-                            globals,
-                            &instances,
-                            lights,
-                        );
+        renderer.profile(ProfileStage::Sprites, |renderer| {
+            for (pos, chunk) in &self.chunks {
+                if chunk.visible {
+                    // Sprites beyond the render distance are faded out entirely, so the chunk is
+                    // drawn as long as any part of it is within it
+                    let chunk_pos = pos.map2(V::RECT_SIZE, |e, sz: u32| e as f32 * sz as f32);
+                    let nearest_in_chunk = Vec2::from(focus_pos)
+                        .clamped(chunk_pos, chunk_pos + V::RECT_SIZE.map(|e| e as f32));
+                    if Vec2::from(focus_pos).distance_squared(nearest_in_chunk)
+                        < SPRITE_RENDER_DISTANCE * SPRITE_RENDER_DISTANCE
+                    {
+                        for (kind, instances) in &chunk.sprite_instances {
+                            renderer.render_sprites(
+                                &self.sprite_models[&kind],
+                                &self.sprite_tex,
+                                globals,
+                                &instances,
+                                lights,
+                            );
+                        }
                     }
                 }
             }
-        }
+        });
+    }
 
-        // Translucent
+    /// Render fluids, which refract everything opaque rendered before them.
+    pub fn render_translucent(
+        &self,
+        renderer: &mut Renderer,
+        globals: &Consts<Globals>,
+        lights: &Consts<Light>,
+    ) {
         for (_, chunk) in &self.chunks {
             if chunk.visible {
                 renderer.render_fluid_chunk(&chunk.fluid_model, globals, &chunk.locals, lights);
             }
         }
     }
+
+    /// Render every chunk that could cast a shadow into a cascade of the shadow map. Chunks out of
+    /// view can still cast shadows into it, so visibility is ignored.
+    pub fn render_shadows(
+        &self,
+        renderer: &mut Renderer,
+        globals: &Consts<Globals>,
+        shadow_locals: &Consts<ShadowLocals>,
+        cascade: usize,
+        shadow_mat: Mat4<f32>,
+    ) {
+        let chunk_sz = V::RECT_SIZE.x as f32;
+
+        for (pos, chunk) in &self.chunks {
+            let chunk_pos = pos.map(|e| e as f32 * chunk_sz);
+            // The cascade reaches back towards the sun, so this also keeps chunks that shade it
+            // from outside of it
+            let casts_shadow = aabb_in_frustum(
+                shadow_mat,
+                Aabb {
+                    min: Vec3::new(chunk_pos.x, chunk_pos.y, chunk.z_bounds.0),
+                    max: Vec3::new(
+                        chunk_pos.x + chunk_sz,
+                        chunk_pos.y + chunk_sz,
+                        chunk.z_bounds.1,
+                    ),
+                },
+            );
+            if casts_shadow {
+                renderer.render_terrain_shadow(
+                    &chunk.opaque_model,
+                    globals,
+                    &chunk.locals,
+                    shadow_locals,
+                    cascade,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wind_scaling() {
+        assert_eq!(scale_wind(0.8, 0.0), 0.0);
+        assert_eq!(scale_wind(0.8, 1.0), 0.8);
+        assert_eq!(scale_wind(0.8, 2.0), 2.0 * scale_wind(0.8, 1.0));
+    }
+
+    #[test]
+    fn sprites_fade_at_render_distance() {
+        assert_eq!(sprite_fade(0.0), 1.0);
+        assert_eq!(
+            sprite_fade(SPRITE_RENDER_DISTANCE - SPRITE_FADE_DISTANCE),
+            1.0
+        );
+        assert_eq!(
+            sprite_fade(SPRITE_RENDER_DISTANCE - SPRITE_FADE_DISTANCE / 2.0),
+            0.5
+        );
+        assert_eq!(sprite_fade(SPRITE_RENDER_DISTANCE), 0.0);
+        assert_eq!(sprite_fade(SPRITE_RENDER_DISTANCE * 2.0), 0.0);
+    }
 }
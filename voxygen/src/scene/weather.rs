@@ -0,0 +1,117 @@
+use crate::render::{
+    create_weather_mesh, Consts, Globals, Instances, Model, Renderer, WeatherInstance,
+    WeatherLocals, WeatherPipeline,
+};
+use client::Client;
+use common::{state::Precipitation, vol::ReadVol};
+use rand::{thread_rng, Rng};
+use vek::*;
+
+/// The most drops that can be falling at once, in the heaviest weather.
+const MAX_DROPS: usize = 6000;
+/// The size of the box around the camera that drops fall within.
+const BOX_SIZE: Vec3<f32> = Vec3 {
+    x: 48.0,
+    y: 48.0,
+    z: 32.0,
+};
+/// How quickly, per second, the weather shown turns towards what's falling where the camera is.
+const FADE_RATE: f32 = 0.5;
+/// How far above the camera a roof keeps the weather off.
+const SHELTER_HEIGHT: f32 = 32.0;
+
+/// Rain and snow falling around the camera, as heavily as the weather in the chunk it's in.
+pub struct WeatherMgr {
+    kind: Precipitation,
+    intensity: f32,
+
+    model: Model<WeatherPipeline>,
+    locals: Consts<WeatherLocals>,
+    instances: Instances<WeatherInstance>,
+}
+
+impl WeatherMgr {
+    pub fn new(renderer: &mut Renderer) -> Self {
+        let mut rng = thread_rng();
+        let drops = (0..MAX_DROPS)
+            .map(|_| WeatherInstance::new(Vec3::new(rng.gen(), rng.gen(), rng.gen())))
+            .collect::<Vec<_>>();
+
+        Self {
+            kind: Precipitation::Rain,
+            intensity: 0.0,
+
+            model: renderer.create_model(&create_weather_mesh()).unwrap(),
+            locals: renderer
+                .create_consts(&[drop_locals(Precipitation::Rain)])
+                .unwrap(),
+            instances: renderer.create_instances(&drops).unwrap(),
+        }
+    }
+
+    pub fn maintain(&mut self, renderer: &mut Renderer, client: &Client, cam_pos: Vec3<f32>) {
+        let dt = client.state().get_delta_time();
+        let terrain = client.state().terrain();
+
+        // Nothing falls on the camera when it's underwater or beneath a roof
+        let sheltered = terrain
+            .get(cam_pos.map(|e| e.floor() as i32))
+            .map_or(false, |block| block.is_fluid())
+            || match terrain
+                .ray(cam_pos, cam_pos + Vec3::unit_z() * SHELTER_HEIGHT)
+                .ignore_error()
+                .until(|block| block.is_opaque())
+                .cast()
+            {
+                (_, Ok(Some(_))) => true,
+                _ => false,
+            };
+
+        let falling = terrain
+            .get_key(terrain.pos_key(cam_pos.map(|e| e.floor() as i32)))
+            .and_then(|chunk| client.state().get_weather().precipitation_in(chunk.meta()))
+            .filter(|_| !sheltered);
+        let (kind, target) = falling.unwrap_or((self.kind, 0.0));
+
+        // When rain turns to snow or back, one fades out before the other fades in
+        let target = if kind == self.kind { target } else { 0.0 };
+        let max_change = FADE_RATE * dt;
+        self.intensity += (target - self.intensity).max(-max_change).min(max_change);
+
+        if kind != self.kind && self.intensity <= 0.0 {
+            self.kind = kind;
+            renderer
+                .update_consts(&mut self.locals, &[drop_locals(kind)])
+                .expect("Failed to update weather constants");
+        }
+    }
+
+    pub fn render(&self, renderer: &mut Renderer, globals: &Consts<Globals>) {
+        if self.intensity > 0.0 {
+            let count = (self.intensity * MAX_DROPS as f32) as usize;
+            renderer.render_weather(&self.model, globals, &self.locals, &self.instances, count);
+        }
+    }
+}
+
+/// How drops of rain and flakes of snow look and fall.
+fn drop_locals(kind: Precipitation) -> WeatherLocals {
+    match kind {
+        // Long, thin streaks, falling straight down
+        Precipitation::Rain => WeatherLocals::new(
+            Rgba::new(0.6, 0.65, 0.75, 0.4),
+            BOX_SIZE,
+            16.0,
+            Vec2::new(0.02, 0.6),
+            0.0,
+        ),
+        // Small flakes, drifting slowly down
+        Precipitation::Snow => WeatherLocals::new(
+            Rgba::new(1.0, 1.0, 1.0, 0.8),
+            BOX_SIZE,
+            1.5,
+            Vec2::new(0.08, 0.08),
+            0.5,
+        ),
+    }
+}
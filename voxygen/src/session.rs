@@ -1,7 +1,7 @@
 use crate::{
     hud::{DebugInfo, Event as HudEvent, Hud},
     key_state::KeyState,
-    render::Renderer,
+    render::{DynamicResolution, ProfileStage, Renderer},
     scene::Scene,
     window::{Event, GameInput},
     Direction, Error, GlobalState, PlayState, PlayStateResult,
@@ -27,6 +27,7 @@ pub struct SessionState {
     key_state: KeyState,
     controller: comp::Controller,
     selected_block: Block,
+    dynamic_resolution: DynamicResolution,
 }
 
 /// Represents an active game session (i.e., the one being played).
@@ -38,6 +39,16 @@ impl SessionState {
         scene
             .camera_mut()
             .set_fov_deg(global_state.settings.graphics.fov);
+        scene.set_ambient_occlusion(global_state.settings.graphics.ambient_occlusion);
+        global_state
+            .window
+            .renderer_mut()
+            .set_gpu_timing(global_state.settings.graphics.dynamic_resolution);
+        scene.set_post_process(
+            global_state.window.renderer_mut(),
+            global_state.settings.graphics.gamma,
+            global_state.settings.graphics.bloom,
+        );
         Self {
             scene,
             client,
@@ -45,6 +56,7 @@ impl SessionState {
             controller: comp::Controller::default(),
             hud: Hud::new(global_state),
             selected_block: Block::new(BlockKind::Normal, Rgb::broadcast(255)),
+            dynamic_resolution: DynamicResolution::new(),
         }
     }
 }
@@ -82,7 +94,9 @@ impl SessionState {
         // Render the screen using the global renderer
         self.scene.render(renderer, &mut self.client.borrow_mut());
         // Draw the UI to the screen
-        self.hud.render(renderer, self.scene.globals());
+        renderer.profile(ProfileStage::Ui, |renderer| {
+            self.hud.render(renderer, self.scene.globals())
+        });
 
         // Finish the frame
         renderer.flush();
@@ -437,6 +451,74 @@ impl PlayState for SessionState {
                         global_state.settings.save_to_file_warn();
                         &self.scene.camera_mut().set_fov_deg(new_fov);
                     }
+                    HudEvent::ChangeAaMode(aa_mode) => {
+                        if let Err(err) = global_state.window.renderer_mut().set_aa_mode(aa_mode) {
+                            error!("Failed to change anti-aliasing mode: {:?}", err);
+                        } else {
+                            global_state.settings.graphics.aa_mode = aa_mode;
+                            global_state.settings.save_to_file_warn();
+                        }
+                    }
+                    HudEvent::ChangeRenderScale(scale) => {
+                        let renderer = global_state.window.renderer_mut();
+                        if let Err(err) = renderer.set_render_scale(scale) {
+                            error!("Failed to change render scale: {:?}", err);
+                        } else {
+                            global_state.settings.graphics.render_scale = renderer.render_scale();
+                            global_state.settings.save_to_file_warn();
+                        }
+                    }
+                    HudEvent::ToggleDynamicResolution(enabled) => {
+                        global_state.settings.graphics.dynamic_resolution = enabled;
+                        global_state.settings.save_to_file_warn();
+                        global_state.window.renderer_mut().set_gpu_timing(enabled);
+
+                        // Go back to the chosen render scale if it had been lowered
+                        if !enabled {
+                            if let Err(err) = global_state
+                                .window
+                                .renderer_mut()
+                                .set_render_scale(global_state.settings.graphics.render_scale)
+                            {
+                                error!("Failed to change render scale: {:?}", err);
+                            }
+                        }
+                    }
+                    HudEvent::ToggleAmbientOcclusion(enabled) => {
+                        global_state.settings.graphics.ambient_occlusion = enabled;
+                        global_state.settings.save_to_file_warn();
+                        self.scene.set_ambient_occlusion(enabled);
+                    }
+                    HudEvent::ChangeShadowQuality(quality) => {
+                        if let Err(err) = global_state
+                            .window
+                            .renderer_mut()
+                            .set_shadow_quality(quality)
+                        {
+                            error!("Failed to change shadow quality: {:?}", err);
+                        } else {
+                            global_state.settings.graphics.shadow_quality = quality;
+                            global_state.settings.save_to_file_warn();
+                        }
+                    }
+                    HudEvent::ChangeGamma(gamma) => {
+                        global_state.settings.graphics.gamma = gamma;
+                        global_state.settings.save_to_file_warn();
+                        self.scene.set_post_process(
+                            global_state.window.renderer_mut(),
+                            gamma,
+                            global_state.settings.graphics.bloom,
+                        );
+                    }
+                    HudEvent::ToggleBloom(enabled) => {
+                        global_state.settings.graphics.bloom = enabled;
+                        global_state.settings.save_to_file_warn();
+                        self.scene.set_post_process(
+                            global_state.window.renderer_mut(),
+                            global_state.settings.graphics.gamma,
+                            enabled,
+                        );
+                    }
                 }
             }
 
@@ -456,6 +538,26 @@ impl PlayState for SessionState {
                 .swap_buffers()
                 .expect("Failed to swap window buffers!");
 
+            // Lower the render scale while the GPU takes longer over frames than it should, and
+            // raise it again once it's quick enough.
+            let graphics = &global_state.settings.graphics;
+            let renderer = global_state.window.renderer_mut();
+            if let Some(gpu_time) = renderer
+                .take_gpu_frame_time()
+                .filter(|_| graphics.dynamic_resolution)
+            {
+                if let Some(scale) = self.dynamic_resolution.update(
+                    gpu_time,
+                    graphics.dynamic_resolution_target,
+                    renderer.render_scale(),
+                    graphics.render_scale,
+                ) {
+                    if let Err(err) = renderer.set_render_scale(scale) {
+                        error!("Failed to change render scale: {:?}", err);
+                    }
+                }
+            }
+
             // Wait for the next tick.
             clock.tick(Duration::from_millis(
                 1000 / global_state.settings.graphics.max_fps as u64,
@@ -1,5 +1,6 @@
 use crate::{
     hud::{BarNumbers, CrosshairType, ShortcutNumbers, XpBar},
+    render::{AaMode, ShadowQuality},
     ui::ScaleMode,
     window::KeyMouse,
 };
@@ -40,6 +41,7 @@ pub struct ControlSettings {
     pub help: KeyMouse,
     pub toggle_interface: KeyMouse,
     pub toggle_debug: KeyMouse,
+    pub toggle_profiler: KeyMouse,
     pub fullscreen: KeyMouse,
     pub screenshot: KeyMouse,
     pub toggle_ingame_ui: KeyMouse,
@@ -78,6 +80,7 @@ impl Default for ControlSettings {
             help: KeyMouse::Key(VirtualKeyCode::F1),
             toggle_interface: KeyMouse::Key(VirtualKeyCode::F2),
             toggle_debug: KeyMouse::Key(VirtualKeyCode::F3),
+            toggle_profiler: KeyMouse::Key(VirtualKeyCode::F7),
             fullscreen: KeyMouse::Key(VirtualKeyCode::F11),
             screenshot: KeyMouse::Key(VirtualKeyCode::F4),
             toggle_ingame_ui: KeyMouse::Key(VirtualKeyCode::F6),
@@ -160,6 +163,22 @@ pub struct GraphicsSettings {
     pub view_distance: u32,
     pub max_fps: u32,
     pub fov: u16,
+    pub shadow_quality: ShadowQuality,
+    /// Whether terrain corners and crevices are darkened. Turning this off lets more faces merge
+    /// when meshing, so chunks can have fewer vertices to draw.
+    pub ambient_occlusion: bool,
+    /// Brightens (above 1.0) or darkens (below 1.0) the final image.
+    pub gamma: f32,
+    /// Whether bright lights glow into their surroundings.
+    pub bloom: bool,
+    pub aa_mode: AaMode,
+    /// How much of the window's resolution the scene is rendered at, from 0.5 to 1.0.
+    pub render_scale: f32,
+    /// Whether the render scale is lowered automatically while the GPU takes too long over frames.
+    pub dynamic_resolution: bool,
+    /// How long, in milliseconds, the GPU should take over each frame when the render scale is
+    /// lowered automatically.
+    pub dynamic_resolution_target: f32,
 }
 
 impl Default for GraphicsSettings {
@@ -168,6 +187,14 @@ impl Default for GraphicsSettings {
             view_distance: 5,
             max_fps: 60,
             fov: 75,
+            shadow_quality: ShadowQuality::default(),
+            ambient_occlusion: true,
+            gamma: 1.0,
+            bloom: true,
+            aa_mode: AaMode::default(),
+            render_scale: 1.0,
+            dynamic_resolution: false,
+            dynamic_resolution_target: 12.0,
         }
     }
 }
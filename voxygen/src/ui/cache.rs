@@ -17,7 +17,6 @@ pub struct Cache {
     glyph_cache: GlyphCache<'static>,
     glyph_cache_tex: Texture<UiPipeline>,
     graphic_cache: GraphicCache,
-    graphic_cache_tex: Texture<UiPipeline>,
 }
 
 // TODO: Should functions be returning UiError instead of Error?
@@ -39,8 +38,7 @@ impl Cache {
                 .position_tolerance(POSITION_TOLERANCE)
                 .build(),
             glyph_cache_tex: renderer.create_dynamic_texture(glyph_cache_dims.map(|e| e as u16))?,
-            graphic_cache: GraphicCache::new(graphic_cache_dims),
-            graphic_cache_tex: renderer.create_dynamic_texture(graphic_cache_dims)?,
+            graphic_cache: GraphicCache::new(renderer, graphic_cache_dims)?,
         })
     }
     pub fn glyph_cache_tex(&self) -> &Texture<UiPipeline> {
@@ -50,10 +48,10 @@ impl Cache {
         (&mut self.glyph_cache, &self.glyph_cache_tex)
     }
     pub fn graphic_cache_tex(&self) -> &Texture<UiPipeline> {
-        &self.graphic_cache_tex
+        self.graphic_cache.texture()
     }
-    pub fn graphic_cache_mut_and_tex(&mut self) -> (&mut GraphicCache, &Texture<UiPipeline>) {
-        (&mut self.graphic_cache, &self.graphic_cache_tex)
+    pub fn graphic_cache_mut(&mut self) -> &mut GraphicCache {
+        &mut self.graphic_cache
     }
     pub fn add_graphic(&mut self, graphic: Graphic) -> GraphicId {
         self.graphic_cache.add_graphic(graphic)
@@ -64,8 +62,7 @@ impl Cache {
         let cache_dims = renderer
             .get_resolution()
             .map(|e| (e * GRAPHIC_CACHE_SIZE).min(max_texture_size as u16));
-        self.graphic_cache.clear_cache(cache_dims);
-        self.graphic_cache_tex = renderer.create_dynamic_texture(cache_dims)?;
+        self.graphic_cache.clear_cache(renderer, cache_dims)?;
         Ok(())
     }
     // Resizes and clears the GlyphCache
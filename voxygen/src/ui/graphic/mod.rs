@@ -1,10 +1,10 @@
 mod renderer;
 
+use crate::render::{AtlasRect, RenderError, Renderer, Texture, TextureAtlas, UiPipeline};
 use dot_vox::DotVoxData;
-use guillotiere::{size2, AllocId, Allocation, AtlasAllocator};
 use hashbrown::HashMap;
 use image::{DynamicImage, RgbaImage};
-use log::{error, warn};
+use log::warn;
 use std::sync::Arc;
 use vek::*;
 
@@ -29,39 +29,35 @@ pub struct Id(u32);
 type Parameters = (Id, Vec2<u16>, Aabr<u64>);
 
 struct CachedDetails {
-    // Id used by AtlasAllocator
-    alloc_id: AllocId,
+    // Where in the cache texture this is
+    rect: AtlasRect,
     // Last frame this was used on
     frame: u32,
-    // Where in the cache texture this is
-    aabr: Aabr<u16>,
 }
 
 pub struct GraphicCache {
     graphic_map: HashMap<Id, Graphic>,
     next_id: u32,
 
-    atlas: AtlasAllocator,
+    atlas: TextureAtlas<UiPipeline>,
     cache_map: HashMap<Parameters, CachedDetails>,
     // The current frame
     current_frame: u32,
     unused_entries_this_frame: Option<Vec<Option<(u32, Parameters)>>>,
 
     soft_cache: HashMap<Parameters, RgbaImage>,
-    transfer_ready: Vec<(Parameters, Aabr<u16>)>,
 }
 impl GraphicCache {
-    pub fn new(size: Vec2<u16>) -> Self {
-        Self {
+    pub fn new(renderer: &mut Renderer, size: Vec2<u16>) -> Result<Self, RenderError> {
+        Ok(Self {
             graphic_map: HashMap::default(),
             next_id: 0,
-            atlas: AtlasAllocator::new(size2(i32::from(size.x), i32::from(size.y))),
+            atlas: TextureAtlas::new(renderer, size)?,
             cache_map: HashMap::default(),
             current_frame: 0,
             unused_entries_this_frame: None,
             soft_cache: HashMap::default(),
-            transfer_ready: Vec::new(),
-        }
+        })
     }
     pub fn add_graphic(&mut self, graphic: Graphic) -> Id {
         let id = self.next_id;
@@ -75,20 +71,31 @@ impl GraphicCache {
     pub fn get_graphic(&self, id: Id) -> Option<&Graphic> {
         self.graphic_map.get(&id)
     }
-    pub fn clear_cache(&mut self, new_size: Vec2<u16>) {
+    pub fn clear_cache(
+        &mut self,
+        renderer: &mut Renderer,
+        new_size: Vec2<u16>,
+    ) -> Result<(), RenderError> {
         self.soft_cache.clear();
-        self.transfer_ready.clear();
         self.cache_map.clear();
-        self.atlas = AtlasAllocator::new(size2(i32::from(new_size.x), i32::from(new_size.y)));
+        self.atlas = TextureAtlas::new(renderer, new_size)?;
+        Ok(())
+    }
+
+    pub fn texture(&self) -> &Texture<UiPipeline> {
+        self.atlas.texture()
     }
 
+    /// Make sure the graphic is in the cache texture at the given resolution, returning the
+    /// texture coordinates it can be drawn from.
     pub fn queue_res(
         &mut self,
+        renderer: &mut Renderer,
         graphic_id: Id,
         dims: Vec2<u16>,
         source: Aabr<f64>,
         rotation: Rotation,
-    ) -> Option<Aabr<u16>> {
+    ) -> Option<Aabr<f32>> {
         let dims = match rotation {
             Rotation::Cw90 | Rotation::Cw270 => Vec2::new(dims.y, dims.x),
             Rotation::None | Rotation::Cw180 => dims,
@@ -112,7 +119,7 @@ impl GraphicCache {
             // Update frame
             details.frame = self.current_frame;
 
-            Some(rotated_aabr(details.aabr))
+            Some(rotated_aabr(self.atlas.uv(details.rect.aabr())))
         } else {
             // Create image if it doesn't already exist
             if !self.soft_cache.contains_key(&key) {
@@ -140,26 +147,24 @@ impl GraphicCache {
                 );
             }
 
-            let aabr_from_alloc_rect = |rect: guillotiere::Rectangle| {
-                let (min, max) = (rect.min, rect.max);
-                Aabr {
-                    min: Vec2::new(min.x as u16, min.y as u16),
-                    max: Vec2::new(max.x as u16, max.y as u16),
-                }
+            let image = &self.soft_cache[&key];
+            let insert_failed = |err: RenderError| {
+                warn!("Failed to upload an image to the graphic cache: {:?}", err);
             };
 
             // Allocate rectangle.
-            let (alloc_id, aabr) = match self
-                .atlas
-                .allocate(size2(i32::from(dims.x), i32::from(dims.y)))
-            {
-                Some(Allocation { id, rectangle }) => (id, aabr_from_alloc_rect(rectangle)),
+            let rect = match self.atlas.insert(renderer, image) {
+                Ok(Some(rect)) => rect,
+                Err(err) => {
+                    insert_failed(err);
+                    return None;
+                }
                 // Out of room.
                 //  1) Remove unused allocations
                 // TODO: Make more room.
                 //  2) Rearrange rectangles (see comments below)
                 //  3) Expand cache size
-                None => {
+                Ok(None) => {
                     // 1) Remove unused allocations
                     if self.unused_entries_this_frame.is_none() {
                         self.unused_entries_this_frame = {
@@ -197,18 +202,20 @@ impl GraphicCache {
                                 .filter(|d| d.frame != current_frame)
                                 .is_some()
                             {
-                                if let Some(alloc_id) =
-                                    self.cache_map.remove(&key).map(|d| d.alloc_id)
-                                {
+                                if let Some(rect) = self.cache_map.remove(&key).map(|d| d.rect) {
                                     // Deallocate
-                                    self.atlas.deallocate(alloc_id);
+                                    self.atlas.remove(rect);
                                     // Try to allocate
-                                    if let Some(alloc) = self
-                                        .atlas
-                                        .allocate(size2(i32::from(dims.x), i32::from(dims.y)))
-                                    {
-                                        allocation = Some(alloc);
-                                        break;
+                                    match self.atlas.insert(renderer, image) {
+                                        Ok(Some(rect)) => {
+                                            allocation = Some(rect);
+                                            break;
+                                        }
+                                        Ok(None) => {}
+                                        Err(err) => {
+                                            insert_failed(err);
+                                            return None;
+                                        }
                                     }
                                 }
                             }
@@ -223,7 +230,7 @@ impl GraphicCache {
                     }
 
                     match allocation {
-                        Some(Allocation { id, rectangle }) => (id, aabr_from_alloc_rect(rectangle)),
+                        Some(rect) => rect,
                         None => {
                             warn!("Can't find space for an image in the graphic cache");
                             return None;
@@ -231,40 +238,22 @@ impl GraphicCache {
                     }
                 }
             };
-            self.transfer_ready.push((key, aabr));
 
             // Insert area into map for retrieval.
             self.cache_map.insert(
                 key,
                 CachedDetails {
-                    alloc_id,
+                    rect,
                     frame: self.current_frame,
-                    aabr,
                 },
             );
 
-            Some(rotated_aabr(aabr))
+            Some(rotated_aabr(self.atlas.uv(rect.aabr())))
         }
     }
 
     // Anything not queued since the last call to this will be removed if there is not enough space in the cache
-    pub fn cache_queued<F>(&mut self, mut cacher: F)
-    where
-        F: FnMut(Aabr<u16>, &[[u8; 4]]),
-    {
-        // Cached queued
-        // TODO: combine nearby transfers
-        for (key, target_aarb) in self.transfer_ready.drain(..) {
-            if let Some(image) = self.soft_cache.get(&key) {
-                cacher(
-                    target_aarb,
-                    &image.pixels().map(|p| p.0).collect::<Vec<[u8; 4]>>(),
-                );
-            } else {
-                error!("Image queued for transfer to gpu cache but it doesn't exist (this should never occur)");
-            }
-        }
-
+    pub fn next_frame(&mut self) {
         // Increment frame
         self.current_frame += 1;
 
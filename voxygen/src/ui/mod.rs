@@ -414,7 +414,7 @@ impl Ui {
                         .image_map
                         .get(&image_id)
                         .expect("Image does not exist in image map");
-                    let (graphic_cache, cache_tex) = self.cache.graphic_cache_mut_and_tex();
+                    let graphic_cache = self.cache.graphic_cache_mut();
 
                     match graphic_cache.get_graphic(*graphic_id) {
                         Some(Graphic::Blank) | None => continue,
@@ -456,26 +456,17 @@ impl Ui {
                             max: Vec2::new(uv_r, uv_t),
                         }
                     };
-                    // TODO: get dims from graphic_cache (or have it return floats directly)
-                    let (cache_w, cache_h) =
-                        cache_tex.get_dimensions().map(|e| e as f32).into_tuple();
-
                     // Cache graphic at particular resolution.
                     let uv_aabr = match graphic_cache.queue_res(
+                        renderer,
                         *graphic_id,
                         resolution,
                         source_aabr,
                         *rotation,
                     ) {
                         Some(aabr) => Aabr {
-                            min: Vec2::new(
-                                (aabr.min.x as f32) / cache_w,
-                                (aabr.max.y as f32) / cache_h,
-                            ),
-                            max: Vec2::new(
-                                (aabr.max.x as f32) / cache_w,
-                                (aabr.min.y as f32) / cache_h,
-                            ),
+                            min: Vec2::new(aabr.min.x, aabr.max.y),
+                            max: Vec2::new(aabr.max.x, aabr.min.y),
                         },
                         None => continue,
                     };
@@ -692,15 +683,8 @@ impl Ui {
         // Update model with new mesh.
         renderer.update_model(&self.model, &mesh, 0).unwrap();
 
-        // Move cached graphics to the gpu
-        let (graphic_cache, cache_tex) = self.cache.graphic_cache_mut_and_tex();
-        graphic_cache.cache_queued(|aabr, data| {
-            let offset = aabr.min.into_array();
-            let size = aabr.size().into_array();
-            if let Err(err) = renderer.update_texture(cache_tex, offset, size, data) {
-                warn!("Failed to update texture: {:?}", err);
-            }
-        });
+        // Graphics not used since the last frame can make way for new ones
+        self.cache.graphic_cache_mut().next_frame();
 
         // Handle window resizing.
         if let Some(new_dims) = self.window_resized.take() {
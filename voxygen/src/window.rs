@@ -38,6 +38,7 @@ pub enum GameInput {
     ToggleInterface,
     Help,
     ToggleDebug,
+    ToggleProfiler,
     Fullscreen,
     Screenshot,
     ToggleIngameUi,
@@ -195,6 +196,9 @@ impl Window {
         map.entry(settings.controls.toggle_debug)
             .or_default()
             .push(GameInput::ToggleDebug);
+        map.entry(settings.controls.toggle_profiler)
+            .or_default()
+            .push(GameInput::ToggleProfiler);
         map.entry(settings.controls.fullscreen)
             .or_default()
             .push(GameInput::Fullscreen);
@@ -218,7 +222,15 @@ impl Window {
 
         Ok(Self {
             events_loop,
-            renderer: Renderer::new(device, factory, win_color_view, win_depth_view)?,
+            renderer: Renderer::new(
+                device,
+                factory,
+                win_color_view,
+                win_depth_view,
+                settings.graphics.shadow_quality,
+                settings.graphics.aa_mode,
+                settings.graphics.render_scale,
+            )?,
             window,
             cursor_grabbed: false,
             pan_sensitivity: settings.gameplay.pan_sensitivity,
@@ -540,7 +540,7 @@ pub fn block_from_structure(
     match sblock {
         StructureBlock::None => None,
         StructureBlock::TemperateLeaves => Some(Block::new(
-            BlockKind::Normal,
+            BlockKind::Leaves,
             Lerp::lerp(
                 Rgb::new(0.0, 132.0, 94.0),
                 Rgb::new(142.0, 181.0, 0.0),
@@ -549,12 +549,12 @@ pub fn block_from_structure(
             .map(|e| e as u8),
         )),
         StructureBlock::PineLeaves => Some(Block::new(
-            BlockKind::Normal,
+            BlockKind::Leaves,
             Lerp::lerp(Rgb::new(0.0, 60.0, 50.0), Rgb::new(30.0, 100.0, 10.0), lerp)
                 .map(|e| e as u8),
         )),
         StructureBlock::PalmLeaves => Some(Block::new(
-            BlockKind::Normal,
+            BlockKind::Leaves,
             Lerp::lerp(
                 Rgb::new(0.0, 108.0, 113.0),
                 Rgb::new(30.0, 156.0, 10.0),
@@ -565,7 +565,7 @@ pub fn block_from_structure(
         StructureBlock::Water => Some(Block::new(BlockKind::Water, Rgb::new(100, 150, 255))),
         StructureBlock::GreenSludge => Some(Block::new(BlockKind::Water, Rgb::new(30, 126, 23))),
         StructureBlock::Acacia => Some(Block::new(
-            BlockKind::Normal,
+            BlockKind::Leaves,
             Lerp::lerp(
                 Rgb::new(15.0, 126.0, 50.0),
                 Rgb::new(30.0, 180.0, 10.0),
@@ -584,7 +584,7 @@ pub fn block_from_structure(
             .map(|e| e as u8),
         )),
         StructureBlock::Mangrove => Some(Block::new(
-            BlockKind::Normal,
+            BlockKind::Leaves,
             Lerp::lerp(Rgb::new(32.0, 56.0, 22.0), Rgb::new(57.0, 69.0, 27.0), lerp)
                 .map(|e| e as u8),
         )),
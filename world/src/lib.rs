@@ -93,7 +93,11 @@ impl World {
             }
         };
 
-        let meta = TerrainChunkMeta::new(sim_chunk.get_name(&self.sim), sim_chunk.get_biome());
+        let meta = TerrainChunkMeta::new(
+            sim_chunk.get_name(&self.sim),
+            sim_chunk.get_biome(),
+            sim_chunk.temp,
+        );
         let mut sampler = self.sample_blocks();
 
         let chunk_block_pos = Vec3::from(chunk_pos) * TerrainChunkSize::RECT_SIZE.map(|e| e as i32);
@@ -1,6 +1,6 @@
 mod location;
 mod settlement;
-mod util;
+pub mod util;
 
 // Reexports
 pub use self::location::Location;
@@ -17,7 +17,7 @@ use crate::{
     CONFIG,
 };
 use common::{
-    terrain::{BiomeKind, TerrainChunkSize},
+    terrain::{BiomeKind, LodMap, LodSample, TerrainChunkSize},
     vol::RectVolSize,
 };
 use noise::{
@@ -539,6 +539,33 @@ impl WorldSim {
 
         Some(cubic(x[0], x[1], x[2], x[3], pos.x.fract() as f32))
     }
+
+    /// A coarse heightmap of the whole world, taking one sample every `chunks_per_sample` chunks,
+    /// for clients to draw the distant landscape with.
+    pub fn lod_map(&self, chunks_per_sample: u32) -> LodMap {
+        let size = WORLD_SIZE.map(|e| e as u32 / chunks_per_sample);
+        let spacing = TerrainChunkSize::RECT_SIZE.x * chunks_per_sample;
+
+        LodMap::generate(size, spacing, |pos| {
+            let chunk_pos = pos.map(|e| (e * chunks_per_sample) as i32);
+            let chunk = self.get(chunk_pos).unwrap();
+            let col = match chunk.get_biome() {
+                BiomeKind::Ocean => Rgb::new(0.1, 0.25, 0.45),
+                BiomeKind::Mountain => Rgb::new(0.5, 0.5, 0.48),
+                BiomeKind::Desert => Rgb::new(0.93, 0.80, 0.54),
+                BiomeKind::Snowlands => Rgb::new(0.8, 0.85, 1.0),
+                BiomeKind::Forest => Rgb::new(0.05, 0.35, 0.05),
+                BiomeKind::Swamp => Rgb::new(0.2, 0.3, 0.15),
+                _ => Rgb::new(0.15, 0.55, 0.05),
+            };
+
+            LodSample {
+                // The sea is flat, whatever lies beneath it
+                alt: chunk.alt.max(CONFIG.sea_level) as u16,
+                col: col.map(|e: f32| (e * 255.0) as u8),
+            }
+        })
+    }
 }
 
 pub struct SimChunk {
@@ -1,5 +1,13 @@
 use super::WORLD_SIZE;
-use common::{terrain::TerrainChunkSize, vol::RectVolSize};
+use crate::util::seed_expan;
+use common::{
+    terrain::{BiomeKind, TerrainChunkSize},
+    vol::RectVolSize,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use rayon::prelude::*;
+use std::collections::HashMap;
 use vek::*;
 
 /// Computes the cumulative distribution function of the weighted sum of k independent,
@@ -9,7 +17,8 @@ use vek::*;
 /// If the precondition is met, the distribution of the result of calling this function will be
 /// uniformly distributed while preserving the same information that was in the original average.
 ///
-/// For N > 33 the function will no longer return correct results since we will overflow u32.
+/// N must be less than 64, since the subsets of the weights are enumerated as the bits of a u64.
+/// Note that the running time is exponential in N.
 ///
 /// NOTE:
 ///
@@ -61,15 +70,17 @@ pub fn cdf_irwin_hall<const N: usize>(weights: &[f32; N], samples: [f32; N]) ->
         .map(|(weight, sample)| weight * sample)
         .sum();
 
+    debug_assert!(N < 64, "cdf_irwin_hall only supports up to 63 weights");
+
     let mut y = 0.0f32;
-    for subset in 0u32..(1 << N) {
+    for subset in 0u64..(1u64 << N) {
         // Number of set elements
         let k = subset.count_ones();
         // Add together exactly the set elements to get B_subset
         let z = weights
             .iter()
             .enumerate()
-            .filter(|(i, _)| subset & (1 << i) as u32 != 0)
+            .filter(|(i, _)| subset & (1u64 << i) != 0)
             .map(|(_, k)| k)
             .sum::<f32>();
         // Compute max(0, x - B_subset)^N
@@ -105,6 +116,75 @@ pub fn vec2_as_uniform_idx(idx: Vec2<i32>) -> usize {
     (idx.y as usize * WORLD_SIZE.x + idx.x as usize) as usize
 }
 
+/// Computes the world-space position of the center of a SimChunk from its index, where the index
+/// was generated by uniform_noise.
+pub fn chunk_center_wpos(idx: usize) -> Vec2<f64> {
+    let chunk_size = TerrainChunkSize::RECT_SIZE.map(|e| e as f64);
+    uniform_idx_as_vec2(idx).map(|e| e as f64) * chunk_size + chunk_size / 2.0
+}
+
+/// Samples a per-chunk field (indexed like the chunks generated by uniform_noise, e.g. the
+/// percentiles of an InverseCdf) at an arbitrary world position, blending bilinearly between the
+/// values at the centers of the four nearest chunks.  Positions beyond the centers of the chunks
+/// at the edge of the world use the edge values.
+pub fn sample_cdf_bilinear(field: &[f32], pos: Vec2<f64>) -> f32 {
+    let chunk_size = TerrainChunkSize::RECT_SIZE.map(|e| e as f64);
+    // Position in chunks, relative to the center of the first chunk
+    let chunk_pos = pos / chunk_size - 0.5;
+    let min = chunk_pos.map(|e| e.floor());
+    let frac = (chunk_pos - min).map(|e| e as f32);
+
+    let max_idx = Vec2::new(WORLD_SIZE.x as i32 - 1, WORLD_SIZE.y as i32 - 1);
+    let value = |offs: Vec2<i32>| {
+        let idx = (min.map(|e| e as i32) + offs).map2(max_idx, |e, max| e.max(0).min(max));
+        field[vec2_as_uniform_idx(idx)]
+    };
+
+    let lower = value(Vec2::new(0, 0)) * (1.0 - frac.x) + value(Vec2::new(1, 0)) * frac.x;
+    let upper = value(Vec2::new(0, 1)) * (1.0 - frac.x) + value(Vec2::new(1, 1)) * frac.x;
+    lower * (1.0 - frac.y) + upper * frac.y
+}
+
+/// Creates a random number generator for the SimChunk at the given index (as generated by
+/// uniform_noise).  `purpose` should be different for each system using the generator (trees,
+/// rocks, settlements, etc.) so that their random streams within a chunk are independent of each
+/// other, while still being reproducible for a given world seed.
+pub fn chunk_rng(world_seed: u32, idx: usize, purpose: u32) -> impl Rng {
+    let seed = seed_expan::diffuse(
+        seed_expan::diffuse(seed_expan::diffuse(world_seed) ^ idx as u32) ^ purpose,
+    );
+    ChaChaRng::from_seed(seed_expan::rng_state(seed))
+}
+
+/// A representative color for each biome, chosen to be easy to tell apart on maps.
+pub fn biome_color(biome: BiomeKind) -> Rgb<u8> {
+    match biome {
+        BiomeKind::Void => Rgb::new(0, 0, 0),
+        BiomeKind::Grassland => Rgb::new(106, 190, 48),
+        BiomeKind::Ocean => Rgb::new(32, 76, 160),
+        BiomeKind::Mountain => Rgb::new(120, 110, 100),
+        BiomeKind::Snowlands => Rgb::new(235, 240, 250),
+        BiomeKind::Desert => Rgb::new(222, 196, 120),
+        BiomeKind::Swamp => Rgb::new(76, 92, 48),
+        BiomeKind::Forest => Rgb::new(24, 100, 40),
+    }
+}
+
+/// The fraction of chunks covered by each biome, given the biome of every chunk.  Biomes that
+/// don't appear at all are left out.
+pub fn biome_distribution(biomes: &[BiomeKind]) -> HashMap<BiomeKind, f32> {
+    let mut counts = HashMap::new();
+    for biome in biomes {
+        *counts.entry(*biome).or_insert(0usize) += 1;
+    }
+
+    let total = biomes.len() as f32;
+    counts
+        .into_iter()
+        .map(|(biome, count)| (biome, count as f32 / total))
+        .collect()
+}
+
 /// Compute inverse cumulative distribution function for arbitrary function f, the hard way.  We
 /// pre-generate noise values prior to worldgen, then sort them in order to determine the correct
 /// position in the sorted order.  That lets us use `(index + 1) / (WORLDSIZE.y * WORLDSIZE.x)` as
@@ -128,30 +208,47 @@ pub fn vec2_as_uniform_idx(idx: Vec2<i32>) -> usize {
 /// f takes an index, which represents the index corresponding to this chunk in any any SimChunk
 /// vector returned by uniform_noise, and (for convenience) the float-translated version of those
 /// coordinates.
-/// f should return a value with no NaNs.  If there is a NaN, it will panic.  There are no other
-/// conditions on f.  If f returns None, the value will be set to 0.0, and will be ignored for the
+/// f is evaluated for every chunk in parallel, so it has to be `Sync`.
+/// f should return a value with no NaNs.  If there is a NaN, it will panic, naming the offending
+/// chunk; use `try_uniform_noise` to handle this case instead.  There are no other conditions on
+/// f.  If f returns None, the value will be set to 0.0, and will be ignored for the
 /// purposes of computing the uniform range.
 ///
 /// Returns a vec of (f32, f32) pairs consisting of the percentage of chunks with a value lower than
 /// this one, and the actual noise value (we don't need to cache it, but it makes ensuring that
 /// subsequent code that needs the noise value actually uses the same one we were using here
 /// easier).
-pub fn uniform_noise(f: impl Fn(usize, Vec2<f64>) -> Option<f32>) -> InverseCdf {
+pub fn uniform_noise(f: impl Fn(usize, Vec2<f64>) -> Option<f32> + Sync) -> InverseCdf {
+    try_uniform_noise(f).unwrap_or_else(|(idx, wposf)| {
+        panic!(
+            "Noise function returned NaN for chunk {} at {:?}",
+            idx, wposf
+        )
+    })
+}
+
+/// Like `uniform_noise`, but rather than panicking when f returns a NaN, returns the index and
+/// coordinates of the (first) chunk for which it did.
+pub fn try_uniform_noise(
+    f: impl Fn(usize, Vec2<f64>) -> Option<f32> + Sync,
+) -> Result<InverseCdf, (usize, Vec2<f64>)> {
+    let wposf = |i| {
+        (uniform_idx_as_vec2(i) * TerrainChunkSize::RECT_SIZE.map(|e| e as i32)).map(|e| e as f64)
+    };
     let mut noise = (0..WORLD_SIZE.x * WORLD_SIZE.y)
-        .filter_map(|i| {
-            (f(
-                i,
-                (uniform_idx_as_vec2(i) * TerrainChunkSize::RECT_SIZE.map(|e| e as i32))
-                    .map(|e| e as f64),
-            )
-            .map(|res| (i, res)))
-        })
+        .into_par_iter()
+        .filter_map(|i| f(i, wposf(i)).map(|res| (i, res)))
         .collect::<Vec<_>>();
 
-    // sort_unstable_by is equivalent to sort_by here since we include a unique index in the
+    if let Some((i, _)) = noise.par_iter().find_first(|(_, res)| res.is_nan()) {
+        return Err((*i, wposf(*i)));
+    }
+
+    // par_sort_unstable_by is equivalent to sort_by here since we include a unique index in the
     // comparison.  We could leave out the index, but this might make the order not
-    // reproduce the same way between different versions of Rust (for example).
-    noise.sort_unstable_by(|f, g| (f.1, f.0).partial_cmp(&(g.1, g.0)).unwrap());
+    // reproduce the same way between different versions of Rust (for example), or between runs
+    // with a different number of threads.
+    noise.par_sort_unstable_by(|f, g| (f.1, f.0).partial_cmp(&(g.1, g.0)).unwrap());
 
     // Construct a vector that associates each chunk position with the 1-indexed
     // position of the noise in the sorted vector (divided by the vector length).
@@ -162,5 +259,242 @@ pub fn uniform_noise(f: impl Fn(usize, Vec2<f64>) -> Option<f32>) -> InverseCdf
     for (noise_idx, (chunk_idx, noise_val)) in noise.into_iter().enumerate() {
         uniform_noise[chunk_idx] = ((1 + noise_idx) as f32 / total, noise_val);
     }
-    uniform_noise
+    Ok(uniform_noise)
+}
+
+/// The (percentile, noise value) pairs of an InverseCdf sorted by noise value, for use with
+/// `cdf_sample_sorted`.  Chunks for which the noise function returned None are left out.
+pub fn sort_cdf_by_value(cdf: &InverseCdf) -> Vec<(f32, f32)> {
+    let mut sorted = cdf
+        .iter()
+        .filter(|(pct, _)| *pct > 0.0)
+        .cloned()
+        .collect::<Vec<_>>();
+    sorted.sort_unstable_by(|a, b| (a.1, a.0).partial_cmp(&(b.1, b.0)).unwrap());
+    sorted
+}
+
+/// Estimates the percentile at which an arbitrary noise value (e.g. one sampled between chunk
+/// centers) would fall in the distribution described by `cdf`, interpolating linearly between the
+/// chunks with the closest values.  Values below or above every chunk's value map to 0 and 1.
+///
+/// This sorts a copy of the whole distribution on every call; when sampling many values, sort it
+/// once with `sort_cdf_by_value` and use `cdf_sample_sorted` instead.
+pub fn cdf_sample(cdf: &InverseCdf, value: f32) -> f32 {
+    cdf_sample_sorted(&sort_cdf_by_value(cdf), value)
+}
+
+/// Like `cdf_sample`, but using a distribution already sorted by `sort_cdf_by_value`.
+pub fn cdf_sample_sorted(sorted: &[(f32, f32)], value: f32) -> f32 {
+    match sorted.binary_search_by(|(_, val)| val.partial_cmp(&value).unwrap()) {
+        Ok(idx) => sorted[idx].0,
+        Err(0) => 0.0,
+        Err(idx) if idx == sorted.len() => 1.0,
+        Err(idx) => {
+            let (lo_pct, lo_val) = sorted[idx - 1];
+            let (hi_pct, hi_val) = sorted[idx];
+            lo_pct + (hi_pct - lo_pct) * (value - lo_val) / (hi_val - lo_val)
+        }
+    }
+}
+
+/// Combines several noise layers into a single uniformly distributed layer.  The CDF values of
+/// each layer are summed per chunk according to `weights`, and the result is passed back through
+/// uniform_noise so that it is uniformly distributed again.
+///
+/// `layers` and `weights` should have the same length; extra entries in either are ignored.
+pub fn combine_layers(layers: &[InverseCdf], weights: &[f32]) -> InverseCdf {
+    uniform_noise(|posi, _wposf| {
+        Some(
+            layers
+                .iter()
+                .zip(weights.iter())
+                .map(|(layer, weight)| layer[posi].0 * weight)
+                .sum(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn irwin_hall_analytic() {
+        // For X = U_1 + 2 U_2, P(X <= x) is x^2 / 4 below 1, and (x - 1/2) / 2 between 1 and 2
+        assert!((cdf_irwin_hall(&[1.0, 2.0], [0.5, 0.0]) - 0.0625).abs() < 1e-6);
+        assert!((cdf_irwin_hall(&[1.0, 2.0], [0.0, 1.0]) - 0.75).abs() < 1e-6);
+        // The sum of three identical uniform variables is symmetric about its mean
+        assert!((cdf_irwin_hall(&[1.0; 3], [0.5; 3]) - 0.5).abs() < 1e-6);
+        assert_eq!(cdf_irwin_hall(&[0.5; 4], [1.0; 4]), 1.0);
+    }
+
+    #[test]
+    fn chunk_centers() {
+        let chunk_size = TerrainChunkSize::RECT_SIZE.map(|e| e as f64);
+        assert_eq!(chunk_center_wpos(0), chunk_size / 2.0);
+        assert_eq!(
+            chunk_center_wpos(1) - chunk_center_wpos(0),
+            Vec2::new(chunk_size.x, 0.0)
+        );
+        assert_eq!(
+            chunk_center_wpos(WORLD_SIZE.x) - chunk_center_wpos(0),
+            Vec2::new(0.0, chunk_size.y)
+        );
+    }
+
+    #[test]
+    fn biome_colors_are_distinct() {
+        let colors = [
+            BiomeKind::Void,
+            BiomeKind::Grassland,
+            BiomeKind::Ocean,
+            BiomeKind::Mountain,
+            BiomeKind::Snowlands,
+            BiomeKind::Desert,
+            BiomeKind::Swamp,
+            BiomeKind::Forest,
+        ]
+        .iter()
+        .map(|biome| biome_color(*biome))
+        .collect::<Vec<_>>();
+
+        for (i, a) in colors.iter().enumerate() {
+            for b in colors.iter().skip(i + 1) {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn combine_single_layer() {
+        let a = uniform_noise(|posi, _| Some(((posi * 7919) % 1000) as f32));
+        let b = uniform_noise(|posi, _| Some((posi % 37) as f32));
+
+        let combined = combine_layers(&[a.clone(), b], &[1.0, 0.0]);
+        for (combined, a) in combined.iter().zip(a.iter()) {
+            assert_eq!(combined.0, a.0);
+        }
+    }
+
+    #[test]
+    fn uniform_noise_ties() {
+        // Lots of ties, which have to be broken by chunk index regardless of thread scheduling
+        let f = |posi: usize, _| {
+            if posi % 5 == 0 {
+                None
+            } else {
+                Some((posi % 13) as f32)
+            }
+        };
+        let cdf = uniform_noise(f);
+
+        let mut expected = (0..WORLD_SIZE.x * WORLD_SIZE.y)
+            .filter_map(|i| f(i, Vec2::zero()).map(|val| (i, val)))
+            .collect::<Vec<_>>();
+        expected.sort_by(|a, b| (a.1, a.0).partial_cmp(&(b.1, b.0)).unwrap());
+        let total = expected.len() as f32;
+        for (noise_idx, (chunk_idx, val)) in expected.into_iter().enumerate() {
+            assert_eq!(cdf[chunk_idx], ((1 + noise_idx) as f32 / total, val));
+        }
+        assert_eq!(cdf[0], (0.0, 0.0));
+    }
+
+    #[test]
+    fn uniform_noise_nan() {
+        let nan_idx = WORLD_SIZE.x * 3 + 5;
+        let result = try_uniform_noise(|posi, _| {
+            Some(if posi == nan_idx || posi == nan_idx + 1 {
+                std::f32::NAN
+            } else {
+                posi as f32
+            })
+        });
+        let wpos = Vec2::new(5.0, 3.0) * TerrainChunkSize::RECT_SIZE.map(|e| e as f64);
+        assert_eq!(result.unwrap_err(), (nan_idx, wpos));
+
+        assert!(try_uniform_noise(|posi, _| Some(posi as f32)).is_ok());
+    }
+
+    #[test]
+    fn cdf_lookup() {
+        // Values 0, 2, 4, ... for every chunk but the first, which has no value
+        let cdf = uniform_noise(|posi, _| {
+            if posi == 0 {
+                None
+            } else {
+                Some(posi as f32 * 2.0)
+            }
+        });
+        let sorted = sort_cdf_by_value(&cdf);
+        assert_eq!(sorted.len(), WORLD_SIZE.x * WORLD_SIZE.y - 1);
+
+        // Chunk values map back to their own percentiles
+        assert_eq!(cdf_sample(&cdf, cdf[10].1), cdf[10].0);
+        // Values between chunks are interpolated
+        let between = cdf_sample_sorted(&sorted, 21.0);
+        assert!((between - (cdf[10].0 + cdf[11].0) / 2.0).abs() < 1e-6);
+        // Values past either end are clamped
+        assert_eq!(cdf_sample_sorted(&sorted, -5.0), 0.0);
+        assert_eq!(cdf_sample_sorted(&sorted, std::f32::MAX), 1.0);
+    }
+
+    #[test]
+    fn bilinear_sampling() {
+        // A field that increases linearly in x, and is constant in y
+        let field = (0..WORLD_SIZE.x * WORLD_SIZE.y)
+            .map(|idx| uniform_idx_as_vec2(idx).x as f32)
+            .collect::<Vec<_>>();
+        let chunk_size = TerrainChunkSize::RECT_SIZE.map(|e| e as f64);
+
+        // Exact at chunk centers, and linear between them
+        assert_eq!(sample_cdf_bilinear(&field, chunk_center_wpos(3)), 3.0);
+        let between = chunk_center_wpos(3) + Vec2::new(chunk_size.x * 0.25, chunk_size.y * 0.5);
+        assert!((sample_cdf_bilinear(&field, between) - 3.25).abs() < 1e-5);
+
+        // Clamped at the edges of the world
+        assert_eq!(sample_cdf_bilinear(&field, Vec2::new(-100.0, -100.0)), 0.0);
+        let far = Vec2::new(WORLD_SIZE.x as f64, WORLD_SIZE.y as f64) * chunk_size * 2.0;
+        assert_eq!(sample_cdf_bilinear(&field, far), (WORLD_SIZE.x - 1) as f32);
+    }
+
+    #[test]
+    fn chunk_rng_streams() {
+        let first = |idx, purpose| chunk_rng(1337, idx, purpose).gen::<f32>();
+
+        // The same purpose always reproduces the same stream
+        assert_eq!(first(42, 0), first(42, 0));
+
+        // Different purposes in the same chunk don't correlate
+        let samples = (0..1000)
+            .map(|idx| (first(idx, 0), first(idx, 1)))
+            .collect::<Vec<_>>();
+        let n = samples.len() as f32;
+        let (mean_a, mean_b) = samples
+            .iter()
+            .fold((0.0, 0.0), |(a, b), (x, y)| (a + x / n, b + y / n));
+        let (cov, var_a, var_b) = samples.iter().fold((0.0, 0.0, 0.0), |(c, va, vb), (x, y)| {
+            let (dx, dy) = (x - mean_a, y - mean_b);
+            (c + dx * dy, va + dx * dx, vb + dy * dy)
+        });
+        let correlation = cov / (var_a * var_b).sqrt();
+        assert!(correlation.abs() < 0.1, "correlation: {}", correlation);
+    }
+
+    #[test]
+    fn biome_fractions() {
+        let biomes = [BiomeKind::Desert; 10]
+            .iter()
+            .chain([BiomeKind::Grassland; 60].iter())
+            .chain([BiomeKind::Ocean; 30].iter())
+            .cloned()
+            .collect::<Vec<_>>();
+        let distribution = biome_distribution(&biomes);
+
+        assert_eq!(distribution.len(), 3);
+        assert!((distribution[&BiomeKind::Desert] - 0.1).abs() < 1e-6);
+        assert!((distribution[&BiomeKind::Grassland] - 0.6).abs() < 1e-6);
+        assert!((distribution[&BiomeKind::Ocean] - 0.3).abs() < 1e-6);
+        assert!((distribution.values().sum::<f32>() - 1.0).abs() < 1e-6);
+    }
 }